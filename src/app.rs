@@ -1,313 +1,1765 @@
-use chrono::{DateTime, Local};
-use iced::widget::{button, column, container, row, text, text_input, scrollable};
-use iced::{Element, Length, Task};
-use rusqlite::{Connection, Result as SqlResult};
-use serde::{Deserialize, Serialize};
+use iced::widget::{column, container, text};
+use iced::{Color, Element, Length, Task};
+use rusqlite::Connection;
 use std::path::PathBuf;
-use uuid::Uuid;
-
-#[derive(Debug, Clone)]
-pub enum Message {
-    AddTransaction,
-    UpdateDescription(String),
-    UpdateAmount(String),
-    UpdateCategory(String),
-    TransactionAdded(Result<(), String>),
-    LoadTransactions,
-    TransactionsLoaded(Result<Vec<Transaction>, String>),
-    DeleteTransaction(Uuid),
-    TransactionDeleted(Result<(), String>),
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Transaction {
-    pub id: Uuid,
-    pub description: String,
-    pub amount: f64,
-    pub category: String,
-    pub date: DateTime<Local>,
-}
+use crate::export;
+use crate::import;
+use crate::report;
+use crate::db::backup;
+use crate::db::{get_database_path, operations, DatabaseConnection, DbError, DbResult};
+use crate::utils::{desktop, today};
+use crate::models::{Account, Budget, CategoryRule, CategorySuggester, Notification, RecurringRule, Transaction, Transfer};
+use crate::ui::account::{AccountMessage, AccountView};
+use crate::ui::backup::{BackupMessage, BackupView};
+use crate::ui::currency::{CurrencyMessage, CurrencyView};
+use crate::ui::export::{ExportMessage, ExportView};
+use crate::ui::import::{ImportMessage, ImportView};
+use crate::ui::notifications::{NotificationsMessage, NotificationsView};
+use crate::ui::payees::{PayeesMessage, PayeesView};
+use crate::ui::recurring::{RecurringMessage, RecurringView};
+use crate::ui::reports::{ReportsMessage, ReportsView};
+use crate::ui::rules::{self, RulesMessage, RulesView};
+use crate::ui::tags::{TagsMessage, TagsView};
+use crate::ui::budget::{BudgetMessage, BudgetView};
+use crate::ui::categories::{CategoriesMessage, CategoriesView};
+use crate::ui::components::nav::Nav;
+use crate::ui::dashboard::{Dashboard, DashboardMessage};
+use crate::ui::transaction::{Submission, TransactionFormMessage, TransactionMessage, TransactionView};
+use crate::ui::{Page, UiState};
+
+pub use crate::ui::Message;
 
-#[derive(Debug)]
 pub struct BudgetTracker {
-    transactions: Vec<Transaction>,
-    new_description: String,
-    new_amount: String,
-    new_category: String,
+    state: UiState,
+    transaction_view: TransactionView,
+    account_view: AccountView,
+    budget_view: BudgetView,
+    categories_view: CategoriesView,
+    tags_view: TagsView,
+    payees_view: PayeesView,
+    recurring_view: RecurringView,
+    import_view: ImportView,
+    export_view: ExportView,
+    backup_view: BackupView,
+    rules_view: RulesView,
+    currency_view: CurrencyView,
+    reports_view: ReportsView,
+    notifications_view: NotificationsView,
     database_path: PathBuf,
+    /// Set when the database cannot be used, such as one written by a newer
+    /// version. Nothing is then read from or written to it.
+    database_error: Option<String>,
 }
 
 impl BudgetTracker {
     pub fn new() -> (Self, Task<Message>) {
         let database_path = get_database_path();
-        
-        let app = BudgetTracker {
-            transactions: Vec::new(),
-            new_description: String::new(),
-            new_amount: String::new(),
-            new_category: String::new(),
+
+        let mut app = BudgetTracker {
+            state: UiState::new(),
+            transaction_view: TransactionView::new(),
+            account_view: AccountView::new(),
+            budget_view: BudgetView::new(),
+            categories_view: CategoriesView::new(),
+            tags_view: TagsView::new(),
+            payees_view: PayeesView::new(),
+            recurring_view: RecurringView::new(),
+            import_view: ImportView::new(),
+            export_view: ExportView::new(),
+            backup_view: BackupView::new(),
+            rules_view: RulesView::new(),
+            currency_view: CurrencyView::new(),
+            reports_view: ReportsView::new(),
+            notifications_view: NotificationsView::new(),
             database_path,
+            database_error: None,
         };
 
         // Initialize database
-        if let Err(e) = app.init_database() {
-            eprintln!("Failed to initialize database: {}", e);
+        match app.init_database() {
+            Ok(()) => {}
+            Err(e @ DbError::UnsupportedVersion { .. }) => {
+                app.database_error = Some(format!(
+                    "{} cannot be opened: {}. Update Budget Tracker to use it.",
+                    app.database_path.display(),
+                    e
+                ));
+                return (app, Task::none());
+            }
+            Err(e) => eprintln!("Failed to initialize database: {}", e),
         }
 
-        (app, Task::perform(async {}, |_| Message::LoadTransactions))
+        // Recurring transactions generated on startup may have crossed
+        // budget alert thresholds
+        (app, Task::batch([Self::load_all(), Task::done(Message::CheckBudgetAlerts)]))
     }
 
     pub fn title(&self) -> String {
         String::from("Budget Tracker")
     }
 
+    fn load_all() -> Task<Message> {
+        Task::batch([
+            Task::done(Message::LoadCategories),
+            Task::done(Message::LoadTags),
+            Task::done(Message::LoadPayees),
+            Task::done(Message::LoadTransactions),
+            Task::done(Message::LoadDismissedDuplicates),
+            Task::done(Message::LoadAccounts),
+            Task::done(Message::LoadBudgets),
+            Task::done(Message::LoadAllocations),
+            Task::done(Message::LoadEnvelopeBudgeting),
+            Task::done(Message::LoadRecurringRules),
+            Task::done(Message::LoadImportProfiles),
+            Task::done(Message::LoadCategoryRules),
+            Task::done(Message::LoadExchangeRates),
+            Task::done(Message::LoadBackups),
+            Task::done(Message::LoadNotifications),
+            Task::done(Message::LoadDesktopNotifications),
+        ])
+    }
+
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        if self.database_error.is_some() {
+            return Task::none();
+        }
+
         match message {
-            Message::UpdateDescription(description) => {
-                self.new_description = description;
+            Message::ChangePage(page) => {
+                self.state.page = page;
                 Task::none()
             }
-            Message::UpdateAmount(amount) => {
-                self.new_amount = amount;
+            Message::Dashboard(message) => match message {
+                DashboardMessage::Refresh => Self::load_all(),
+                DashboardMessage::ViewTransactions => Task::done(Message::ChangePage(Page::Transactions)),
+                DashboardMessage::ViewBudgets => Task::done(Message::ChangePage(Page::Budgets)),
+                DashboardMessage::PeriodSelected(period) => {
+                    self.state.dashboard_range = period.containing(today());
+                    Task::none()
+                }
+                DashboardMessage::PreviousPeriod => {
+                    self.state.dashboard_range = self.state.dashboard_range.previous();
+                    Task::none()
+                }
+                DashboardMessage::NextPeriod => {
+                    self.state.dashboard_range = self.state.dashboard_range.next();
+                    Task::none()
+                }
+            },
+            Message::Transaction(message) => self.update_transactions(message),
+            Message::Account(message) => self.update_accounts(message),
+            Message::Categories(message) => self.update_categories(message),
+            Message::Tags(message) => self.update_tags(message),
+            Message::Payees(message) => self.update_payees(message),
+            Message::Budget(message) => self.update_budgets(message),
+            Message::Recurring(message) => self.update_recurring(message),
+            Message::Import(message) => self.update_import(message),
+            Message::Export(message) => self.update_export(message),
+            Message::Backup(message) => self.update_backup(message),
+            Message::Reports(message) => self.update_reports(message),
+            Message::Rules(message) => self.update_rules(message),
+            Message::Currency(message) => self.update_currencies(message),
+            Message::Notifications(message) => self.update_notifications(message),
+            Message::LoadTransactions => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_all_transactions)
+                    },
+                    Message::TransactionsLoaded,
+                )
+            }
+            Message::TransactionsLoaded(result) => {
+                match result {
+                    Ok(transactions) => {
+                        self.state.suggester = CategorySuggester::train(&transactions);
+                        self.state.transactions = transactions;
+                        // Spending in budget periods may have changed
+                        Task::done(Message::SyncBudgetPeriods)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load transactions: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::TransactionSaved(result) => {
+                match result {
+                    Ok(()) => {
+                        self.transaction_view.close_form();
+                        // Typing a new tag on the transaction creates it
+                        Task::batch([
+                            Task::done(Message::LoadTransactions),
+                            Task::done(Message::LoadTags),
+                            Task::done(Message::CheckBudgetAlerts),
+                        ])
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to save transaction: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::TransactionDeleted(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadTransactions),
+                    Err(e) => {
+                        eprintln!("Failed to delete transaction: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadDismissedDuplicates => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_dismissed_duplicates)
+                    },
+                    Message::DismissedDuplicatesLoaded,
+                )
+            }
+            Message::DismissedDuplicatesLoaded(result) => {
+                match result {
+                    Ok(pairs) => {
+                        self.state.dismissed_duplicates = pairs;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load dismissed duplicates: {}", e);
+                    }
+                }
                 Task::none()
             }
-            Message::UpdateCategory(category) => {
-                self.new_category = category;
+            Message::DuplicatesMerged(result) => {
+                match result {
+                    Ok(()) => Task::batch([
+                        Task::done(Message::LoadTransactions),
+                        Task::done(Message::LoadDismissedDuplicates),
+                    ]),
+                    Err(e) => {
+                        eprintln!("Failed to merge duplicates: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::DuplicateDismissed(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadDismissedDuplicates),
+                    Err(e) => {
+                        eprintln!("Failed to dismiss duplicate: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadCategories => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_all_categories)
+                    },
+                    Message::CategoriesLoaded,
+                )
+            }
+            Message::CategoriesLoaded(result) => {
+                match result {
+                    Ok(categories) => {
+                        self.state.categories = categories;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load categories: {}", e);
+                    }
+                }
                 Task::none()
             }
-            Message::AddTransaction => {
-                if let Ok(amount) = self.new_amount.parse::<f64>() {
-                    let transaction = Transaction {
-                        id: Uuid::new_v4(),
-                        description: self.new_description.clone(),
-                        amount,
-                        category: self.new_category.clone(),
-                        date: Local::now(),
-                    };
-
-                    let db_path = self.database_path.clone();
-                    Task::perform(
-                        async move {
-                            save_transaction(&db_path, &transaction).await
-                        },
-                        Message::TransactionAdded,
-                    )
-                } else {
-                    Task::none()
+            Message::CategoryRemoved(result) => {
+                match result {
+                    // Transactions, budgets and rules may have moved with it
+                    Ok(()) => Task::batch([
+                        Task::done(Message::LoadCategories),
+                        Task::done(Message::LoadTransactions),
+                        Task::done(Message::LoadBudgets),
+                        Task::done(Message::LoadAllocations),
+                        Task::done(Message::LoadRecurringRules),
+                        Task::done(Message::LoadCategoryRules),
+                    ]),
+                    Err(e) => {
+                        self.categories_view.set_error(format!("Failed to remove category: {}", e));
+                        Task::none()
+                    }
                 }
             }
-            Message::TransactionAdded(result) => {
+            Message::CategorySaved(result) => {
                 match result {
                     Ok(()) => {
-                        self.new_description.clear();
-                        self.new_amount.clear();
-                        self.new_category.clear();
-                        Task::perform(async {}, |_| Message::LoadTransactions)
+                        self.categories_view.category_saved();
+                        Task::done(Message::LoadCategories)
                     }
                     Err(e) => {
-                        eprintln!("Failed to add transaction: {}", e);
+                        self.categories_view.set_error(format!("Failed to save category: {}", e));
                         Task::none()
                     }
                 }
             }
-            Message::LoadTransactions => {
+            Message::LoadTags => {
                 let db_path = self.database_path.clone();
                 Task::perform(
                     async move {
-                        load_transactions(&db_path).await
+                        with_connection(&db_path, operations::get_all_tags)
                     },
-                    Message::TransactionsLoaded,
+                    Message::TagsLoaded,
                 )
             }
-            Message::TransactionsLoaded(result) => {
+            Message::TagsLoaded(result) => {
                 match result {
-                    Ok(transactions) => {
-                        self.transactions = transactions;
+                    Ok(tags) => {
+                        self.state.tags = tags;
                     }
                     Err(e) => {
-                        eprintln!("Failed to load transactions: {}", e);
+                        eprintln!("Failed to load tags: {}", e);
                     }
                 }
                 Task::none()
             }
-            Message::DeleteTransaction(id) => {
+            Message::TagSaved(result) => {
+                match result {
+                    // A renamed tag is renamed on its transactions too
+                    Ok(()) => {
+                        self.tags_view.tag_saved();
+                        Task::batch([
+                            Task::done(Message::LoadTags),
+                            Task::done(Message::LoadTransactions),
+                        ])
+                    }
+                    Err(e) => {
+                        self.tags_view.set_error(format!("Failed to save tag: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::TagDeleted(result) => {
+                match result {
+                    Ok(()) => Task::batch([
+                        Task::done(Message::LoadTags),
+                        Task::done(Message::LoadTransactions),
+                    ]),
+                    Err(e) => {
+                        self.tags_view.set_error(format!("Failed to delete tag: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadPayees => {
                 let db_path = self.database_path.clone();
                 Task::perform(
                     async move {
-                        delete_transaction(&db_path, id).await
+                        with_connection(&db_path, operations::get_all_payees)
                     },
-                    Message::TransactionDeleted,
+                    Message::PayeesLoaded,
                 )
             }
-            Message::TransactionDeleted(result) => {
+            Message::PayeesLoaded(result) => {
+                match result {
+                    Ok(payees) => {
+                        self.state.payees = payees;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load payees: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::PayeeSaved(result) => {
                 match result {
                     Ok(()) => {
-                        Task::perform(async {}, |_| Message::LoadTransactions)
+                        self.payees_view.payee_saved();
+                        Task::done(Message::LoadPayees)
                     }
                     Err(e) => {
-                        eprintln!("Failed to delete transaction: {}", e);
+                        self.payees_view.set_error(format!("Failed to save payee: {}", e));
                         Task::none()
                     }
                 }
             }
-        }
-    }
-
-    pub fn view(&self) -> Element<Message> {
-        let total_balance: f64 = self.transactions.iter().map(|t| t.amount).sum();
-
-        let input_section = column![
-            text("Add New Transaction").size(20),
-            text_input("Description", &self.new_description)
-                .on_input(Message::UpdateDescription)
-                .padding(10),
-            text_input("Amount", &self.new_amount)
-                .on_input(Message::UpdateAmount)
-                .padding(10),
-            text_input("Category", &self.new_category)
-                .on_input(Message::UpdateCategory)
-                .padding(10),
-            button("Add Transaction")
-                .on_press(Message::AddTransaction)
-                .padding(10),
-        ]
-        .spacing(10)
-        .padding(20);
-
-        let balance_section = column![
-            text(format!("Total Balance: ${:.2}", total_balance))
-                .size(24)
-        ]
-        .padding(20);
-
-        let transactions_list = scrollable(
-            column(
-                self.transactions
-                    .iter()
-                    .map(|transaction| {
-                        row![
-                            text(&transaction.description).width(Length::Fill),
-                            text(format!("${:.2}", transaction.amount)).width(Length::Shrink),
-                            text(&transaction.category).width(Length::Shrink),
-                            text(transaction.date.format("%Y-%m-%d").to_string()).width(Length::Shrink),
-                            button("Delete")
-                                .on_press(Message::DeleteTransaction(transaction.id))
-                                .padding(5),
-                        ]
-                        .spacing(10)
-                        .padding(10)
-                        .into()
-                    })
-                    .collect::<Vec<_>>()
-            )
-            .spacing(5)
-        )
-        .height(Length::Fill);
-
-        container(
-            column![
-                input_section,
-                balance_section,
-                text("Transactions").size(20),
-                transactions_list
-            ]
-            .spacing(10)
-        )
-        .padding(20)
-        .width(Length::Fill)
-        .height(Length::Fill)
-        .into()
-    }
-
-    fn init_database(&self) -> SqlResult<()> {
-        let conn = Connection::open(&self.database_path)?;
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS transactions (
-                id TEXT PRIMARY KEY,
-                description TEXT NOT NULL,
-                amount REAL NOT NULL,
-                category TEXT NOT NULL,
-                date TEXT NOT NULL
-            )",
-            [],
-        )?;
-        Ok(())
-    }
-}
-
-fn get_database_path() -> PathBuf {
-    if let Some(data_dir) = directories::ProjectDirs::from("com", "budgettracker", "BudgetTracker") {
-        let data_path = data_dir.data_dir();
-        std::fs::create_dir_all(data_path).unwrap_or_else(|_| {
-            eprintln!("Failed to create data directory");
-        });
-        data_path.join("budget.db")
-    } else {
-        PathBuf::from("budget.db")
-    }
-}
-
-async fn save_transaction(db_path: &PathBuf, transaction: &Transaction) -> Result<(), String> {
-    let conn = Connection::open(db_path)
-        .map_err(|e| format!("Failed to open database: {}", e))?;
-    
-    conn.execute(
-        "INSERT INTO transactions (id, description, amount, category, date) VALUES (?1, ?2, ?3, ?4, ?5)",
-        [
-            transaction.id.to_string(),
-            transaction.description.clone(),
-            transaction.amount.to_string(),
-            transaction.category.clone(),
-            transaction.date.to_rfc3339(),
-        ],
-    )
-    .map_err(|e| format!("Failed to insert transaction: {}", e))?;
-    
-    Ok(())
-}
-
-async fn load_transactions(db_path: &PathBuf) -> Result<Vec<Transaction>, String> {
-    let conn = Connection::open(db_path)
-        .map_err(|e| format!("Failed to open database: {}", e))?;
-    
-    let mut stmt = conn
-        .prepare("SELECT id, description, amount, category, date FROM transactions ORDER BY date DESC")
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-    
-    let transaction_iter = stmt
-        .query_map([], |row| {
-            let id_str: String = row.get(0)?;
-            let date_str: String = row.get(4)?;
-            
-            Ok(Transaction {
-                id: Uuid::parse_str(&id_str).map_err(|_| rusqlite::Error::InvalidColumnType(0, "id".to_string(), rusqlite::types::Type::Text))?,
-                description: row.get(1)?,
-                amount: row.get(2)?,
-                category: row.get(3)?,
-                date: DateTime::parse_from_rfc3339(&date_str)
-                    .map_err(|_| rusqlite::Error::InvalidColumnType(4, "date".to_string(), rusqlite::types::Type::Text))?
-                    .with_timezone(&Local),
-            })
-        })
-        .map_err(|e| format!("Failed to query transactions: {}", e))?;
-    
-    let mut transactions = Vec::new();
-    for transaction in transaction_iter {
-        transactions.push(transaction.map_err(|e| format!("Failed to parse transaction: {}", e))?);
-    }
-    
-    Ok(transactions)
-}
-
-async fn delete_transaction(db_path: &PathBuf, id: Uuid) -> Result<(), String> {
-    let conn = Connection::open(db_path)
-        .map_err(|e| format!("Failed to open database: {}", e))?;
-    
-    conn.execute(
-        "DELETE FROM transactions WHERE id = ?1",
-        [id.to_string()],
-    )
-    .map_err(|e| format!("Failed to delete transaction: {}", e))?;
-    
-    Ok(())
-}
\ No newline at end of file
+            Message::PayeeDeleted(result) => {
+                match result {
+                    // Its transactions lose the link and its rules are gone
+                    Ok(()) => Task::batch([
+                        Task::done(Message::LoadPayees),
+                        Task::done(Message::LoadTransactions),
+                        Task::done(Message::LoadCategoryRules),
+                    ]),
+                    Err(e) => {
+                        self.payees_view.set_error(format!("Failed to delete payee: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::PayeesApplied(result) => {
+                match result {
+                    Ok(linked) => {
+                        self.payees_view.applied(linked);
+                        Task::done(Message::LoadTransactions)
+                    }
+                    Err(e) => {
+                        self.payees_view.set_error(format!("Failed to apply payees: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadAccounts => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_all_accounts)
+                    },
+                    Message::AccountsLoaded,
+                )
+            }
+            Message::AccountsLoaded(result) => {
+                match result {
+                    Ok(accounts) => {
+                        self.state.accounts = accounts;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load accounts: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::AccountSaved(result) => {
+                match result {
+                    Ok(()) => {
+                        self.account_view.close_form();
+                        Task::done(Message::LoadAccounts)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to save account: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::AccountDeleted(result) => {
+                match result {
+                    // Deleting an account detaches its transactions and rules
+                    Ok(()) => Task::batch([
+                        Task::done(Message::LoadAccounts),
+                        Task::done(Message::LoadTransactions),
+                        Task::done(Message::LoadRecurringRules),
+                        Task::done(Message::LoadCategoryRules),
+                        Task::done(Message::LoadImportProfiles),
+                    ]),
+                    Err(e) => {
+                        self.account_view.set_error(format!("Failed to delete account: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadBudgets => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_all_budgets)
+                    },
+                    Message::BudgetsLoaded,
+                )
+            }
+            Message::BudgetsLoaded(result) => {
+                match result {
+                    Ok(budgets) => {
+                        self.state.budgets = budgets;
+                        Task::done(Message::SyncBudgetPeriods)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load budgets: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::SyncBudgetPeriods => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::sync_budget_periods(conn, today()))
+                    },
+                    Message::BudgetPeriodsSynced,
+                )
+            }
+            Message::BudgetPeriodsSynced(result) => {
+                match result {
+                    Ok(periods) => {
+                        self.state.budget_periods = periods;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to update budget periods: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::BudgetSaved(result) => {
+                match result {
+                    Ok(()) => {
+                        self.budget_view.close_form();
+                        Task::done(Message::LoadBudgets)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to save budget: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::BudgetDeleted(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadBudgets),
+                    Err(e) => {
+                        eprintln!("Failed to delete budget: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadAllocations => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_all_allocations)
+                    },
+                    Message::AllocationsLoaded,
+                )
+            }
+            Message::AllocationsLoaded(result) => {
+                match result {
+                    Ok(allocations) => {
+                        self.state.allocations = allocations;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load envelope allocations: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::AllocationSaved(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadAllocations),
+                    Err(e) => {
+                        self.budget_view.set_envelope_error(format!("Failed to assign: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadEnvelopeBudgeting => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_envelope_budgeting)
+                    },
+                    Message::EnvelopeBudgetingLoaded,
+                )
+            }
+            Message::EnvelopeBudgetingLoaded(result) => {
+                match result {
+                    Ok(enabled) => {
+                        self.budget_view.set_envelope_mode(enabled);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load budgeting mode: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::EnvelopeBudgetingSaved(result) => {
+                if let Err(e) = result {
+                    eprintln!("Failed to save budgeting mode: {}", e);
+                }
+                Task::none()
+            }
+            Message::CheckBudgetAlerts => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        check_budget_alerts(&db_path)
+                    },
+                    Message::BudgetAlertsChecked,
+                )
+            }
+            Message::BudgetAlertsChecked(result) => {
+                match result {
+                    Ok(notifications) if !notifications.is_empty() => Task::done(Message::LoadNotifications),
+                    Ok(_) => Task::none(),
+                    Err(e) => {
+                        eprintln!("Failed to check budget alerts: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadNotifications => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_all_notifications)
+                    },
+                    Message::NotificationsLoaded,
+                )
+            }
+            Message::NotificationsLoaded(result) => {
+                match result {
+                    Ok(notifications) => {
+                        self.state.notifications = notifications;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load notifications: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::NotificationsUpdated(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadNotifications),
+                    Err(e) => {
+                        self.notifications_view.set_error(format!("Failed to update notifications: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadDesktopNotifications => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_desktop_notifications)
+                    },
+                    Message::DesktopNotificationsLoaded,
+                )
+            }
+            Message::DesktopNotificationsLoaded(result) => {
+                match result {
+                    Ok(enabled) => {
+                        self.notifications_view.set_desktop(enabled);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load desktop notification setting: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::DesktopNotificationsSaved(result) => {
+                if let Err(e) = result {
+                    self.notifications_view.set_error(format!("Failed to save desktop notification setting: {}", e));
+                }
+                Task::none()
+            }
+            Message::LoadRecurringRules => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_all_recurring_rules)
+                    },
+                    Message::RecurringRulesLoaded,
+                )
+            }
+            Message::RecurringRulesLoaded(result) => {
+                match result {
+                    Ok(rules) => {
+                        self.state.recurring_rules = rules;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load recurring transactions: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::RecurringRuleSaved(result) => {
+                match result {
+                    Ok(()) => {
+                        self.recurring_view.close_form();
+                        // Saving may have generated occurrences that were already due
+                        Task::batch([
+                            Task::done(Message::LoadRecurringRules),
+                            Task::done(Message::LoadTransactions),
+                            Task::done(Message::CheckBudgetAlerts),
+                        ])
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to save recurring transaction: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::RecurringRuleDeleted(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadRecurringRules),
+                    Err(e) => {
+                        eprintln!("Failed to delete recurring transaction: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadCategoryRules => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_all_category_rules)
+                    },
+                    Message::CategoryRulesLoaded,
+                )
+            }
+            Message::CategoryRulesLoaded(result) => {
+                match result {
+                    Ok(rules) => {
+                        self.state.category_rules = rules;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load category rules: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::CategoryRuleSaved(result) => {
+                match result {
+                    Ok(()) => {
+                        self.rules_view.close_form();
+                        Task::done(Message::LoadCategoryRules)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to save category rule: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::CategoryRuleDeleted(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadCategoryRules),
+                    Err(e) => {
+                        eprintln!("Failed to delete category rule: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::CategoryRulesReordered(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadCategoryRules),
+                    Err(e) => {
+                        eprintln!("Failed to reorder category rules: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::CategoryRulesApplied(result) => {
+                match result {
+                    Ok(count) => {
+                        self.rules_view.set_status(format!("Recategorised {} transactions", count));
+                        Task::done(Message::LoadTransactions)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to apply category rules: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadImportProfiles => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_all_import_profiles)
+                    },
+                    Message::ImportProfilesLoaded,
+                )
+            }
+            Message::ImportProfilesLoaded(result) => {
+                match result {
+                    Ok(profiles) => {
+                        self.state.import_profiles = profiles;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load import profiles: {}", e);
+                    }
+                }
+                Task::none()
+            }
+            Message::ImportProfileSaved(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadImportProfiles),
+                    Err(e) => {
+                        eprintln!("Failed to save import profile: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::TransactionsImported(result) => {
+                match result {
+                    Ok(count) => {
+                        self.import_view.imported(count);
+                        Task::batch([
+                            Task::done(Message::LoadTransactions),
+                            Task::done(Message::LoadDismissedDuplicates),
+                            Task::done(Message::CheckBudgetAlerts),
+                        ])
+                    }
+                    Err(e) => {
+                        self.import_view.set_error(format!("Import failed: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::DataExported(result) => {
+                match result {
+                    Ok(status) => self.export_view.exported(status),
+                    Err(e) => self.export_view.set_error(format!("Export failed: {}", e)),
+                }
+                Task::none()
+            }
+            Message::ReportGenerated(result) => {
+                match result {
+                    Ok(status) => self.reports_view.generated(status),
+                    Err(e) => self.reports_view.set_error(format!("Report failed: {}", e)),
+                }
+                Task::none()
+            }
+            Message::LoadBackups => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move { load_backups(&db_path) },
+                    Message::BackupsLoaded,
+                )
+            }
+            Message::BackupsLoaded(result) => {
+                match result {
+                    Ok((backups, keep)) => self.backup_view.set_backups(backups, keep),
+                    Err(e) => eprintln!("Failed to load backups: {}", e),
+                }
+                Task::none()
+            }
+            Message::BackupCreated(result) => {
+                match result {
+                    Ok(path) => {
+                        self.backup_view.set_status(format!("Backed up to {}", path.display()));
+                        Task::done(Message::LoadBackups)
+                    }
+                    Err(e) => {
+                        self.backup_view.set_error(format!("Backup failed: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::BackupsToKeepSaved(result) => {
+                match result {
+                    Ok(()) => {
+                        self.backup_view.set_status("Saved".to_string());
+                        Task::done(Message::LoadBackups)
+                    }
+                    Err(e) => {
+                        self.backup_view.set_error(format!("Failed to save: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::BackupRestored(result) => {
+                match result {
+                    Ok(safety_backup) => {
+                        self.backup_view.set_status(format!(
+                            "Restored. The data it replaced was backed up to {}",
+                            safety_backup.display()
+                        ));
+                        Self::load_all()
+                    }
+                    Err(e) => {
+                        self.backup_view.set_error(format!("Restore failed: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::LoadExchangeRates => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::get_currency_converter)
+                    },
+                    Message::ExchangeRatesLoaded,
+                )
+            }
+            Message::ExchangeRatesLoaded(result) => {
+                match result {
+                    Ok(converter) => {
+                        self.transaction_view.set_default_currency(converter.base());
+                        self.account_view.set_default_currency(converter.base());
+                        self.budget_view.set_default_currency(converter.base());
+                        self.recurring_view.set_default_currency(converter.base());
+                        self.rules_view.set_default_currency(converter.base());
+                        self.state.converter = converter;
+                        // Spending in other currencies converts differently
+                        Task::done(Message::SyncBudgetPeriods)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load exchange rates: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::ExchangeRateSaved(result) => {
+                match result {
+                    Ok(()) => {
+                        self.currency_view.rate_saved();
+                        Task::done(Message::LoadExchangeRates)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to save exchange rate: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::ExchangeRateDeleted(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadExchangeRates),
+                    Err(e) => {
+                        eprintln!("Failed to delete exchange rate: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+            Message::BaseCurrencySaved(result) => {
+                match result {
+                    Ok(()) => Task::done(Message::LoadExchangeRates),
+                    Err(e) => {
+                        eprintln!("Failed to save base currency: {}", e);
+                        Task::none()
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_transactions(&mut self, message: TransactionMessage) -> Task<Message> {
+        match message {
+            TransactionMessage::FormUpdated(TransactionFormMessage::Submit) => {
+                let Some(submission) = self.transaction_view.submit() else {
+                    return Task::none();
+                };
+
+                let is_new = !self.transaction_view.is_editing();
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        match submission {
+                            Submission::Transaction(transaction) => save_transaction(&db_path, &transaction, is_new),
+                            Submission::Transfer(transfer) => save_transfer(&db_path, &transfer),
+                        }
+                    },
+                    Message::TransactionSaved,
+                )
+            }
+            TransactionMessage::MergeDuplicates { keep, duplicate } => {
+                self.transaction_view.update(TransactionMessage::MergeDuplicates {
+                    keep,
+                    duplicate: duplicate.clone(),
+                });
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::merge_transactions(conn, keep, &duplicate))
+                    },
+                    Message::DuplicatesMerged,
+                )
+            }
+            TransactionMessage::DismissDuplicates(pair) => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::dismiss_duplicate(conn, pair))
+                    },
+                    Message::DuplicateDismissed,
+                )
+            }
+            TransactionMessage::DeleteTransaction(id) => {
+                self.transaction_view.update(TransactionMessage::DeleteTransaction(id));
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| {
+                            operations::delete_transaction(conn, &id.to_string())
+                        })
+                    },
+                    Message::TransactionDeleted,
+                )
+            }
+            message => {
+                self.transaction_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_accounts(&mut self, message: AccountMessage) -> Task<Message> {
+        match message {
+            AccountMessage::Save => {
+                let Some(account) = self.account_view.submit() else {
+                    return Task::none();
+                };
+
+                let is_new = !self.account_view.is_editing();
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        save_account(&db_path, &account, is_new)
+                    },
+                    Message::AccountSaved,
+                )
+            }
+            AccountMessage::Delete(id) => {
+                self.account_view.update(AccountMessage::Delete(id.clone()));
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::delete_account(conn, &id))
+                    },
+                    Message::AccountDeleted,
+                )
+            }
+            message => {
+                self.account_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_budgets(&mut self, message: BudgetMessage) -> Task<Message> {
+        match message {
+            BudgetMessage::Save => {
+                let Some(budget) = self.budget_view.submit() else {
+                    return Task::none();
+                };
+
+                let is_new = !self.budget_view.is_editing();
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        save_budget(&db_path, &budget, is_new)
+                    },
+                    Message::BudgetSaved,
+                )
+            }
+            BudgetMessage::EnvelopeModeToggled(enabled) => {
+                self.budget_view.update(BudgetMessage::EnvelopeModeToggled(enabled));
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::set_envelope_budgeting(conn, enabled))
+                    },
+                    Message::EnvelopeBudgetingSaved,
+                )
+            }
+            BudgetMessage::Assign(category_id) => {
+                let Some(allocation) = self.budget_view.submit_assignment(&category_id, &self.state) else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::set_allocation(conn, &allocation))
+                    },
+                    Message::AllocationSaved,
+                )
+            }
+            BudgetMessage::Delete(id) => {
+                self.budget_view.update(BudgetMessage::Delete(id.clone()));
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::delete_budget(conn, &id))
+                    },
+                    Message::BudgetDeleted,
+                )
+            }
+            message => {
+                self.budget_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_recurring(&mut self, message: RecurringMessage) -> Task<Message> {
+        match message {
+            RecurringMessage::Save => {
+                let Some(rule) = self.recurring_view.submit() else {
+                    return Task::none();
+                };
+
+                let is_new = !self.recurring_view.is_editing();
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        save_recurring_rule(&db_path, &rule, is_new)
+                    },
+                    Message::RecurringRuleSaved,
+                )
+            }
+            RecurringMessage::Delete(id) => {
+                self.recurring_view.update(RecurringMessage::Delete(id.clone()));
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::delete_recurring_rule(conn, &id))
+                    },
+                    Message::RecurringRuleDeleted,
+                )
+            }
+            message => {
+                self.recurring_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_rules(&mut self, message: RulesMessage) -> Task<Message> {
+        match message {
+            RulesMessage::Save => {
+                let Some(rule) = self.rules_view.submit(&self.state.category_rules) else {
+                    return Task::none();
+                };
+
+                let is_new = !self.rules_view.is_editing();
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        save_category_rule(&db_path, &rule, is_new)
+                    },
+                    Message::CategoryRuleSaved,
+                )
+            }
+            RulesMessage::Delete(id) => {
+                self.rules_view.update(RulesMessage::Delete(id.clone()));
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::delete_category_rule(conn, &id))
+                    },
+                    Message::CategoryRuleDeleted,
+                )
+            }
+            RulesMessage::MoveUp(id) => self.move_category_rule(&id, true),
+            RulesMessage::MoveDown(id) => self.move_category_rule(&id, false),
+            RulesMessage::Apply => {
+                let overwrite = self.rules_view.overwrite();
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::apply_category_rules(conn, overwrite))
+                    },
+                    Message::CategoryRulesApplied,
+                )
+            }
+            message => {
+                self.rules_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn move_category_rule(&self, rule_id: &str, up: bool) -> Task<Message> {
+        let Some(rule_ids) = rules::reordered(&self.state.category_rules, rule_id, up) else {
+            return Task::none();
+        };
+
+        let db_path = self.database_path.clone();
+        Task::perform(
+            async move {
+                with_connection(&db_path, |conn| operations::reorder_category_rules(conn, &rule_ids))
+            },
+            Message::CategoryRulesReordered,
+        )
+    }
+
+    fn update_import(&mut self, message: ImportMessage) -> Task<Message> {
+        match message {
+            ImportMessage::LoadFile => {
+                let path = PathBuf::from(self.import_view.path());
+                Task::perform(
+                    async move {
+                        std::fs::read(&path)
+                            .map(|bytes| import::decode_statement(&bytes))
+                            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+                    },
+                    |result| Message::Import(ImportMessage::FileLoaded(result)),
+                )
+            }
+            ImportMessage::SaveProfile => {
+                let Some(profile) = self.import_view.profile() else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::save_import_profile(conn, &profile))
+                    },
+                    Message::ImportProfileSaved,
+                )
+            }
+            ImportMessage::DeleteProfile(name) => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::delete_import_profile(conn, &name))
+                    },
+                    Message::ImportProfileSaved,
+                )
+            }
+            ImportMessage::Import => {
+                let batch = self.import_view.batch(&self.state);
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::import_transactions(conn, &batch))
+                    },
+                    Message::TransactionsImported,
+                )
+            }
+            message => {
+                self.import_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_export(&mut self, message: ExportMessage) -> Task<Message> {
+        match message {
+            ExportMessage::ExportCsv => {
+                let Some(filter) = self.export_view.filter() else {
+                    return Task::none();
+                };
+
+                let path = self.export_view.csv_path();
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        export_data(&db_path, |conn| export::export_transactions_csv(conn, &path, &filter))
+                            .map(|count| format!("Exported {} transactions to {}", count, path.display()))
+                    },
+                    Message::DataExported,
+                )
+            }
+            ExportMessage::ExportJson => {
+                let path = self.export_view.json_path();
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        export_data(&db_path, |conn| export::export_json(conn, &path))
+                            .map(|()| format!("Exported all data to {}", path.display()))
+                    },
+                    Message::DataExported,
+                )
+            }
+            message => {
+                self.export_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_reports(&mut self, message: ReportsMessage) -> Task<Message> {
+        match message {
+            ReportsMessage::GeneratePdf => {
+                let month = self.reports_view.month();
+                let path = self.reports_view.path();
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        let db = DatabaseConnection::new(&db_path)
+                            .map_err(|e| format!("Failed to open database: {}", e))?;
+                        report::export_monthly_report(db.get_connection(), &path, month)
+                            .map(|pages| format!("Saved a {}-page report to {}", pages, path.display()))
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::ReportGenerated,
+                )
+            }
+            message => {
+                self.reports_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_backup(&mut self, message: BackupMessage) -> Task<Message> {
+        match message {
+            BackupMessage::BackUpNow => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move { create_backup(&db_path) },
+                    Message::BackupCreated,
+                )
+            }
+            BackupMessage::SaveKeep => {
+                let Some(keep) = self.backup_view.keep() else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::set_backups_to_keep(conn, keep))
+                    },
+                    Message::BackupsToKeepSaved,
+                )
+            }
+            BackupMessage::ConfirmRestore => {
+                let Some(backup_path) = self.backup_view.take_restore() else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        backup::restore_backup(&backup_path, &db_path, &backup::backup_dir(&db_path))
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::BackupRestored,
+                )
+            }
+            message => {
+                self.backup_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_categories(&mut self, message: CategoriesMessage) -> Task<Message> {
+        match message {
+            CategoriesMessage::AddCategory => {
+                let Some(category) = self.categories_view.submit(&self.state.categories) else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::add_category(conn, &category))
+                    },
+                    Message::CategorySaved,
+                )
+            }
+            CategoriesMessage::SaveEdit => {
+                let Some(category) = self.categories_view.submit_edit(&self.state.categories) else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::update_category(conn, &category))
+                    },
+                    Message::CategorySaved,
+                )
+            }
+            CategoriesMessage::ConfirmRemoval => {
+                let Some(removal) = self.categories_view.take_removal() else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| match (removal.merge, &removal.replacement) {
+                            (true, Some(target)) => operations::merge_categories(conn, &removal.id, target),
+                            _ => operations::delete_category(conn, &removal.id, removal.replacement.as_deref()),
+                        })
+                    },
+                    Message::CategoryRemoved,
+                )
+            }
+            CategoriesMessage::MoveCategory(id, choice) => {
+                let Some(parent_id) = self.categories_view.validate_move(&self.state.categories, &id, &choice) else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::set_category_parent(conn, &id, parent_id.as_deref()))
+                    },
+                    Message::CategorySaved,
+                )
+            }
+            message => {
+                self.categories_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_tags(&mut self, message: TagsMessage) -> Task<Message> {
+        match message {
+            TagsMessage::AddTag => {
+                let Some(tag) = self.tags_view.submit(&self.state.tags) else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::add_tag(conn, &tag))
+                    },
+                    Message::TagSaved,
+                )
+            }
+            TagsMessage::SaveRename => {
+                let Some((id, name)) = self.tags_view.submit_rename(&self.state.tags) else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::rename_tag(conn, &id, &name))
+                    },
+                    Message::TagSaved,
+                )
+            }
+            TagsMessage::Delete(id) => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::delete_tag(conn, &id))
+                    },
+                    Message::TagDeleted,
+                )
+            }
+            message => {
+                self.tags_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_payees(&mut self, message: PayeesMessage) -> Task<Message> {
+        match message {
+            PayeesMessage::Save => {
+                let Some((payee, is_new)) = self.payees_view.submit(&self.state.payees) else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| {
+                            if is_new {
+                                operations::add_payee(conn, &payee)
+                            } else {
+                                operations::update_payee(conn, &payee)
+                            }
+                        })
+                    },
+                    Message::PayeeSaved,
+                )
+            }
+            PayeesMessage::Delete(id) => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::delete_payee(conn, &id))
+                    },
+                    Message::PayeeDeleted,
+                )
+            }
+            PayeesMessage::Apply => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, operations::apply_payees)
+                    },
+                    Message::PayeesApplied,
+                )
+            }
+            message => {
+                self.payees_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_currencies(&mut self, message: CurrencyMessage) -> Task<Message> {
+        match message {
+            CurrencyMessage::BaseCurrencySelected(currency) => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::set_base_currency(conn, currency))
+                    },
+                    Message::BaseCurrencySaved,
+                )
+            }
+            CurrencyMessage::AddRate => {
+                let Some(rate) = self.currency_view.submit() else {
+                    return Task::none();
+                };
+
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::add_exchange_rate(conn, &rate))
+                    },
+                    Message::ExchangeRateSaved,
+                )
+            }
+            CurrencyMessage::DeleteRate(id) => {
+                let db_path = self.database_path.clone();
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::delete_exchange_rate(conn, &id))
+                    },
+                    Message::ExchangeRateDeleted,
+                )
+            }
+            message => {
+                self.currency_view.update(message);
+                Task::none()
+            }
+        }
+    }
+
+    fn update_notifications(&mut self, message: NotificationsMessage) -> Task<Message> {
+        let db_path = self.database_path.clone();
+        match message {
+            NotificationsMessage::MarkAllRead => Task::perform(
+                async move {
+                    with_connection(&db_path, operations::mark_notifications_read)
+                },
+                Message::NotificationsUpdated,
+            ),
+            NotificationsMessage::Dismiss(id) => Task::perform(
+                async move {
+                    with_connection(&db_path, |conn| operations::delete_notification(conn, &id))
+                },
+                Message::NotificationsUpdated,
+            ),
+            NotificationsMessage::ClearAll => Task::perform(
+                async move {
+                    with_connection(&db_path, operations::clear_notifications)
+                },
+                Message::NotificationsUpdated,
+            ),
+            NotificationsMessage::DesktopToggled(enabled) => {
+                self.notifications_view.update(NotificationsMessage::DesktopToggled(enabled));
+                Task::perform(
+                    async move {
+                        with_connection(&db_path, |conn| operations::set_desktop_notifications(conn, enabled))
+                    },
+                    Message::DesktopNotificationsSaved,
+                )
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        if let Some(error) = &self.database_error {
+            return container(text(error).size(16).color(Color::from_rgb(0.8, 0.0, 0.0)))
+                .padding(20)
+                .center(Length::Fill)
+                .into();
+        }
+
+        let unread = self.state.notifications.iter().filter(|n| !n.read).count();
+        let nav = Nav::new(self.state.page.clone(), unread)
+            .view()
+            .map(Message::ChangePage);
+
+        let page: Element<Message> = match self.state.page {
+            Page::Dashboard => Dashboard::new(
+                &self.state.transactions,
+                &self.state.categories,
+                &self.state.accounts,
+                &self.state.budgets,
+                &self.state.budget_periods,
+                &self.state.converter,
+                self.state.dashboard_range,
+            )
+            .view()
+            .map(Message::Dashboard),
+            Page::Transactions => self.transaction_view
+                .view(&self.state)
+                .map(Message::Transaction),
+            Page::Accounts => self.account_view
+                .view(&self.state)
+                .map(Message::Account),
+            Page::Categories => self.categories_view
+                .view(&self.state)
+                .map(Message::Categories),
+            Page::Tags => self.tags_view
+                .view(&self.state)
+                .map(Message::Tags),
+            Page::Payees => self.payees_view
+                .view(&self.state)
+                .map(Message::Payees),
+            Page::Budgets => self.budget_view
+                .view(&self.state)
+                .map(Message::Budget),
+            Page::Recurring => self.recurring_view
+                .view(&self.state)
+                .map(Message::Recurring),
+            Page::Import => self.import_view
+                .view(&self.state)
+                .map(Message::Import),
+            Page::Export => self.export_view
+                .view(&self.state)
+                .map(Message::Export),
+            Page::Backup => self.backup_view
+                .view()
+                .map(Message::Backup),
+            Page::Rules => self.rules_view
+                .view(&self.state)
+                .map(Message::Rules),
+            Page::Currencies => self.currency_view
+                .view(&self.state)
+                .map(Message::Currency),
+            Page::Reports => self.reports_view
+                .view(&self.state)
+                .map(Message::Reports),
+            Page::Notifications => self.notifications_view
+                .view(&self.state)
+                .map(Message::Notifications),
+        };
+
+        container(column![nav, page])
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn init_database(&self) -> DbResult<()> {
+        let db = DatabaseConnection::new(&self.database_path)?;
+        db.initialize_schema()?;
+
+        // Catch up on recurring transactions that fell due while closed
+        operations::generate_due_transactions(db.get_connection(), today())?;
+
+        let keep = operations::get_backups_to_keep(db.get_connection())?;
+        if let Err(e) = backup::rotate_backups(&backup::backup_dir(&self.database_path), keep) {
+            eprintln!("Failed to rotate backups: {}", e);
+        }
+        Ok(())
+    }
+}
+
+fn with_connection<T, F>(db_path: &PathBuf, f: F) -> Result<T, String>
+where
+    F: FnOnce(&Connection) -> rusqlite::Result<T>,
+{
+    let db = DatabaseConnection::new(db_path)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    f(db.get_connection()).map_err(|e| format!("Database error: {}", e))
+}
+
+fn export_data<T, F>(db_path: &PathBuf, f: F) -> Result<T, String>
+where
+    F: FnOnce(&Connection) -> Result<T, export::ExportError>,
+{
+    let db = DatabaseConnection::new(db_path)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    f(db.get_connection()).map_err(|e| e.to_string())
+}
+
+fn load_backups(db_path: &PathBuf) -> Result<(Vec<backup::BackupInfo>, usize), String> {
+    let keep = with_connection(db_path, operations::get_backups_to_keep)?;
+    let backups = backup::list_backups(&backup::backup_dir(db_path)).map_err(|e| e.to_string())?;
+
+    Ok((backups, keep))
+}
+
+/// Takes a backup, then drops the oldest beyond the number to keep.
+fn create_backup(db_path: &PathBuf) -> Result<PathBuf, String> {
+    let dir = backup::backup_dir(db_path);
+    let keep = with_connection(db_path, operations::get_backups_to_keep)?;
+
+    let db = DatabaseConnection::new(db_path)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let path = backup::create_backup(db.get_connection(), &dir).map_err(|e| e.to_string())?;
+    backup::rotate_backups(&dir, keep).map_err(|e| e.to_string())?;
+
+    Ok(path)
+}
+
+fn save_transaction(db_path: &PathBuf, transaction: &Transaction, is_new: bool) -> Result<(), String> {
+    with_connection(db_path, |conn| {
+        if is_new {
+            let mut transaction = transaction.clone();
+            operations::categorize_transactions(conn, std::slice::from_mut(&mut transaction))?;
+            operations::add_transaction(conn, &transaction)
+        } else {
+            let mut transaction = transaction.clone();
+            operations::assign_payees(conn, std::slice::from_mut(&mut transaction))?;
+            operations::update_transaction(conn, &transaction)
+        }
+    })
+}
+
+fn save_transfer(db_path: &PathBuf, transfer: &Transfer) -> Result<(), String> {
+    with_connection(db_path, |conn| operations::save_transfer(conn, transfer))
+}
+
+fn save_account(db_path: &PathBuf, account: &Account, is_new: bool) -> Result<(), String> {
+    with_connection(db_path, |conn| {
+        if is_new {
+            operations::add_account(conn, account)
+        } else {
+            operations::update_account(conn, account)
+        }
+    })
+}
+
+/// Saves a rule and generates any of its occurrences that are already due.
+fn save_recurring_rule(db_path: &PathBuf, rule: &RecurringRule, is_new: bool) -> Result<(), String> {
+    with_connection(db_path, |conn| {
+        if is_new {
+            operations::add_recurring_rule(conn, rule)?;
+        } else {
+            operations::update_recurring_rule(conn, rule)?;
+        }
+        operations::generate_due_transactions(conn, today()).map(|_| ())
+    })
+}
+
+/// Raises alerts for budgets whose spending has reached a new threshold,
+/// also showing them on the desktop when that is turned on. Failing to
+/// reach the desktop does not lose the alerts, which are already in the
+/// notification centre.
+fn check_budget_alerts(db_path: &PathBuf) -> Result<Vec<Notification>, String> {
+    let (notifications, desktop) = with_connection(db_path, |conn| {
+        let notifications = operations::check_budget_alerts(conn, chrono::Local::now().naive_local())?;
+        Ok((notifications, operations::get_desktop_notifications(conn)?))
+    })?;
+
+    if desktop && !notifications.is_empty() {
+        if let Err(e) = desktop::notify(&notifications) {
+            eprintln!("Failed to show desktop notifications: {}", e);
+        }
+    }
+
+    Ok(notifications)
+}
+
+fn save_budget(db_path: &PathBuf, budget: &Budget, is_new: bool) -> Result<(), String> {
+    with_connection(db_path, |conn| {
+        if is_new {
+            operations::add_budget(conn, budget)
+        } else {
+            operations::update_budget(conn, budget)
+        }
+    })
+}
+
+fn save_category_rule(db_path: &PathBuf, rule: &CategoryRule, is_new: bool) -> Result<(), String> {
+    with_connection(db_path, |conn| {
+        if is_new {
+            operations::add_category_rule(conn, rule)
+        } else {
+            operations::update_category_rule(conn, rule)
+        }
+    })
+}
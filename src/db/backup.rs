@@ -0,0 +1,293 @@
+use chrono::{Local, NaiveDateTime};
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use std::path::{Path, PathBuf};
+
+use crate::db::migrations::{self, SCHEMA_VERSION};
+use crate::db::DbError;
+
+/// How many backups rotation keeps unless the user picks another number.
+pub const DEFAULT_BACKUPS_TO_KEEP: usize = 10;
+
+const FILE_PREFIX: &str = "budget-";
+const FILE_EXTENSION: &str = "db";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("File error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0} does not exist")]
+    NotFound(PathBuf),
+    #[error("{0} is not a Budget Tracker database")]
+    NotABudgetDatabase(PathBuf),
+    #[error("Backup schema version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion { found: i64, supported: i64 },
+    #[error("Failed to upgrade the restored database: {0}")]
+    Migration(#[from] DbError),
+}
+
+/// A backup file and when it was taken, read from its name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub created: NaiveDateTime,
+    pub size: u64,
+}
+
+/// Backups are kept in a `backups` folder next to the database.
+pub fn backup_dir(db_path: &Path) -> PathBuf {
+    db_path.parent().unwrap_or(Path::new(".")).join("backups")
+}
+
+/// Copies the open database into a new timestamped file in `dir` using
+/// SQLite's online backup API, which gives a consistent copy even while
+/// the database is in use.
+pub fn create_backup(conn: &Connection, dir: &Path) -> Result<PathBuf, BackupError> {
+    std::fs::create_dir_all(dir)?;
+
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let mut path = dir.join(format!("{}{}.{}", FILE_PREFIX, timestamp, FILE_EXTENSION));
+    let mut counter = 1;
+    while path.exists() {
+        path = dir.join(format!("{}{}-{}.{}", FILE_PREFIX, timestamp, counter, FILE_EXTENSION));
+        counter += 1;
+    }
+
+    conn.backup(DatabaseName::Main, &path, None)?;
+    Ok(path)
+}
+
+/// The time in a backup's file name and the counter added to backups taken
+/// in the same second, 0 for the first.
+fn parse_file_name(path: &Path) -> Option<(NaiveDateTime, u32)> {
+    if path.extension()? != FILE_EXTENSION {
+        return None;
+    }
+
+    let stem = path.file_stem()?.to_str()?.strip_prefix(FILE_PREFIX)?;
+    let (timestamp, counter) = match stem.get(15..) {
+        Some("") => (stem, 0),
+        Some(counter) => (stem.get(..15)?, counter.strip_prefix('-')?.parse().ok()?),
+        None => return None,
+    };
+
+    let created = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+    Some((created, counter))
+}
+
+/// The backups in `dir`, newest first. Other files are ignored.
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupInfo>, BackupError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if let Some((created, counter)) = parse_file_name(&path) {
+            let backup = BackupInfo {
+                path,
+                created,
+                size: entry.metadata()?.len(),
+            };
+            backups.push((counter, backup));
+        }
+    }
+
+    backups.sort_by(|(a_counter, a), (b_counter, b)| {
+        b.created.cmp(&a.created).then(b_counter.cmp(a_counter))
+    });
+    Ok(backups.into_iter().map(|(_, backup)| backup).collect())
+}
+
+/// Deletes all but the newest `keep` backups in `dir`, returning how many
+/// were removed.
+pub fn rotate_backups(dir: &Path, keep: usize) -> Result<usize, BackupError> {
+    let backups = list_backups(dir)?;
+    let mut removed = 0;
+
+    for backup in backups.iter().skip(keep) {
+        std::fs::remove_file(&backup.path)?;
+        removed += 1;
+    }
+
+    Ok(removed)
+}
+
+/// Checks that `path` is a Budget Tracker database this build can open,
+/// returning its schema version.
+pub fn validate_backup(path: &Path) -> Result<i64, BackupError> {
+    if !path.is_file() {
+        return Err(BackupError::NotFound(path.to_path_buf()));
+    }
+
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|_| BackupError::NotABudgetDatabase(path.to_path_buf()))?;
+    let version = migrations::schema_version(&conn)
+        .map_err(|_| BackupError::NotABudgetDatabase(path.to_path_buf()))?;
+
+    if version > SCHEMA_VERSION {
+        return Err(BackupError::UnsupportedVersion {
+            found: version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    if version < 1 {
+        return Err(BackupError::NotABudgetDatabase(path.to_path_buf()));
+    }
+
+    let has_transactions: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'transactions')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_transactions {
+        return Err(BackupError::NotABudgetDatabase(path.to_path_buf()));
+    }
+
+    Ok(version)
+}
+
+/// Replaces the database at `db_path` with the backup at `backup_path`.
+/// The backup is validated first and the current data is itself backed up
+/// to `dir`, so a restore can be undone; backups from older versions are
+/// then migrated to the current schema. If that upgrade fails the safety
+/// backup is put back, leaving the database as it was. Returns the safety
+/// backup's path.
+pub fn restore_backup(backup_path: &Path, db_path: &Path, dir: &Path) -> Result<PathBuf, BackupError> {
+    validate_backup(backup_path)?;
+
+    let mut live = Connection::open(db_path)?;
+    let safety_backup = create_backup(&live, dir)?;
+
+    live.restore(DatabaseName::Main, backup_path, None::<fn(rusqlite::backup::Progress)>)?;
+    if let Err(e) = migrations::migrate(&live) {
+        live.restore(DatabaseName::Main, &safety_backup, None::<fn(rusqlite::backup::Progress)>)?;
+        return Err(e.into());
+    }
+
+    Ok(safety_backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::operations::{add_transaction, get_all_transactions};
+    use crate::models::testing::{date, expense, money};
+    use crate::models::Transaction;
+
+    /// A fresh directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let path = std::env::temp_dir().join(format!("budget-backup-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn database(path: &Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        migrations::migrate(&conn).unwrap();
+        conn
+    }
+
+    /// Turns a current database back into version 17 by undoing the last
+    /// migration.
+    fn downgrade_to_v17(conn: &Connection) {
+        conn.execute_batch(
+            "DROP TABLE budget_alert_thresholds;
+            DROP TABLE budget_alerts;
+            DROP TABLE notifications;
+            PRAGMA user_version = 17;",
+        ).unwrap();
+    }
+
+    fn descriptions(conn: &Connection) -> Vec<String> {
+        let mut descriptions: Vec<String> = get_all_transactions(conn).unwrap()
+            .into_iter()
+            .map(|t| t.description)
+            .collect();
+        descriptions.sort();
+        descriptions
+    }
+
+    fn add(conn: &Connection, description: &str) {
+        let transaction = Transaction { description: description.to_string(), ..expense(money(100), date(2026, 1, 1)) };
+        add_transaction(conn, &transaction).unwrap();
+    }
+
+    #[test]
+    fn only_budget_databases_this_build_can_open_are_valid() {
+        let temp = TempDir::new();
+        assert!(matches!(validate_backup(&temp.0.join("missing.db")), Err(BackupError::NotFound(_))));
+
+        let text = temp.0.join("notes.db");
+        std::fs::write(&text, "not a database").unwrap();
+        assert!(matches!(validate_backup(&text), Err(BackupError::NotABudgetDatabase(_))));
+
+        let empty = temp.0.join("empty.db");
+        Connection::open(&empty).unwrap().execute_batch("CREATE TABLE other (id INTEGER)").unwrap();
+        assert!(matches!(validate_backup(&empty), Err(BackupError::NotABudgetDatabase(_))));
+
+        let newer = temp.0.join("newer.db");
+        database(&newer).pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+        assert!(matches!(
+            validate_backup(&newer),
+            Err(BackupError::UnsupportedVersion { found, supported }) if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+        ));
+
+        let current = temp.0.join("current.db");
+        database(&current);
+        assert_eq!(validate_backup(&current).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn restoring_an_older_backup_migrates_it_and_keeps_a_safety_copy() {
+        let temp = TempDir::new();
+        let dir = temp.0.join("backups");
+        let db_path = temp.0.join("budget.db");
+        let conn = database(&db_path);
+        add(&conn, "Before backup");
+        let backup = create_backup(&conn, &dir).unwrap();
+        downgrade_to_v17(&Connection::open(&backup).unwrap());
+        add(&conn, "After backup");
+        drop(conn);
+
+        let safety = restore_backup(&backup, &db_path, &dir).unwrap();
+        let conn = Connection::open(&db_path).unwrap();
+        assert_eq!(migrations::schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        assert_eq!(descriptions(&conn), vec!["Before backup"]);
+        assert_eq!(descriptions(&Connection::open(&safety).unwrap()), vec!["After backup", "Before backup"]);
+        assert_eq!(list_backups(&dir).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn a_failed_upgrade_puts_the_current_data_back() {
+        let temp = TempDir::new();
+        let dir = temp.0.join("backups");
+        let db_path = temp.0.join("budget.db");
+        let conn = database(&db_path);
+        add(&conn, "Before backup");
+        let backup = create_backup(&conn, &dir).unwrap();
+        // Claims version 17 but already has the tables version 18 creates
+        Connection::open(&backup).unwrap().pragma_update(None, "user_version", 17).unwrap();
+        add(&conn, "After backup");
+        drop(conn);
+
+        assert!(matches!(restore_backup(&backup, &db_path, &dir), Err(BackupError::Migration(_))));
+        let conn = Connection::open(&db_path).unwrap();
+        assert_eq!(migrations::schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        assert_eq!(descriptions(&conn), vec!["After backup", "Before backup"]);
+    }
+}
@@ -0,0 +1,742 @@
+use crate::db::{schema, DbError, DbResult};
+use crate::models::TransactionType;
+use crate::utils::{format_date, get_month_end, get_month_start};
+use chrono::{DateTime, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use uuid::Uuid;
+
+type Migration = fn(&Connection) -> SqlResult<()>;
+
+/// Schema migrations in order. Entry `n` upgrades a database whose
+/// `user_version` is `n` to version `n + 1`.
+const MIGRATIONS: &[Migration] = &[
+    migrate_to_v1,
+    migrate_to_v2,
+    migrate_to_v3,
+    migrate_to_v4,
+    migrate_to_v5,
+    migrate_to_v6,
+    migrate_to_v7,
+    migrate_to_v8,
+    migrate_to_v9,
+    migrate_to_v10,
+    migrate_to_v11,
+    migrate_to_v12,
+    migrate_to_v13,
+    migrate_to_v14,
+    migrate_to_v15,
+    migrate_to_v16,
+    migrate_to_v17,
+    migrate_to_v18,
+];
+
+/// The schema version this build reads and writes.
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Colour given to categories created from free-text legacy categories.
+const LEGACY_CATEGORY_COLOR: &str = "#9E9E9E";
+
+pub fn schema_version(conn: &Connection) -> SqlResult<i64> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// Brings the database up to `SCHEMA_VERSION`. Each migration runs in its
+/// own transaction together with the version bump, so a failed upgrade
+/// leaves the database at the last good version.
+pub fn migrate(conn: &Connection) -> DbResult<()> {
+    let version = schema_version(conn)?;
+    if version > SCHEMA_VERSION {
+        return Err(DbError::UnsupportedVersion {
+            found: version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let tx = conn.unchecked_transaction()?;
+        migration(&tx)?;
+        tx.pragma_update(None, "user_version", index as i64 + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+fn table_exists(conn: &Connection, table: &str) -> SqlResult<bool> {
+    conn.query_row(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|found| found.is_some())
+}
+
+fn table_columns(conn: &Connection, table: &str) -> SqlResult<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    columns.collect()
+}
+
+/// Version 1 is the first versioned schema. Databases created before it may
+/// hold any of the earlier ad-hoc layouts, so existing tables are moved
+/// aside, the canonical tables are created and the rows are copied over.
+fn migrate_to_v1(conn: &Connection) -> SqlResult<()> {
+    let mut legacy_tables = Vec::new();
+    for table in ["categories", "transactions", "budgets"] {
+        if table_exists(conn, table)? {
+            let legacy = format!("legacy_{}", table);
+            conn.execute_batch(&format!("ALTER TABLE {} RENAME TO {}", table, legacy))?;
+            legacy_tables.push(legacy);
+        }
+    }
+
+    schema::create_tables(conn)?;
+
+    if table_exists(conn, "legacy_categories")? {
+        copy_legacy_categories(conn)?;
+    }
+    schema::initialize_default_categories(conn)?;
+
+    if table_exists(conn, "legacy_transactions")? {
+        copy_legacy_transactions(conn)?;
+    }
+    if table_exists(conn, "legacy_budgets")? {
+        copy_legacy_budgets(conn)?;
+    }
+
+    for legacy in legacy_tables {
+        conn.execute_batch(&format!("DROP TABLE {}", legacy))?;
+    }
+
+    Ok(())
+}
+
+fn copy_legacy_categories(conn: &Connection) -> SqlResult<()> {
+    let columns = table_columns(conn, "legacy_categories")?;
+    let icon = if columns.iter().any(|c| c == "icon") { "icon" } else { "NULL" };
+
+    conn.execute(
+        &format!(
+            "INSERT INTO categories (id, name, color, icon)
+             SELECT id, name, color, {} FROM legacy_categories",
+            icon
+        ),
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn copy_legacy_transactions(conn: &Connection) -> SqlResult<()> {
+    let columns = table_columns(conn, "legacy_transactions")?;
+    let has = |name: &str| columns.iter().any(|c| c == name);
+
+    // The original single-screen app stored a free-text category and an
+    // RFC 3339 timestamp per row.
+    if has("category") {
+        return convert_app_transactions(conn);
+    }
+
+    let transaction_type = if has("transaction_type") {
+        "transaction_type"
+    } else if has("type") {
+        "type"
+    } else {
+        "'Expense'"
+    };
+    let category_id = if has("category_id") { "category_id" } else { "NULL" };
+
+    conn.execute(
+        &format!(
+            "INSERT INTO transactions (id, amount, description, date, transaction_type, category_id)
+             SELECT id, amount, COALESCE(description, ''), date, {}, {} FROM legacy_transactions",
+            transaction_type, category_id
+        ),
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Converts rows written by the original app. Amounts there were summed
+/// into a single balance, so the sign is what told income from spending:
+/// negative rows become expenses and the rest income, which keeps the
+/// balance unchanged. Each distinct category name becomes a category.
+fn convert_app_transactions(conn: &Connection) -> SqlResult<()> {
+    let rows = {
+        let mut stmt = conn.prepare(
+            "SELECT id, description, amount, category, date FROM legacy_transactions"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                row.get::<_, f64>(2)?,
+                row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+        rows.collect::<SqlResult<Vec<_>>>()?
+    };
+
+    for (id, description, amount, category, date) in rows {
+        let date = parse_legacy_date(&date).ok_or_else(|| {
+            rusqlite::Error::InvalidColumnType(4, "date".to_string(), rusqlite::types::Type::Text)
+        })?;
+        let category_id = legacy_category_id(conn, &category)?;
+        let (transaction_type, amount) = if amount < 0.0 {
+            (TransactionType::Expense, -amount)
+        } else {
+            (TransactionType::Income, amount)
+        };
+
+        conn.execute(
+            "INSERT INTO transactions (id, amount, description, date, transaction_type, category_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                amount,
+                description,
+                format_date(&date),
+                transaction_type.to_string(),
+                category_id,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Legacy timestamps were stored with the local offset, so the calendar
+/// date written in the string is the one the user saw.
+fn parse_legacy_date(value: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.date_naive())
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok())
+}
+
+fn legacy_category_id(conn: &Connection, name: &str) -> SqlResult<Option<String>> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(None);
+    }
+
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM categories WHERE name = ?1 COLLATE NOCASE",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    match existing {
+        Some(id) => Ok(Some(id)),
+        None => {
+            let id = Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO categories (id, name, color) VALUES (?1, ?2, ?3)",
+                params![id, name, LEGACY_CATEGORY_COLOR],
+            )?;
+            Ok(Some(id))
+        }
+    }
+}
+
+fn copy_legacy_budgets(conn: &Connection) -> SqlResult<()> {
+    let columns = table_columns(conn, "legacy_budgets")?;
+
+    if columns.iter().any(|c| c == "start_date") {
+        conn.execute(
+            "INSERT INTO budgets (id, category_id, amount, start_date, end_date, name)
+             SELECT id, category_id, amount, start_date, end_date, name FROM legacy_budgets",
+            [],
+        )?;
+        return Ok(());
+    }
+
+    // Budgets keyed on a named period have no date range; they become
+    // budgets for the current month named after their period.
+    conn.execute(
+        "INSERT INTO budgets (id, category_id, amount, start_date, end_date, name)
+         SELECT id, category_id, amount, ?1, ?2, period FROM legacy_budgets",
+        params![
+            format_date(&get_month_start()),
+            format_date(&get_month_end()),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Version 2 stores amounts as integer minor units with a currency code
+/// instead of `REAL`, so sums are exact. Every earlier amount was entered in
+/// dollars.
+fn migrate_to_v2(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE transactions_v2 (
+            id TEXT PRIMARY KEY,
+            amount INTEGER NOT NULL,
+            currency TEXT NOT NULL DEFAULT 'USD',
+            description TEXT NOT NULL DEFAULT '',
+            date TEXT NOT NULL,
+            transaction_type TEXT NOT NULL,
+            category_id TEXT,
+            FOREIGN KEY (category_id) REFERENCES categories (id)
+        );
+        INSERT INTO transactions_v2 (id, amount, currency, description, date, transaction_type, category_id)
+            SELECT id, CAST(ROUND(amount * 100) AS INTEGER), 'USD', description, date, transaction_type, category_id
+            FROM transactions;
+        DROP TABLE transactions;
+        ALTER TABLE transactions_v2 RENAME TO transactions;
+
+        CREATE TABLE budgets_v2 (
+            id TEXT PRIMARY KEY,
+            category_id TEXT,
+            amount INTEGER NOT NULL,
+            currency TEXT NOT NULL DEFAULT 'USD',
+            start_date TEXT NOT NULL,
+            end_date TEXT NOT NULL,
+            name TEXT NOT NULL,
+            FOREIGN KEY (category_id) REFERENCES categories (id)
+        );
+        INSERT INTO budgets_v2 (id, category_id, amount, currency, start_date, end_date, name)
+            SELECT id, category_id, CAST(ROUND(amount * 100) AS INTEGER), 'USD', start_date, end_date, name
+            FROM budgets;
+        DROP TABLE budgets;
+        ALTER TABLE budgets_v2 RENAME TO budgets;",
+    )
+}
+
+/// Version 3 adds dated exchange rates and a key/value settings table, which
+/// holds the base currency totals are reported in.
+fn migrate_to_v3(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE exchange_rates (
+            id TEXT PRIMARY KEY,
+            from_currency TEXT NOT NULL,
+            to_currency TEXT NOT NULL,
+            rate REAL NOT NULL CHECK (rate > 0),
+            effective_date TEXT NOT NULL
+        );
+        CREATE INDEX idx_exchange_rates_pair
+            ON exchange_rates (from_currency, to_currency, effective_date);
+
+        CREATE TABLE settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )
+}
+
+/// Version 4 adds accounts with an opening balance, and lets each
+/// transaction name the account it belongs to.
+fn migrate_to_v4(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE accounts (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            account_type TEXT NOT NULL,
+            opening_balance INTEGER NOT NULL DEFAULT 0,
+            currency TEXT NOT NULL DEFAULT 'USD',
+            opening_date TEXT NOT NULL
+        );
+
+        ALTER TABLE transactions ADD COLUMN account_id TEXT REFERENCES accounts (id);
+        CREATE INDEX idx_transactions_account ON transactions (account_id);",
+    )
+}
+
+/// Version 5 links the two legs of a transfer between accounts.
+fn migrate_to_v5(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "ALTER TABLE transactions ADD COLUMN transfer_id TEXT;
+        CREATE INDEX idx_transactions_transfer ON transactions (transfer_id);",
+    )
+}
+
+/// Version 6 adds recurring transaction rules. `end_date` and
+/// `occurrence_count` are both NULL for rules that never end.
+fn migrate_to_v6(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE recurring_rules (
+            id TEXT PRIMARY KEY,
+            description TEXT NOT NULL DEFAULT '',
+            amount INTEGER NOT NULL,
+            currency TEXT NOT NULL DEFAULT 'USD',
+            transaction_type TEXT NOT NULL,
+            category_id TEXT REFERENCES categories (id),
+            account_id TEXT REFERENCES accounts (id),
+            frequency TEXT NOT NULL,
+            interval INTEGER NOT NULL DEFAULT 1,
+            nth_week INTEGER,
+            weekday INTEGER,
+            start_date TEXT NOT NULL,
+            end_date TEXT,
+            occurrence_count INTEGER,
+            last_generated TEXT
+        );",
+    )
+}
+
+/// Version 7 stores CSV import mappings, one per bank, as JSON.
+fn migrate_to_v7(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE import_profiles (
+            name TEXT PRIMARY KEY,
+            mapping TEXT NOT NULL,
+            account_id TEXT REFERENCES accounts (id)
+        );",
+    )
+}
+
+/// Version 8 records the bank's id for imported transactions so that
+/// overlapping statements are only imported once per account.
+fn migrate_to_v8(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "ALTER TABLE transactions ADD COLUMN external_id TEXT;
+        CREATE INDEX idx_transactions_external ON transactions (account_id, external_id);",
+    )
+}
+
+/// Version 9 remembers pairs of transactions the user has confirmed are not
+/// duplicates, with the smaller id first.
+fn migrate_to_v9(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE dismissed_duplicates (
+            first_id TEXT NOT NULL,
+            second_id TEXT NOT NULL,
+            PRIMARY KEY (first_id, second_id)
+        );",
+    )
+}
+
+/// Version 10 adds rules that categorise transactions. Unset conditions are
+/// NULL; both amount bounds share `currency`.
+fn migrate_to_v10(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE category_rules (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            priority INTEGER NOT NULL DEFAULT 0,
+            match_kind TEXT NOT NULL,
+            pattern TEXT NOT NULL DEFAULT '',
+            min_amount INTEGER,
+            max_amount INTEGER,
+            currency TEXT NOT NULL DEFAULT 'USD',
+            account_id TEXT REFERENCES accounts (id),
+            category_id TEXT NOT NULL REFERENCES categories (id)
+        );",
+    )
+}
+
+/// Version 11 lets categories have a parent. The seeded defaults are given
+/// common subcategories and Utilities moves under Housing; a default the
+/// user has renamed or removed is left as it is.
+fn migrate_to_v11(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "ALTER TABLE categories ADD COLUMN parent_id TEXT REFERENCES categories (id);
+        CREATE INDEX idx_categories_parent ON categories (parent_id);",
+    )?;
+
+    let is_default = |id: &str, name: &str| -> SqlResult<bool> {
+        conn.query_row(
+            "SELECT 1 FROM categories WHERE id = ?1 AND name = ?2",
+            [id, name],
+            |_| Ok(()),
+        )
+        .optional()
+        .map(|found| found.is_some())
+    };
+
+    if is_default("cat_1", "Housing")? && is_default("cat_6", "Utilities")? {
+        conn.execute("UPDATE categories SET parent_id = 'cat_1' WHERE id = 'cat_6'", [])?;
+    }
+
+    let subcategories = [
+        ("cat_1", "Housing", "cat_11", "Rent & Mortgage", "#7BAAF7"),
+        ("cat_2", "Food", "cat_12", "Groceries", "#57BB8A"),
+        ("cat_2", "Food", "cat_13", "Restaurants", "#1E7145"),
+        ("cat_3", "Transportation", "cat_14", "Fuel", "#FFB74D"),
+        ("cat_3", "Transportation", "cat_15", "Public Transport", "#F57C00"),
+        ("cat_9", "Income", "cat_16", "Salary", "#33B679"),
+    ];
+    for (parent_id, parent_name, id, name, color) in subcategories {
+        if is_default(parent_id, parent_name)? {
+            conn.execute(
+                "INSERT OR IGNORE INTO categories (id, name, color, parent_id) VALUES (?1, ?2, ?3, ?4)",
+                params![id, name, color, parent_id],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Version 12 adds split lines, which divide a transaction's amount between
+/// categories. Amounts share the transaction's currency.
+fn migrate_to_v12(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE transaction_splits (
+            transaction_id TEXT NOT NULL REFERENCES transactions (id),
+            position INTEGER NOT NULL,
+            category_id TEXT REFERENCES categories (id),
+            amount INTEGER NOT NULL,
+            memo TEXT NOT NULL DEFAULT '',
+            PRIMARY KEY (transaction_id, position)
+        );
+        CREATE INDEX idx_transaction_splits_category ON transaction_splits (category_id);",
+    )
+}
+
+/// Version 13 adds tags, free-form labels of which a transaction can carry
+/// any number.
+fn migrate_to_v13(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE tags (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE
+        );
+        CREATE TABLE transaction_tags (
+            transaction_id TEXT NOT NULL REFERENCES transactions (id),
+            tag_id TEXT NOT NULL REFERENCES tags (id),
+            PRIMARY KEY (transaction_id, tag_id)
+        );
+        CREATE INDEX idx_transaction_tags_tag ON transaction_tags (tag_id);",
+    )
+}
+
+/// Version 14 adds payees, which group the varying descriptions banks give
+/// one merchant, and lets transactions and category rules refer to one.
+fn migrate_to_v14(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE payees (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            category_id TEXT REFERENCES categories (id)
+        );
+        CREATE TABLE payee_aliases (
+            payee_id TEXT NOT NULL REFERENCES payees (id),
+            pattern TEXT NOT NULL,
+            PRIMARY KEY (payee_id, pattern)
+        );
+        ALTER TABLE transactions ADD COLUMN payee_id TEXT REFERENCES payees (id);
+        CREATE INDEX idx_transactions_payee ON transactions (payee_id);
+        ALTER TABLE category_rules ADD COLUMN payee_id TEXT REFERENCES payees (id);",
+    )
+}
+
+/// Version 15 lets budgets renew each month, quarter or year, optionally
+/// carrying what is left into the next period, and records each period's
+/// allocation, spending and carry.
+fn migrate_to_v15(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "ALTER TABLE budgets ADD COLUMN renewal TEXT;
+        ALTER TABLE budgets ADD COLUMN rollover INTEGER NOT NULL DEFAULT 0;
+        CREATE TABLE budget_periods (
+            budget_id TEXT NOT NULL REFERENCES budgets (id),
+            start_date TEXT NOT NULL,
+            end_date TEXT NOT NULL,
+            allocated INTEGER NOT NULL,
+            spent INTEGER NOT NULL,
+            carried_in INTEGER NOT NULL,
+            currency TEXT NOT NULL,
+            PRIMARY KEY (budget_id, start_date)
+        );",
+    )
+}
+
+/// Version 16 lets a renewing budget run without an end date, which needs
+/// the budgets table rebuilt, and widens renewal to weekly, bi-weekly and
+/// monthly-from-a-day periods, renaming the calendar ones to match.
+fn migrate_to_v16(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE budgets_v3 (
+            id TEXT PRIMARY KEY,
+            category_id TEXT,
+            amount INTEGER NOT NULL,
+            currency TEXT NOT NULL DEFAULT 'USD',
+            start_date TEXT NOT NULL,
+            end_date TEXT,
+            name TEXT NOT NULL,
+            renewal TEXT,
+            rollover INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (category_id) REFERENCES categories (id)
+        );
+        INSERT INTO budgets_v3 (id, category_id, amount, currency, start_date, end_date, name, renewal, rollover)
+            SELECT id, category_id, amount, currency, start_date, end_date, name,
+                   CASE renewal
+                       WHEN 'Month' THEN 'Monthly'
+                       WHEN 'Quarter' THEN 'Quarterly'
+                       WHEN 'Year' THEN 'Yearly'
+                   END,
+                   rollover
+            FROM budgets;
+        DROP TABLE budgets;
+        ALTER TABLE budgets_v3 RENAME TO budgets;",
+    )
+}
+
+/// Version 17 adds the money assigned to each category's envelope per
+/// month, for zero-based budgeting.
+fn migrate_to_v17(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE envelope_allocations (
+            category_id TEXT NOT NULL REFERENCES categories (id),
+            month TEXT NOT NULL,
+            amount INTEGER NOT NULL,
+            currency TEXT NOT NULL,
+            PRIMARY KEY (category_id, month)
+        );",
+    )
+}
+
+/// Version 18 adds budget alert thresholds, the alerts already raised for
+/// each budget period and the notification centre. Existing budgets alert
+/// at the thresholds new ones start with.
+fn migrate_to_v18(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE budget_alert_thresholds (
+            budget_id TEXT NOT NULL REFERENCES budgets (id),
+            percent INTEGER NOT NULL,
+            PRIMARY KEY (budget_id, percent)
+        );
+        CREATE TABLE budget_alerts (
+            budget_id TEXT NOT NULL REFERENCES budgets (id),
+            period_start TEXT NOT NULL,
+            percent INTEGER NOT NULL,
+            PRIMARY KEY (budget_id, period_start, percent)
+        );
+        CREATE TABLE notifications (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            read INTEGER NOT NULL DEFAULT 0
+        );
+        INSERT INTO budget_alert_thresholds (budget_id, percent)
+            SELECT id, percent FROM budgets, (SELECT 50 AS percent UNION SELECT 80 UNION SELECT 100);",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrated(legacy_schema: &str) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(legacy_schema).unwrap();
+        migrate(&conn).unwrap();
+        conn
+    }
+
+    fn transaction(conn: &Connection, id: &str) -> (i64, String, String, String, Option<String>) {
+        conn.query_row(
+            "SELECT amount, currency, date, transaction_type, category_id FROM transactions WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .unwrap()
+    }
+
+    fn category_name(conn: &Connection, id: &str) -> String {
+        conn.query_row("SELECT name FROM categories WHERE id = ?1", [id], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn original_app_rows_are_converted() {
+        let conn = migrated(
+            "CREATE TABLE transactions (
+                id TEXT PRIMARY KEY,
+                description TEXT NOT NULL,
+                amount REAL NOT NULL,
+                category TEXT NOT NULL,
+                date TEXT NOT NULL
+            );
+            INSERT INTO transactions VALUES
+                ('t1', 'Weekly shop', -45.5, 'food', '2024-03-05T23:30:00+11:00'),
+                ('t2', 'Salary', 2500.0, 'Wages', '2024-03-01T09:00:00-05:00'),
+                ('t3', 'Coffee', -3.2, 'Wages', '2024-03-06T08:15:00Z'),
+                ('t4', 'Refund', 0.07, '  ', '2024-03-07T12:00:00+00:00');",
+        );
+
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+
+        // Free-text names reuse a matching category, ignoring case
+        let (amount, currency, date, transaction_type, category_id) = transaction(&conn, "t1");
+        assert_eq!((amount, currency.as_str()), (4550, "USD"));
+        assert_eq!(date, "2024-03-05");
+        assert_eq!(transaction_type, "Expense");
+        assert_eq!(category_id.as_deref(), Some("cat_2"));
+
+        // or become one new category per distinct name
+        let (amount, _, date, transaction_type, wages) = transaction(&conn, "t2");
+        assert_eq!((amount, date.as_str(), transaction_type.as_str()), (250_000, "2024-03-01", "Income"));
+        let wages = wages.unwrap();
+        assert_eq!(category_name(&conn, &wages), "Wages");
+        assert_eq!(transaction(&conn, "t3").4.as_deref(), Some(wages.as_str()));
+        assert_eq!(transaction(&conn, "t3").0, 320);
+
+        let (amount, _, _, transaction_type, category_id) = transaction(&conn, "t4");
+        assert_eq!((amount, transaction_type.as_str(), category_id), (7, "Income", None));
+    }
+
+    #[test]
+    fn early_schema_rows_are_converted() {
+        let conn = migrated(
+            "CREATE TABLE categories (id TEXT PRIMARY KEY, name TEXT NOT NULL, color TEXT NOT NULL);
+            CREATE TABLE transactions (
+                id TEXT PRIMARY KEY,
+                amount REAL NOT NULL,
+                description TEXT,
+                date TEXT NOT NULL,
+                type TEXT NOT NULL,
+                category_id TEXT
+            );
+            CREATE TABLE budgets (
+                id TEXT PRIMARY KEY,
+                category_id TEXT,
+                amount REAL NOT NULL,
+                start_date TEXT NOT NULL,
+                end_date TEXT NOT NULL,
+                name TEXT NOT NULL
+            );
+            INSERT INTO categories VALUES ('home', 'Home', '#123456');
+            INSERT INTO transactions VALUES ('t1', 1234.56, NULL, '2024-02-29', 'Expense', 'home');
+            INSERT INTO budgets VALUES ('b1', 'home', 300.1, '2024-02-01', '2024-02-29', 'Home');",
+        );
+
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        assert_eq!(category_name(&conn, "home"), "Home");
+
+        let (amount, currency, date, transaction_type, category_id) = transaction(&conn, "t1");
+        assert_eq!((amount, currency.as_str(), date.as_str()), (123_456, "USD", "2024-02-29"));
+        assert_eq!((transaction_type.as_str(), category_id.as_deref()), ("Expense", Some("home")));
+
+        let budget: (i64, String) = conn
+            .query_row("SELECT amount, currency FROM budgets WHERE id = 'b1'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(budget, (30_010, "USD".to_string()));
+    }
+
+    #[test]
+    fn current_databases_are_left_alone() {
+        let conn = migrated("");
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        conn.execute("INSERT INTO settings (key, value) VALUES ('base_currency', 'EUR')", [])
+            .unwrap();
+
+        migrate(&conn).unwrap();
+        let value: String = conn
+            .query_row("SELECT value FROM settings WHERE key = 'base_currency'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(value, "EUR");
+
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+        assert!(matches!(migrate(&conn), Err(DbError::UnsupportedVersion { .. })));
+    }
+}
@@ -1,8 +1,20 @@
+pub mod backup;
+pub mod migrations;
 pub mod operations;
 pub mod schema;
 
 use rusqlite::{Connection, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("database schema version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion { found: i64, supported: i64 },
+}
+
+pub type DbResult<T> = std::result::Result<T, DbError>;
 
 pub struct DatabaseConnection {
     connection: Connection,
@@ -18,42 +30,20 @@ impl DatabaseConnection {
         &self.connection
     }
     
-    pub fn initialize_schema(&self) -> Result<()> {
-        // Create transactions table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS transactions (
-                id TEXT PRIMARY KEY,
-                amount REAL NOT NULL,
-                description TEXT,
-                date TEXT NOT NULL,
-                category_id TEXT,
-                FOREIGN KEY (category_id) REFERENCES categories (id)
-            )",
-            [],
-        )?;
-        
-        // Create categories table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS categories (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                color TEXT NOT NULL
-            )",
-            [],
-        )?;
-        
-        // Create budgets table
-        self.connection.execute(
-            "CREATE TABLE IF NOT EXISTS budgets (
-                id TEXT PRIMARY KEY,
-                category_id TEXT NOT NULL,
-                amount REAL NOT NULL,
-                period TEXT NOT NULL,
-                FOREIGN KEY (category_id) REFERENCES categories (id)
-            )",
-            [],
-        )?;
-        
-        Ok(())
+    /// Creates the schema on a new database or upgrades an existing one.
+    pub fn initialize_schema(&self) -> DbResult<()> {
+        migrations::migrate(&self.connection)
+    }
+}
+
+pub fn get_database_path() -> PathBuf {
+    if let Some(data_dir) = directories::ProjectDirs::from("com", "budgettracker", "BudgetTracker") {
+        let data_path = data_dir.data_dir();
+        std::fs::create_dir_all(data_path).unwrap_or_else(|_| {
+            eprintln!("Failed to create data directory");
+        });
+        data_path.join("budget.db")
+    } else {
+        PathBuf::from("budget.db")
     }
 }
@@ -1,60 +1,156 @@
-use crate::models::{Budget, Category, Transaction, TransactionType};
-use chrono::NaiveDate;
-use rusqlite::{params, Connection, Result as SqlResult};
+use crate::db::backup::DEFAULT_BACKUPS_TO_KEEP;
+use crate::import::csv::ImportProfile;
+use crate::import::ImportBatch;
+use crate::models::payee;
+use crate::models::{
+    Account, AccountType, Allocation, Budget, BudgetPeriod, Category, CategoryRule, Categorizer, Currency, CurrencyConverter,
+    DateRange, DuplicatePair, ExchangeRate, Frequency, MatchKind, Money, Notification, NthWeekday, Payee, RecurrenceEnd,
+    RecurringRule, Renewal, Split, Tag, Transaction, TransactionType, Transfer,
+};
+use chrono::{NaiveDate, NaiveDateTime, Weekday};
+use std::collections::{HashMap, HashSet};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult, Row};
+use uuid::Uuid;
+
+/// Reads an amount stored as integer minor units plus a currency code.
+fn money_from_row(row: &Row, amount_idx: usize, currency_idx: usize) -> SqlResult<Money> {
+    let minor: i64 = row.get(amount_idx)?;
+    Ok(Money::from_minor(minor, currency_from_row(row, currency_idx)?))
+}
+
+fn currency_from_row(row: &Row, idx: usize) -> SqlResult<Currency> {
+    let code: String = row.get(idx)?;
+    code.parse::<Currency>().map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
 
 // Transaction operations
 pub fn add_transaction(conn: &Connection, transaction: &Transaction) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    insert_transaction(&tx, transaction)?;
+    tx.commit()
+}
+
+/// Inserts a transaction with its split lines and tags, within the
+/// caller's database transaction.
+fn insert_transaction(conn: &Connection, transaction: &Transaction) -> SqlResult<()> {
     conn.execute(
-        "INSERT INTO transactions (id, amount, description, date, category_id, transaction_type)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO transactions (id, amount, currency, description, date, category_id, transaction_type, account_id,
+                                   transfer_id, external_id, payee_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
         params![
-            transaction.id,
-            transaction.amount,
+            transaction.id.to_string(),
+            transaction.amount.minor_units(),
+            transaction.amount.currency().code(),
             transaction.description,
             transaction.date.to_string(),
             transaction.category_id,
             transaction.transaction_type.to_string(),
+            transaction.account_id,
+            transaction.transfer_id,
+            transaction.external_id,
+            transaction.payee_id,
         ],
     )?;
+    save_splits(conn, transaction)?;
+    save_tags(conn, transaction)?;
     
     Ok(())
 }
 
 pub fn update_transaction(conn: &Connection, transaction: &Transaction) -> SqlResult<()> {
-    conn.execute(
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
         "UPDATE transactions
-         SET amount = ?1, description = ?2, date = ?3, category_id = ?4, transaction_type = ?5
-         WHERE id = ?6",
+         SET amount = ?1, currency = ?2, description = ?3, date = ?4, category_id = ?5, transaction_type = ?6,
+             account_id = ?7, payee_id = ?8
+         WHERE id = ?9",
         params![
-            transaction.amount,
+            transaction.amount.minor_units(),
+            transaction.amount.currency().code(),
             transaction.description,
             transaction.date.to_string(),
             transaction.category_id,
             transaction.transaction_type.to_string(),
-            transaction.id,
+            transaction.account_id,
+            transaction.payee_id,
+            transaction.id.to_string(),
         ],
     )?;
-    
+    save_splits(&tx, transaction)?;
+    save_tags(&tx, transaction)?;
+    tx.commit()
+}
+
+/// Replaces the split lines stored for a transaction with its own.
+fn save_splits(conn: &Connection, transaction: &Transaction) -> SqlResult<()> {
+    let id = transaction.id.to_string();
+    conn.execute("DELETE FROM transaction_splits WHERE transaction_id = ?1", [&id])?;
+    for (position, split) in transaction.splits.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO transaction_splits (transaction_id, position, category_id, amount, memo)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, position as i64, split.category_id, split.amount.minor_units(), split.memo],
+        )?;
+    }
+
     Ok(())
 }
 
-pub fn delete_transaction(conn: &Connection, transaction_id: &str) -> SqlResult<()> {
-    conn.execute(
-        "DELETE FROM transactions WHERE id = ?1",
-        [transaction_id],
-    )?;
-    
+/// Replaces the tags of a transaction with its own, creating any tag not
+/// seen before.
+fn save_tags(conn: &Connection, transaction: &Transaction) -> SqlResult<()> {
+    let id = transaction.id.to_string();
+    conn.execute("DELETE FROM transaction_tags WHERE transaction_id = ?1", [&id])?;
+    for name in &transaction.tags {
+        conn.execute(
+            "INSERT OR IGNORE INTO tags (id, name) VALUES (?1, ?2)",
+            params![Uuid::new_v4().to_string(), name],
+        )?;
+        conn.execute(
+            "INSERT OR IGNORE INTO transaction_tags (transaction_id, tag_id)
+             SELECT ?1, id FROM tags WHERE name = ?2",
+            params![id, name],
+        )?;
+    }
+
     Ok(())
 }
 
+/// Deletes a transaction. Deleting either leg of a transfer deletes both.
+pub fn delete_transaction(conn: &Connection, transaction_id: &str) -> SqlResult<()> {
+    const LEGS: &str = "SELECT id FROM transactions
+         WHERE id = ?1
+            OR transfer_id = (SELECT transfer_id FROM transactions WHERE id = ?1)";
+
+    let tx = conn.unchecked_transaction()?;
+    for table in ["transaction_splits", "transaction_tags"] {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE transaction_id IN ({})", table, LEGS),
+            [transaction_id],
+        )?;
+    }
+    tx.execute(&format!("DELETE FROM transactions WHERE id IN ({})", LEGS), [transaction_id])?;
+    tx.commit()
+}
+
 pub fn get_all_transactions(conn: &Connection) -> SqlResult<Vec<Transaction>> {
     let mut stmt = conn.prepare(
-        "SELECT id, amount, description, date, category_id, transaction_type
+        "SELECT id, amount, description, date, category_id, transaction_type, currency, account_id, transfer_id,
+                external_id, payee_id
          FROM transactions
          ORDER BY date DESC"
     )?;
     
+    let mut splits = get_all_splits(conn)?;
+    let mut tags = get_all_transaction_tags(conn)?;
     let transaction_iter = stmt.query_map([], |row| {
+        let id_str: String = row.get(0)?;
+        let id = Uuid::parse_str(&id_str).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
         let date_str: String = row.get(3)?;
         let date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
             .unwrap_or_else(|_| chrono::Local::now().naive_local().date());
@@ -62,16 +158,23 @@ pub fn get_all_transactions(conn: &Connection) -> SqlResult<Vec<Transaction>> {
         let transaction_type_str: String = row.get(5)?;
         let transaction_type = match transaction_type_str.as_str() {
             "Income" => TransactionType::Income,
+            "Transfer" => TransactionType::Transfer,
             _ => TransactionType::Expense,
         };
         
         Ok(Transaction {
-            id: row.get(0)?,
-            amount: row.get(1)?,
-            description: row.get(2)?,
+            id,
+            amount: money_from_row(row, 1, 6)?,
+            description: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
             date,
             category_id: row.get(4)?,
             transaction_type,
+            account_id: row.get(7)?,
+            payee_id: row.get(10)?,
+            transfer_id: row.get(8)?,
+            external_id: row.get(9)?,
+            splits: splits.remove(&id_str).unwrap_or_default(),
+            tags: tags.remove(&id_str).unwrap_or_default(),
         })
     })?;
     
@@ -83,121 +186,1752 @@ pub fn get_all_transactions(conn: &Connection) -> SqlResult<Vec<Transaction>> {
     Ok(transactions)
 }
 
-// Category operations
-pub fn add_category(conn: &Connection, category: &Category) -> SqlResult<()> {
+/// Split lines by transaction id, in order.
+fn get_all_splits(conn: &Connection) -> SqlResult<HashMap<String, Vec<Split>>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.transaction_id, s.category_id, s.amount, t.currency, s.memo
+         FROM transaction_splits s
+         JOIN transactions t ON t.id = s.transaction_id
+         ORDER BY s.transaction_id, s.position"
+    )?;
+
+    let mut splits: HashMap<String, Vec<Split>> = HashMap::new();
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            Split {
+                category_id: row.get(1)?,
+                amount: money_from_row(row, 2, 3)?,
+                memo: row.get(4)?,
+            },
+        ))
+    })?;
+    for row in rows {
+        let (transaction_id, split) = row?;
+        splits.entry(transaction_id).or_default().push(split);
+    }
+
+    Ok(splits)
+}
+
+/// Tag names by transaction id, in alphabetical order.
+fn get_all_transaction_tags(conn: &Connection) -> SqlResult<HashMap<String, Vec<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT tt.transaction_id, t.name
+         FROM transaction_tags tt
+         JOIN tags t ON t.id = tt.tag_id
+         ORDER BY t.name"
+    )?;
+
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (transaction_id, name) = row?;
+        tags.entry(transaction_id).or_default().push(name);
+    }
+
+    Ok(tags)
+}
+
+/// Writes both legs of a transfer in one database transaction, replacing
+/// the legs of an earlier version of it.
+pub fn save_transfer(conn: &Connection, transfer: &Transfer) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    for table in ["transaction_splits", "transaction_tags"] {
+        tx.execute(
+            &format!(
+                "DELETE FROM {} WHERE transaction_id IN (SELECT id FROM transactions WHERE transfer_id = ?1)",
+                table
+            ),
+            [&transfer.id],
+        )?;
+    }
+    tx.execute(
+        "DELETE FROM transactions WHERE transfer_id = ?1",
+        [&transfer.id],
+    )?;
+    for leg in transfer.legs() {
+        insert_transaction(&tx, &leg)?;
+    }
+    tx.commit()
+}
+
+// Recurring rule operations
+pub fn add_recurring_rule(conn: &Connection, rule: &RecurringRule) -> SqlResult<()> {
+    let (end_date, occurrence_count) = recurrence_end_columns(rule.end);
     conn.execute(
-        "INSERT INTO categories (id, name, color, icon)
-         VALUES (?1, ?2, ?3, ?4)",
+        "INSERT INTO recurring_rules (id, description, amount, currency, transaction_type, category_id, account_id,
+                                      frequency, interval, nth_week, weekday, start_date, end_date,
+                                      occurrence_count, last_generated)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
         params![
-            category.id,
-            category.name,
-            category.color,
-            category.icon,
+            rule.id,
+            rule.description,
+            rule.amount.minor_units(),
+            rule.amount.currency().code(),
+            rule.transaction_type.to_string(),
+            rule.category_id,
+            rule.account_id,
+            rule.frequency.to_string(),
+            rule.interval,
+            rule.nth_weekday.map(|nth| nth.week),
+            rule.nth_weekday.map(|nth| nth.weekday.num_days_from_monday()),
+            rule.start_date.to_string(),
+            end_date,
+            occurrence_count,
+            rule.last_generated.map(|date| date.to_string()),
         ],
     )?;
-    
+
     Ok(())
 }
 
-pub fn get_all_categories(conn: &Connection) -> SqlResult<Vec<Category>> {
+/// Updates a rule's template and schedule. How far it has been generated is
+/// left alone, so editing a rule never repeats past occurrences.
+pub fn update_recurring_rule(conn: &Connection, rule: &RecurringRule) -> SqlResult<()> {
+    let (end_date, occurrence_count) = recurrence_end_columns(rule.end);
+    conn.execute(
+        "UPDATE recurring_rules
+         SET description = ?1, amount = ?2, currency = ?3, transaction_type = ?4, category_id = ?5,
+             account_id = ?6, frequency = ?7, interval = ?8, nth_week = ?9, weekday = ?10, start_date = ?11,
+             end_date = ?12, occurrence_count = ?13
+         WHERE id = ?14",
+        params![
+            rule.description,
+            rule.amount.minor_units(),
+            rule.amount.currency().code(),
+            rule.transaction_type.to_string(),
+            rule.category_id,
+            rule.account_id,
+            rule.frequency.to_string(),
+            rule.interval,
+            rule.nth_weekday.map(|nth| nth.week),
+            rule.nth_weekday.map(|nth| nth.weekday.num_days_from_monday()),
+            rule.start_date.to_string(),
+            end_date,
+            occurrence_count,
+            rule.id,
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn recurrence_end_columns(end: RecurrenceEnd) -> (Option<String>, Option<u32>) {
+    match end {
+        RecurrenceEnd::Never => (None, None),
+        RecurrenceEnd::OnDate(date) => (Some(date.to_string()), None),
+        RecurrenceEnd::AfterCount(count) => (None, Some(count)),
+    }
+}
+
+/// Deletes a rule. Transactions it already generated are kept.
+pub fn delete_recurring_rule(conn: &Connection, rule_id: &str) -> SqlResult<()> {
+    conn.execute(
+        "DELETE FROM recurring_rules WHERE id = ?1",
+        [rule_id],
+    )?;
+
+    Ok(())
+}
+
+pub fn get_all_recurring_rules(conn: &Connection) -> SqlResult<Vec<RecurringRule>> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, color, icon
-         FROM categories
-         ORDER BY name"
+        "SELECT id, description, amount, currency, transaction_type, category_id, account_id, frequency,
+                interval, nth_week, weekday, start_date, end_date, occurrence_count, last_generated
+         FROM recurring_rules
+         ORDER BY description"
     )?;
-    
-    let category_iter = stmt.query_map([], |row| {
-        Ok(Category {
+
+    let rule_iter = stmt.query_map([], |row| {
+        let parse_date = |idx: usize, value: String| {
+            NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+            })
+        };
+
+        let transaction_type = match row.get::<_, String>(4)?.as_str() {
+            "Income" => TransactionType::Income,
+            _ => TransactionType::Expense,
+        };
+
+        let frequency_str: String = row.get(7)?;
+        let frequency = frequency_str.parse::<Frequency>().map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(7, rusqlite::types::Type::Text, e.into())
+        })?;
+
+        let nth_week: Option<i8> = row.get(9)?;
+        let weekday: Option<u8> = row.get(10)?;
+        let nth_weekday = match (nth_week, weekday.and_then(|day| Weekday::try_from(day).ok())) {
+            (Some(week), Some(weekday)) => Some(NthWeekday { week, weekday }),
+            _ => None,
+        };
+
+        let end = match (row.get::<_, Option<String>>(12)?, row.get::<_, Option<u32>>(13)?) {
+            (Some(date), _) => RecurrenceEnd::OnDate(parse_date(12, date)?),
+            (None, Some(count)) => RecurrenceEnd::AfterCount(count),
+            (None, None) => RecurrenceEnd::Never,
+        };
+
+        Ok(RecurringRule {
             id: row.get(0)?,
-            name: row.get(1)?,
-            color: row.get(2)?,
-            icon: row.get::<_, Option<String>>(3)?,
+            description: row.get(1)?,
+            amount: money_from_row(row, 2, 3)?,
+            transaction_type,
+            category_id: row.get(5)?,
+            account_id: row.get(6)?,
+            frequency,
+            interval: row.get(8)?,
+            nth_weekday,
+            start_date: parse_date(11, row.get(11)?)?,
+            end,
+            last_generated: row.get::<_, Option<String>>(14)?
+                .map(|date| parse_date(14, date))
+                .transpose()?,
         })
     })?;
-    
-    let mut categories = Vec::new();
-    for category in category_iter {
-        categories.push(category?);
+
+    let mut rules = Vec::new();
+    for rule in rule_iter {
+        rules.push(rule?);
     }
-    
-    Ok(categories)
+
+    Ok(rules)
 }
 
-// Budget operations
-pub fn add_budget(conn: &Connection, budget: &Budget) -> SqlResult<()> {
+/// Writes every recurring occurrence due on or before `today` to
+/// `transactions`, with payees and categories filled in as for manual
+/// entry, and returns how many were written. Runs in one database
+/// transaction, so an occurrence is never written without its rule's
+/// progress being recorded.
+pub fn generate_due_transactions(conn: &Connection, today: NaiveDate) -> SqlResult<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut generated = 0;
+
+    for rule in get_all_recurring_rules(&tx)? {
+        let due = rule.due(today);
+        let Some(last) = due.last() else {
+            continue;
+        };
+
+        let mut transactions: Vec<Transaction> = due.iter().map(|date| rule.to_transaction(*date)).collect();
+        categorize_transactions(&tx, &mut transactions)?;
+        for transaction in &transactions {
+            insert_transaction(&tx, transaction)?;
+        }
+        tx.execute(
+            "UPDATE recurring_rules SET last_generated = ?1 WHERE id = ?2",
+            params![last.to_string(), rule.id],
+        )?;
+        generated += due.len();
+    }
+
+    tx.commit()?;
+    Ok(generated)
+}
+
+/// Writes an import batch all-or-nothing and returns how many transactions
+/// were inserted. Transactions whose external id is already present in the
+/// same account are skipped.
+pub fn import_transactions(conn: &Connection, batch: &ImportBatch) -> SqlResult<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut transactions = batch.transactions.clone();
+    categorize_transactions(&tx, &mut transactions)?;
+
+    let mut imported = 0;
+    for transaction in &transactions {
+        if let Some(external_id) = &transaction.external_id {
+            if external_id_exists(&tx, transaction.account_id.as_deref(), external_id)? {
+                continue;
+            }
+        }
+        insert_transaction(&tx, transaction)?;
+        imported += 1;
+    }
+    for (keep, duplicate) in &batch.merges {
+        merge_into(&tx, *keep, duplicate)?;
+    }
+    for pair in &batch.dismissed {
+        dismiss_duplicate(&tx, *pair)?;
+    }
+    tx.commit()?;
+
+    Ok(imported)
+}
+
+fn external_id_exists(conn: &Connection, account_id: Option<&str>, external_id: &str) -> SqlResult<bool> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM transactions WHERE account_id IS ?1 AND external_id = ?2)",
+        params![account_id, external_id],
+        |row| row.get(0),
+    )
+}
+
+// Category rule operations
+pub fn add_category_rule(conn: &Connection, rule: &CategoryRule) -> SqlResult<()> {
+    let (min_amount, max_amount, currency) = amount_range_columns(rule);
     conn.execute(
-        "INSERT INTO budgets (id, category_id, amount, start_date, end_date, name)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO category_rules (id, name, priority, match_kind, pattern, min_amount, max_amount, currency,
+                                     account_id, category_id, payee_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
         params![
-            budget.id,
-            budget.category_id,
-            budget.amount,
-            budget.start_date.to_string(),
-            budget.end_date.to_string(),
-            budget.name,
+            rule.id,
+            rule.name,
+            rule.priority,
+            rule.match_kind.to_string(),
+            rule.pattern,
+            min_amount,
+            max_amount,
+            currency,
+            rule.account_id,
+            rule.category_id,
+            rule.payee_id,
         ],
     )?;
-    
+
     Ok(())
 }
 
-pub fn update_budget(conn: &Connection, budget: &Budget) -> SqlResult<()> {
+pub fn update_category_rule(conn: &Connection, rule: &CategoryRule) -> SqlResult<()> {
+    let (min_amount, max_amount, currency) = amount_range_columns(rule);
     conn.execute(
-        "UPDATE budgets
-         SET category_id = ?1, amount = ?2, start_date = ?3, end_date = ?4, name = ?5
-         WHERE id = ?6",
+        "UPDATE category_rules
+         SET name = ?1, priority = ?2, match_kind = ?3, pattern = ?4, min_amount = ?5, max_amount = ?6,
+             currency = ?7, account_id = ?8, category_id = ?9, payee_id = ?10
+         WHERE id = ?11",
         params![
-            budget.category_id,
-            budget.amount,
-            budget.start_date.to_string(),
-            budget.end_date.to_string(),
-            budget.name,
-            budget.id,
+            rule.name,
+            rule.priority,
+            rule.match_kind.to_string(),
+            rule.pattern,
+            min_amount,
+            max_amount,
+            currency,
+            rule.account_id,
+            rule.category_id,
+            rule.payee_id,
+            rule.id,
         ],
     )?;
-    
+
     Ok(())
 }
 
-pub fn delete_budget(conn: &Connection, budget_id: &str) -> SqlResult<()> {
+fn amount_range_columns(rule: &CategoryRule) -> (Option<i64>, Option<i64>, &'static str) {
+    let currency = rule.min_amount
+        .or(rule.max_amount)
+        .map(|amount| amount.currency())
+        .unwrap_or_default();
+
+    (
+        rule.min_amount.map(|amount| amount.minor_units()),
+        rule.max_amount.map(|amount| amount.minor_units()),
+        currency.code(),
+    )
+}
+
+pub fn delete_category_rule(conn: &Connection, rule_id: &str) -> SqlResult<()> {
     conn.execute(
-        "DELETE FROM budgets WHERE id = ?1",
-        [budget_id],
+        "DELETE FROM category_rules WHERE id = ?1",
+        [rule_id],
     )?;
-    
+
     Ok(())
 }
 
-pub fn get_all_budgets(conn: &Connection) -> SqlResult<Vec<Budget>> {
+/// Gives the rules ascending priorities in the order of `rule_ids`.
+pub fn reorder_category_rules(conn: &Connection, rule_ids: &[String]) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    for (priority, rule_id) in rule_ids.iter().enumerate() {
+        tx.execute(
+            "UPDATE category_rules SET priority = ?1 WHERE id = ?2",
+            params![priority as i64, rule_id],
+        )?;
+    }
+    tx.commit()
+}
+
+pub fn get_all_category_rules(conn: &Connection) -> SqlResult<Vec<CategoryRule>> {
     let mut stmt = conn.prepare(
-        "SELECT id, category_id, amount, start_date, end_date, name
-         FROM budgets
-         ORDER BY start_date DESC"
+        "SELECT id, name, priority, match_kind, pattern, min_amount, max_amount, currency, account_id, category_id,
+                payee_id
+         FROM category_rules
+         ORDER BY priority, name"
     )?;
-    
-    let budget_iter = stmt.query_map([], |row| {
-        let start_date_str: String = row.get(3)?;
-        let start_date = NaiveDate::parse_from_str(&start_date_str, "%Y-%m-%d")
-            .unwrap_or_else(|_| chrono::Local::now().naive_local().date());
-            
-        let end_date_str: String = row.get(4)?;
-        let end_date = NaiveDate::parse_from_str(&end_date_str, "%Y-%m-%d")
-            .unwrap_or_else(|_| chrono::Local::now().naive_local().date());
-        
-        Ok(Budget {
+
+    let rule_iter = stmt.query_map([], |row| {
+        let match_kind_str: String = row.get(3)?;
+        let match_kind = match_kind_str.parse::<MatchKind>().map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, e.into())
+        })?;
+
+        let currency = currency_from_row(row, 7)?;
+        let amount = |idx: usize| -> SqlResult<Option<Money>> {
+            Ok(row.get::<_, Option<i64>>(idx)?.map(|minor| Money::from_minor(minor, currency)))
+        };
+
+        Ok(CategoryRule {
             id: row.get(0)?,
-            category_id: row.get(1)?,
-            amount: row.get(2)?,
-            start_date,
-            end_date,
-            name: row.get(5)?,
+            name: row.get(1)?,
+            priority: row.get(2)?,
+            match_kind,
+            pattern: row.get(4)?,
+            min_amount: amount(5)?,
+            max_amount: amount(6)?,
+            account_id: row.get(8)?,
+            category_id: row.get(9)?,
+            payee_id: row.get(10)?,
         })
     })?;
-    
-    let mut budgets = Vec::new();
-    for budget in budget_iter {
-        budgets.push(budget?);
+
+    let mut rules = Vec::new();
+    for rule in rule_iter {
+        rules.push(rule?);
+    }
+
+    Ok(rules)
+}
+
+/// Recognises the payee of transactions without one, then fills in the
+/// category of uncategorised transactions from the rules, falling back to
+/// the payee's default category.
+pub fn categorize_transactions(conn: &Connection, transactions: &mut [Transaction]) -> SqlResult<()> {
+    let payees = get_all_payees(conn)?;
+    payee::assign(&payees, transactions);
+    Categorizer::new(&get_all_category_rules(conn)?).categorize(transactions);
+    payee::apply_default_categories(&payees, transactions);
+    Ok(())
+}
+
+/// Recognises the payee of transactions without one.
+pub fn assign_payees(conn: &Connection, transactions: &mut [Transaction]) -> SqlResult<()> {
+    payee::assign(&get_all_payees(conn)?, transactions);
+    Ok(())
+}
+
+/// Re-applies the rules to stored transactions and returns how many changed
+/// category. Only uncategorised transactions are touched unless
+/// `overwrite` is set, in which case every transaction a rule matches is
+/// recategorised.
+pub fn apply_category_rules(conn: &Connection, overwrite: bool) -> SqlResult<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let categorizer = Categorizer::new(&get_all_category_rules(&tx)?);
+
+    let mut changed = 0;
+    for transaction in get_all_transactions(&tx)? {
+        // Split transactions are categorised line by line
+        if transaction.is_split() || (transaction.category_id.is_some() && !overwrite) {
+            continue;
+        }
+
+        let Some(category_id) = categorizer.category_for(&transaction) else {
+            continue;
+        };
+        if transaction.category_id.as_deref() == Some(category_id) {
+            continue;
+        }
+
+        tx.execute(
+            "UPDATE transactions SET category_id = ?1 WHERE id = ?2",
+            params![category_id, transaction.id.to_string()],
+        )?;
+        changed += 1;
+    }
+
+    tx.commit()?;
+    Ok(changed)
+}
+
+// Duplicate operations
+/// Folds `duplicate` into the transaction `keep` and deletes it. Details
+/// `keep` lacks, such as a category or the bank's id, are taken from
+/// `duplicate`. Transfer legs are never merged, as each needs its other
+/// leg.
+pub fn merge_transactions(conn: &Connection, keep: Uuid, duplicate: &Transaction) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    merge_into(&tx, keep, duplicate)?;
+    tx.commit()
+}
+
+fn merge_into(conn: &Connection, keep: Uuid, duplicate: &Transaction) -> SqlResult<()> {
+    let transfer_legs: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM transactions WHERE id IN (?1, ?2) AND transfer_id IS NOT NULL",
+        params![keep.to_string(), duplicate.id.to_string()],
+        |row| row.get(0),
+    )?;
+    if transfer_legs > 0 || duplicate.is_transfer() {
+        return Err(rusqlite::Error::ToSqlConversionFailure(
+            "transfer legs cannot be merged".into(),
+        ));
+    }
+
+    conn.execute(
+        "UPDATE transactions
+         SET category_id = COALESCE(category_id, ?1),
+             account_id = COALESCE(account_id, ?2),
+             external_id = COALESCE(external_id, ?3),
+             payee_id = COALESCE(payee_id, ?5)
+         WHERE id = ?4",
+        params![
+            duplicate.category_id,
+            duplicate.account_id,
+            duplicate.external_id,
+            keep.to_string(),
+            duplicate.payee_id,
+        ],
+    )?;
+
+    let duplicate_id = duplicate.id.to_string();
+    conn.execute(
+        "INSERT OR IGNORE INTO transaction_tags (transaction_id, tag_id)
+         SELECT ?1, tag_id FROM transaction_tags WHERE transaction_id = ?2",
+        params![keep.to_string(), duplicate_id],
+    )?;
+    conn.execute("DELETE FROM transaction_tags WHERE transaction_id = ?1", [&duplicate_id])?;
+    conn.execute("DELETE FROM transaction_splits WHERE transaction_id = ?1", [&duplicate_id])?;
+    conn.execute("DELETE FROM transactions WHERE id = ?1", [&duplicate_id])?;
+    conn.execute(
+        "DELETE FROM dismissed_duplicates WHERE first_id = ?1 OR second_id = ?1",
+        [&duplicate_id],
+    )?;
+
+    Ok(())
+}
+
+/// Records that two transactions are not duplicates so they are no longer
+/// flagged.
+pub fn dismiss_duplicate(conn: &Connection, pair: DuplicatePair) -> SqlResult<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO dismissed_duplicates (first_id, second_id) VALUES (?1, ?2)",
+        params![pair.first.to_string(), pair.second.to_string()],
+    )?;
+
+    Ok(())
+}
+
+pub fn get_dismissed_duplicates(conn: &Connection) -> SqlResult<HashSet<DuplicatePair>> {
+    let mut stmt = conn.prepare(
+        "SELECT first_id, second_id FROM dismissed_duplicates"
+    )?;
+
+    let pair_iter = stmt.query_map([], |row| {
+        let id = |idx: usize| -> SqlResult<Uuid> {
+            let id_str: String = row.get(idx)?;
+            Uuid::parse_str(&id_str).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+            })
+        };
+
+        Ok(DuplicatePair::new(id(0)?, id(1)?))
+    })?;
+
+    let mut pairs = HashSet::new();
+    for pair in pair_iter {
+        pairs.insert(pair?);
+    }
+
+    Ok(pairs)
+}
+
+// Import profile operations
+/// Saves a profile, replacing any existing profile with the same name.
+pub fn save_import_profile(conn: &Connection, profile: &ImportProfile) -> SqlResult<()> {
+    let mapping = serde_json::to_string(&profile.mapping)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+    conn.execute(
+        "INSERT INTO import_profiles (name, mapping, account_id) VALUES (?1, ?2, ?3)
+         ON CONFLICT (name) DO UPDATE SET mapping = excluded.mapping, account_id = excluded.account_id",
+        params![profile.name, mapping, profile.account_id],
+    )?;
+
+    Ok(())
+}
+
+pub fn delete_import_profile(conn: &Connection, name: &str) -> SqlResult<()> {
+    conn.execute(
+        "DELETE FROM import_profiles WHERE name = ?1",
+        [name],
+    )?;
+
+    Ok(())
+}
+
+pub fn get_all_import_profiles(conn: &Connection) -> SqlResult<Vec<ImportProfile>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, mapping, account_id
+         FROM import_profiles
+         ORDER BY name"
+    )?;
+
+    let profile_iter = stmt.query_map([], |row| {
+        let mapping_json: String = row.get(1)?;
+        let mapping = serde_json::from_str(&mapping_json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
+        Ok(ImportProfile {
+            name: row.get(0)?,
+            mapping,
+            account_id: row.get(2)?,
+        })
+    })?;
+
+    let mut profiles = Vec::new();
+    for profile in profile_iter {
+        profiles.push(profile?);
+    }
+
+    Ok(profiles)
+}
+
+// Account operations
+pub fn add_account(conn: &Connection, account: &Account) -> SqlResult<()> {
+    conn.execute(
+        "INSERT INTO accounts (id, name, account_type, opening_balance, currency, opening_date)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            account.id,
+            account.name,
+            account.account_type.to_string(),
+            account.opening_balance.minor_units(),
+            account.opening_balance.currency().code(),
+            account.opening_date.to_string(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn update_account(conn: &Connection, account: &Account) -> SqlResult<()> {
+    conn.execute(
+        "UPDATE accounts
+         SET name = ?1, account_type = ?2, opening_balance = ?3, currency = ?4, opening_date = ?5
+         WHERE id = ?6",
+        params![
+            account.name,
+            account.account_type.to_string(),
+            account.opening_balance.minor_units(),
+            account.opening_balance.currency().code(),
+            account.opening_date.to_string(),
+            account.id,
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Deletes an account. Its transactions, recurring rules and import
+/// profiles are kept and left without an account, and category rules that
+/// only applied to it are deleted. An account with transfers is refused,
+/// as a transfer needs both its accounts; the transfers must be deleted
+/// first.
+pub fn delete_account(conn: &Connection, account_id: &str) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    let transfers: i64 = tx.query_row(
+        "SELECT COUNT(*) FROM transactions WHERE account_id = ?1 AND transfer_id IS NOT NULL",
+        [account_id],
+        |row| row.get(0),
+    )?;
+    if transfers > 0 {
+        return Err(rusqlite::Error::ToSqlConversionFailure(format!(
+            "the account has {} transfer(s); delete them first",
+            transfers
+        ).into()));
+    }
+
+    for table in ["transactions", "recurring_rules", "import_profiles"] {
+        tx.execute(
+            &format!("UPDATE {} SET account_id = NULL WHERE account_id = ?1", table),
+            [account_id],
+        )?;
+    }
+    tx.execute("DELETE FROM category_rules WHERE account_id = ?1", [account_id])?;
+    tx.execute(
+        "DELETE FROM accounts WHERE id = ?1",
+        [account_id],
+    )?;
+    tx.commit()
+}
+
+pub fn get_all_accounts(conn: &Connection) -> SqlResult<Vec<Account>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, account_type, opening_balance, currency, opening_date
+         FROM accounts
+         ORDER BY name"
+    )?;
+
+    let account_iter = stmt.query_map([], |row| {
+        let account_type_str: String = row.get(2)?;
+        let account_type = account_type_str.parse::<AccountType>().map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, e.into())
+        })?;
+
+        let date_str: String = row.get(5)?;
+        let opening_date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+            .unwrap_or_else(|_| chrono::Local::now().naive_local().date());
+
+        Ok(Account {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            account_type,
+            opening_balance: money_from_row(row, 3, 4)?,
+            opening_date,
+        })
+    })?;
+
+    let mut accounts = Vec::new();
+    for account in account_iter {
+        accounts.push(account?);
+    }
+
+    Ok(accounts)
+}
+
+// Category operations
+pub fn add_category(conn: &Connection, category: &Category) -> SqlResult<()> {
+    conn.execute(
+        "INSERT INTO categories (id, name, color, icon, parent_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![category.id, category.name, category.color, category.icon, category.parent_id],
+    )?;
+
+    Ok(())
+}
+
+pub fn set_category_parent(conn: &Connection, category_id: &str, parent_id: Option<&str>) -> SqlResult<()> {
+    conn.execute(
+        "UPDATE categories SET parent_id = ?2 WHERE id = ?1",
+        params![category_id, parent_id],
+    )?;
+
+    Ok(())
+}
+
+pub fn update_category(conn: &Connection, category: &Category) -> SqlResult<()> {
+    conn.execute(
+        "UPDATE categories SET name = ?2, color = ?3, icon = ?4, parent_id = ?5 WHERE id = ?1",
+        params![category.id, category.name, category.color, category.icon, category.parent_id],
+    )?;
+
+    Ok(())
+}
+
+/// Deletes a category, moving everything filed under it to `replacement`.
+/// Without a replacement its transactions and recurring rules become
+/// uncategorised, and its budgets and category rules are deleted; a budget
+/// left without a category would start counting all spending. Subcategories
+/// move up to the deleted category's parent. `replacement` must not be
+/// the category or one of its subcategories.
+pub fn delete_category(conn: &Connection, category_id: &str, replacement: Option<&str>) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    let parent_id: Option<String> = tx.query_row(
+        "SELECT parent_id FROM categories WHERE id = ?1",
+        [category_id],
+        |row| row.get(0),
+    )?;
+    remove_category(&tx, category_id, replacement, parent_id.as_deref())?;
+    tx.commit()
+}
+
+/// Merges `source` into `target`: everything filed under `source`,
+/// including its subcategories, moves to `target` and `source` is deleted.
+/// `target` must not be within `source`'s branch.
+pub fn merge_categories(conn: &Connection, source_id: &str, target_id: &str) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    remove_category(&tx, source_id, Some(target_id), Some(target_id))?;
+    tx.commit()
+}
+
+fn remove_category(
+    conn: &Connection,
+    category_id: &str,
+    replacement: Option<&str>,
+    children_parent: Option<&str>,
+) -> SqlResult<()> {
+    for table in ["transactions", "transaction_splits", "recurring_rules", "payees"] {
+        conn.execute(
+            &format!("UPDATE {} SET category_id = ?2 WHERE category_id = ?1", table),
+            params![category_id, replacement],
+        )?;
+    }
+
+    match replacement {
+        Some(replacement) => {
+            conn.execute(
+                "UPDATE budgets SET category_id = ?2 WHERE category_id = ?1",
+                params![category_id, replacement],
+            )?;
+        }
+        None => {
+            let budget_ids: Vec<String> = get_all_budgets(conn)?
+                .into_iter()
+                .filter(|b| b.category_id.as_deref() == Some(category_id))
+                .map(|b| b.id)
+                .collect();
+            for budget_id in &budget_ids {
+                remove_budget(conn, budget_id)?;
+            }
+        }
+    }
+
+    // Envelope money moves with the category, added to any the replacement
+    // already has that month; without one it goes back to be assigned
+    if let Some(replacement) = replacement {
+        merge_allocations(conn, category_id, replacement)?;
+    }
+    conn.execute("DELETE FROM envelope_allocations WHERE category_id = ?1", [category_id])?;
+
+    // A rule has to assign some category
+    match replacement {
+        Some(replacement) => conn.execute(
+            "UPDATE category_rules SET category_id = ?2 WHERE category_id = ?1",
+            params![category_id, replacement],
+        )?,
+        None => conn.execute("DELETE FROM category_rules WHERE category_id = ?1", [category_id])?,
+    };
+
+    conn.execute(
+        "UPDATE categories SET parent_id = ?2 WHERE parent_id = ?1",
+        params![category_id, children_parent],
+    )?;
+    conn.execute("DELETE FROM categories WHERE id = ?1", [category_id])?;
+
+    Ok(())
+}
+
+/// Adds `source`'s envelope allocations to `target`'s. Where `target`
+/// already has money that month in another currency, the amount moved is
+/// converted at the rate for that month, and the merge fails if there is
+/// none.
+fn merge_allocations(conn: &Connection, source_id: &str, target_id: &str) -> SqlResult<()> {
+    let allocations = get_all_allocations(conn)?;
+    let converter = get_currency_converter(conn)?;
+
+    for moving in allocations.iter().filter(|a| a.category_id == source_id) {
+        let existing = allocations.iter()
+            .find(|a| a.category_id == target_id && a.month == moving.month);
+        let amount = match existing {
+            Some(existing) => {
+                let currency = existing.amount.currency();
+                let converted = converter.convert(moving.amount, currency, moving.month).ok_or_else(|| {
+                    rusqlite::Error::ToSqlConversionFailure(format!(
+                        "no exchange rate from {} to {} for {}",
+                        moving.amount.currency().code(),
+                        currency.code(),
+                        moving.month.format("%B %Y")
+                    ).into())
+                })?;
+                existing.amount + converted
+            }
+            None => moving.amount,
+        };
+        set_allocation(conn, &Allocation::new(target_id.to_string(), moving.month, amount))?;
+    }
+
+    Ok(())
+}
+
+pub fn get_all_categories(conn: &Connection) -> SqlResult<Vec<Category>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, color, icon, parent_id
+         FROM categories
+         ORDER BY name"
+    )?;
+    
+    let category_iter = stmt.query_map([], |row| {
+        Ok(Category {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            color: row.get(2)?,
+            icon: row.get::<_, Option<String>>(3)?,
+            parent_id: row.get::<_, Option<String>>(4)?,
+        })
+    })?;
+    
+    let mut categories = Vec::new();
+    for category in category_iter {
+        categories.push(category?);
+    }
+    
+    Ok(categories)
+}
+
+// Tag operations
+pub fn add_tag(conn: &Connection, tag: &Tag) -> SqlResult<()> {
+    conn.execute(
+        "INSERT INTO tags (id, name) VALUES (?1, ?2)",
+        params![tag.id, tag.name],
+    )?;
+
+    Ok(())
+}
+
+/// Renames a tag. Transactions keep it under its new name.
+pub fn rename_tag(conn: &Connection, tag_id: &str, name: &str) -> SqlResult<()> {
+    conn.execute(
+        "UPDATE tags SET name = ?2 WHERE id = ?1",
+        params![tag_id, name],
+    )?;
+
+    Ok(())
+}
+
+/// Deletes a tag, removing it from every transaction.
+pub fn delete_tag(conn: &Connection, tag_id: &str) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM transaction_tags WHERE tag_id = ?1", [tag_id])?;
+    tx.execute("DELETE FROM tags WHERE id = ?1", [tag_id])?;
+    tx.commit()
+}
+
+pub fn get_all_tags(conn: &Connection) -> SqlResult<Vec<Tag>> {
+    let mut stmt = conn.prepare("SELECT id, name FROM tags ORDER BY name")?;
+    let tag_iter = stmt.query_map([], |row| {
+        Ok(Tag {
+            id: row.get(0)?,
+            name: row.get(1)?,
+        })
+    })?;
+
+    let mut tags = Vec::new();
+    for tag in tag_iter {
+        tags.push(tag?);
+    }
+
+    Ok(tags)
+}
+
+// Payee operations
+pub fn add_payee(conn: &Connection, payee: &Payee) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO payees (id, name, category_id) VALUES (?1, ?2, ?3)",
+        params![payee.id, payee.name, payee.category_id],
+    )?;
+    save_aliases(&tx, payee)?;
+    tx.commit()
+}
+
+pub fn update_payee(conn: &Connection, payee: &Payee) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE payees SET name = ?1, category_id = ?2 WHERE id = ?3",
+        params![payee.name, payee.category_id, payee.id],
+    )?;
+    save_aliases(&tx, payee)?;
+    tx.commit()
+}
+
+/// Replaces the alias patterns stored for a payee with its own.
+fn save_aliases(conn: &Connection, payee: &Payee) -> SqlResult<()> {
+    conn.execute("DELETE FROM payee_aliases WHERE payee_id = ?1", [&payee.id])?;
+    for pattern in &payee.aliases {
+        conn.execute(
+            "INSERT OR IGNORE INTO payee_aliases (payee_id, pattern) VALUES (?1, ?2)",
+            params![payee.id, pattern],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Deletes a payee. Its transactions keep their descriptions and
+/// categories but lose the link, and category rules that depend on it are
+/// deleted.
+pub fn delete_payee(conn: &Connection, payee_id: &str) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("UPDATE transactions SET payee_id = NULL WHERE payee_id = ?1", [payee_id])?;
+    tx.execute("DELETE FROM category_rules WHERE payee_id = ?1", [payee_id])?;
+    tx.execute("DELETE FROM payee_aliases WHERE payee_id = ?1", [payee_id])?;
+    tx.execute("DELETE FROM payees WHERE id = ?1", [payee_id])?;
+    tx.commit()
+}
+
+pub fn get_all_payees(conn: &Connection) -> SqlResult<Vec<Payee>> {
+    let mut aliases: HashMap<String, Vec<String>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT payee_id, pattern FROM payee_aliases ORDER BY pattern")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (payee_id, pattern) = row?;
+        aliases.entry(payee_id).or_default().push(pattern);
+    }
+
+    let mut stmt = conn.prepare("SELECT id, name, category_id FROM payees ORDER BY name")?;
+    let payee_iter = stmt.query_map([], |row| {
+        let id: String = row.get(0)?;
+        Ok(Payee {
+            aliases: aliases.remove(&id).unwrap_or_default(),
+            id,
+            name: row.get(1)?,
+            category_id: row.get(2)?,
+        })
+    })?;
+
+    let mut payees = Vec::new();
+    for payee in payee_iter {
+        payees.push(payee?);
+    }
+
+    Ok(payees)
+}
+
+/// Links stored transactions without a payee to the payee their
+/// description belongs to, giving uncategorised ones the payee's default
+/// category, and returns how many were linked.
+pub fn apply_payees(conn: &Connection) -> SqlResult<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let payees = get_all_payees(&tx)?;
+
+    let mut transactions: Vec<Transaction> = get_all_transactions(&tx)?
+        .into_iter()
+        .filter(|t| t.payee_id.is_none())
+        .collect();
+    payee::assign(&payees, &mut transactions);
+    payee::apply_default_categories(&payees, &mut transactions);
+
+    let mut linked = 0;
+    for transaction in transactions.iter().filter(|t| t.payee_id.is_some()) {
+        tx.execute(
+            "UPDATE transactions SET payee_id = ?1, category_id = ?2 WHERE id = ?3",
+            params![transaction.payee_id, transaction.category_id, transaction.id.to_string()],
+        )?;
+        linked += 1;
+    }
+
+    tx.commit()?;
+    Ok(linked)
+}
+
+// Budget operations
+pub fn add_budget(conn: &Connection, budget: &Budget) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO budgets (id, category_id, amount, currency, start_date, end_date, name, renewal, rollover)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            budget.id,
+            budget.category_id,
+            budget.amount.minor_units(),
+            budget.amount.currency().code(),
+            budget.start_date.to_string(),
+            budget.end_date.map(|date| date.to_string()),
+            budget.name,
+            budget.renewal.map(|renewal| renewal.to_string()),
+            budget.rollover,
+        ],
+    )?;
+    save_alert_thresholds(&tx, budget)?;
+    tx.commit()
+}
+
+pub fn update_budget(conn: &Connection, budget: &Budget) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE budgets
+         SET category_id = ?1, amount = ?2, currency = ?3, start_date = ?4, end_date = ?5, name = ?6,
+             renewal = ?7, rollover = ?8
+         WHERE id = ?9",
+        params![
+            budget.category_id,
+            budget.amount.minor_units(),
+            budget.amount.currency().code(),
+            budget.start_date.to_string(),
+            budget.end_date.map(|date| date.to_string()),
+            budget.name,
+            budget.renewal.map(|renewal| renewal.to_string()),
+            budget.rollover,
+            budget.id,
+        ],
+    )?;
+    save_alert_thresholds(&tx, budget)?;
+    tx.commit()
+}
+
+/// Replaces the alert thresholds stored for a budget with its own.
+fn save_alert_thresholds(conn: &Connection, budget: &Budget) -> SqlResult<()> {
+    conn.execute("DELETE FROM budget_alert_thresholds WHERE budget_id = ?1", [&budget.id])?;
+    for percent in &budget.alert_thresholds {
+        conn.execute(
+            "INSERT OR IGNORE INTO budget_alert_thresholds (budget_id, percent) VALUES (?1, ?2)",
+            params![budget.id, percent],
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn delete_budget(conn: &Connection, budget_id: &str) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    remove_budget(&tx, budget_id)?;
+    tx.commit()
+}
+
+/// Deletes a budget along with its periods, alert thresholds and the
+/// alerts it has raised.
+fn remove_budget(conn: &Connection, budget_id: &str) -> SqlResult<()> {
+    conn.execute("DELETE FROM budget_periods WHERE budget_id = ?1", [budget_id])?;
+    conn.execute("DELETE FROM budget_alert_thresholds WHERE budget_id = ?1", [budget_id])?;
+    conn.execute("DELETE FROM budget_alerts WHERE budget_id = ?1", [budget_id])?;
+    conn.execute(
+        "DELETE FROM budgets WHERE id = ?1",
+        [budget_id],
+    )?;
+
+    Ok(())
+}
+
+pub fn get_all_budgets(conn: &Connection) -> SqlResult<Vec<Budget>> {
+    let mut thresholds: HashMap<String, Vec<u32>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT budget_id, percent FROM budget_alert_thresholds ORDER BY percent")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)))?;
+    for row in rows {
+        let (budget_id, percent) = row?;
+        thresholds.entry(budget_id).or_default().push(percent);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, category_id, amount, start_date, end_date, name, currency, renewal, rollover
+         FROM budgets
+         ORDER BY start_date DESC"
+    )?;
+    
+    let budget_iter = stmt.query_map([], |row| {
+        let start_date_str: String = row.get(3)?;
+        let start_date = NaiveDate::parse_from_str(&start_date_str, "%Y-%m-%d")
+            .unwrap_or_else(|_| chrono::Local::now().naive_local().date());
+            
+        let end_date_str: Option<String> = row.get(4)?;
+        let end_date = end_date_str.map(|date| {
+            NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .unwrap_or_else(|_| chrono::Local::now().naive_local().date())
+        });
+
+        // An unknown renewal is read as not renewing
+        let renewal: Option<String> = row.get(7)?;
+        let renewal = renewal.and_then(|renewal| renewal.parse::<Renewal>().ok());
+        
+        let id: String = row.get(0)?;
+        Ok(Budget {
+            alert_thresholds: thresholds.remove(&id).unwrap_or_default(),
+            id,
+            category_id: row.get(1)?,
+            amount: money_from_row(row, 2, 6)?,
+            start_date,
+            end_date,
+            name: row.get(5)?,
+            renewal,
+            rollover: row.get(8)?,
+        })
+    })?;
+    
+    let mut budgets = Vec::new();
+    for budget in budget_iter {
+        budgets.push(budget?);
+    }
+    
+    Ok(budgets)
+}
+
+pub fn get_all_budget_periods(conn: &Connection) -> SqlResult<Vec<BudgetPeriod>> {
+    let mut stmt = conn.prepare(
+        "SELECT budget_id, start_date, end_date, allocated, spent, carried_in, currency
+         FROM budget_periods
+         ORDER BY budget_id, start_date"
+    )?;
+
+    let period_iter = stmt.query_map([], |row| {
+        let start_date_str: String = row.get(1)?;
+        let end_date_str: String = row.get(2)?;
+        let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap_or_else(|_| chrono::Local::now().naive_local().date());
+
+        Ok(BudgetPeriod {
+            budget_id: row.get(0)?,
+            range: DateRange::new(parse(&start_date_str), parse(&end_date_str)),
+            allocated: money_from_row(row, 3, 6)?,
+            spent: money_from_row(row, 4, 6)?,
+            carried_in: money_from_row(row, 5, 6)?,
+        })
+    })?;
+
+    let mut periods = Vec::new();
+    for period in period_iter {
+        periods.push(period?);
+    }
+
+    Ok(periods)
+}
+
+/// Records every budget's periods up to the one containing `today`,
+/// returning them. Allocations already recorded for closed periods are
+/// kept, while the current period takes the budget's amount; spending and
+/// carry are recalculated from the current transactions. Periods no longer
+/// part of a budget, after its dates or renewal changed, are dropped.
+pub fn sync_budget_periods(conn: &Connection, today: NaiveDate) -> SqlResult<Vec<BudgetPeriod>> {
+    let budgets = get_all_budgets(conn)?;
+    let recorded = get_all_budget_periods(conn)?;
+    let transactions = get_all_transactions(conn)?;
+    let categories = get_all_categories(conn)?;
+    let converter = get_currency_converter(conn)?;
+
+    let periods: Vec<BudgetPeriod> = budgets.iter()
+        .flat_map(|budget| budget.history(&recorded, today, &transactions, &categories, &converter))
+        .collect();
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM budget_periods", [])?;
+    for period in &periods {
+        tx.execute(
+            "INSERT INTO budget_periods (budget_id, start_date, end_date, allocated, spent, carried_in, currency)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                period.budget_id,
+                period.range.from.to_string(),
+                period.range.to.to_string(),
+                period.allocated.minor_units(),
+                period.spent.minor_units(),
+                period.carried_in.minor_units(),
+                period.allocated.currency().code(),
+            ],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(periods)
+}
+
+// Budget alert operations
+/// Raises a notification for each budget whose current period's spending
+/// has reached an alert threshold it had not reached before, returning the
+/// new notifications. Each threshold alerts once per period, and when
+/// spending jumps past several at once only the highest is notified.
+pub fn check_budget_alerts(conn: &Connection, now: NaiveDateTime) -> SqlResult<Vec<Notification>> {
+    let today = now.date();
+    let budgets = get_all_budgets(conn)?;
+    let recorded = get_all_budget_periods(conn)?;
+    let transactions = get_all_transactions(conn)?;
+    let categories = get_all_categories(conn)?;
+    let converter = get_currency_converter(conn)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let mut notifications = Vec::new();
+    for budget in &budgets {
+        let Some(period) = budget.history(&recorded, today, &transactions, &categories, &converter).pop() else {
+            continue;
+        };
+        if !period.range.contains(today) {
+            continue;
+        }
+
+        let mut highest = None;
+        for threshold in budget.thresholds_reached(&period) {
+            let raised = tx.execute(
+                "INSERT OR IGNORE INTO budget_alerts (budget_id, period_start, percent) VALUES (?1, ?2, ?3)",
+                params![budget.id, period.range.from.to_string(), threshold],
+            )?;
+            if raised > 0 {
+                highest = Some(threshold);
+            }
+        }
+
+        if let Some(threshold) = highest {
+            let notification = budget.alert(&period, threshold, now);
+            add_notification(&tx, &notification)?;
+            notifications.push(notification);
+        }
+    }
+    tx.commit()?;
+
+    Ok(notifications)
+}
+
+// Notification operations
+pub fn add_notification(conn: &Connection, notification: &Notification) -> SqlResult<()> {
+    conn.execute(
+        "INSERT INTO notifications (id, title, body, created_at, read) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            notification.id,
+            notification.title,
+            notification.body,
+            notification.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            notification.read,
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn mark_notifications_read(conn: &Connection) -> SqlResult<()> {
+    conn.execute("UPDATE notifications SET read = 1 WHERE read = 0", [])?;
+
+    Ok(())
+}
+
+pub fn delete_notification(conn: &Connection, notification_id: &str) -> SqlResult<()> {
+    conn.execute("DELETE FROM notifications WHERE id = ?1", [notification_id])?;
+
+    Ok(())
+}
+
+pub fn clear_notifications(conn: &Connection) -> SqlResult<()> {
+    conn.execute("DELETE FROM notifications", [])?;
+
+    Ok(())
+}
+
+/// Every notification, newest first.
+pub fn get_all_notifications(conn: &Connection) -> SqlResult<Vec<Notification>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, title, body, created_at, read
+         FROM notifications
+         ORDER BY created_at DESC, rowid DESC"
+    )?;
+
+    let notification_iter = stmt.query_map([], |row| {
+        let created_at_str: String = row.get(3)?;
+        let created_at = NaiveDateTime::parse_from_str(&created_at_str, "%Y-%m-%d %H:%M:%S").map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
+        Ok(Notification {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            body: row.get(2)?,
+            created_at,
+            read: row.get(4)?,
+        })
+    })?;
+
+    let mut notifications = Vec::new();
+    for notification in notification_iter {
+        notifications.push(notification?);
+    }
+
+    Ok(notifications)
+}
+
+// Envelope allocation operations
+/// Records what is assigned to a category for a month, replacing what was
+/// assigned before. Assigning nothing removes the allocation.
+pub fn set_allocation(conn: &Connection, allocation: &Allocation) -> SqlResult<()> {
+    if allocation.amount.is_zero() {
+        conn.execute(
+            "DELETE FROM envelope_allocations WHERE category_id = ?1 AND month = ?2",
+            params![allocation.category_id, allocation.month.to_string()],
+        )?;
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO envelope_allocations (category_id, month, amount, currency)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (category_id, month) DO UPDATE SET amount = excluded.amount, currency = excluded.currency",
+        params![
+            allocation.category_id,
+            allocation.month.to_string(),
+            allocation.amount.minor_units(),
+            allocation.amount.currency().code(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn get_all_allocations(conn: &Connection) -> SqlResult<Vec<Allocation>> {
+    let mut stmt = conn.prepare(
+        "SELECT category_id, month, amount, currency
+         FROM envelope_allocations
+         ORDER BY month, category_id"
+    )?;
+
+    let allocation_iter = stmt.query_map([], |row| {
+        let month_str: String = row.get(1)?;
+        let month = NaiveDate::parse_from_str(&month_str, "%Y-%m-%d").map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
+        Ok(Allocation {
+            category_id: row.get(0)?,
+            month,
+            amount: money_from_row(row, 2, 3)?,
+        })
+    })?;
+
+    let mut allocations = Vec::new();
+    for allocation in allocation_iter {
+        allocations.push(allocation?);
+    }
+
+    Ok(allocations)
+}
+
+// Exchange rate operations
+pub fn add_exchange_rate(conn: &Connection, rate: &ExchangeRate) -> SqlResult<()> {
+    conn.execute(
+        "INSERT INTO exchange_rates (id, from_currency, to_currency, rate, effective_date)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            rate.id,
+            rate.from.code(),
+            rate.to.code(),
+            rate.rate,
+            rate.effective_date.to_string(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn delete_exchange_rate(conn: &Connection, rate_id: &str) -> SqlResult<()> {
+    conn.execute(
+        "DELETE FROM exchange_rates WHERE id = ?1",
+        [rate_id],
+    )?;
+
+    Ok(())
+}
+
+pub fn get_all_exchange_rates(conn: &Connection) -> SqlResult<Vec<ExchangeRate>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, from_currency, to_currency, rate, effective_date
+         FROM exchange_rates
+         ORDER BY effective_date DESC, from_currency, to_currency"
+    )?;
+
+    let rate_iter = stmt.query_map([], |row| {
+        let date_str: String = row.get(4)?;
+        let effective_date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
+        Ok(ExchangeRate {
+            id: row.get(0)?,
+            from: currency_from_row(row, 1)?,
+            to: currency_from_row(row, 2)?,
+            rate: row.get(3)?,
+            effective_date,
+        })
+    })?;
+
+    let mut rates = Vec::new();
+    for rate in rate_iter {
+        rates.push(rate?);
+    }
+
+    Ok(rates)
+}
+
+// Settings operations
+const BASE_CURRENCY_KEY: &str = "base_currency";
+const BACKUPS_TO_KEEP_KEY: &str = "backups_to_keep";
+const ENVELOPE_BUDGETING_KEY: &str = "envelope_budgeting";
+const DESKTOP_NOTIFICATIONS_KEY: &str = "desktop_notifications";
+
+pub fn get_setting(conn: &Connection, key: &str) -> SqlResult<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        [key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> SqlResult<()> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        [key, value],
+    )?;
+
+    Ok(())
+}
+
+pub fn get_all_settings(conn: &Connection) -> SqlResult<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT key, value FROM settings ORDER BY key")?;
+
+    let setting_iter = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+    let mut settings = Vec::new();
+    for setting in setting_iter {
+        settings.push(setting?);
+    }
+
+    Ok(settings)
+}
+
+/// The currency totals are reported in, USD until the user picks another.
+pub fn get_base_currency(conn: &Connection) -> SqlResult<Currency> {
+    Ok(get_setting(conn, BASE_CURRENCY_KEY)?
+        .and_then(|code| code.parse().ok())
+        .unwrap_or_default())
+}
+
+pub fn set_base_currency(conn: &Connection, currency: Currency) -> SqlResult<()> {
+    set_setting(conn, BASE_CURRENCY_KEY, currency.code())
+}
+
+/// How many backups rotation keeps.
+pub fn get_backups_to_keep(conn: &Connection) -> SqlResult<usize> {
+    Ok(get_setting(conn, BACKUPS_TO_KEEP_KEY)?
+        .and_then(|keep| keep.parse().ok())
+        .unwrap_or(DEFAULT_BACKUPS_TO_KEEP))
+}
+
+pub fn set_backups_to_keep(conn: &Connection, keep: usize) -> SqlResult<()> {
+    set_setting(conn, BACKUPS_TO_KEEP_KEY, &keep.to_string())
+}
+
+/// Whether the Budgets page assigns income to category envelopes instead
+/// of tracking spending limits.
+pub fn get_envelope_budgeting(conn: &Connection) -> SqlResult<bool> {
+    Ok(get_setting(conn, ENVELOPE_BUDGETING_KEY)?.is_some_and(|value| value == "true"))
+}
+
+pub fn set_envelope_budgeting(conn: &Connection, enabled: bool) -> SqlResult<()> {
+    set_setting(conn, ENVELOPE_BUDGETING_KEY, &enabled.to_string())
+}
+
+/// Whether budget alerts are also shown as desktop notifications.
+pub fn get_desktop_notifications(conn: &Connection) -> SqlResult<bool> {
+    Ok(get_setting(conn, DESKTOP_NOTIFICATIONS_KEY)?.is_some_and(|value| value == "true"))
+}
+
+pub fn set_desktop_notifications(conn: &Connection, enabled: bool) -> SqlResult<()> {
+    set_setting(conn, DESKTOP_NOTIFICATIONS_KEY, &enabled.to_string())
+}
+
+pub fn get_currency_converter(conn: &Connection) -> SqlResult<CurrencyConverter> {
+    Ok(CurrencyConverter::new(
+        get_base_currency(conn)?,
+        get_all_exchange_rates(conn)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabaseConnection;
+    use crate::models::testing::{date, expense, money};
+    use crate::models::{Frequency, RecurrenceEnd};
+
+    fn database() -> DatabaseConnection {
+        let db = DatabaseConnection::new(":memory:").unwrap();
+        db.initialize_schema().unwrap();
+        db
+    }
+
+    fn monthly_rule(description: &str, start_date: NaiveDate, end: RecurrenceEnd) -> RecurringRule {
+        RecurringRule::new(
+            description.to_string(),
+            money(1_500),
+            TransactionType::Expense,
+            None,
+            None,
+            Frequency::Monthly,
+            1,
+            None,
+            start_date,
+            end,
+        )
+    }
+
+    fn account(conn: &Connection, name: &str) -> Account {
+        let account = Account::new(name.to_string(), AccountType::Checking, money(0), date(2026, 1, 1));
+        add_account(conn, &account).unwrap();
+        account
+    }
+
+    #[test]
+    fn deleting_an_account_detaches_or_removes_what_refers_to_it() {
+        let db = database();
+        let conn = db.get_connection();
+        let checking = account(conn, "Checking");
+
+        let mut rule = monthly_rule("Rent", date(2026, 1, 1), RecurrenceEnd::Never);
+        rule.account_id = Some(checking.id.clone());
+        add_recurring_rule(conn, &rule).unwrap();
+        let mut category_rule = CategoryRule::new("Cafe".to_string(), MatchKind::Contains, "cafe".to_string(), "cat_2".to_string());
+        category_rule.account_id = Some(checking.id.clone());
+        add_category_rule(conn, &category_rule).unwrap();
+        let mut transaction = expense(money(500), date(2026, 1, 2));
+        transaction.account_id = Some(checking.id.clone());
+        add_transaction(conn, &transaction).unwrap();
+
+        delete_account(conn, &checking.id).unwrap();
+        assert!(get_all_accounts(conn).unwrap().is_empty());
+        assert_eq!(get_all_transactions(conn).unwrap()[0].account_id, None);
+        assert_eq!(get_all_recurring_rules(conn).unwrap()[0].account_id, None);
+        assert!(get_all_category_rules(conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn an_account_with_transfers_is_not_deleted() {
+        let db = database();
+        let conn = db.get_connection();
+        let checking = account(conn, "Checking");
+        let savings = account(conn, "Savings");
+        let transfer = Transfer::new("Saving".to_string(), money(1_000), date(2026, 1, 2), checking.id.clone(), savings.id.clone());
+        save_transfer(conn, &transfer).unwrap();
+
+        assert!(delete_account(conn, &savings.id).is_err());
+        assert_eq!(get_all_accounts(conn).unwrap().len(), 2);
+        assert_eq!(Transfer::find(&get_all_transactions(conn).unwrap(), &transfer.id), Some(transfer));
+    }
+
+    #[test]
+    fn generating_catches_up_without_writing_anything_twice() {
+        let db = database();
+        let conn = db.get_connection();
+        add_recurring_rule(conn, &monthly_rule("Gym", date(2026, 1, 31), RecurrenceEnd::AfterCount(4))).unwrap();
+
+        assert_eq!(generate_due_transactions(conn, date(2026, 3, 30)).unwrap(), 2);
+        assert_eq!(generate_due_transactions(conn, date(2026, 3, 30)).unwrap(), 0);
+        assert_eq!(generate_due_transactions(conn, date(2026, 12, 31)).unwrap(), 2);
+
+        let mut dates: Vec<NaiveDate> = get_all_transactions(conn).unwrap().iter().map(|t| t.date).collect();
+        dates.sort();
+        assert_eq!(dates, vec![date(2026, 1, 31), date(2026, 2, 28), date(2026, 3, 31), date(2026, 4, 30)]);
+        assert_eq!(get_all_recurring_rules(conn).unwrap()[0].last_generated, Some(date(2026, 4, 30)));
+    }
+
+    #[test]
+    fn recurring_occurrences_get_their_payee_and_its_category() {
+        let db = database();
+        let conn = db.get_connection();
+        let payee = Payee::new("Netflix".to_string(), vec!["NETFLIX.COM".to_string()], Some("cat_4".to_string()));
+        add_payee(conn, &payee).unwrap();
+        add_recurring_rule(conn, &monthly_rule("NETFLIX.COM 866-579", date(2026, 1, 5), RecurrenceEnd::Never)).unwrap();
+
+        assert_eq!(generate_due_transactions(conn, date(2026, 2, 10)).unwrap(), 2);
+        let transactions = get_all_transactions(conn).unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert!(transactions.iter().all(|t| t.payee_id.as_deref() == Some(payee.id.as_str())));
+        assert!(transactions.iter().all(|t| t.category_id.as_deref() == Some("cat_4")));
+    }
+
+    #[test]
+    fn merging_keeps_the_details_the_kept_transaction_lacks() {
+        let db = database();
+        let conn = db.get_connection();
+        let checking = account(conn, "Checking");
+        let keep = expense(money(4_999), date(2026, 3, 10));
+        add_transaction(conn, &Transaction { tags: vec!["online".to_string()], ..keep.clone() }).unwrap();
+        let duplicate = Transaction {
+            category_id: Some("cat_2".to_string()),
+            account_id: Some(checking.id.clone()),
+            external_id: Some("FIT1".to_string()),
+            tags: vec!["amazon".to_string(), "online".to_string()],
+            ..expense(money(4_999), date(2026, 3, 12))
+        };
+        add_transaction(conn, &duplicate).unwrap();
+        dismiss_duplicate(conn, DuplicatePair::new(duplicate.id, Uuid::new_v4())).unwrap();
+
+        merge_transactions(conn, keep.id, &duplicate).unwrap();
+        let transactions = get_all_transactions(conn).unwrap();
+        assert_eq!(transactions.len(), 1);
+        let merged = &transactions[0];
+        assert_eq!(merged.id, keep.id);
+        assert_eq!(merged.date, keep.date);
+        assert_eq!(merged.category_id.as_deref(), Some("cat_2"));
+        assert_eq!(merged.account_id, Some(checking.id));
+        assert_eq!(merged.external_id.as_deref(), Some("FIT1"));
+        assert_eq!(merged.tags, vec!["amazon", "online"]);
+        assert!(get_dismissed_duplicates(conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn transfer_legs_are_not_merged() {
+        let db = database();
+        let conn = db.get_connection();
+        let checking = account(conn, "Checking");
+        let savings = account(conn, "Savings");
+        save_transfer(conn, &Transfer::new("Saving".to_string(), money(1_000), date(2026, 1, 2), checking.id.clone(), savings.id.clone())).unwrap();
+        let leg = get_all_transactions(conn).unwrap().remove(0);
+        let other = Transaction { account_id: leg.account_id.clone(), ..expense(money(1_000), date(2026, 1, 2)) };
+        add_transaction(conn, &other).unwrap();
+
+        assert!(merge_transactions(conn, other.id, &leg).is_err());
+        assert!(merge_transactions(conn, leg.id, &other).is_err());
+        let batch = ImportBatch { merges: vec![(leg.id, other.clone())], ..ImportBatch::default() };
+        assert!(import_transactions(conn, &batch).is_err());
+        assert_eq!(get_all_transactions(conn).unwrap().len(), 3);
+    }
+
+    fn category(conn: &Connection, name: &str, parent_id: Option<&str>) -> Category {
+        let category = Category::new(name.to_string(), "#000000".to_string(), parent_id.map(str::to_string));
+        add_category(conn, &category).unwrap();
+        category
+    }
+
+    #[test]
+    fn merging_a_category_moves_everything_filed_under_it() {
+        let db = database();
+        let conn = db.get_connection();
+        let dining = category(conn, "Dining", None);
+        let coffee = category(conn, "Coffee", Some(&dining.id));
+        let food = category(conn, "Food", None);
+        add_transaction(conn, &Transaction { category_id: Some(dining.id.clone()), ..expense(money(2_000), date(2026, 3, 2)) }).unwrap();
+        add_budget(conn, &Budget::new("Eating out".to_string(), Some(dining.id.clone()), money(10_000), date(2026, 3, 1), None, None, false)).unwrap();
+        add_category_rule(conn, &CategoryRule::new("Cafe".to_string(), MatchKind::Contains, "cafe".to_string(), dining.id.clone())).unwrap();
+        set_allocation(conn, &Allocation::new(dining.id.clone(), date(2026, 3, 1), money(5_000))).unwrap();
+        set_allocation(conn, &Allocation::new(food.id.clone(), date(2026, 3, 1), money(30_000))).unwrap();
+
+        merge_categories(conn, &dining.id, &food.id).unwrap();
+        let categories = get_all_categories(conn).unwrap();
+        assert!(!categories.iter().any(|c| c.id == dining.id));
+        assert_eq!(categories.iter().find(|c| c.id == coffee.id).unwrap().parent_id, Some(food.id.clone()));
+        assert_eq!(get_all_transactions(conn).unwrap()[0].category_id, Some(food.id.clone()));
+        assert_eq!(get_all_budgets(conn).unwrap()[0].category_id, Some(food.id.clone()));
+        assert_eq!(get_all_category_rules(conn).unwrap()[0].category_id, food.id);
+        let allocations = get_all_allocations(conn).unwrap();
+        assert_eq!(allocations.len(), 1);
+        assert_eq!((allocations[0].category_id.as_str(), allocations[0].amount), (food.id.as_str(), money(35_000)));
+    }
+
+    #[test]
+    fn deleting_a_category_without_a_replacement_uncategorises_its_transactions() {
+        let db = database();
+        let conn = db.get_connection();
+        let food = category(conn, "Food", None);
+        let dining = category(conn, "Dining", Some(&food.id));
+        let coffee = category(conn, "Coffee", Some(&dining.id));
+        add_transaction(conn, &Transaction { category_id: Some(dining.id.clone()), ..expense(money(2_000), date(2026, 3, 2)) }).unwrap();
+        add_budget(conn, &Budget::new("Eating out".to_string(), Some(dining.id.clone()), money(10_000), date(2026, 3, 1), None, None, false)).unwrap();
+        add_category_rule(conn, &CategoryRule::new("Cafe".to_string(), MatchKind::Contains, "cafe".to_string(), dining.id.clone())).unwrap();
+
+        delete_category(conn, &dining.id, None).unwrap();
+        let categories = get_all_categories(conn).unwrap();
+        assert_eq!(categories.iter().find(|c| c.id == coffee.id).unwrap().parent_id, Some(food.id));
+        assert_eq!(get_all_transactions(conn).unwrap()[0].category_id, None);
+        assert!(get_all_budgets(conn).unwrap().is_empty());
+        assert!(get_all_category_rules(conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn each_alert_threshold_is_notified_once_per_period() {
+        let db = database();
+        let conn = db.get_connection();
+        let budget = Budget {
+            alert_thresholds: vec![50, 80, 100],
+            ..Budget::new("Spending".to_string(), None, money(10_000), date(2026, 3, 1), None, Some(Renewal::Monthly), false)
+        };
+        add_budget(conn, &budget).unwrap();
+        let at = |day: u32| date(2026, 3, day).and_hms_opt(12, 0, 0).unwrap();
+
+        add_transaction(conn, &expense(money(8_500), date(2026, 3, 5))).unwrap();
+        let raised = check_budget_alerts(conn, at(5)).unwrap();
+        // Jumping past two thresholds at once notifies only the higher one
+        assert_eq!(raised.len(), 1);
+        assert!(raised[0].body.contains("80%"));
+        assert!(check_budget_alerts(conn, at(6)).unwrap().is_empty());
+
+        add_transaction(conn, &expense(money(2_000), date(2026, 3, 7))).unwrap();
+        assert_eq!(check_budget_alerts(conn, at(7)).unwrap().len(), 1);
+        assert!(check_budget_alerts(conn, at(8)).unwrap().is_empty());
+        assert_eq!(get_all_notifications(conn).unwrap().len(), 2);
+
+        // A new period starts over
+        add_transaction(conn, &expense(money(6_000), date(2026, 4, 2))).unwrap();
+        assert_eq!(check_budget_alerts(conn, date(2026, 4, 2).and_hms_opt(12, 0, 0).unwrap()).unwrap().len(), 1);
     }
-    
-    Ok(budgets)
 }
@@ -1,6 +1,8 @@
 use rusqlite::{Connection, Result as SqlResult};
 
-pub fn initialize_schema(conn: &Connection) -> SqlResult<()> {
+/// Creates the tables of the version 1 schema. Later versions are reached
+/// through the migrations in `db::migrations`.
+pub fn create_tables(conn: &Connection) -> SqlResult<()> {
     // Create categories table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS categories (
@@ -17,9 +19,9 @@ pub fn initialize_schema(conn: &Connection) -> SqlResult<()> {
         "CREATE TABLE IF NOT EXISTS transactions (
             id TEXT PRIMARY KEY,
             amount REAL NOT NULL,
-            description TEXT,
+            description TEXT NOT NULL DEFAULT '',
             date TEXT NOT NULL,
-            type TEXT NOT NULL,
+            transaction_type TEXT NOT NULL,
             category_id TEXT,
             FOREIGN KEY (category_id) REFERENCES categories (id)
         )",
@@ -40,13 +42,10 @@ pub fn initialize_schema(conn: &Connection) -> SqlResult<()> {
         [],
     )?;
     
-    // Initialize default categories if they don't exist
-    initialize_default_categories(conn)?;
-    
     Ok(())
 }
 
-fn initialize_default_categories(conn: &Connection) -> SqlResult<()> {
+pub fn initialize_default_categories(conn: &Connection) -> SqlResult<()> {
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0))?;
     
     if count == 0 {
@@ -63,16 +62,12 @@ fn initialize_default_categories(conn: &Connection) -> SqlResult<()> {
             ("cat_10", "Other", "#9E9E9E"),
         ];
         
-        let tx = conn.transaction()?;
-        
         for (id, name, color) in default_categories.iter() {
-            tx.execute(
+            conn.execute(
                 "INSERT INTO categories (id, name, color) VALUES (?1, ?2, ?3)",
                 [id, name, color],
             )?;
         }
-        
-        tx.commit()?;
     }
     
     Ok(())
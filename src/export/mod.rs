@@ -0,0 +1,230 @@
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::db::migrations::schema_version;
+use crate::db::operations;
+use crate::import::csv::ImportProfile;
+use crate::models::{
+    Account, Allocation, Budget, BudgetPeriod, Category, CategoryRule, DuplicatePair, ExchangeRate, Notification, Payee,
+    RecurringRule, Tag, Transaction, TransactionType,
+};
+use crate::models::category;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("Failed to write file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to write CSV: {0}")]
+    Csv(#[from] ::csv::Error),
+    #[error("Failed to write JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+}
+
+/// Which transactions a CSV export includes. Unset fields do not filter;
+/// the date range is inclusive, a category includes its subcategories and
+/// a tag is matched by name.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub category_id: Option<String>,
+    pub account_id: Option<String>,
+    pub tag: Option<String>,
+}
+
+impl ExportFilter {
+    pub fn matches(&self, transaction: &Transaction, categories: &[Category]) -> bool {
+        self.from.is_none_or(|from| transaction.date >= from)
+            && self.to.is_none_or(|to| transaction.date <= to)
+            && self.category_id.as_ref().is_none_or(|id| {
+                transaction.category_amounts().iter()
+                    .any(|(category, _)| category.is_some_and(|category| category::is_within(categories, category, id)))
+            })
+            && self.account_id.as_ref().is_none_or(|id| transaction.account_id.as_ref() == Some(id))
+            && self.tag.as_ref().is_none_or(|tag| transaction.has_tag(tag))
+    }
+}
+
+/// Every table of the database, for a complete machine-readable copy.
+#[derive(Debug, Serialize)]
+pub struct DataDump {
+    pub schema_version: i64,
+    pub exported_at: String,
+    pub settings: BTreeMap<String, String>,
+    pub accounts: Vec<Account>,
+    pub categories: Vec<Category>,
+    pub tags: Vec<Tag>,
+    pub payees: Vec<Payee>,
+    pub transactions: Vec<Transaction>,
+    pub budgets: Vec<Budget>,
+    pub budget_periods: Vec<BudgetPeriod>,
+    pub envelope_allocations: Vec<Allocation>,
+    pub notifications: Vec<Notification>,
+    pub recurring_rules: Vec<RecurringRule>,
+    pub category_rules: Vec<CategoryRule>,
+    pub exchange_rates: Vec<ExchangeRate>,
+    pub import_profiles: Vec<ImportProfile>,
+    pub dismissed_duplicates: Vec<DuplicatePair>,
+}
+
+impl DataDump {
+    pub fn load(conn: &Connection) -> rusqlite::Result<Self> {
+        Ok(Self {
+            schema_version: schema_version(conn)?,
+            exported_at: chrono::Local::now().to_rfc3339(),
+            settings: operations::get_all_settings(conn)?.into_iter().collect(),
+            accounts: operations::get_all_accounts(conn)?,
+            categories: operations::get_all_categories(conn)?,
+            tags: operations::get_all_tags(conn)?,
+            payees: operations::get_all_payees(conn)?,
+            transactions: operations::get_all_transactions(conn)?,
+            budgets: operations::get_all_budgets(conn)?,
+            budget_periods: operations::get_all_budget_periods(conn)?,
+            envelope_allocations: operations::get_all_allocations(conn)?,
+            notifications: operations::get_all_notifications(conn)?,
+            recurring_rules: operations::get_all_recurring_rules(conn)?,
+            category_rules: operations::get_all_category_rules(conn)?,
+            exchange_rates: operations::get_all_exchange_rates(conn)?,
+            import_profiles: operations::get_all_import_profiles(conn)?,
+            dismissed_duplicates: operations::get_dismissed_duplicates(conn)?.into_iter().collect(),
+        })
+    }
+}
+
+/// Writes the transactions matching `filter` as CSV, one row each, oldest
+/// first, and returns how many were written. Amounts are signed plain
+/// numbers so spreadsheets can sum them; the file starts with a UTF-8 byte
+/// order mark so spreadsheets detect the encoding.
+pub fn write_transactions_csv<W: Write>(
+    mut writer: W,
+    transactions: &[Transaction],
+    categories: &[Category],
+    accounts: &[Account],
+    payees: &[Payee],
+    filter: &ExportFilter,
+) -> Result<usize, ExportError> {
+    writer.write_all("\u{feff}".as_bytes())?;
+    let mut csv = ::csv::Writer::from_writer(writer);
+    csv.write_record(["Date", "Description", "Payee", "Type", "Amount", "Currency", "Category", "Account", "Tags"])?;
+
+    let mut selected: Vec<&Transaction> = transactions.iter().filter(|t| filter.matches(t, categories)).collect();
+    selected.sort_by_key(|t| t.date);
+
+    for transaction in &selected {
+        let amount = match transaction.transaction_type {
+            TransactionType::Expense => -transaction.amount,
+            TransactionType::Income | TransactionType::Transfer => transaction.amount,
+        };
+        let account = transaction.account_id.as_ref()
+            .and_then(|id| accounts.iter().find(|a| &a.id == id))
+            .map_or("", |a| a.name.as_str());
+        let name = |category_id: Option<&str>| match category_id {
+            Some(id) if categories.iter().any(|c| c.id == id) => category::full_name(categories, id),
+            Some(_) => "No Category".to_string(),
+            None => String::new(),
+        };
+        // Split lines are listed with their amounts in the one cell
+        let category = if transaction.is_split() {
+            transaction.splits.iter()
+                .map(|split| format!("{} {}", name(split.category_id.as_deref()), split.amount.to_input_string()))
+                .collect::<Vec<_>>()
+                .join("; ")
+        } else {
+            name(transaction.category_id.as_deref())
+        };
+
+        let payee = transaction.payee_id.as_ref()
+            .and_then(|id| payees.iter().find(|p| &p.id == id))
+            .map_or("", |p| p.name.as_str());
+        let tags = transaction.tags.join(", ");
+
+        csv.write_record([
+            transaction.date.to_string().as_str(),
+            transaction.description.as_str(),
+            payee,
+            transaction.transaction_type.to_string().as_str(),
+            amount.to_input_string().as_str(),
+            amount.currency().code(),
+            category.as_str(),
+            account,
+            tags.as_str(),
+        ])?;
+    }
+
+    csv.flush()?;
+    Ok(selected.len())
+}
+
+/// Exports the stored transactions matching `filter` to a CSV file.
+pub fn export_transactions_csv(conn: &Connection, path: &Path, filter: &ExportFilter) -> Result<usize, ExportError> {
+    let transactions = operations::get_all_transactions(conn)?;
+    let categories = operations::get_all_categories(conn)?;
+    let accounts = operations::get_all_accounts(conn)?;
+    let payees = operations::get_all_payees(conn)?;
+
+    write_transactions_csv(BufWriter::new(File::create(path)?), &transactions, &categories, &accounts, &payees, filter)
+}
+
+/// Writes every table to a pretty-printed JSON file.
+pub fn export_json(conn: &Connection, path: &Path) -> Result<(), ExportError> {
+    let dump = DataDump::load(conn)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, &dump)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense, income, money};
+    use crate::models::{AccountType, Split};
+
+    #[test]
+    fn csv_rows_are_signed_filtered_and_oldest_first() {
+        let food = Category::new("Food".to_string(), "#000000".to_string(), None);
+        let dining = Category::new("Dining".to_string(), "#000000".to_string(), Some(food.id.clone()));
+        let rent = Category::new("Rent".to_string(), "#000000".to_string(), None);
+        let categories = [food.clone(), dining.clone(), rent.clone()];
+        let checking = Account::new("Checking".to_string(), AccountType::Checking, money(0), date(2026, 1, 1));
+
+        let transactions = [
+            Transaction {
+                description: "Pizza, to go".to_string(),
+                category_id: Some(dining.id.clone()),
+                account_id: Some(checking.id.clone()),
+                tags: vec!["friends".to_string(), "weekend".to_string()],
+                ..expense(money(2_450), date(2026, 3, 14))
+            },
+            Transaction {
+                description: "Market".to_string(),
+                splits: vec![
+                    Split { category_id: Some(food.id.clone()), amount: money(3_000), memo: String::new() },
+                    Split { category_id: Some(rent.id.clone()), amount: money(1_000), memo: String::new() },
+                ],
+                ..expense(money(4_000), date(2026, 3, 2))
+            },
+            Transaction { category_id: Some(rent.id.clone()), ..expense(money(90_000), date(2026, 3, 1)) },
+            Transaction { description: "Salary".to_string(), ..income(money(250_000), date(2026, 2, 28)) },
+        ];
+
+        let filter = ExportFilter { from: Some(date(2026, 3, 1)), category_id: Some(food.id.clone()), ..ExportFilter::default() };
+        let mut output = Vec::new();
+        let written = write_transactions_csv(&mut output, &transactions, &categories, &[checking], &[], &filter).unwrap();
+
+        assert_eq!(written, 2);
+        assert_eq!(String::from_utf8(output).unwrap(), "\u{feff}\
+Date,Description,Payee,Type,Amount,Currency,Category,Account,Tags
+2026-03-02,Market,,Expense,-40.00,USD,Food 30.00; Rent 10.00,,
+2026-03-14,\"Pizza, to go\",,Expense,-24.50,USD,Food / Dining,Checking,\"friends, weekend\"
+");
+    }
+}
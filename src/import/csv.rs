@@ -0,0 +1,214 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::{match_category, parse_amount, DecimalSeparator, ImportError, ImportRow};
+use crate::models::{Category, Currency, Money, Transaction, TransactionType};
+
+/// Where the amount of a row comes from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AmountColumns {
+    /// One signed column; negative amounts are expenses.
+    Single(usize),
+    /// Separate columns for money out and money in, one of them blank.
+    DebitCredit { debit: usize, credit: usize },
+}
+
+/// How the columns of a bank's CSV export map onto a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CsvMapping {
+    pub delimiter: char,
+    pub has_header: bool,
+    pub date_column: usize,
+    /// A `chrono` format string such as `%d/%m/%Y`.
+    pub date_format: String,
+    pub description_column: usize,
+    pub amount: AmountColumns,
+    pub category_column: Option<usize>,
+    pub decimal_separator: DecimalSeparator,
+    pub currency: Currency,
+}
+
+impl Default for CsvMapping {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            has_header: true,
+            date_column: 0,
+            date_format: "%Y-%m-%d".to_string(),
+            description_column: 1,
+            amount: AmountColumns::Single(2),
+            category_column: None,
+            decimal_separator: DecimalSeparator::Dot,
+            currency: Currency::default(),
+        }
+    }
+}
+
+/// A saved mapping for one bank, with the account its statements go to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportProfile {
+    pub name: String,
+    pub mapping: CsvMapping,
+    pub account_id: Option<String>,
+}
+
+impl std::fmt::Display for ImportProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Splits CSV text into records. Rows may have differing lengths; the
+/// mapping decides which cells matter. The delimiter must be an ASCII
+/// character.
+pub fn read_records(contents: &str, delimiter: char) -> Result<Vec<Vec<String>>, ImportError> {
+    let delimiter = u8::try_from(delimiter)
+        .ok()
+        .filter(u8::is_ascii)
+        .ok_or_else(|| ImportError::Format(format!("Unsupported delimiter: {:?}", delimiter)))?;
+
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(contents.as_bytes());
+
+    let mut records = Vec::new();
+    for record in reader.records() {
+        records.push(record?.iter().map(str::to_string).collect());
+    }
+
+    Ok(records)
+}
+
+/// Parses every data row with `mapping`. Blank lines are skipped; line
+/// numbers are 1-based and count the header.
+pub fn parse_records(
+    records: &[Vec<String>],
+    mapping: &CsvMapping,
+    categories: &[Category],
+    account_id: Option<&str>,
+) -> Vec<ImportRow> {
+    let skip = usize::from(mapping.has_header);
+
+    records
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .filter(|(_, record)| record.iter().any(|cell| !cell.is_empty()))
+        .map(|(index, record)| ImportRow {
+            line: index + 1,
+            result: parse_record(record, mapping, categories, account_id),
+        })
+        .collect()
+}
+
+fn parse_record(
+    record: &[String],
+    mapping: &CsvMapping,
+    categories: &[Category],
+    account_id: Option<&str>,
+) -> Result<Transaction, String> {
+    let cell = |column: usize| record.get(column).map(String::as_str).unwrap_or("");
+
+    let date_str = cell(mapping.date_column);
+    let date = NaiveDate::parse_from_str(date_str, &mapping.date_format)
+        .map_err(|_| format!("Invalid date: {:?} (expected {})", date_str, mapping.date_format))?;
+
+    let parse = |column: usize| parse_amount(cell(column), mapping.decimal_separator, mapping.currency);
+    let (transaction_type, amount) = match mapping.amount {
+        AmountColumns::Single(column) => {
+            let amount = parse(column)?;
+            if amount.is_negative() {
+                (TransactionType::Expense, -amount)
+            } else {
+                (TransactionType::Income, amount)
+            }
+        }
+        AmountColumns::DebitCredit { debit, credit } => {
+            // Some banks write 0.00 in the unused column rather than leave
+            // it blank, so a zero counts as no amount
+            let amount_in = |column: usize| -> Result<Option<Money>, String> {
+                if cell(column).is_empty() {
+                    return Ok(None);
+                }
+                let amount = parse(column)?;
+                Ok((!amount.is_zero()).then(|| if amount.is_negative() { -amount } else { amount }))
+            };
+
+            match (amount_in(debit)?, amount_in(credit)?) {
+                (Some(amount), _) => (TransactionType::Expense, amount),
+                (None, Some(amount)) => (TransactionType::Income, amount),
+                (None, None) if cell(debit).is_empty() && cell(credit).is_empty() => {
+                    return Err("No debit or credit amount".to_string());
+                }
+                (None, None) => return Err("Amount is zero".to_string()),
+            }
+        }
+    };
+    if amount.is_zero() {
+        return Err("Amount is zero".to_string());
+    }
+
+    let category_id = mapping.category_column
+        .and_then(|column| match_category(categories, cell(column)));
+
+    Ok(Transaction::new(
+        cell(mapping.description_column).to_string(),
+        amount,
+        date,
+        transaction_type,
+        category_id,
+        account_id.map(str::to_string),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An export with separate money out and money in columns that writes
+    /// 0.00, or nothing, in the unused one.
+    const DEBIT_CREDIT_SAMPLE: &str = "\
+Date,Description,Debit,Credit,Balance
+15/03/2026,TESCO STORES 3021,42.17,0.00,1957.83
+16/03/2026,SALARY ACME LTD,0.00,2500.00,4457.83
+17/03/2026,CARD REFUND AMAZON,,12.50,4470.33
+18/03/2026,DIRECT DEBIT GYM,29.99,,4440.34
+19/03/2026,INTEREST,0.00,0.00,4440.34
+";
+
+    #[test]
+    fn debit_credit_columns_skip_zero_cells() {
+        let mapping = CsvMapping {
+            date_format: "%d/%m/%Y".to_string(),
+            amount: AmountColumns::DebitCredit { debit: 2, credit: 3 },
+            currency: Currency::GBP,
+            ..CsvMapping::default()
+        };
+        let records = read_records(DEBIT_CREDIT_SAMPLE, ',').unwrap();
+        let rows = parse_records(&records, &mapping, &[], None);
+
+        let parsed: Vec<Result<(TransactionType, i64), String>> = rows.into_iter()
+            .map(|row| row.result.map(|t| (t.transaction_type, t.amount.minor_units())))
+            .collect();
+        assert_eq!(parsed, vec![
+            Ok((TransactionType::Expense, 4_217)),
+            Ok((TransactionType::Income, 250_000)),
+            Ok((TransactionType::Income, 1_250)),
+            Ok((TransactionType::Expense, 2_999)),
+            Err("Amount is zero".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn delimiters_must_be_ascii() {
+        let records = read_records("2026-03-01;Caf\u{e9};-4,50\n", ';').unwrap();
+        assert_eq!(records, vec![vec!["2026-03-01", "Caf\u{e9}", "-4,50"]]);
+
+        // U+00A7 fits in a byte but would split UTF-8 text mid-character
+        assert!(read_records("a\u{a7}b\n", '\u{a7}').is_err());
+        assert!(read_records("a\u{2016}b\n", '\u{2016}').is_err());
+    }
+}
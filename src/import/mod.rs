@@ -0,0 +1,158 @@
+pub mod csv;
+pub mod ofx;
+pub mod qif;
+
+use crate::models::{Category, Currency, DuplicatePair, Money, Transaction};
+use uuid::Uuid;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Failed to read file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid CSV: {0}")]
+    Csv(#[from] ::csv::Error),
+    #[error("Invalid statement: {0}")]
+    Format(String),
+}
+
+/// What an import writes: new transactions, rows folded into the existing
+/// transaction they duplicate, and duplicate warnings overridden by keeping
+/// both.
+#[derive(Debug, Clone, Default)]
+pub struct ImportBatch {
+    pub transactions: Vec<Transaction>,
+    pub merges: Vec<(Uuid, Transaction)>,
+    pub dismissed: Vec<DuplicatePair>,
+}
+
+/// The file formats statements can be imported from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatementFormat {
+    #[default]
+    Csv,
+    Ofx,
+    Qif,
+}
+
+impl StatementFormat {
+    pub const ALL: [StatementFormat; 3] = [StatementFormat::Csv, StatementFormat::Ofx, StatementFormat::Qif];
+
+    /// Guesses the format from a file extension, falling back to CSV.
+    pub fn from_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("ofx") | Some("qfx") => StatementFormat::Ofx,
+            Some("qif") => StatementFormat::Qif,
+            _ => StatementFormat::Csv,
+        }
+    }
+}
+
+impl std::fmt::Display for StatementFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatementFormat::Csv => write!(f, "CSV"),
+            StatementFormat::Ofx => write!(f, "OFX / QFX"),
+            StatementFormat::Qif => write!(f, "QIF"),
+        }
+    }
+}
+
+/// One row of a statement after parsing. Rows that could not be parsed keep
+/// the reason so the preview can show it.
+#[derive(Debug, Clone)]
+pub struct ImportRow {
+    pub line: usize,
+    pub result: Result<Transaction, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum DecimalSeparator {
+    #[default]
+    Dot,
+    Comma,
+}
+
+impl std::fmt::Display for DecimalSeparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecimalSeparator::Dot => write!(f, "1,234.56"),
+            DecimalSeparator::Comma => write!(f, "1.234,56"),
+        }
+    }
+}
+
+/// Parses an amount as written in a statement. Currency symbols, spaces and
+/// thousands separators are ignored, and `(12.50)` is read as negative.
+pub fn parse_amount(raw: &str, separator: DecimalSeparator, currency: Currency) -> Result<Money, String> {
+    let mut value: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | ',' | '(' | ')'))
+        .collect();
+
+    let parenthesised = value.starts_with('(') && value.ends_with(')');
+    value.retain(|c| c != '(' && c != ')');
+
+    if separator == DecimalSeparator::Comma {
+        value = value.replace('.', "").replace(',', ".");
+    }
+
+    let amount = Money::parse(&value, currency).map_err(|e| format!("{} ({})", e, raw.trim()))?;
+    Ok(if parenthesised { -amount } else { amount })
+}
+
+/// Characters of Windows-1252 bytes 0x80 to 0x9F, where it differs from
+/// Latin-1. Unassigned bytes keep their Latin-1 control character.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}', '\u{178}',
+];
+
+/// Decodes a statement file. Files that are not valid UTF-8 are read as
+/// Windows-1252, which also covers Latin-1 and is what banks exporting
+/// from Windows usually write. A UTF-8 byte order mark is dropped.
+pub fn decode_statement(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes
+            .iter()
+            .map(|&byte| match byte {
+                0x80..=0x9F => WINDOWS_1252_HIGH[usize::from(byte - 0x80)],
+                _ => char::from(byte),
+            })
+            .collect(),
+    }
+}
+
+/// Finds a category by name, ignoring case.
+pub fn match_category(categories: &[Category], name: &str) -> Option<String> {
+    let name = name.trim();
+    categories
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case(name))
+        .map(|c| c.id.clone())
+}
+
+/// Whether a transaction with the same external id is already recorded in
+/// the same account.
+pub fn already_imported(transaction: &Transaction, existing: &[Transaction]) -> bool {
+    transaction.external_id.as_ref().is_some_and(|external_id| {
+        existing.iter().any(|t| {
+            t.external_id.as_ref() == Some(external_id) && t.account_id == transaction.account_id
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statements_that_are_not_utf8_are_read_as_windows_1252() {
+        assert_eq!(decode_statement("Caf\u{e9} \u{20AC}5".as_bytes()), "Caf\u{e9} \u{20AC}5");
+        assert_eq!(decode_statement(b"\xEF\xBB\xBFDate,Amount"), "Date,Amount");
+        assert_eq!(decode_statement(b"Caf\xE9 \x80 5,00 \x93Stra\xDFe\x94"), "Caf\u{e9} \u{20AC} 5,00 \u{201C}Stra\u{df}e\u{201D}");
+    }
+}
@@ -0,0 +1,254 @@
+use chrono::NaiveDate;
+
+use super::{parse_amount, DecimalSeparator, ImportError, ImportRow};
+use crate::models::{Currency, Transaction, TransactionType};
+
+/// A tag of the statement with the text that follows it.
+struct Element<'a> {
+    name: &'a str,
+    value: String,
+    offset: usize,
+}
+
+/// Parses the transactions of an OFX or QFX statement. Both the SGML
+/// (version 1) and XML (version 2) flavours are read; leaf elements and
+/// `STMTTRN` need no closing tag. Amounts use the statement's `CURDEF`, or `fallback_currency`
+/// when it has none, and each FITID becomes the transaction's external id.
+pub fn parse_ofx(
+    contents: &str,
+    fallback_currency: Currency,
+    account_id: Option<&str>,
+) -> Result<Vec<ImportRow>, ImportError> {
+    let elements = elements(contents);
+    if !elements.iter().any(|e| e.name.eq_ignore_ascii_case("OFX")) {
+        return Err(ImportError::Format("No OFX data found".to_string()));
+    }
+
+    let mut rows = Vec::new();
+    let mut currency = Ok(fallback_currency);
+    let mut current: Option<(usize, Vec<&Element>)> = None;
+
+    for element in &elements {
+        match element.name.to_ascii_uppercase().as_str() {
+            "CURDEF" => {
+                currency = element.value.parse::<Currency>()
+                    .map_err(|_| format!("Unsupported currency: {}", element.value));
+            }
+            // Some SGML exports leave `STMTTRN` unclosed, so the next
+            // transaction or the end of the list also ends one
+            "STMTTRN" | "/STMTTRN" | "/BANKTRANLIST" => {
+                if let Some((offset, fields)) = current.take() {
+                    rows.push(ImportRow {
+                        line: line_number(contents, offset),
+                        result: currency.clone().and_then(|currency| {
+                            parse_transaction(&fields, currency, account_id)
+                        }),
+                    });
+                }
+                if element.name.eq_ignore_ascii_case("STMTTRN") {
+                    current = Some((element.offset, Vec::new()));
+                }
+            }
+            _ => {
+                if let Some((_, fields)) = &mut current {
+                    fields.push(element);
+                }
+            }
+        }
+    }
+
+    Ok(rows)
+}
+
+fn parse_transaction(
+    fields: &[&Element],
+    currency: Currency,
+    account_id: Option<&str>,
+) -> Result<Transaction, String> {
+    let field = |name: &str| {
+        fields.iter()
+            .find(|e| e.name.eq_ignore_ascii_case(name) && !e.value.is_empty())
+            .map(|e| e.value.as_str())
+    };
+
+    let posted = field("DTPOSTED").ok_or("Missing DTPOSTED")?;
+    // Dates are YYYYMMDD followed by an optional time and time zone
+    let date = posted.get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .ok_or_else(|| format!("Invalid date: {:?}", posted))?;
+
+    let raw_amount = field("TRNAMT").ok_or("Missing TRNAMT")?;
+    // Whichever of `.` and `,` comes last is the decimal separator, so both
+    // 1,234.56 and 1.234,56 are read
+    let separator = if raw_amount.rfind(',') > raw_amount.rfind('.') {
+        DecimalSeparator::Comma
+    } else {
+        DecimalSeparator::Dot
+    };
+    let amount = parse_amount(raw_amount, separator, currency)?;
+    if amount.is_zero() {
+        return Err("Amount is zero".to_string());
+    }
+
+    let (transaction_type, amount) = if amount.is_negative() {
+        (TransactionType::Expense, -amount)
+    } else {
+        (TransactionType::Income, amount)
+    };
+
+    let description = field("NAME")
+        .or_else(|| field("MEMO"))
+        .unwrap_or_default()
+        .to_string();
+
+    let mut transaction = Transaction::new(
+        description,
+        amount,
+        date,
+        transaction_type,
+        None,
+        account_id.map(str::to_string),
+    );
+    transaction.external_id = field("FITID").map(str::to_string);
+
+    Ok(transaction)
+}
+
+/// Splits the document into tags and their text. Headers, processing
+/// instructions and comments are skipped.
+fn elements(contents: &str) -> Vec<Element<'_>> {
+    let mut elements = Vec::new();
+    let mut rest = contents;
+
+    while let Some(start) = rest.find('<') {
+        let offset = contents.len() - rest.len() + start;
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+
+        let name = rest[start + 1..start + end].trim();
+        rest = &rest[start + end + 1..];
+
+        let value_end = rest.find('<').unwrap_or(rest.len());
+        let value = decode_entities(rest[..value_end].trim());
+
+        if !name.starts_with(['?', '!']) {
+            elements.push(Element { name, value, offset });
+        }
+    }
+
+    elements
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn line_number(contents: &str, offset: usize) -> usize {
+    contents[..offset].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Version 1: an SGML header, unclosed leaf elements and a transaction
+    /// whose `STMTTRN` is never closed.
+    const SGML_SAMPLE: &str = "\
+OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+
+<OFX>
+<BANKMSGSRSV1><STMTTRNRS><STMTRS>
+<CURDEF>EUR
+<BANKTRANLIST>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20260315120000[+1:CET]
+<TRNAMT>-1.234,56
+<FITID>A1
+<NAME>Rent &amp; charges
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20260316
+<TRNAMT>2500,00
+<FITID>A2
+<MEMO>Salary
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20260317
+<TRNAMT>-12.50
+<FITID>A3
+<NAME>Bakery
+</BANKTRANLIST>
+</STMTRS></STMTTRNRS></BANKMSGSRSV1>
+</OFX>
+";
+
+    /// Version 2: an XML declaration, every element closed and no `CURDEF`.
+    const XML_SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<?OFX OFXHEADER="200" VERSION="220"?>
+<OFX>
+  <BANKTRANLIST>
+    <STMTTRN>
+      <DTPOSTED>20260320</DTPOSTED>
+      <TRNAMT>-1,234.56</TRNAMT>
+      <FITID>B1</FITID>
+      <NAME>Laptop</NAME>
+    </STMTTRN>
+    <STMTTRN>
+      <DTPOSTED>2026-03-21</DTPOSTED>
+      <TRNAMT>10.00</TRNAMT>
+      <FITID>B2</FITID>
+    </STMTTRN>
+  </BANKTRANLIST>
+</OFX>
+"#;
+
+    fn summary(row: &ImportRow) -> Result<(TransactionType, i64, Currency, String), String> {
+        row.result.clone().map(|t| {
+            (t.transaction_type, t.amount.minor_units(), t.amount.currency(), t.external_id.unwrap_or_default())
+        })
+    }
+
+    #[test]
+    fn sgml_statements_need_no_closing_tags() {
+        let rows = parse_ofx(SGML_SAMPLE, Currency::USD, Some("account")).unwrap();
+        let parsed: Vec<_> = rows.iter().map(summary).collect();
+        assert_eq!(parsed, vec![
+            Ok((TransactionType::Expense, 123_456, Currency::EUR, "A1".to_string())),
+            Ok((TransactionType::Income, 250_000, Currency::EUR, "A2".to_string())),
+            Ok((TransactionType::Expense, 1_250, Currency::EUR, "A3".to_string())),
+        ]);
+
+        let first = rows[0].result.as_ref().unwrap();
+        assert_eq!(first.description, "Rent & charges");
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2026, 3, 15).unwrap());
+        assert_eq!(first.account_id.as_deref(), Some("account"));
+        assert_eq!(rows[1].result.as_ref().unwrap().description, "Salary");
+        assert_eq!(rows.iter().map(|row| row.line).collect::<Vec<_>>(), vec![9, 16, 22]);
+    }
+
+    #[test]
+    fn xml_statements_use_the_fallback_currency() {
+        let rows = parse_ofx(XML_SAMPLE, Currency::GBP, None).unwrap();
+        let parsed: Vec<_> = rows.iter().map(summary).collect();
+        assert_eq!(parsed, vec![
+            Ok((TransactionType::Expense, 123_456, Currency::GBP, "B1".to_string())),
+            Err("Invalid date: \"2026-03-21\"".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn other_documents_are_rejected() {
+        assert!(parse_ofx("Date,Amount\n2026-03-01,10.00\n", Currency::USD, None).is_err());
+    }
+}
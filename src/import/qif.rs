@@ -0,0 +1,257 @@
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+
+use super::{match_category, parse_amount, DecimalSeparator, ImportRow};
+use crate::models::{Category, Currency, Transaction, TransactionType};
+
+/// One `^`-terminated record: its first line and its fields by code.
+struct Record<'a> {
+    line: usize,
+    fields: Vec<(char, &'a str)>,
+}
+
+impl Record<'_> {
+    fn field(&self, code: char) -> Option<&str> {
+        self.fields.iter()
+            .find(|(c, value)| *c == code && !value.is_empty())
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Parses the bank, cash and credit card sections of a QIF file. QIF dates
+/// have no fixed layout, so `date_format` says how to read them; `'` is
+/// accepted as a year separator (`1/31'24`).
+///
+/// QIF carries no transaction ids. Each transaction's external id is built
+/// from its date, amount and payee, numbered when the same combination
+/// repeats within the file, so re-importing an overlapping statement
+/// produces the same ids.
+pub fn parse_qif(
+    contents: &str,
+    date_format: &str,
+    separator: DecimalSeparator,
+    currency: Currency,
+    categories: &[Category],
+    account_id: Option<&str>,
+) -> Vec<ImportRow> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    records(contents)
+        .into_iter()
+        .map(|record| {
+            let result = parse_record(&record, date_format, separator, currency, categories, account_id)
+                .map(|mut transaction| {
+                    let key = format!(
+                        "qif:{}:{}:{}",
+                        transaction.date,
+                        signed_minor_units(&transaction),
+                        transaction.description
+                    );
+                    let count = seen.entry(key.clone()).or_insert(0);
+                    *count += 1;
+                    transaction.external_id = Some(format!("{}:{}", key, count));
+                    transaction
+                });
+
+            ImportRow { line: record.line, result }
+        })
+        .collect()
+}
+
+fn parse_record(
+    record: &Record,
+    date_format: &str,
+    separator: DecimalSeparator,
+    currency: Currency,
+    categories: &[Category],
+    account_id: Option<&str>,
+) -> Result<Transaction, String> {
+    let raw_date = record.field('D').ok_or("Missing date")?;
+    let date = parse_date(raw_date, date_format)
+        .ok_or_else(|| format!("Invalid date: {:?} (expected {})", raw_date, date_format))?;
+
+    let raw_amount = record.field('T')
+        .or_else(|| record.field('U'))
+        .ok_or("Missing amount")?;
+    let amount = parse_amount(raw_amount, separator, currency)?;
+    if amount.is_zero() {
+        return Err("Amount is zero".to_string());
+    }
+
+    let (transaction_type, amount) = if amount.is_negative() {
+        (TransactionType::Expense, -amount)
+    } else {
+        (TransactionType::Income, amount)
+    };
+
+    let description = record.field('P')
+        .or_else(|| record.field('M'))
+        .unwrap_or_default()
+        .to_string();
+
+    // `[Name]` is a transfer to another QIF account rather than a category;
+    // subcategories are written `Parent:Child`
+    let category_id = record.field('L')
+        .filter(|name| !name.starts_with('['))
+        .and_then(|name| {
+            match_category(categories, name)
+                .or_else(|| name.split_once(':').and_then(|(parent, _)| match_category(categories, parent)))
+        });
+
+    Ok(Transaction::new(
+        description,
+        amount,
+        date,
+        transaction_type,
+        category_id,
+        account_id.map(str::to_string),
+    ))
+}
+
+fn parse_date(raw: &str, format: &str) -> Option<NaiveDate> {
+    let normalized: String = raw
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == '\'' { '/' } else { c })
+        .collect();
+
+    let parse = |format: &str| NaiveDate::parse_from_str(&normalized, format).ok();
+    // A two-digit year also satisfies `%Y`, as the year 24
+    parse(format)
+        .filter(|date| date.year() >= 100)
+        .or_else(|| parse(&format.replace("%Y", "%y")))
+}
+
+fn signed_minor_units(transaction: &Transaction) -> i64 {
+    match transaction.transaction_type {
+        TransactionType::Expense => -transaction.amount.minor_units(),
+        _ => transaction.amount.minor_units(),
+    }
+}
+
+/// Collects the records of transaction sections, skipping account lists,
+/// category lists and other non-transaction sections.
+fn records(contents: &str) -> Vec<Record<'_>> {
+    let mut records = Vec::new();
+    let mut in_transactions = false;
+    let mut current: Option<Record> = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('!') {
+            let header = line.to_ascii_lowercase();
+            in_transactions = ["!type:bank", "!type:cash", "!type:ccard", "!type:oth a", "!type:oth l"]
+                .iter()
+                .any(|section| header.starts_with(section));
+            current = None;
+            continue;
+        }
+
+        if !in_transactions {
+            continue;
+        }
+
+        if line.starts_with('^') {
+            if let Some(record) = current.take() {
+                records.push(record);
+            }
+            continue;
+        }
+
+        let mut chars = line.chars();
+        if let Some(code) = chars.next() {
+            current
+                .get_or_insert_with(|| Record { line: index + 1, fields: Vec::new() })
+                .fields
+                .push((code, chars.as_str().trim()));
+        }
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A bank section between an account list and a category list, with a
+    /// `'` year separator, a two-digit year, a split category and a
+    /// transfer.
+    const SAMPLE: &str = "\
+!Account
+NChecking
+TBank
+^
+!Type:Bank
+D03/15'26
+T-1,234.56
+PLANDLORD
+LRent
+^
+D3/16/2026
+T2,500.00
+MSalary
+LIncome:Payroll
+^
+D03/17/26
+U-45.00
+PATM
+L[Savings]
+^
+D03/17/26
+T-45.00
+PATM
+^
+D31/03/2026
+T-1.00
+^
+!Type:Cat
+NRent
+^
+";
+
+    #[test]
+    fn bank_records_are_parsed_with_the_date_format() {
+        let rent = Category::new("Rent".to_string(), "#000000".to_string(), None);
+        let income = Category::new("Income".to_string(), "#000000".to_string(), None);
+        let categories = [rent.clone(), income.clone()];
+        let rows = parse_qif(SAMPLE, "%m/%d/%Y", DecimalSeparator::Dot, Currency::USD, &categories, None);
+
+        assert_eq!(rows.iter().map(|row| row.line).collect::<Vec<_>>(), vec![6, 11, 16, 21, 25]);
+        let parsed: Vec<_> = rows.iter()
+            .map(|row| row.result.clone().map(|t| (t.date, signed_minor_units(&t), t.description, t.category_id)))
+            .collect();
+        let date = |day| NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+        assert_eq!(parsed, vec![
+            Ok((date(15), -123_456, "LANDLORD".to_string(), Some(rent.id))),
+            Ok((date(16), 250_000, "Salary".to_string(), Some(income.id))),
+            Ok((date(17), -4_500, "ATM".to_string(), None)),
+            Ok((date(17), -4_500, "ATM".to_string(), None)),
+            Err("Invalid date: \"31/03/2026\" (expected %m/%d/%Y)".to_string()),
+        ]);
+
+        // The same date, amount and payee twice are told apart by a count
+        let ids: Vec<_> = rows[2..4].iter()
+            .map(|row| row.result.as_ref().unwrap().external_id.clone().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["qif:2026-03-17:-4500:ATM:1", "qif:2026-03-17:-4500:ATM:2"]);
+    }
+
+    #[test]
+    fn day_first_dates_and_comma_decimals() {
+        let sample = "!Type:CCard\nD31.03.26\nT-1.234,56\n^\nD1.4.2026\nT12,5\n^\n";
+        let rows = parse_qif(sample, "%d.%m.%Y", DecimalSeparator::Comma, Currency::EUR, &[], None);
+
+        let parsed: Vec<_> = rows.into_iter()
+            .map(|row| row.result.map(|t| (t.date, signed_minor_units(&t))))
+            .collect();
+        assert_eq!(parsed, vec![
+            Ok((NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(), -123_456)),
+            Ok((NaiveDate::from_ymd_opt(2026, 4, 1).unwrap(), 1_250)),
+        ]);
+    }
+}
@@ -1,4 +1,11 @@
 mod app;
+mod db;
+mod export;
+mod import;
+mod models;
+mod report;
+mod ui;
+mod utils;
 
 fn main() -> iced::Result {
     iced::application(app::BudgetTracker::title, app::BudgetTracker::update, app::BudgetTracker::view)
@@ -0,0 +1,173 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::{CurrencyConverter, Money, Transaction, TransactionType};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AccountType {
+    Checking,
+    Savings,
+    CreditCard,
+    Cash,
+}
+
+impl AccountType {
+    pub const ALL: [AccountType; 4] = [
+        AccountType::Checking,
+        AccountType::Savings,
+        AccountType::CreditCard,
+        AccountType::Cash,
+    ];
+}
+
+impl fmt::Display for AccountType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountType::Checking => write!(f, "Checking"),
+            AccountType::Savings => write!(f, "Savings"),
+            AccountType::CreditCard => write!(f, "Credit Card"),
+            AccountType::Cash => write!(f, "Cash"),
+        }
+    }
+}
+
+impl FromStr for AccountType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AccountType::ALL
+            .into_iter()
+            .find(|t| t.to_string() == s)
+            .ok_or_else(|| format!("Unknown account type: {}", s))
+    }
+}
+
+/// Where money lives. The opening balance is in the account's currency, and
+/// a credit card's balance is normally negative.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Account {
+    pub id: String,
+    pub name: String,
+    pub account_type: AccountType,
+    pub opening_balance: Money,
+    pub opening_date: NaiveDate,
+}
+
+impl fmt::Display for Account {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Account {
+    pub fn new(
+        name: String,
+        account_type: AccountType,
+        opening_balance: Money,
+        opening_date: NaiveDate,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            account_type,
+            opening_balance,
+            opening_date,
+        }
+    }
+
+    /// The account's transactions oldest first, each with the balance after
+    /// it. Amounts in another currency are converted at the rate on their
+    /// date; from the first one without a usable rate on, the balance is
+    /// unknown and `None`.
+    pub fn running_balances<'a>(
+        &self,
+        transactions: &'a [Transaction],
+        converter: &CurrencyConverter,
+    ) -> Vec<(&'a Transaction, Option<Money>)> {
+        let currency = self.opening_balance.currency();
+        let mut entries: Vec<&Transaction> = transactions
+            .iter()
+            .filter(|t| t.account_id.as_deref() == Some(self.id.as_str()))
+            .collect();
+        entries.sort_by_key(|t| t.date);
+
+        let mut balance = Some(self.opening_balance);
+        entries
+            .into_iter()
+            .map(|t| {
+                balance = balance.zip(converter.convert(t.amount, currency, t.date))
+                    .map(|(balance, amount)| match t.transaction_type {
+                        TransactionType::Income => balance + amount,
+                        TransactionType::Expense => balance - amount,
+                        // Transfer legs carry their own sign
+                        TransactionType::Transfer => balance + amount,
+                    });
+                (t, balance)
+            })
+            .collect()
+    }
+
+    /// The balance after all of `transactions`, or `None` when one of them
+    /// cannot be converted into the account's currency.
+    pub fn balance(&self, transactions: &[Transaction], converter: &CurrencyConverter) -> Option<Money> {
+        self.running_balances(transactions, converter)
+            .last()
+            .map_or(Some(self.opening_balance), |(_, balance)| *balance)
+    }
+}
+
+/// The combined balance of all accounts in the base currency as of `date`,
+/// or `None` when an account's balance cannot be converted.
+pub fn net_worth(
+    accounts: &[Account],
+    transactions: &[Transaction],
+    converter: &CurrencyConverter,
+    date: NaiveDate,
+) -> Option<Money> {
+    accounts.iter().try_fold(Money::zero(converter.base()), |total, account| {
+        let balance = account.balance(transactions, converter)?;
+        Some(total + converter.to_base(balance, date)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense};
+    use crate::models::{Currency, ExchangeRate};
+
+    #[test]
+    fn balance_is_unknown_after_a_transaction_without_a_rate() {
+        let account = Account::new(
+            "Checking".to_string(),
+            AccountType::Checking,
+            Money::from_minor(10_000, Currency::USD),
+            date(2026, 3, 1),
+        );
+        let converter = CurrencyConverter::new(
+            Currency::USD,
+            vec![ExchangeRate::new(Currency::EUR, Currency::USD, 2.0, date(2026, 3, 1))],
+        );
+        let spend = |currency: Currency, day: u32| Transaction {
+            account_id: Some(account.id.clone()),
+            ..expense(Money::from_minor(1_000, currency), date(2026, 3, day))
+        };
+
+        let mut transactions = vec![spend(Currency::USD, 2), spend(Currency::EUR, 3)];
+        assert_eq!(account.balance(&transactions, &converter), Some(Money::from_minor(7_000, Currency::USD)));
+
+        transactions.push(spend(Currency::GBP, 4));
+        transactions.push(spend(Currency::USD, 5));
+        let balances: Vec<_> = account.running_balances(&transactions, &converter)
+            .into_iter()
+            .map(|(_, balance)| balance)
+            .collect();
+        assert_eq!(balances[1], Some(Money::from_minor(7_000, Currency::USD)));
+        assert_eq!(balances[2..], [None, None]);
+        assert_eq!(account.balance(&transactions, &converter), None);
+        assert_eq!(net_worth(std::slice::from_ref(&account), &transactions, &converter, date(2026, 3, 5)), None);
+    }
+}
@@ -1,24 +1,136 @@
-use chrono::NaiveDate;
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 
+use super::category::is_within;
+use super::{Category, CurrencyConverter, DateRange, Money, Notification, Period, Transaction};
+use crate::utils::{month_end, month_start};
+
+/// How often a budget starts afresh with a new allocation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Renewal {
+    /// Seven days at a time from the budget's start date.
+    Weekly,
+    /// Fourteen days at a time from the budget's start date, for fortnightly
+    /// pay.
+    BiWeekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+    /// Monthly from this day of the month, such as payday, to the day
+    /// before it next month. Days past the end of a short month fall on
+    /// its last day.
+    MonthlyOnDay(u32),
+}
+
+impl Renewal {
+    /// The period of this kind that contains `date`, counting weekly and
+    /// bi-weekly periods from `anchor`.
+    pub fn containing(&self, date: NaiveDate, anchor: NaiveDate) -> DateRange {
+        let every_days = |days: i64| {
+            let from = anchor + Duration::days((date - anchor).num_days().div_euclid(days) * days);
+            DateRange::new(from, from + Duration::days(days - 1))
+        };
+
+        match *self {
+            Renewal::Weekly => every_days(7),
+            Renewal::BiWeekly => every_days(14),
+            Renewal::Monthly => Period::Month.containing(date),
+            Renewal::Quarterly => Period::Quarter.containing(date),
+            Renewal::Yearly => Period::Year.containing(date),
+            Renewal::MonthlyOnDay(day) => {
+                let on_day = |month: NaiveDate| {
+                    month.with_day(day.min(month_end(month).day())).unwrap_or(month)
+                };
+                let this_month = on_day(month_start(date));
+                let from = if this_month <= date {
+                    this_month
+                } else {
+                    on_day(month_start(date) - Months::new(1))
+                };
+                let next = on_day(month_start(from) + Months::new(1));
+                DateRange::new(from, next - Duration::days(1))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Renewal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Renewal::Weekly => write!(f, "Weekly"),
+            Renewal::BiWeekly => write!(f, "Bi-weekly"),
+            Renewal::Monthly => write!(f, "Monthly"),
+            Renewal::Quarterly => write!(f, "Quarterly"),
+            Renewal::Yearly => write!(f, "Yearly"),
+            Renewal::MonthlyOnDay(day) => write!(f, "Monthly from day {}", day),
+        }
+    }
+}
+
+impl FromStr for Renewal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let renewal = match s {
+            "Weekly" => Renewal::Weekly,
+            "Bi-weekly" => Renewal::BiWeekly,
+            "Monthly" => Renewal::Monthly,
+            "Quarterly" => Renewal::Quarterly,
+            "Yearly" => Renewal::Yearly,
+            _ => s.strip_prefix("Monthly from day ")
+                .and_then(|day| day.parse().ok())
+                .filter(|day| (1..=31).contains(day))
+                .map(Renewal::MonthlyOnDay)
+                .ok_or_else(|| format!("Unknown renewal: {}", s))?,
+        };
+        Ok(renewal)
+    }
+}
+
+/// How a budget without a category is labelled. It counts every expense,
+/// whatever its category.
+pub const ALL_SPENDING: &str = "All spending";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Budget {
     pub id: String,
     pub name: String,
+    /// The category tracked, with its subcategories, or `None` to track all
+    /// spending.
     pub category_id: Option<String>,
-    pub amount: f64,
+    /// The allocation for the whole date range, or for each period of a
+    /// renewing budget.
+    pub amount: Money,
     pub start_date: NaiveDate,
-    pub end_date: NaiveDate,
+    /// The last day of the budget. A renewing budget without one carries on
+    /// until it is deleted.
+    pub end_date: Option<NaiveDate>,
+    /// Starts the budget afresh with a new allocation every period between
+    /// the start and end dates.
+    #[serde(default)]
+    pub renewal: Option<Renewal>,
+    /// Carries what is left at the end of each period, or the overspend,
+    /// into the next one. Only meaningful for a renewing budget.
+    #[serde(default)]
+    pub rollover: bool,
+    /// Percentages of a period's available money at which spending raises
+    /// an alert, lowest first.
+    #[serde(default)]
+    pub alert_thresholds: Vec<u32>,
 }
 
 impl Budget {
     pub fn new(
         name: String,
         category_id: Option<String>,
-        amount: f64,
+        amount: Money,
         start_date: NaiveDate,
-        end_date: NaiveDate,
+        end_date: Option<NaiveDate>,
+        renewal: Option<Renewal>,
+        rollover: bool,
     ) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
@@ -27,6 +139,436 @@ impl Budget {
             amount,
             start_date,
             end_date,
+            renewal,
+            rollover: rollover && renewal.is_some(),
+            alert_thresholds: Vec::new(),
+        }
+    }
+
+    /// The days the budget runs on.
+    fn range(&self) -> DateRange {
+        DateRange::new(self.start_date, self.end_date.unwrap_or(NaiveDate::MAX))
+    }
+
+    /// The periods the budget has run for up to and including the one
+    /// containing `until`, oldest first. A budget that does not renew has a
+    /// single period covering its whole date range. Periods are clipped to
+    /// the start and end dates.
+    pub fn periods(&self, until: NaiveDate) -> Vec<DateRange> {
+        let range = self.range();
+        let Some(renewal) = self.renewal else {
+            return if self.start_date <= until { vec![range] } else { Vec::new() };
+        };
+
+        let last = until.min(range.to);
+        let mut periods = Vec::new();
+        let mut period = renewal.containing(self.start_date, self.start_date);
+        while period.from.max(range.from) <= last {
+            periods.push(DateRange::new(period.from.max(range.from), period.to.min(range.to)));
+            let Some(next) = period.to.succ_opt() else { break };
+            period = renewal.containing(next, self.start_date);
         }
+        periods
+    }
+
+    /// The period in effect on `date`, or the last one when the budget
+    /// ended before then. `None` before the budget starts.
+    pub fn current_period(&self, date: NaiveDate) -> Option<DateRange> {
+        self.periods(date).pop()
+    }
+
+    /// This budget's periods among `periods`, in their order.
+    pub fn recorded<'a>(&'a self, periods: &'a [BudgetPeriod]) -> impl Iterator<Item = &'a BudgetPeriod> + 'a {
+        periods.iter().filter(move |p| p.budget_id == self.id)
+    }
+
+    /// The budget's history up to the period containing `until`. Periods
+    /// that ended before `until` keep the allocation already recorded for
+    /// them in `recorded`, so changing the amount leaves closed periods
+    /// alone but applies to the one still running. Spending and carry are
+    /// worked out afresh so late transactions count.
+    pub fn history(
+        &self,
+        recorded: &[BudgetPeriod],
+        until: NaiveDate,
+        transactions: &[Transaction],
+        categories: &[Category],
+        converter: &CurrencyConverter,
+    ) -> Vec<BudgetPeriod> {
+        let currency = self.amount.currency();
+        let mut carried_in = Money::zero(currency);
+
+        self.periods(until).into_iter()
+            .map(|range| {
+                let allocated = recorded.iter()
+                    .filter(|_| range.to < until)
+                    .find(|p| p.budget_id == self.id && p.range.from == range.from)
+                    .map(|p| p.allocated)
+                    .filter(|allocated| allocated.currency() == currency)
+                    .unwrap_or(self.amount);
+                let period = BudgetPeriod {
+                    budget_id: self.id.clone(),
+                    range,
+                    allocated,
+                    spent: self.spent_in(range, transactions, categories, converter).0,
+                    carried_in,
+                };
+                if self.rollover {
+                    carried_in = period.left();
+                }
+                period
+            })
+            .collect()
+    }
+
+    /// The alert thresholds `period`'s spending has reached, lowest first.
+    /// With nothing available, any spending reaches every threshold.
+    pub fn thresholds_reached(&self, period: &BudgetPeriod) -> Vec<u32> {
+        let available = period.available().minor_units();
+        let spent = period.spent.minor_units();
+
+        self.alert_thresholds.iter()
+            .copied()
+            .filter(|&threshold| if available > 0 {
+                spent * 100 >= available * threshold as i64
+            } else {
+                spent > 0
+            })
+            .collect()
+    }
+
+    /// The notification for `period` reaching `threshold`.
+    pub fn alert(&self, period: &BudgetPeriod, threshold: u32, now: NaiveDateTime) -> Notification {
+        let dates = format!("{} to {}", period.range.from.format("%Y-%m-%d"), period.range.to.format("%Y-%m-%d"));
+        let body = if period.left().is_negative() {
+            format!(
+                "{} has gone over budget: {} spent of {} available for {}.",
+                self.name, period.spent, period.available(), dates
+            )
+        } else {
+            format!(
+                "{} has reached {}% of its budget: {} spent of {} available for {}.",
+                self.name, threshold, period.spent, period.available(), dates
+            )
+        };
+
+        Notification::new(format!("Budget alert: {}", self.name), body, now)
+    }
+
+    /// Whether the budget runs on any day from `from` to `to` inclusive.
+    pub fn is_active_between(&self, from: NaiveDate, to: NaiveDate) -> bool {
+        self.start_date <= to && self.end_date.is_none_or(|end| from <= end)
+    }
+
+    /// Whether spending filed under `category_id` on `date` counts against
+    /// this budget. A budget on a parent category includes its
+    /// subcategories, and one for all spending counts every expense in its
+    /// date range, uncategorized ones included.
+    pub fn covers(&self, date: NaiveDate, category_id: Option<&str>, categories: &[Category]) -> bool {
+        self.range().contains(date)
+            && match (&self.category_id, category_id) {
+                (Some(budget_category), Some(category)) => is_within(categories, category, budget_category),
+                (Some(_), None) => false,
+                (None, _) => true,
+            }
+    }
+
+    /// Spending against this budget in the budget's own currency, and how
+    /// many expense lines were left out of it. Only the lines of a split
+    /// expense filed under the budget's category count. Expenses in other
+    /// currencies are converted at the rate on their date; any without a
+    /// usable rate are the ones left out.
+    pub fn spent(&self, transactions: &[Transaction], categories: &[Category], converter: &CurrencyConverter) -> (Money, usize) {
+        self.spent_in(self.range(), transactions, categories, converter)
+    }
+
+    /// Spending against this budget within `range`, as for `spent`.
+    pub fn spent_in(
+        &self,
+        range: DateRange,
+        transactions: &[Transaction],
+        categories: &[Category],
+        converter: &CurrencyConverter,
+    ) -> (Money, usize) {
+        let currency = self.amount.currency();
+        transactions
+            .iter()
+            .filter(|t| t.is_expense() && range.contains(t.date))
+            .flat_map(|t| {
+                t.category_amounts().into_iter()
+                    .filter(|(category_id, _)| self.covers(t.date, *category_id, categories))
+                    .map(|(_, amount)| (amount, t.date))
+                    .collect::<Vec<_>>()
+            })
+            .fold((Money::zero(currency), 0), |(total, unconverted), (amount, date)| {
+                match converter.convert(amount, currency, date) {
+                    Some(amount) => (total + amount, unconverted),
+                    None => (total, unconverted + 1),
+                }
+            })
+    }
+}
+
+/// One period of a budget: what was allocated to it, what was spent and
+/// what it started with from the period before.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BudgetPeriod {
+    pub budget_id: String,
+    pub range: DateRange,
+    pub allocated: Money,
+    pub spent: Money,
+    /// Left over from the previous period, negative when it was overspent.
+    pub carried_in: Money,
+}
+
+impl BudgetPeriod {
+    /// What could be spent in the period.
+    pub fn available(&self) -> Money {
+        self.allocated + self.carried_in
+    }
+
+    /// What is left at the end of the period, negative when overspent.
+    pub fn left(&self) -> Money {
+        self.available() - self.spent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense, money};
+    use crate::models::{Currency, ExchangeRate};
+
+    fn budget(category_id: Option<String>) -> Budget {
+        Budget::new(
+            "Test".to_string(),
+            category_id,
+            money(10_000),
+            date(2026, 3, 1),
+            Some(date(2026, 3, 31)),
+            None,
+            false,
+        )
+    }
+
+    fn renewing(amount: Money, start_date: NaiveDate, renewal: Renewal, rollover: bool) -> Budget {
+        Budget::new("Test".to_string(), None, amount, start_date, None, Some(renewal), rollover)
+    }
+
+    fn recorded(budget: &Budget, range: DateRange, allocated: Money) -> BudgetPeriod {
+        BudgetPeriod {
+            budget_id: budget.id.clone(),
+            range,
+            allocated,
+            spent: Money::zero(allocated.currency()),
+            carried_in: Money::zero(allocated.currency()),
+        }
+    }
+
+    #[test]
+    fn all_spending_budget_covers_every_expense_in_its_dates() {
+        let food = Category::new("Food".to_string(), "#000000".to_string(), None);
+        let categories = vec![food.clone()];
+        let budget = budget(None);
+
+        assert!(budget.covers(date(2026, 3, 10), Some(&food.id), &categories));
+        assert!(budget.covers(date(2026, 3, 10), None, &categories));
+        assert!(!budget.covers(date(2026, 4, 1), Some(&food.id), &categories));
+    }
+
+    #[test]
+    fn category_budget_covers_its_subcategories_only() {
+        let food = Category::new("Food".to_string(), "#000000".to_string(), None);
+        let groceries = Category::new("Groceries".to_string(), "#000000".to_string(), Some(food.id.clone()));
+        let fuel = Category::new("Fuel".to_string(), "#000000".to_string(), None);
+        let categories = vec![food.clone(), groceries.clone(), fuel.clone()];
+        let budget = budget(Some(food.id.clone()));
+
+        assert!(budget.covers(date(2026, 3, 10), Some(&food.id), &categories));
+        assert!(budget.covers(date(2026, 3, 10), Some(&groceries.id), &categories));
+        assert!(!budget.covers(date(2026, 3, 10), Some(&fuel.id), &categories));
+        assert!(!budget.covers(date(2026, 3, 10), None, &categories));
+    }
+
+    #[test]
+    fn spending_without_a_rate_is_counted_as_left_out() {
+        let converter = CurrencyConverter::new(
+            Currency::USD,
+            vec![ExchangeRate::new(Currency::EUR, Currency::USD, 1.5, date(2026, 3, 15))],
+        );
+        let day = date(2026, 3, 20);
+        let transactions = vec![
+            expense(Money::from_minor(1_000, Currency::USD), date(2026, 3, 5)),
+            expense(Money::from_minor(1_000, Currency::EUR), day),
+            expense(Money::from_minor(1_000, Currency::GBP), day),
+            expense(Money::from_minor(1_000, Currency::CHF), day),
+        ];
+
+        let (spent, unconverted) = budget(None).spent(&transactions, &[], &converter);
+        assert_eq!(spent, Money::from_minor(2_500, Currency::USD));
+        assert_eq!(unconverted, 2);
+    }
+
+    #[test]
+    fn monthly_on_day_falls_on_the_last_day_of_short_months() {
+        let renewal = Renewal::MonthlyOnDay(31);
+        let anchor = date(2026, 1, 31);
+
+        assert_eq!(
+            renewal.containing(date(2026, 2, 15), anchor),
+            DateRange::new(date(2026, 1, 31), date(2026, 2, 27))
+        );
+        assert_eq!(
+            renewal.containing(date(2026, 2, 28), anchor),
+            DateRange::new(date(2026, 2, 28), date(2026, 3, 30))
+        );
+        assert_eq!(
+            renewal.containing(date(2026, 4, 30), anchor),
+            DateRange::new(date(2026, 4, 30), date(2026, 5, 30))
+        );
+        assert_eq!(
+            renewal.containing(date(2026, 12, 31), anchor),
+            DateRange::new(date(2026, 12, 31), date(2027, 1, 30))
+        );
+    }
+
+    #[test]
+    fn monthly_on_day_handles_leap_days() {
+        let budget = renewing(money(10_000), date(2028, 1, 29), Renewal::MonthlyOnDay(29), false);
+        assert_eq!(budget.periods(date(2028, 3, 29)), vec![
+            DateRange::new(date(2028, 1, 29), date(2028, 2, 28)),
+            DateRange::new(date(2028, 2, 29), date(2028, 3, 28)),
+            DateRange::new(date(2028, 3, 29), date(2028, 4, 28)),
+        ]);
+
+        // Outside a leap year the 29th falls on February's last day
+        let budget = renewing(money(10_000), date(2027, 1, 29), Renewal::MonthlyOnDay(29), false);
+        assert_eq!(budget.periods(date(2027, 3, 1)), vec![
+            DateRange::new(date(2027, 1, 29), date(2027, 2, 27)),
+            DateRange::new(date(2027, 2, 28), date(2027, 3, 28)),
+        ]);
+    }
+
+    #[test]
+    fn bi_weekly_periods_count_from_the_start_date() {
+        let anchor = date(2026, 1, 2);
+        assert_eq!(
+            Renewal::BiWeekly.containing(date(2026, 1, 15), anchor),
+            DateRange::new(date(2026, 1, 2), date(2026, 1, 15))
+        );
+        assert_eq!(
+            Renewal::BiWeekly.containing(date(2026, 1, 16), anchor),
+            DateRange::new(date(2026, 1, 16), date(2026, 1, 29))
+        );
+        assert_eq!(
+            Renewal::BiWeekly.containing(date(2025, 12, 25), anchor),
+            DateRange::new(date(2025, 12, 19), date(2026, 1, 1))
+        );
+
+        let budget = renewing(money(10_000), anchor, Renewal::BiWeekly, false);
+        assert_eq!(budget.periods(date(2026, 2, 1)), vec![
+            DateRange::new(date(2026, 1, 2), date(2026, 1, 15)),
+            DateRange::new(date(2026, 1, 16), date(2026, 1, 29)),
+            DateRange::new(date(2026, 1, 30), date(2026, 2, 12)),
+        ]);
+    }
+
+    #[test]
+    fn periods_are_clipped_to_the_budget_dates() {
+        let mut budget = renewing(money(10_000), date(2026, 1, 10), Renewal::Monthly, false);
+        budget.end_date = Some(date(2026, 3, 15));
+
+        assert!(budget.periods(date(2026, 1, 9)).is_empty());
+        assert_eq!(budget.periods(date(2026, 6, 1)), vec![
+            DateRange::new(date(2026, 1, 10), date(2026, 1, 31)),
+            DateRange::new(date(2026, 2, 1), date(2026, 2, 28)),
+            DateRange::new(date(2026, 3, 1), date(2026, 3, 15)),
+        ]);
+        assert_eq!(budget.current_period(date(2026, 6, 1)), Some(DateRange::new(date(2026, 3, 1), date(2026, 3, 15))));
+    }
+
+    #[test]
+    fn rollover_carries_leftovers_and_overspending() {
+        let budget = renewing(money(10_000), date(2026, 1, 1), Renewal::Monthly, true);
+        let transactions = vec![
+            expense(money(6_000), date(2026, 1, 20)),
+            expense(money(18_000), date(2026, 2, 10)),
+        ];
+
+        let history = budget.history(&[], date(2026, 3, 5), &transactions, &[], &CurrencyConverter::default());
+        let carried: Vec<(i64, i64, i64)> = history.iter()
+            .map(|p| (p.carried_in.minor_units(), p.available().minor_units(), p.left().minor_units()))
+            .collect();
+        assert_eq!(carried, vec![
+            (0, 10_000, 4_000),
+            (4_000, 14_000, -4_000),
+            (-4_000, 6_000, 6_000),
+        ]);
+
+        let budget = Budget { rollover: false, ..budget };
+        let history = budget.history(&[], date(2026, 3, 5), &transactions, &[], &CurrencyConverter::default());
+        assert!(history.iter().all(|p| p.carried_in.is_zero()));
+        assert_eq!(history[1].left(), money(-8_000));
+    }
+
+    #[test]
+    fn carry_follows_a_change_of_currency() {
+        // The budget was in dollars for January, then switched to euros
+        let budget = renewing(Money::from_minor(5_000, Currency::EUR), date(2026, 1, 1), Renewal::Monthly, true);
+        let january = DateRange::new(date(2026, 1, 1), date(2026, 1, 31));
+        let recorded = vec![recorded(&budget, january, Money::from_minor(10_000, Currency::USD))];
+        let converter = CurrencyConverter::new(
+            Currency::USD,
+            vec![ExchangeRate::new(Currency::USD, Currency::EUR, 0.5, date(2026, 1, 1))],
+        );
+        let transactions = vec![expense(Money::from_minor(1_000, Currency::USD), date(2026, 1, 10))];
+
+        let history = budget.history(&recorded, date(2026, 2, 10), &transactions, &[], &converter);
+        assert_eq!(history[0].allocated, Money::from_minor(5_000, Currency::EUR));
+        assert_eq!(history[0].spent, Money::from_minor(500, Currency::EUR));
+        assert_eq!(history[1].carried_in, Money::from_minor(4_500, Currency::EUR));
+        assert_eq!(history[1].available(), Money::from_minor(9_500, Currency::EUR));
+    }
+
+    #[test]
+    fn editing_the_amount_applies_to_the_running_period_only() {
+        let converter = CurrencyConverter::default();
+        let mut monthly = renewing(money(10_000), date(2026, 1, 1), Renewal::Monthly, false);
+        let recorded = monthly.history(&[], date(2026, 2, 10), &[], &[], &converter);
+
+        monthly.amount = money(15_000);
+        let history = monthly.history(&recorded, date(2026, 2, 20), &[], &[], &converter);
+        assert_eq!(history[0].allocated, money(10_000));
+        assert_eq!(history[1].allocated, money(15_000));
+
+        // A budget that does not renew has only the one period
+        let mut one_off = budget(None);
+        let recorded = one_off.history(&[], date(2026, 3, 10), &[], &[], &converter);
+        one_off.amount = money(12_000);
+        let history = one_off.history(&recorded, date(2026, 3, 20), &[], &[], &converter);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].allocated, money(12_000));
+    }
+
+    #[test]
+    fn thresholds_are_reached_against_what_is_available() {
+        let budget = Budget { alert_thresholds: vec![50, 80, 100], ..budget(None) };
+        let range = DateRange::new(date(2026, 3, 1), date(2026, 3, 31));
+        let period = |spent, carried_in| BudgetPeriod {
+            spent: money(spent),
+            carried_in: money(carried_in),
+            ..recorded(&budget, range, money(10_000))
+        };
+
+        assert!(budget.thresholds_reached(&period(4_999, 0)).is_empty());
+        assert_eq!(budget.thresholds_reached(&period(8_000, 0)), vec![50, 80]);
+        // Money carried in raises what is available
+        assert_eq!(budget.thresholds_reached(&period(8_000, 6_000)), vec![50]);
+        // With nothing available any spending reaches every threshold
+        assert_eq!(budget.thresholds_reached(&period(1, -10_000)), vec![50, 80, 100]);
+        assert!(budget.thresholds_reached(&period(0, -10_000)).is_empty());
+
+        let over = budget.alert(&period(12_000, 0), 100, date(2026, 3, 20).and_hms_opt(9, 0, 0).unwrap());
+        assert!(over.body.contains("gone over budget"));
     }
 }
@@ -1,21 +1,191 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Colour given to new top-level categories.
+pub const DEFAULT_CATEGORY_COLOR: &str = "#9E9E9E";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Category {
     pub id: String,
     pub name: String,
     pub color: String,
     pub icon: Option<String>,
+    /// The category this one is a subcategory of, if any.
+    #[serde(default)]
+    pub parent_id: Option<String>,
 }
 
 impl Category {
-    pub fn new(name: String, color: String, icon: Option<String>) -> Self {
+    pub fn new(name: String, color: String, parent_id: Option<String>) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             name,
             color,
-            icon,
+            icon: None,
+            parent_id,
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn find<'a>(categories: &'a [Category], id: &str) -> Option<&'a Category> {
+    categories.iter().find(|c| c.id == id)
+}
+
+/// The ids from `id` up to its top-level category. Parents that no longer
+/// exist end the chain, and a loop is cut off where it repeats.
+pub fn ancestor_ids<'a>(categories: &'a [Category], id: &'a str) -> Vec<&'a str> {
+    let mut chain = vec![id];
+    let mut current = id;
+    while let Some(parent) = find(categories, current).and_then(|c| c.parent_id.as_deref()) {
+        if chain.contains(&parent) || find(categories, parent).is_none() {
+            break;
+        }
+        chain.push(parent);
+        current = parent;
+    }
+    chain
+}
+
+/// The top-level category that `id` rolls up into.
+pub fn root_id<'a>(categories: &'a [Category], id: &'a str) -> &'a str {
+    ancestor_ids(categories, id).last().copied().unwrap_or(id)
+}
+
+/// Whether `id` is `ancestor_id` or one of its subcategories at any depth.
+pub fn is_within(categories: &[Category], id: &str, ancestor_id: &str) -> bool {
+    ancestor_ids(categories, id).contains(&ancestor_id)
+}
+
+/// The category's name preceded by those of its parents, e.g.
+/// "Food / Groceries".
+pub fn full_name(categories: &[Category], id: &str) -> String {
+    let names: Vec<&str> = ancestor_ids(categories, id).iter().rev()
+        .filter_map(|id| find(categories, id))
+        .map(|c| c.name.as_str())
+        .collect();
+    names.join(" / ")
+}
+
+/// Every category in display order, each followed by its subcategories,
+/// with its depth below the top level. Siblings are sorted by name, and
+/// categories whose parent is missing are shown at the top level.
+pub fn tree(categories: &[Category]) -> Vec<(&Category, usize)> {
+    fn visit<'a>(categories: &'a [Category], parent: &Category, depth: usize, out: &mut Vec<(&'a Category, usize)>) {
+        for child in children(categories, &parent.id) {
+            // A child already placed means the parents loop back on themselves
+            if out.iter().any(|(c, _)| c.id == child.id) {
+                continue;
+            }
+            out.push((child, depth));
+            visit(categories, child, depth + 1, out);
         }
     }
+
+    let mut out = Vec::with_capacity(categories.len());
+    let mut roots: Vec<&Category> = categories.iter()
+        .filter(|c| c.parent_id.as_deref().and_then(|id| find(categories, id)).is_none())
+        .collect();
+    roots.sort_by(|a, b| a.name.cmp(&b.name));
+    for root in roots {
+        out.push((root, 0));
+        visit(categories, root, 1, &mut out);
+    }
+
+    // Categories whose parents loop back on themselves have no root, so
+    // they are shown at the top level rather than hidden
+    let mut unplaced: Vec<&Category> = categories.iter()
+        .filter(|c| !out.iter().any(|(placed, _)| placed.id == c.id))
+        .collect();
+    unplaced.sort_by(|a, b| a.name.cmp(&b.name));
+    for category in unplaced {
+        if !out.iter().any(|(placed, _)| placed.id == category.id) {
+            out.push((category, 0));
+            visit(categories, category, 1, &mut out);
+        }
+    }
+    out
+}
+
+/// The direct subcategories of `id`, sorted by name.
+pub fn children<'a>(categories: &'a [Category], id: &str) -> Vec<&'a Category> {
+    let mut children: Vec<&Category> = categories.iter()
+        .filter(|c| c.parent_id.as_deref() == Some(id) && c.id != id)
+        .collect();
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    children
+}
+
+/// Whether `id` can be moved under `parent_id` without becoming its own
+/// ancestor.
+pub fn can_set_parent(categories: &[Category], id: &str, parent_id: &str) -> bool {
+    !is_within(categories, parent_id, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(id: &str, name: &str, parent_id: Option<&str>) -> Category {
+        Category {
+            id: id.to_string(),
+            ..Category::new(name.to_string(), "#000000".to_string(), parent_id.map(str::to_string))
+        }
+    }
+
+    fn categories() -> Vec<Category> {
+        vec![
+            category("groceries", "Groceries", Some("food")),
+            category("food", "Food", None),
+            category("dining", "Dining", Some("food")),
+            category("coffee", "Coffee", Some("dining")),
+            category("rent", "Rent", None),
+            category("orphan", "Orphan", Some("deleted")),
+        ]
+    }
+
+    #[test]
+    fn subcategories_roll_up_into_their_parents() {
+        let categories = categories();
+        assert_eq!(ancestor_ids(&categories, "coffee"), vec!["coffee", "dining", "food"]);
+        assert_eq!(root_id(&categories, "coffee"), "food");
+        assert_eq!(root_id(&categories, "orphan"), "orphan");
+        assert!(is_within(&categories, "coffee", "food"));
+        assert!(is_within(&categories, "food", "food"));
+        assert!(!is_within(&categories, "food", "coffee"));
+        assert_eq!(full_name(&categories, "coffee"), "Food / Dining / Coffee");
+    }
+
+    #[test]
+    fn the_tree_lists_children_under_their_parent_by_name() {
+        let categories = categories();
+        let tree: Vec<(&str, usize)> = tree(&categories).into_iter().map(|(c, depth)| (c.id.as_str(), depth)).collect();
+        assert_eq!(tree, vec![
+            ("food", 0),
+            ("dining", 1),
+            ("coffee", 2),
+            ("groceries", 1),
+            ("orphan", 0),
+            ("rent", 0),
+        ]);
+    }
+
+    #[test]
+    fn a_category_cannot_move_under_its_own_branch() {
+        let mut categories = categories();
+        assert!(can_set_parent(&categories, "dining", "rent"));
+        assert!(!can_set_parent(&categories, "food", "coffee"));
+        assert!(!can_set_parent(&categories, "food", "food"));
+
+        // A loop already stored is cut off rather than followed forever
+        categories[1].parent_id = Some("coffee".to_string());
+        assert_eq!(ancestor_ids(&categories, "coffee"), vec!["coffee", "dining", "food"]);
+        assert_eq!(tree(&categories).len(), categories.len());
+    }
 }
@@ -0,0 +1,223 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::{Money, Transaction};
+
+/// How a rule tests a transaction's description.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MatchKind {
+    /// Any description matches.
+    Any,
+    /// The description contains the pattern, ignoring case.
+    Contains,
+    /// The description matches the pattern as a regular expression,
+    /// ignoring case.
+    Regex,
+}
+
+impl MatchKind {
+    pub const ALL: [MatchKind; 3] = [MatchKind::Any, MatchKind::Contains, MatchKind::Regex];
+}
+
+impl fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchKind::Any => write!(f, "Any description"),
+            MatchKind::Contains => write!(f, "Contains"),
+            MatchKind::Regex => write!(f, "Matches regex"),
+        }
+    }
+}
+
+impl FromStr for MatchKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MatchKind::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == s)
+            .ok_or_else(|| format!("Unknown match kind: {}", s))
+    }
+}
+
+/// Assigns `category_id` to transactions meeting every condition that is
+/// set. Rules are tried in ascending `priority` and the first match wins.
+/// Amount bounds are inclusive, compare the unsigned amount and only match
+/// transactions in the bounds' currency.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CategoryRule {
+    pub id: String,
+    pub name: String,
+    pub priority: i64,
+    pub match_kind: MatchKind,
+    pub pattern: String,
+    pub min_amount: Option<Money>,
+    pub max_amount: Option<Money>,
+    pub account_id: Option<String>,
+    #[serde(default)]
+    pub payee_id: Option<String>,
+    pub category_id: String,
+}
+
+impl CategoryRule {
+    pub fn new(name: String, match_kind: MatchKind, pattern: String, category_id: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            priority: 0,
+            match_kind,
+            pattern,
+            min_amount: None,
+            max_amount: None,
+            account_id: None,
+            payee_id: None,
+            category_id,
+        }
+    }
+
+    /// Compiles the description pattern of a regex rule.
+    pub fn regex(&self) -> Result<Option<Regex>, regex::Error> {
+        match self.match_kind {
+            MatchKind::Regex => RegexBuilder::new(&self.pattern)
+                .case_insensitive(true)
+                .build()
+                .map(Some),
+            MatchKind::Any | MatchKind::Contains => Ok(None),
+        }
+    }
+
+    fn matches(&self, transaction: &Transaction, regex: Option<&Regex>) -> bool {
+        let description_matches = match self.match_kind {
+            MatchKind::Any => true,
+            MatchKind::Contains => transaction.description
+                .to_lowercase()
+                .contains(&self.pattern.to_lowercase()),
+            MatchKind::Regex => regex.is_some_and(|regex| regex.is_match(&transaction.description)),
+        };
+
+        let within = |bound: Option<Money>, ok: fn(i64, i64) -> bool| {
+            bound.is_none_or(|bound| {
+                bound.currency() == transaction.amount.currency()
+                    && ok(transaction.amount.minor_units().abs(), bound.minor_units())
+            })
+        };
+
+        description_matches
+            && within(self.min_amount, |amount, min| amount >= min)
+            && within(self.max_amount, |amount, max| amount <= max)
+            && self.account_id.as_ref().is_none_or(|id| transaction.account_id.as_ref() == Some(id))
+            && self.payee_id.as_ref().is_none_or(|id| transaction.payee_id.as_ref() == Some(id))
+    }
+}
+
+/// Rules ready to apply, in priority order with their patterns compiled.
+/// Regex rules whose pattern does not compile never match.
+pub struct Categorizer {
+    rules: Vec<(CategoryRule, Option<Regex>)>,
+}
+
+impl Categorizer {
+    pub fn new(rules: &[CategoryRule]) -> Self {
+        let mut rules: Vec<(CategoryRule, Option<Regex>)> = rules
+            .iter()
+            .map(|rule| (rule.clone(), rule.regex().ok().flatten()))
+            .collect();
+        rules.sort_by_key(|(rule, _)| rule.priority);
+
+        Self { rules }
+    }
+
+    /// The category of the first rule `transaction` matches. Transfers are
+    /// never categorised.
+    pub fn category_for(&self, transaction: &Transaction) -> Option<&str> {
+        if transaction.is_transfer() {
+            return None;
+        }
+
+        self.rules
+            .iter()
+            .find(|(rule, regex)| rule.matches(transaction, regex.as_ref()))
+            .map(|(rule, _)| rule.category_id.as_str())
+    }
+
+    /// Fills in the category of each uncategorised transaction from the
+    /// rules.
+    pub fn categorize(&self, transactions: &mut [Transaction]) {
+        for transaction in transactions.iter_mut().filter(|t| t.category_id.is_none() && !t.is_split()) {
+            transaction.category_id = self.category_for(transaction).map(str::to_string);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense, money};
+    use crate::models::{Currency, TransactionType};
+
+    fn spend(description: &str, minor: i64) -> Transaction {
+        Transaction { description: description.to_string(), ..expense(money(minor), date(2026, 3, 1)) }
+    }
+
+    fn rule(match_kind: MatchKind, pattern: &str, category_id: &str, priority: i64) -> CategoryRule {
+        CategoryRule {
+            priority,
+            ..CategoryRule::new(category_id.to_string(), match_kind, pattern.to_string(), category_id.to_string())
+        }
+    }
+
+    #[test]
+    fn the_first_matching_rule_by_priority_wins() {
+        let categorizer = Categorizer::new(&[
+            rule(MatchKind::Any, "", "other", 9),
+            rule(MatchKind::Contains, "tesco", "groceries", 1),
+            rule(MatchKind::Regex, r"^uber\s+(eats|trip)", "transport", 2),
+            rule(MatchKind::Regex, "(unclosed", "broken", 0),
+        ]);
+
+        assert_eq!(categorizer.category_for(&spend("TESCO STORES 3021", 4_217)), Some("groceries"));
+        assert_eq!(categorizer.category_for(&spend("Uber Trip help.uber.com", 1_250)), Some("transport"));
+        assert_eq!(categorizer.category_for(&spend("Pay with Uber Trip", 1_250)), Some("other"));
+        let transfer = Transaction { transaction_type: TransactionType::Transfer, ..spend("Tesco", 100) };
+        assert_eq!(categorizer.category_for(&transfer), None);
+    }
+
+    #[test]
+    fn amount_account_and_payee_conditions_must_all_hold() {
+        let mut large = rule(MatchKind::Contains, "amazon", "electronics", 0);
+        large.min_amount = Some(money(10_000));
+        large.account_id = Some("card".to_string());
+        let mut small = rule(MatchKind::Contains, "amazon", "books", 1);
+        small.max_amount = Some(money(2_000));
+        let mut payee = rule(MatchKind::Any, "", "subscriptions", 2);
+        payee.payee_id = Some("netflix".to_string());
+        let categorizer = Categorizer::new(&[large, small, payee]);
+
+        let on_card = |minor| Transaction { account_id: Some("card".to_string()), ..spend("AMAZON MKTPLACE", minor) };
+        assert_eq!(categorizer.category_for(&on_card(10_000)), Some("electronics"));
+        assert_eq!(categorizer.category_for(&spend("AMAZON MKTPLACE", 10_000)), None);
+        assert_eq!(categorizer.category_for(&on_card(2_000)), Some("books"));
+        assert_eq!(categorizer.category_for(&on_card(2_001)), None);
+        // Bounds only match amounts in their own currency
+        let in_euros = Transaction { amount: Money::from_minor(1_000, Currency::EUR), ..on_card(0) };
+        assert_eq!(categorizer.category_for(&in_euros), None);
+        let netflix = Transaction { payee_id: Some("netflix".to_string()), ..spend("NETFLIX.COM", 999) };
+        assert_eq!(categorizer.category_for(&netflix), Some("subscriptions"));
+    }
+
+    #[test]
+    fn categorizing_keeps_existing_categories() {
+        let categorizer = Categorizer::new(&[rule(MatchKind::Any, "", "other", 0)]);
+        let mut transactions = vec![
+            Transaction { category_id: Some("rent".to_string()), ..spend("Landlord", 90_000) },
+            spend("Corner shop", 350),
+        ];
+        categorizer.categorize(&mut transactions);
+
+        let categories: Vec<_> = transactions.iter().map(|t| t.category_id.as_deref()).collect();
+        assert_eq!(categories, vec![Some("rent"), Some("other")]);
+    }
+}
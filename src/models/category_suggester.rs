@@ -0,0 +1,151 @@
+use std::collections::{HashMap, HashSet};
+
+use super::Transaction;
+
+/// A category proposed for a description, with the model's confidence from
+/// 0 to 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub category_id: String,
+    pub confidence: f64,
+}
+
+/// A multinomial naive Bayes classifier over description words, trained on
+/// the categories already assigned to past transactions. Everything is
+/// computed locally from the transactions passed to `train`.
+#[derive(Debug, Clone, Default)]
+pub struct CategorySuggester {
+    /// Training transactions per category.
+    documents: HashMap<String, usize>,
+    /// Occurrences of each word per category.
+    words: HashMap<String, HashMap<String, usize>>,
+    /// Total words seen per category.
+    word_totals: HashMap<String, usize>,
+    vocabulary: usize,
+    total_documents: usize,
+}
+
+impl CategorySuggester {
+    /// Learns from every categorised transaction that is not a transfer.
+    pub fn train(transactions: &[Transaction]) -> Self {
+        let mut suggester = Self::default();
+        let mut vocabulary = HashSet::new();
+
+        for transaction in transactions.iter().filter(|t| !t.is_transfer()) {
+            let Some(category_id) = &transaction.category_id else {
+                continue;
+            };
+
+            *suggester.documents.entry(category_id.clone()).or_default() += 1;
+            suggester.total_documents += 1;
+
+            let counts = suggester.words.entry(category_id.clone()).or_default();
+            for word in tokenize(&transaction.description) {
+                *counts.entry(word.clone()).or_default() += 1;
+                *suggester.word_totals.entry(category_id.clone()).or_default() += 1;
+                vocabulary.insert(word);
+            }
+        }
+
+        suggester.vocabulary = vocabulary.len();
+        suggester
+    }
+
+    /// Up to `limit` categories for `description`, most likely first. Words
+    /// never seen in training carry no evidence, so a description made only
+    /// of them gets no suggestions.
+    pub fn suggest(&self, description: &str, limit: usize) -> Vec<Suggestion> {
+        let words: Vec<String> = tokenize(description)
+            .into_iter()
+            .filter(|word| self.words.values().any(|counts| counts.contains_key(word)))
+            .collect();
+        if words.is_empty() || self.total_documents == 0 {
+            return Vec::new();
+        }
+
+        // Log-probabilities with Laplace smoothing, then normalised into
+        // posteriors so they read as confidences
+        let scores: Vec<(&String, f64)> = self.documents
+            .iter()
+            .map(|(category_id, documents)| {
+                let counts = &self.words[category_id];
+                let total = self.word_totals.get(category_id).copied().unwrap_or(0);
+                let denominator = (total + self.vocabulary) as f64;
+
+                let prior = (*documents as f64 / self.total_documents as f64).ln();
+                let likelihood: f64 = words
+                    .iter()
+                    .map(|word| {
+                        let count = counts.get(word).copied().unwrap_or(0);
+                        ((count + 1) as f64 / denominator).ln()
+                    })
+                    .sum();
+
+                (category_id, prior + likelihood)
+            })
+            .collect();
+
+        let max = scores.iter().map(|(_, score)| *score).fold(f64::NEG_INFINITY, f64::max);
+        let total: f64 = scores.iter().map(|(_, score)| (score - max).exp()).sum();
+
+        let mut suggestions: Vec<Suggestion> = scores
+            .into_iter()
+            .map(|(category_id, score)| Suggestion {
+                category_id: category_id.clone(),
+                confidence: (score - max).exp() / total,
+            })
+            .collect();
+        suggestions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        suggestions.truncate(limit);
+
+        suggestions
+    }
+}
+
+/// Lower-cased words of two or more characters. Numbers are dropped since
+/// card numbers and references rarely say anything about the category.
+fn tokenize(description: &str) -> Vec<String> {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 2 && !word.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense, money};
+
+    fn categorised(description: &str, category_id: &str) -> Transaction {
+        Transaction {
+            description: description.to_string(),
+            category_id: Some(category_id.to_string()),
+            ..expense(money(1_000), date(2026, 3, 1))
+        }
+    }
+
+    #[test]
+    fn suggestions_follow_past_descriptions() {
+        let suggester = CategorySuggester::train(&[
+            categorised("TESCO STORES 3021", "groceries"),
+            categorised("Tesco Express", "groceries"),
+            categorised("SAINSBURYS", "groceries"),
+            categorised("Shell petrol station", "transport"),
+            categorised("Uber trip", "transport"),
+            Transaction { category_id: None, ..categorised("Tesco petrol", "") },
+        ]);
+
+        let suggestions = suggester.suggest("TESCO 4411", 5);
+        assert_eq!(suggestions[0].category_id, "groceries");
+        assert!(suggestions[0].confidence > 0.5);
+        let total: f64 = suggestions.iter().map(|s| s.confidence).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        assert_eq!(suggester.suggest("shell", 1).len(), 1);
+        assert_eq!(suggester.suggest("Shell", 1)[0].category_id, "transport");
+        // Unknown words and numbers carry no evidence
+        assert!(suggester.suggest("Amazon 1234", 3).is_empty());
+        assert!(CategorySuggester::default().suggest("Tesco", 3).is_empty());
+    }
+}
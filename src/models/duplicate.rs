@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::Transaction;
+
+/// How many days apart two entries of the same purchase may be dated, e.g.
+/// a receipt entered on the day and the bank posting a few days later.
+pub const DATE_TOLERANCE_DAYS: i64 = 3;
+
+/// Minimum description similarity, from 0 to 1, for a duplicate.
+pub const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+/// Two transactions judged not to be duplicates. The ids are kept in a
+/// fixed order so the pair compares equal whichever way round it is built.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct DuplicatePair {
+    pub first: Uuid,
+    pub second: Uuid,
+}
+
+impl DuplicatePair {
+    pub fn new(a: Uuid, b: Uuid) -> Self {
+        if a <= b {
+            Self { first: a, second: b }
+        } else {
+            Self { first: b, second: a }
+        }
+    }
+}
+
+/// Whether `a` and `b` look like the same real-world transaction: same
+/// account, type and amount, dated within `DATE_TOLERANCE_DAYS` of each
+/// other, with similar descriptions. Transfer legs are never duplicates.
+pub fn is_duplicate(a: &Transaction, b: &Transaction) -> bool {
+    a.id != b.id
+        && !a.is_transfer()
+        && !b.is_transfer()
+        && a.account_id == b.account_id
+        && a.transaction_type == b.transaction_type
+        && a.amount == b.amount
+        && (a.date - b.date).num_days().abs() <= DATE_TOLERANCE_DAYS
+        && description_similarity(&a.description, &b.description) >= SIMILARITY_THRESHOLD
+}
+
+/// Existing transactions that `transaction` may duplicate, skipping pairs
+/// already dismissed.
+pub fn find_candidates<'a>(
+    transaction: &Transaction,
+    existing: &'a [Transaction],
+    dismissed: &HashSet<DuplicatePair>,
+) -> Vec<&'a Transaction> {
+    existing
+        .iter()
+        .filter(|t| is_duplicate(transaction, t))
+        .filter(|t| !dismissed.contains(&DuplicatePair::new(transaction.id, t.id)))
+        .collect()
+}
+
+/// Every pair of likely duplicates among `transactions`, most recent first,
+/// skipping pairs already dismissed.
+pub fn find_duplicates<'a>(
+    transactions: &'a [Transaction],
+    dismissed: &HashSet<DuplicatePair>,
+) -> Vec<(&'a Transaction, &'a Transaction)> {
+    let mut sorted: Vec<&Transaction> = transactions.iter().collect();
+    sorted.sort_by_key(|t| std::cmp::Reverse(t.date));
+
+    let mut pairs = Vec::new();
+    for (i, a) in sorted.iter().enumerate() {
+        // Sorted by date, so only the following few days need comparing
+        for b in sorted[i + 1..]
+            .iter()
+            .take_while(|b| (a.date - b.date).num_days() <= DATE_TOLERANCE_DAYS)
+        {
+            if is_duplicate(a, b) && !dismissed.contains(&DuplicatePair::new(a.id, b.id)) {
+                pairs.push((*a, *b));
+            }
+        }
+    }
+
+    pairs
+}
+
+/// Similarity of two descriptions from 0 to 1, using the Dice coefficient
+/// of their character bigrams. Case, punctuation and spacing are ignored,
+/// so "AMAZON MKTPLACE" and "Amazon Marketplace" score highly.
+pub fn description_similarity(a: &str, b: &str) -> f64 {
+    let a = normalize(a);
+    let b = normalize(b);
+    if a == b {
+        return 1.0;
+    }
+
+    let a = bigrams(&a);
+    let b = bigrams(&b);
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let mut remaining = b.clone();
+    let mut shared = 0;
+    for bigram in &a {
+        if let Some(index) = remaining.iter().position(|other| other == bigram) {
+            remaining.swap_remove(index);
+            shared += 1;
+        }
+    }
+
+    2.0 * shared as f64 / (a.len() + b.len()) as f64
+}
+
+fn normalize(text: &str) -> Vec<char> {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn bigrams(chars: &[char]) -> Vec<(char, char)> {
+    chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense, income, money};
+    use crate::models::TransactionType;
+
+    fn purchase(description: &str, day: u32) -> Transaction {
+        Transaction {
+            description: description.to_string(),
+            account_id: Some("checking".to_string()),
+            ..expense(money(4_999), date(2026, 3, day))
+        }
+    }
+
+    #[test]
+    fn similarity_ignores_case_and_punctuation() {
+        assert_eq!(description_similarity("AMAZON MKTPLACE", "amazon-mktplace"), 1.0);
+        assert!(description_similarity("AMAZON MKTPLACE", "Amazon Marketplace") >= SIMILARITY_THRESHOLD);
+        assert!(description_similarity("Tesco", "Shell") < SIMILARITY_THRESHOLD);
+        assert_eq!(description_similarity("", "Tesco"), 0.0);
+    }
+
+    #[test]
+    fn duplicates_need_every_detail_to_match() {
+        let original = purchase("AMAZON MKTPLACE", 10);
+        assert!(is_duplicate(&original, &purchase("Amazon Marketplace", 13)));
+        assert!(is_duplicate(&original, &purchase("Amazon Marketplace", 7)));
+        assert!(!is_duplicate(&original, &original));
+
+        assert!(!is_duplicate(&original, &purchase("Amazon Marketplace", 14)));
+        assert!(!is_duplicate(&original, &purchase("Shell Petrol", 10)));
+        assert!(!is_duplicate(&original, &Transaction { amount: money(5_000), ..purchase("AMAZON MKTPLACE", 10) }));
+        assert!(!is_duplicate(&original, &Transaction { account_id: None, ..purchase("AMAZON MKTPLACE", 10) }));
+        assert!(!is_duplicate(&original, &Transaction {
+            description: "AMAZON MKTPLACE".to_string(),
+            account_id: original.account_id.clone(),
+            ..income(money(4_999), date(2026, 3, 10))
+        }));
+        assert!(!is_duplicate(&original, &Transaction {
+            transaction_type: TransactionType::Transfer,
+            transfer_id: Some("transfer".to_string()),
+            ..purchase("AMAZON MKTPLACE", 10)
+        }));
+    }
+
+    #[test]
+    fn dismissed_pairs_are_not_reported_again() {
+        let transactions = vec![
+            purchase("Tesco Stores", 1),
+            purchase("TESCO STORES 3021", 3),
+            purchase("Tesco", 20),
+        ];
+        let pairs = find_duplicates(&transactions, &HashSet::new());
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].0.id, pairs[0].1.id), (transactions[1].id, transactions[0].id));
+
+        let dismissed = HashSet::from([DuplicatePair::new(transactions[0].id, transactions[1].id)]);
+        assert!(find_duplicates(&transactions, &dismissed).is_empty());
+        assert!(find_candidates(&transactions[0], &transactions, &dismissed).is_empty());
+        assert_eq!(find_candidates(&transactions[0], &transactions, &HashSet::new()).len(), 1);
+    }
+}
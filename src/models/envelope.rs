@@ -0,0 +1,264 @@
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::{CurrencyConverter, Money, Transaction, TransactionType};
+use crate::utils::{format_currency, month_end, month_start};
+
+/// Money assigned to a category's envelope for one month in zero-based
+/// budgeting. Income waits to be assigned, and whatever an envelope does
+/// not spend stays in it for the following months.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Allocation {
+    pub category_id: String,
+    /// The first day of the month.
+    pub month: NaiveDate,
+    pub amount: Money,
+}
+
+impl Allocation {
+    pub fn new(category_id: String, month: NaiveDate, amount: Money) -> Self {
+        Self {
+            category_id,
+            month: month_start(month),
+            amount,
+        }
+    }
+}
+
+/// Envelope balances worked out from allocations and transactions, in the
+/// base currency. Envelope budgeting begins with the first month anything
+/// is assigned; income and spending before then are left out. Amounts
+/// without a usable exchange rate are left out too, and `unconverted`
+/// counts them.
+pub struct Envelopes<'a> {
+    allocations: &'a [Allocation],
+    transactions: &'a [Transaction],
+    converter: &'a CurrencyConverter,
+    start: Option<NaiveDate>,
+}
+
+impl<'a> Envelopes<'a> {
+    pub fn new(allocations: &'a [Allocation], transactions: &'a [Transaction], converter: &'a CurrencyConverter) -> Self {
+        Self {
+            allocations,
+            transactions,
+            converter,
+            start: allocations.iter().map(|a| a.month).min(),
+        }
+    }
+
+    /// The first month counted when looking at `month`.
+    fn start(&self, month: NaiveDate) -> NaiveDate {
+        self.start.map_or(month, |start| start.min(month))
+    }
+
+    fn to_base(&self, amount: Money, date: NaiveDate) -> Money {
+        self.converter.to_base(amount, date).unwrap_or_else(|| Money::zero(self.converter.base()))
+    }
+
+    /// How many of the amounts counted up to the end of `month` were left
+    /// out for want of an exchange rate to the base currency.
+    pub fn unconverted(&self, month: NaiveDate) -> usize {
+        let (from, to) = (self.start(month), month_end(month));
+        let transactions = self.transactions.iter()
+            .filter(|t| (t.is_expense() || t.transaction_type == TransactionType::Income) && from <= t.date && t.date <= to)
+            .flat_map(|t| t.category_amounts().into_iter().map(move |(_, amount)| (amount, t.date)));
+        let allocations = self.allocations.iter()
+            .filter(|a| a.month <= month)
+            .map(|a| (a.amount, a.month));
+
+        transactions.chain(allocations)
+            .filter(|(amount, date)| self.converter.to_base(*amount, *date).is_none())
+            .count()
+    }
+
+    fn total(&self, amounts: impl Iterator<Item = Money>) -> Money {
+        amounts.fold(Money::zero(self.converter.base()), |total, amount| total + amount)
+    }
+
+    /// Income received up to the end of `month` less everything assigned
+    /// up to and including `month`. Negative when more has been assigned
+    /// than received.
+    pub fn to_be_assigned(&self, month: NaiveDate) -> Money {
+        let (from, to) = (self.start(month), month_end(month));
+        let income = self.total(
+            self.transactions.iter()
+                .filter(|t| t.transaction_type == TransactionType::Income && from <= t.date && t.date <= to)
+                .map(|t| self.to_base(t.amount, t.date))
+        );
+        let assigned = self.total(
+            self.allocations.iter()
+                .filter(|a| a.month <= month)
+                .map(|a| self.to_base(a.amount, a.month))
+        );
+
+        income - assigned
+    }
+
+    /// Assigned to `category_id` for `month` alone.
+    pub fn assigned(&self, category_id: &str, month: NaiveDate) -> Money {
+        self.total(
+            self.allocations.iter()
+                .filter(|a| a.category_id == category_id && a.month == month)
+                .map(|a| self.to_base(a.amount, a.month))
+        )
+    }
+
+    /// Spending filed under `category_id` itself in `month`.
+    pub fn activity(&self, category_id: &str, month: NaiveDate) -> Money {
+        self.spent(category_id, month, month_end(month))
+    }
+
+    /// What is left in the envelope at the end of `month`: everything
+    /// assigned to it so far less everything spent from it. Negative when
+    /// overspent.
+    pub fn available(&self, category_id: &str, month: NaiveDate) -> Money {
+        let assigned = self.total(
+            self.allocations.iter()
+                .filter(|a| a.category_id == category_id && a.month <= month)
+                .map(|a| self.to_base(a.amount, a.month))
+        );
+
+        assigned - self.spent(category_id, self.start(month), month_end(month))
+    }
+
+    fn spent(&self, category_id: &str, from: NaiveDate, to: NaiveDate) -> Money {
+        self.total(
+            self.transactions.iter()
+                .filter(|t| t.is_expense() && from <= t.date && t.date <= to)
+                .flat_map(|t| {
+                    t.category_amounts().into_iter()
+                        .filter(|(id, _)| *id == Some(category_id))
+                        .map(|(_, amount)| self.to_base(amount, t.date))
+                        .collect::<Vec<_>>()
+                })
+        )
+    }
+
+    /// Checks that making `allocation` leaves no month, from its own to the
+    /// last one with anything assigned, with more assigned than received.
+    pub fn check(&self, allocation: &Allocation) -> Result<(), String> {
+        let allocations: Vec<Allocation> = self.allocations.iter()
+            .filter(|a| !(a.category_id == allocation.category_id && a.month == allocation.month))
+            .cloned()
+            .chain(std::iter::once(allocation.clone()))
+            .collect();
+        let envelopes = Envelopes::new(&allocations, self.transactions, self.converter);
+
+        let last = allocations.iter().map(|a| a.month).max().unwrap_or(allocation.month);
+        let mut month = allocation.month;
+        while month <= last {
+            let left = envelopes.to_be_assigned(month);
+            if left.is_negative() {
+                return Err(format!(
+                    "That assigns {} more than is available in {}",
+                    format_currency(-left),
+                    month.format("%B %Y")
+                ));
+            }
+            month = month + Months::new(1);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense, income, money};
+    use crate::models::Currency;
+
+    fn spend(category_id: &str, amount: i64, date: NaiveDate) -> Transaction {
+        Transaction {
+            category_id: Some(category_id.to_string()),
+            ..expense(money(amount), date)
+        }
+    }
+
+    fn assign(category_id: &str, month: NaiveDate, amount: i64) -> Allocation {
+        Allocation::new(category_id.to_string(), month, money(amount))
+    }
+
+    #[test]
+    fn allocations_are_kept_per_month_and_category() {
+        let allocations = vec![
+            assign("food", date(2026, 3, 15), 20_000),
+            assign("rent", date(2026, 3, 1), 100_000),
+            assign("food", date(2026, 4, 1), 25_000),
+        ];
+        let converter = CurrencyConverter::default();
+        let envelopes = Envelopes::new(&allocations, &[], &converter);
+
+        // Allocations are filed under the first of their month
+        assert_eq!(allocations[0].month, date(2026, 3, 1));
+        assert_eq!(envelopes.assigned("food", date(2026, 3, 1)), money(20_000));
+        assert_eq!(envelopes.assigned("food", date(2026, 4, 1)), money(25_000));
+        assert_eq!(envelopes.assigned("food", date(2026, 5, 1)), money(0));
+        assert_eq!(envelopes.assigned("rent", date(2026, 4, 1)), money(0));
+    }
+
+    #[test]
+    fn envelopes_carry_what_is_left_and_what_is_overspent() {
+        let allocations = vec![
+            assign("food", date(2026, 3, 1), 20_000),
+            assign("food", date(2026, 4, 1), 20_000),
+        ];
+        let transactions = vec![
+            spend("food", 15_000, date(2026, 3, 20)),
+            spend("food", 30_000, date(2026, 4, 10)),
+            spend("rent", 50_000, date(2026, 4, 1)),
+        ];
+        let converter = CurrencyConverter::default();
+        let envelopes = Envelopes::new(&allocations, &transactions, &converter);
+
+        assert_eq!(envelopes.available("food", date(2026, 3, 1)), money(5_000));
+        assert_eq!(envelopes.activity("food", date(2026, 4, 1)), money(30_000));
+        assert_eq!(envelopes.available("food", date(2026, 4, 1)), money(-5_000));
+        assert_eq!(envelopes.available("food", date(2026, 5, 1)), money(-5_000));
+        assert_eq!(envelopes.available("rent", date(2026, 4, 1)), money(-50_000));
+    }
+
+    #[test]
+    fn to_be_assigned_counts_income_from_the_first_assigned_month() {
+        let allocations = vec![
+            assign("food", date(2026, 3, 1), 20_000),
+            assign("rent", date(2026, 4, 1), 100_000),
+        ];
+        let transactions = vec![
+            income(money(500_000), date(2026, 2, 25)),
+            income(money(100_000), date(2026, 3, 25)),
+            income(money(50_000), date(2026, 4, 25)),
+        ];
+        let converter = CurrencyConverter::default();
+        let envelopes = Envelopes::new(&allocations, &transactions, &converter);
+
+        assert_eq!(envelopes.to_be_assigned(date(2026, 3, 1)), money(80_000));
+        assert_eq!(envelopes.to_be_assigned(date(2026, 4, 1)), money(30_000));
+
+        let too_much = assign("fun", date(2026, 3, 1), 90_000);
+        assert!(envelopes.check(&too_much).is_err());
+        // What March assigns must also leave enough for April
+        assert!(envelopes.check(&assign("fun", date(2026, 3, 1), 30_000)).is_ok());
+        assert!(envelopes.check(&assign("fun", date(2026, 3, 1), 40_000)).is_err());
+    }
+
+    #[test]
+    fn amounts_without_a_rate_are_counted() {
+        let allocations = vec![assign("food", date(2026, 3, 1), 20_000)];
+        let transactions = vec![
+            income(Money::from_minor(10_000, Currency::EUR), date(2026, 3, 2)),
+            Transaction {
+                category_id: Some("food".to_string()),
+                ..expense(Money::from_minor(1_000, Currency::GBP), date(2026, 3, 5))
+            },
+            spend("food", 1_000, date(2026, 3, 6)),
+        ];
+        let converter = CurrencyConverter::default();
+        let envelopes = Envelopes::new(&allocations, &transactions, &converter);
+
+        assert_eq!(envelopes.unconverted(date(2026, 3, 1)), 2);
+        assert_eq!(envelopes.available("food", date(2026, 3, 1)), money(19_000));
+        assert_eq!(envelopes.to_be_assigned(date(2026, 3, 1)), money(-20_000));
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Currency, Money};
+
+/// A user-entered rate: one unit of `from` buys `rate` units of `to` from
+/// `effective_date` until the next rate for the same pair.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExchangeRate {
+    pub id: String,
+    pub from: Currency,
+    pub to: Currency,
+    pub rate: f64,
+    pub effective_date: NaiveDate,
+}
+
+impl ExchangeRate {
+    pub fn new(from: Currency, to: Currency, rate: f64, effective_date: NaiveDate) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            from,
+            to,
+            rate,
+            effective_date,
+        }
+    }
+}
+
+/// Converts amounts between currencies using the stored exchange rates, and
+/// knows the base currency that totals are reported in.
+#[derive(Debug, Clone, Default)]
+pub struct CurrencyConverter {
+    base: Currency,
+    rates: Vec<ExchangeRate>,
+}
+
+impl CurrencyConverter {
+    pub fn new(base: Currency, rates: Vec<ExchangeRate>) -> Self {
+        Self { base, rates }
+    }
+
+    pub fn base(&self) -> Currency {
+        self.base
+    }
+
+    pub fn rates(&self) -> &[ExchangeRate] {
+        &self.rates
+    }
+
+    /// Units of `to` per unit of `from` on `date`. Pairs without a rate of
+    /// their own are crossed through the base currency.
+    pub fn rate(&self, from: Currency, to: Currency, date: NaiveDate) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+
+        self.pair_rate(from, to, date).or_else(|| {
+            if from == self.base || to == self.base {
+                return None;
+            }
+            Some(self.pair_rate(from, self.base, date)? * self.pair_rate(self.base, to, date)?)
+        })
+    }
+
+    pub fn convert(&self, amount: Money, to: Currency, date: NaiveDate) -> Option<Money> {
+        if amount.currency() == to {
+            return Some(amount);
+        }
+
+        self.rate(amount.currency(), to, date)
+            .map(|rate| amount.exchange(rate, to))
+    }
+
+    pub fn to_base(&self, amount: Money, date: NaiveDate) -> Option<Money> {
+        self.convert(amount, self.base, date)
+    }
+
+    /// The rate in effect on `date`, taken from either direction of the
+    /// pair. There is none before the first recorded rate.
+    fn pair_rate(&self, from: Currency, to: Currency, date: NaiveDate) -> Option<f64> {
+        self.rates.iter()
+            .filter(|r| r.rate > 0.0 && r.effective_date <= date)
+            .filter_map(|r| {
+                if r.from == from && r.to == to {
+                    Some((r.effective_date, r.rate))
+                } else if r.from == to && r.to == from {
+                    Some((r.effective_date, 1.0 / r.rate))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(since, _)| *since)
+            .map(|(_, rate)| rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::date;
+
+    fn converter() -> CurrencyConverter {
+        CurrencyConverter::new(Currency::USD, vec![
+            ExchangeRate::new(Currency::EUR, Currency::USD, 1.10, date(2026, 3, 1)),
+            ExchangeRate::new(Currency::EUR, Currency::USD, 1.20, date(2026, 4, 1)),
+            ExchangeRate::new(Currency::USD, Currency::GBP, 0.80, date(2026, 3, 1)),
+        ])
+    }
+
+    #[test]
+    fn the_latest_rate_on_or_before_the_date_applies() {
+        let converter = converter();
+        let euros = Money::from_minor(1_000, Currency::EUR);
+
+        assert_eq!(converter.to_base(euros, date(2026, 3, 1)), Some(Money::from_minor(1_100, Currency::USD)));
+        assert_eq!(converter.to_base(euros, date(2026, 3, 31)), Some(Money::from_minor(1_100, Currency::USD)));
+        assert_eq!(converter.to_base(euros, date(2026, 5, 1)), Some(Money::from_minor(1_200, Currency::USD)));
+        // The reverse direction of a stored pair
+        assert_eq!(
+            converter.convert(Money::from_minor(1_200, Currency::USD), Currency::EUR, date(2026, 4, 2)),
+            Some(Money::from_minor(1_000, Currency::EUR))
+        );
+    }
+
+    #[test]
+    fn there_is_no_rate_before_the_first_one_recorded() {
+        let converter = converter();
+        assert_eq!(converter.to_base(Money::from_minor(1_000, Currency::EUR), date(2026, 2, 28)), None);
+        assert_eq!(converter.rate(Currency::EUR, Currency::GBP, date(2026, 2, 28)), None);
+        assert_eq!(converter.rate(Currency::USD, Currency::USD, date(2000, 1, 1)), Some(1.0));
+    }
+
+    #[test]
+    fn pairs_without_a_rate_cross_through_the_base_currency() {
+        let converter = converter();
+        let rate = converter.rate(Currency::EUR, Currency::GBP, date(2026, 3, 15)).unwrap();
+        assert!((rate - 0.88).abs() < 1e-9);
+        assert_eq!(converter.rate(Currency::EUR, Currency::JPY, date(2026, 3, 15)), None);
+    }
+}
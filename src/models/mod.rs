@@ -1,7 +1,35 @@
+pub mod account;
 pub mod budget;
 pub mod category;
+pub mod category_rule;
+pub mod category_suggester;
+pub mod duplicate;
+pub mod envelope;
+pub mod exchange_rate;
+pub mod money;
+pub mod notification;
+pub mod payee;
+pub mod period;
+pub mod recurring;
+pub mod tag;
+#[cfg(test)]
+pub mod testing;
 pub mod transaction;
+pub mod transfer;
 
-pub use budget::Budget;
+pub use account::{Account, AccountType};
+pub use budget::{Budget, BudgetPeriod, Renewal};
 pub use category::Category;
-pub use transaction::{Transaction, TransactionType};
+pub use category_rule::{CategoryRule, Categorizer, MatchKind};
+pub use category_suggester::{CategorySuggester, Suggestion};
+pub use duplicate::DuplicatePair;
+pub use envelope::{Allocation, Envelopes};
+pub use exchange_rate::{CurrencyConverter, ExchangeRate};
+pub use money::{Currency, Money};
+pub use notification::Notification;
+pub use payee::Payee;
+pub use period::{DateRange, Period};
+pub use recurring::{Frequency, NthWeekday, RecurrenceEnd, RecurringRule};
+pub use tag::Tag;
+pub use transaction::{Split, Transaction, TransactionType};
+pub use transfer::Transfer;
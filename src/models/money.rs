@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+// Variants are named by their ISO 4217 codes
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Currency {
+    #[default]
+    USD,
+    EUR,
+    GBP,
+    CAD,
+    AUD,
+    CHF,
+    JPY,
+}
+
+impl Currency {
+    pub const ALL: [Currency; 7] = [
+        Currency::USD,
+        Currency::EUR,
+        Currency::GBP,
+        Currency::CAD,
+        Currency::AUD,
+        Currency::CHF,
+        Currency::JPY,
+    ];
+
+    /// ISO 4217 code, as stored in the database.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::USD => "USD",
+            Currency::EUR => "EUR",
+            Currency::GBP => "GBP",
+            Currency::CAD => "CAD",
+            Currency::AUD => "AUD",
+            Currency::CHF => "CHF",
+            Currency::JPY => "JPY",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::USD => "$",
+            Currency::EUR => "€",
+            Currency::GBP => "£",
+            Currency::CAD => "CA$",
+            Currency::AUD => "A$",
+            Currency::CHF => "CHF ",
+            Currency::JPY => "¥",
+        }
+    }
+
+    /// Number of decimal places in the currency's minor unit.
+    pub fn decimals(&self) -> u32 {
+        match self {
+            Currency::JPY => 0,
+            _ => 2,
+        }
+    }
+
+    fn minor_per_major(&self) -> i64 {
+        10_i64.pow(self.decimals())
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim().to_ascii_uppercase();
+        Currency::ALL
+            .into_iter()
+            .find(|c| c.code() == code)
+            .ok_or(MoneyError::UnknownCurrency(code))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MoneyError {
+    #[error("Amount is required")]
+    Empty,
+    #[error("Invalid amount: {0}")]
+    Invalid(String),
+    #[error("{currency} amounts allow at most {allowed} decimal places")]
+    TooManyDecimals { currency: Currency, allowed: u32 },
+    #[error("Amount is too large")]
+    Overflow,
+    #[error("Unknown currency: {0}")]
+    UnknownCurrency(String),
+}
+
+/// An exact amount of money, held as an integer number of the currency's
+/// minor units (cents for USD).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Money {
+    minor: i64,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn from_minor(minor: i64, currency: Currency) -> Self {
+        Self { minor, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::from_minor(0, currency)
+    }
+
+    pub fn minor_units(&self) -> i64 {
+        self.minor
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minor == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.minor < 0
+    }
+
+    /// Approximate value in major units, for chart scales and percentages
+    /// only. Never feed this back into stored amounts.
+    pub fn to_f64(self) -> f64 {
+        self.minor as f64 / self.currency.minor_per_major() as f64
+    }
+
+    /// Converts into `to` at `rate` units of `to` per unit of this
+    /// currency, rounding to the nearest minor unit of `to`.
+    pub fn exchange(self, rate: f64, to: Currency) -> Money {
+        let scale = to.minor_per_major() as f64 / self.currency.minor_per_major() as f64;
+        Money::from_minor((self.minor as f64 * rate * scale).round() as i64, to)
+    }
+
+    /// Parses user input such as `12`, `12.5` or `-1,234.56`. Commas are
+    /// accepted as thousands separators; more decimal places than the
+    /// currency has are rejected rather than rounded.
+    pub fn parse(input: &str, currency: Currency) -> Result<Self, MoneyError> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Err(MoneyError::Empty);
+        }
+
+        let invalid = || MoneyError::Invalid(trimmed.to_string());
+
+        let (negative, unsigned) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let unsigned = unsigned.replace(',', "");
+
+        let (whole, fraction) = match unsigned.split_once('.') {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (unsigned.as_str(), ""),
+        };
+
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+        if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let decimals = currency.decimals();
+        if fraction.len() > decimals as usize {
+            return Err(MoneyError::TooManyDecimals { currency, allowed: decimals });
+        }
+
+        let whole: i64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| MoneyError::Overflow)?
+        };
+        let fraction: i64 = if fraction.is_empty() {
+            0
+        } else {
+            let padding = 10_i64.pow(decimals - fraction.len() as u32);
+            fraction.parse::<i64>().map_err(|_| invalid())? * padding
+        };
+
+        let minor = whole
+            .checked_mul(currency.minor_per_major())
+            .and_then(|m| m.checked_add(fraction))
+            .ok_or(MoneyError::Overflow)?;
+
+        Ok(Self::from_minor(if negative { -minor } else { minor }, currency))
+    }
+
+    /// The amount without symbol or grouping, as it would be typed into a
+    /// form, e.g. `1234.50`.
+    pub fn to_input_string(self) -> String {
+        let decimals = self.currency.decimals() as usize;
+        let per_major = self.currency.minor_per_major();
+        let sign = if self.minor < 0 { "-" } else { "" };
+        let major = (self.minor / per_major).abs();
+        let minor = (self.minor % per_major).abs();
+
+        if decimals == 0 {
+            format!("{}{}", sign, major)
+        } else {
+            format!("{}{}.{:0width$}", sign, major, minor, width = decimals)
+        }
+    }
+
+    fn assert_same_currency(&self, other: &Money) {
+        assert_eq!(
+            self.currency, other.currency,
+            "cannot combine {} and {} amounts without conversion",
+            self.currency, other.currency
+        );
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimals = self.currency.decimals() as usize;
+        let per_major = self.currency.minor_per_major();
+        let sign = if self.minor < 0 { "-" } else { "" };
+        let major = (self.minor / per_major).unsigned_abs();
+        let minor = (self.minor % per_major).unsigned_abs();
+
+        // Group the whole part in thousands
+        let digits = major.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+
+        if decimals == 0 {
+            write!(f, "{}{}{}", sign, self.currency.symbol(), grouped)
+        } else {
+            write!(f, "{}{}{}.{:0width$}", sign, self.currency.symbol(), grouped, minor, width = decimals)
+        }
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        self.assert_same_currency(&other);
+        Money::from_minor(self.minor + other.minor, self.currency)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        self.assert_same_currency(&other);
+        Money::from_minor(self.minor - other.minor, self.currency)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money::from_minor(-self.minor, self.currency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd(input: &str) -> Result<Money, MoneyError> {
+        Money::parse(input, Currency::USD)
+    }
+
+    #[test]
+    fn parse_pads_missing_decimals() {
+        assert_eq!(usd("12").unwrap().minor_units(), 1200);
+        assert_eq!(usd("12.5").unwrap().minor_units(), 1250);
+        assert_eq!(usd(".05").unwrap().minor_units(), 5);
+        assert_eq!(usd(" 1,234.56 ").unwrap().minor_units(), 123_456);
+        assert_eq!(Money::parse("1,500", Currency::JPY).unwrap().minor_units(), 1500);
+    }
+
+    #[test]
+    fn parse_rejects_extra_decimals_instead_of_rounding() {
+        assert_eq!(
+            usd("0.125"),
+            Err(MoneyError::TooManyDecimals { currency: Currency::USD, allowed: 2 })
+        );
+        assert_eq!(
+            Money::parse("100.5", Currency::JPY),
+            Err(MoneyError::TooManyDecimals { currency: Currency::JPY, allowed: 0 })
+        );
+    }
+
+    #[test]
+    fn parse_signs_and_invalid_input() {
+        assert_eq!(usd("-1,234.56").unwrap().minor_units(), -123_456);
+        assert_eq!(usd("+3").unwrap().minor_units(), 300);
+        assert_eq!(usd("-0.01").unwrap().minor_units(), -1);
+        assert_eq!(usd("  "), Err(MoneyError::Empty));
+        assert_eq!(usd("-"), Err(MoneyError::Invalid("-".to_string())));
+        assert_eq!(usd("--5"), Err(MoneyError::Invalid("--5".to_string())));
+        assert_eq!(usd("1.2.3"), Err(MoneyError::Invalid("1.2.3".to_string())));
+        assert_eq!(usd("$5"), Err(MoneyError::Invalid("$5".to_string())));
+        assert_eq!(usd("99999999999999999999"), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn exchange_rounds_to_the_nearest_minor_unit() {
+        let amount = Money::from_minor(1000, Currency::USD);
+        assert_eq!(amount.exchange(0.92345, Currency::EUR), Money::from_minor(923, Currency::EUR));
+        assert_eq!(amount.exchange(0.92355, Currency::EUR), Money::from_minor(924, Currency::EUR));
+        assert_eq!(amount.exchange(151.256, Currency::JPY), Money::from_minor(1513, Currency::JPY));
+        assert_eq!(
+            Money::from_minor(1513, Currency::JPY).exchange(0.0066, Currency::USD),
+            Money::from_minor(999, Currency::USD)
+        );
+    }
+
+    #[test]
+    fn display_groups_thousands() {
+        assert_eq!(Money::from_minor(0, Currency::USD).to_string(), "$0.00");
+        assert_eq!(Money::from_minor(99_999, Currency::USD).to_string(), "$999.99");
+        assert_eq!(Money::from_minor(100_000, Currency::USD).to_string(), "$1,000.00");
+        assert_eq!(Money::from_minor(123_456_789, Currency::EUR).to_string(), "€1,234,567.89");
+        assert_eq!(Money::from_minor(1_234_567, Currency::JPY).to_string(), "¥1,234,567");
+        assert_eq!(Money::from_minor(-5, Currency::GBP).to_string(), "-£0.05");
+        assert_eq!(Money::from_minor(-123_456, Currency::CHF).to_string(), "-CHF 1,234.56");
+    }
+
+    #[test]
+    fn input_string_round_trips() {
+        for minor in [0, 5, -5, 123_456, -100_000_000] {
+            let amount = Money::from_minor(minor, Currency::USD);
+            assert_eq!(usd(&amount.to_input_string()), Ok(amount));
+        }
+        assert_eq!(Money::from_minor(-123_456, Currency::USD).to_input_string(), "-1234.56");
+    }
+}
@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A message for the notification centre, such as a budget passing one of
+/// its alert thresholds.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Notification {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub created_at: NaiveDateTime,
+    pub read: bool,
+}
+
+impl Notification {
+    pub fn new(title: String, body: String, created_at: NaiveDateTime) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            title,
+            body,
+            created_at,
+            read: false,
+        }
+    }
+}
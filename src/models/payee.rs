@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+use super::Transaction;
+
+/// A merchant or person money is paid to or received from. Bank
+/// descriptions vary ("AMZN MKTP US*2K3", "Amazon.com"), so a payee lists
+/// alias patterns; a description containing the name or any alias,
+/// ignoring case, belongs to the payee.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Payee {
+    pub id: String,
+    pub name: String,
+    pub aliases: Vec<String>,
+    /// Given to the payee's transactions that no category rule matches.
+    pub category_id: Option<String>,
+}
+
+impl Payee {
+    pub fn new(name: String, aliases: Vec<String>, category_id: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            aliases,
+            category_id,
+        }
+    }
+
+    /// The length of the longest of the name and aliases found in
+    /// `description`, which must already be lowercase.
+    fn match_len(&self, description: &str) -> Option<usize> {
+        std::iter::once(&self.name)
+            .chain(&self.aliases)
+            .map(|pattern| pattern.trim().to_lowercase())
+            .filter(|pattern| !pattern.is_empty() && description.contains(pattern.as_str()))
+            .map(|pattern| pattern.len())
+            .max()
+    }
+}
+
+impl fmt::Display for Payee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// The payee a raw description belongs to. When several match, the one
+/// with the longest matching pattern wins, so "AMZN PRIME" can be told
+/// apart from "AMZN".
+pub fn find<'a>(payees: &'a [Payee], description: &str) -> Option<&'a Payee> {
+    let description = description.to_lowercase();
+    payees
+        .iter()
+        .filter_map(|payee| payee.match_len(&description).map(|len| (payee, len)))
+        .max_by_key(|(_, len)| *len)
+        .map(|(payee, _)| payee)
+}
+
+/// Links each transaction without a payee to the payee its description
+/// belongs to. Transfers are left alone.
+pub fn assign(payees: &[Payee], transactions: &mut [Transaction]) {
+    for transaction in transactions.iter_mut().filter(|t| t.payee_id.is_none() && !t.is_transfer()) {
+        transaction.payee_id = find(payees, &transaction.description).map(|payee| payee.id.clone());
+    }
+}
+
+/// Gives uncategorised transactions their payee's default category.
+pub fn apply_default_categories(payees: &[Payee], transactions: &mut [Transaction]) {
+    for transaction in transactions.iter_mut().filter(|t| t.category_id.is_none() && !t.is_split()) {
+        transaction.category_id = transaction.payee_id.as_ref()
+            .and_then(|id| payees.iter().find(|payee| &payee.id == id))
+            .and_then(|payee| payee.category_id.clone());
+    }
+}
+
+/// The payee's name, or `description` when the transaction has none.
+pub fn display_name<'a>(payees: &'a [Payee], transaction: &'a Transaction) -> &'a str {
+    transaction.payee_id.as_ref()
+        .and_then(|id| payees.iter().find(|payee| &payee.id == id))
+        .map_or(transaction.description.as_str(), |payee| payee.name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense, money};
+    use crate::models::TransactionType;
+
+    fn payee(name: &str, aliases: &[&str], category_id: Option<&str>) -> Payee {
+        Payee::new(name.to_string(), aliases.iter().map(|a| a.to_string()).collect(), category_id.map(str::to_string))
+    }
+
+    fn spend(description: &str) -> Transaction {
+        Transaction { description: description.to_string(), ..expense(money(1_000), date(2026, 3, 1)) }
+    }
+
+    #[test]
+    fn the_longest_matching_pattern_wins() {
+        let payees = [
+            payee("Amazon", &["AMZN", "amazon.com"], Some("shopping")),
+            payee("Prime Video", &["amzn prime"], Some("subscriptions")),
+            payee("Blank", &["  "], None),
+        ];
+
+        assert_eq!(find(&payees, "AMZN MKTP US*2K3").map(|p| p.name.as_str()), Some("Amazon"));
+        assert_eq!(find(&payees, "Amzn Prime*1A2B").map(|p| p.name.as_str()), Some("Prime Video"));
+        assert_eq!(find(&payees, "Corner shop"), None);
+    }
+
+    #[test]
+    fn assigning_payees_fills_in_their_default_category() {
+        let payees = [payee("Amazon", &["AMZN"], Some("shopping")), payee("Tesco", &[], None)];
+        let mut transactions = [
+            spend("AMZN MKTP US"),
+            Transaction { category_id: Some("gifts".to_string()), ..spend("AMZN MKTP US") },
+            spend("TESCO STORES"),
+            Transaction { transaction_type: TransactionType::Transfer, ..spend("AMZN") },
+        ];
+        assign(&payees, &mut transactions);
+        apply_default_categories(&payees, &mut transactions);
+
+        let assigned: Vec<_> = transactions.iter()
+            .map(|t| (display_name(&payees, t), t.category_id.as_deref()))
+            .collect();
+        assert_eq!(assigned, vec![
+            ("Amazon", Some("shopping")),
+            ("Amazon", Some("gifts")),
+            ("Tesco", None),
+            ("AMZN", None),
+        ]);
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::utils::{month_end, month_start};
+
+/// A calendar period that a date range can be snapped to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Period {
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    pub const ALL: [Period; 3] = [Period::Month, Period::Quarter, Period::Year];
+
+    fn months(&self) -> u32 {
+        match self {
+            Period::Month => 1,
+            Period::Quarter => 3,
+            Period::Year => 12,
+        }
+    }
+
+    /// The period of this kind that contains `date`.
+    pub fn containing(&self, date: NaiveDate) -> DateRange {
+        let first_month = match self {
+            Period::Month => date.month(),
+            Period::Quarter => (date.month() - 1) / 3 * 3 + 1,
+            Period::Year => 1,
+        };
+        let from = NaiveDate::from_ymd_opt(date.year(), first_month, 1).unwrap_or(date);
+
+        DateRange::new(from, month_end(from + Months::new(self.months() - 1)))
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Month => write!(f, "Month"),
+            Period::Quarter => write!(f, "Quarter"),
+            Period::Year => write!(f, "Year"),
+        }
+    }
+}
+
+/// An inclusive range of dates.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Self {
+        Self { from, to }
+    }
+
+    /// The `count` whole months ending with the month of `date`.
+    pub fn months_ending(date: NaiveDate, count: u32) -> Self {
+        let from = month_start(date) - Months::new(count.max(1) - 1);
+        Self::new(from, month_end(date))
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from <= date && date <= self.to
+    }
+
+    pub fn days(&self) -> i64 {
+        (self.to - self.from).num_days() + 1
+    }
+
+    /// The number of months when the range runs from the first of a month
+    /// to the last of a month.
+    fn whole_months(&self) -> Option<u32> {
+        if self.from != month_start(self.from) || self.to != month_end(self.to) || self.to < self.from {
+            return None;
+        }
+
+        let months = (self.to.year() - self.from.year()) * 12 + self.to.month() as i32 - self.from.month() as i32 + 1;
+        Some(months as u32)
+    }
+
+    /// The first day of each month the range touches, in order.
+    pub fn months(&self) -> Vec<NaiveDate> {
+        let mut months = Vec::new();
+        let mut month = month_start(self.from);
+        while month <= self.to {
+            months.push(month);
+            month = month + Months::new(1);
+        }
+        months
+    }
+
+    /// The range of the same length just before this one. Ranges of whole
+    /// months step back by months, so the quarter before Q2 is Q1 however
+    /// many days each has.
+    pub fn previous(&self) -> Self {
+        match self.whole_months() {
+            Some(months) => {
+                let from = self.from - Months::new(months);
+                Self::new(from, self.from - Duration::days(1))
+            }
+            None => {
+                let length = Duration::days(self.days());
+                Self::new(self.from - length, self.to - length)
+            }
+        }
+    }
+
+    /// The range of the same length just after this one.
+    pub fn next(&self) -> Self {
+        match self.whole_months() {
+            Some(months) => {
+                let from = self.to + Duration::days(1);
+                Self::new(from, month_end(from + Months::new(months - 1)))
+            }
+            None => {
+                let length = Duration::days(self.days());
+                Self::new(self.from + length, self.to + length)
+            }
+        }
+    }
+
+    /// A short name, e.g. "March 2026", "Q1 2026", "2026" or the dates.
+    pub fn label(&self) -> String {
+        if *self == Period::Month.containing(self.from) {
+            self.from.format("%B %Y").to_string()
+        } else if *self == Period::Quarter.containing(self.from) {
+            format!("Q{} {}", (self.from.month() - 1) / 3 + 1, self.from.year())
+        } else if *self == Period::Year.containing(self.from) {
+            self.from.year().to_string()
+        } else {
+            format!("{} to {}", self.from.format("%Y-%m-%d"), self.to.format("%Y-%m-%d"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::date;
+
+    #[test]
+    fn whole_months_step_by_months() {
+        let q2 = Period::Quarter.containing(date(2026, 5, 17));
+        assert_eq!(q2, DateRange::new(date(2026, 4, 1), date(2026, 6, 30)));
+        assert_eq!(q2.previous(), DateRange::new(date(2026, 1, 1), date(2026, 3, 31)));
+        assert_eq!(q2.next(), DateRange::new(date(2026, 7, 1), date(2026, 9, 30)));
+        assert_eq!(Period::Month.containing(date(2026, 3, 9)).previous(), DateRange::new(date(2026, 2, 1), date(2026, 2, 28)));
+        assert_eq!(DateRange::months_ending(date(2026, 2, 14), 3), DateRange::new(date(2025, 12, 1), date(2026, 2, 28)));
+
+        let labels: Vec<String> = [q2, q2.previous().previous(), Period::Year.containing(date(2026, 1, 1))]
+            .iter()
+            .map(DateRange::label)
+            .collect();
+        assert_eq!(labels, vec!["Q2 2026", "Q4 2025", "2026"]);
+    }
+
+    #[test]
+    fn other_ranges_step_by_their_length_in_days() {
+        let range = DateRange::new(date(2026, 3, 10), date(2026, 3, 16));
+        assert_eq!(range.days(), 7);
+        assert_eq!(range.previous(), DateRange::new(date(2026, 3, 3), date(2026, 3, 9)));
+        assert_eq!(range.next(), DateRange::new(date(2026, 3, 17), date(2026, 3, 23)));
+        assert_eq!(range.label(), "2026-03-10 to 2026-03-16");
+        assert_eq!(DateRange::new(date(2026, 1, 20), date(2026, 3, 5)).months(), vec![date(2026, 1, 1), date(2026, 2, 1), date(2026, 3, 1)]);
+    }
+}
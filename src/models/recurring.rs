@@ -0,0 +1,337 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::{Money, Transaction, TransactionType};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    pub const ALL: [Frequency; 4] = [
+        Frequency::Daily,
+        Frequency::Weekly,
+        Frequency::Monthly,
+        Frequency::Yearly,
+    ];
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Frequency::Daily => write!(f, "Daily"),
+            Frequency::Weekly => write!(f, "Weekly"),
+            Frequency::Monthly => write!(f, "Monthly"),
+            Frequency::Yearly => write!(f, "Yearly"),
+        }
+    }
+}
+
+impl FromStr for Frequency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Frequency::ALL
+            .into_iter()
+            .find(|f| f.to_string() == s)
+            .ok_or_else(|| format!("Unknown frequency: {}", s))
+    }
+}
+
+/// A weekday within the month, such as the second Tuesday. `week` is 1 to
+/// 4, or -1 for the last one.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NthWeekday {
+    pub week: i8,
+    pub weekday: Weekday,
+}
+
+impl NthWeekday {
+    fn in_month(&self, month_start: NaiveDate) -> Option<NaiveDate> {
+        let days_from = |from: Weekday, to: Weekday| {
+            (7 + to.num_days_from_monday() as i64 - from.num_days_from_monday() as i64) % 7
+        };
+
+        if self.week < 0 {
+            let month_end = month_start.checked_add_months(Months::new(1))?.pred_opt()?;
+            return Some(month_end - Duration::days(days_from(self.weekday, month_end.weekday())));
+        }
+
+        let first = month_start + Duration::days(days_from(month_start.weekday(), self.weekday));
+        Some(first + Duration::weeks(self.week as i64 - 1))
+    }
+}
+
+impl fmt::Display for NthWeekday {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let week = match self.week {
+            1 => "first",
+            2 => "second",
+            3 => "third",
+            4 => "fourth",
+            _ => "last",
+        };
+        write!(f, "{} {}", week, self.weekday)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RecurrenceEnd {
+    Never,
+    OnDate(NaiveDate),
+    AfterCount(u32),
+}
+
+/// A transaction that repeats on a schedule. Occurrences up to today are
+/// written to `transactions` by `db::operations::generate_due_transactions`;
+/// `last_generated` records how far that has got so none is written twice.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecurringRule {
+    pub id: String,
+    pub description: String,
+    pub amount: Money,
+    pub transaction_type: TransactionType,
+    pub category_id: Option<String>,
+    pub account_id: Option<String>,
+    pub frequency: Frequency,
+    /// Repeat every `interval` days, weeks, months or years.
+    pub interval: u32,
+    /// For monthly rules, a weekday of the month instead of the start date's
+    /// day of the month.
+    pub nth_weekday: Option<NthWeekday>,
+    pub start_date: NaiveDate,
+    pub end: RecurrenceEnd,
+    pub last_generated: Option<NaiveDate>,
+}
+
+impl RecurringRule {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        description: String,
+        amount: Money,
+        transaction_type: TransactionType,
+        category_id: Option<String>,
+        account_id: Option<String>,
+        frequency: Frequency,
+        interval: u32,
+        nth_weekday: Option<NthWeekday>,
+        start_date: NaiveDate,
+        end: RecurrenceEnd,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            description,
+            amount,
+            transaction_type,
+            category_id,
+            account_id,
+            frequency,
+            interval,
+            nth_weekday,
+            start_date,
+            end,
+            last_generated: None,
+        }
+    }
+
+    /// The `index`th scheduled date, counted from the start date. Each date
+    /// is computed from the start rather than the previous occurrence, so a
+    /// rule starting on the 31st falls on the last day of shorter months
+    /// without drifting.
+    fn scheduled(&self, index: u32) -> Option<NaiveDate> {
+        let steps = index.checked_mul(self.interval.max(1))?;
+
+        match self.frequency {
+            Frequency::Daily => self.start_date.checked_add_signed(Duration::days(steps as i64)),
+            Frequency::Weekly => self.start_date.checked_add_signed(Duration::weeks(steps as i64)),
+            Frequency::Monthly => match self.nth_weekday {
+                Some(nth) => {
+                    let month_start = self.start_date.with_day(1)?.checked_add_months(Months::new(steps))?;
+                    nth.in_month(month_start)
+                }
+                None => self.start_date.checked_add_months(Months::new(steps)),
+            },
+            Frequency::Yearly => self.start_date.checked_add_months(Months::new(steps.checked_mul(12)?)),
+        }
+    }
+
+    /// Every occurrence of the rule in order, stopping at its end.
+    pub fn occurrences(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        let end_date = match self.end {
+            RecurrenceEnd::OnDate(date) => Some(date),
+            _ => None,
+        };
+        let count = match self.end {
+            RecurrenceEnd::AfterCount(count) => count as usize,
+            _ => usize::MAX,
+        };
+
+        (0..)
+            .map_while(|index| self.scheduled(index))
+            // A weekday-of-month rule can fall before the start date in the
+            // first month
+            .filter(|date| *date >= self.start_date)
+            .take_while(move |date| end_date.is_none_or(|end| *date <= end))
+            .take(count)
+    }
+
+    /// Occurrences not yet generated, up to and including `today`.
+    pub fn due(&self, today: NaiveDate) -> Vec<NaiveDate> {
+        self.occurrences()
+            .skip_while(|date| self.last_generated.is_some_and(|last| *date <= last))
+            .take_while(|date| *date <= today)
+            .collect()
+    }
+
+    /// The first occurrence after `today`, if the rule has not ended.
+    pub fn next_after(&self, today: NaiveDate) -> Option<NaiveDate> {
+        self.occurrences().find(|date| *date > today)
+    }
+
+    /// Occurrences after `today` up to and including `until`.
+    pub fn upcoming(&self, today: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+        self.occurrences()
+            .skip_while(|date| *date <= today)
+            .take_while(|date| *date <= until)
+            .collect()
+    }
+
+    pub fn to_transaction(&self, date: NaiveDate) -> Transaction {
+        Transaction::new(
+            self.description.clone(),
+            self.amount,
+            date,
+            self.transaction_type,
+            self.category_id.clone(),
+            self.account_id.clone(),
+        )
+    }
+
+    /// A short description of the schedule, e.g. "Every 2 weeks".
+    pub fn schedule_label(&self) -> String {
+        let unit = match self.frequency {
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+            Frequency::Yearly => "year",
+        };
+        let every = if self.interval > 1 {
+            format!("Every {} {}s", self.interval, unit)
+        } else {
+            format!("Every {}", unit)
+        };
+
+        match (self.frequency, self.nth_weekday) {
+            (Frequency::Monthly, Some(nth)) => format!("{} on the {}", every, nth),
+            _ => every,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, money};
+
+    fn rule(frequency: Frequency, interval: u32, start_date: NaiveDate, end: RecurrenceEnd) -> RecurringRule {
+        RecurringRule::new(
+            "Test".to_string(),
+            money(1_000),
+            TransactionType::Expense,
+            None,
+            None,
+            frequency,
+            interval,
+            None,
+            start_date,
+            end,
+        )
+    }
+
+    fn first(rule: &RecurringRule, count: usize) -> Vec<NaiveDate> {
+        rule.occurrences().take(count).collect()
+    }
+
+    #[test]
+    fn monthly_rules_fall_on_the_last_day_of_short_months() {
+        let rule = rule(Frequency::Monthly, 1, date(2026, 1, 31), RecurrenceEnd::Never);
+        assert_eq!(first(&rule, 4), vec![
+            date(2026, 1, 31),
+            date(2026, 2, 28),
+            date(2026, 3, 31),
+            date(2026, 4, 30),
+        ]);
+    }
+
+    #[test]
+    fn yearly_rules_from_a_leap_day_keep_to_it() {
+        let rule = rule(Frequency::Yearly, 1, date(2028, 2, 29), RecurrenceEnd::Never);
+        assert_eq!(first(&rule, 5), vec![
+            date(2028, 2, 29),
+            date(2029, 2, 28),
+            date(2030, 2, 28),
+            date(2031, 2, 28),
+            date(2032, 2, 29),
+        ]);
+    }
+
+    #[test]
+    fn intervals_skip_periods() {
+        let fortnightly = rule(Frequency::Weekly, 2, date(2026, 1, 2), RecurrenceEnd::Never);
+        assert_eq!(first(&fortnightly, 3), vec![date(2026, 1, 2), date(2026, 1, 16), date(2026, 1, 30)]);
+
+        let every_third_day = rule(Frequency::Daily, 3, date(2026, 2, 26), RecurrenceEnd::Never);
+        assert_eq!(first(&every_third_day, 3), vec![date(2026, 2, 26), date(2026, 3, 1), date(2026, 3, 4)]);
+
+        let quarterly = rule(Frequency::Monthly, 3, date(2026, 11, 30), RecurrenceEnd::Never);
+        assert_eq!(first(&quarterly, 3), vec![date(2026, 11, 30), date(2027, 2, 28), date(2027, 5, 30)]);
+    }
+
+    #[test]
+    fn weekday_of_month_rules_start_no_earlier_than_the_start_date() {
+        // The second Tuesday of January 2026 is the 13th, before the start
+        let mut second_tuesday = rule(Frequency::Monthly, 1, date(2026, 1, 20), RecurrenceEnd::Never);
+        second_tuesday.nth_weekday = Some(NthWeekday { week: 2, weekday: Weekday::Tue });
+        assert_eq!(first(&second_tuesday, 2), vec![date(2026, 2, 10), date(2026, 3, 10)]);
+
+        let mut last_friday = rule(Frequency::Monthly, 1, date(2026, 1, 1), RecurrenceEnd::Never);
+        last_friday.nth_weekday = Some(NthWeekday { week: -1, weekday: Weekday::Fri });
+        assert_eq!(first(&last_friday, 3), vec![date(2026, 1, 30), date(2026, 2, 27), date(2026, 3, 27)]);
+    }
+
+    #[test]
+    fn rules_stop_at_their_end() {
+        let on_date = rule(Frequency::Weekly, 1, date(2026, 1, 1), RecurrenceEnd::OnDate(date(2026, 1, 15)));
+        assert_eq!(on_date.occurrences().collect::<Vec<_>>(), vec![
+            date(2026, 1, 1),
+            date(2026, 1, 8),
+            date(2026, 1, 15),
+        ]);
+        assert_eq!(on_date.next_after(date(2026, 1, 15)), None);
+
+        let mut after_count = rule(Frequency::Monthly, 1, date(2026, 1, 20), RecurrenceEnd::AfterCount(2));
+        after_count.nth_weekday = Some(NthWeekday { week: 1, weekday: Weekday::Mon });
+        // The January occurrence falls before the start and does not count
+        assert_eq!(after_count.occurrences().collect::<Vec<_>>(), vec![date(2026, 2, 2), date(2026, 3, 2)]);
+    }
+
+    #[test]
+    fn due_catches_up_on_missed_occurrences_once() {
+        let mut rule = rule(Frequency::Monthly, 1, date(2026, 1, 15), RecurrenceEnd::AfterCount(4));
+        assert!(rule.due(date(2026, 1, 14)).is_empty());
+        assert_eq!(rule.due(date(2026, 3, 20)), vec![date(2026, 1, 15), date(2026, 2, 15), date(2026, 3, 15)]);
+
+        rule.last_generated = Some(date(2026, 3, 15));
+        assert!(rule.due(date(2026, 4, 14)).is_empty());
+        assert_eq!(rule.due(date(2026, 12, 31)), vec![date(2026, 4, 15)]);
+        assert_eq!(rule.next_after(date(2026, 3, 20)), Some(date(2026, 4, 15)));
+        assert_eq!(rule.upcoming(date(2026, 1, 1), date(2026, 2, 15)), vec![date(2026, 1, 15), date(2026, 2, 15)]);
+    }
+}
@@ -0,0 +1,22 @@
+use chrono::NaiveDate;
+
+use super::{Currency, Money, Transaction, TransactionType};
+
+pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+/// `minor` units of the default currency.
+pub fn money(minor: i64) -> Money {
+    Money::from_minor(minor, Currency::default())
+}
+
+/// An expense with no category, account or payee.
+pub fn expense(amount: Money, date: NaiveDate) -> Transaction {
+    Transaction::new("Test".to_string(), amount, date, TransactionType::Expense, None, None)
+}
+
+/// Income with no category, account or payee.
+pub fn income(amount: Money, date: NaiveDate) -> Transaction {
+    Transaction::new("Test".to_string(), amount, date, TransactionType::Income, None, None)
+}
@@ -1,26 +1,66 @@
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use super::Money;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum TransactionType {
     Income,
     Expense,
+    Transfer,
+}
+
+impl fmt::Display for TransactionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionType::Income => write!(f, "Income"),
+            TransactionType::Expense => write!(f, "Expense"),
+            TransactionType::Transfer => write!(f, "Transfer"),
+        }
+    }
+}
+
+/// Part of a transaction's amount filed under its own category, such as
+/// the household items on a supermarket receipt.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Split {
+    pub category_id: Option<String>,
+    pub amount: Money,
+    pub memo: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
     pub id: Uuid,
     pub description: String,
-    pub amount: f64,
+    pub amount: Money,
     pub date: NaiveDate,
     pub transaction_type: TransactionType,
-    pub category_id: Option<Uuid>,
+    pub category_id: Option<String>,
+    pub account_id: Option<String>,
+    /// The merchant the raw description was recognised as, if any.
+    #[serde(default)]
+    pub payee_id: Option<String>,
+    /// Shared by the two legs of a transfer.
+    pub transfer_id: Option<String>,
+    /// The bank's own id for an imported transaction, such as an OFX
+    /// FITID. Importing the same id into the same account again is skipped.
+    pub external_id: Option<String>,
+    /// How the amount divides between categories. Empty unless the
+    /// transaction is split, in which case `category_id` is unset.
+    #[serde(default)]
+    pub splits: Vec<Split>,
+    /// Names of the tags on the transaction, in alphabetical order.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl Transaction {
-    pub fn new(description: String, amount: f64, date: NaiveDate, 
-               transaction_type: TransactionType, category_id: Option<Uuid>) -> Self {
+    pub fn new(description: String, amount: Money, date: NaiveDate,
+               transaction_type: TransactionType, category_id: Option<String>,
+               account_id: Option<String>) -> Self {
         Self {
             id: Uuid::new_v4(),
             description,
@@ -28,6 +68,66 @@ impl Transaction {
             date,
             transaction_type,
             category_id,
+            account_id,
+            payee_id: None,
+            transfer_id: None,
+            external_id: None,
+            splits: Vec::new(),
+            tags: Vec::new(),
         }
     }
+
+    pub fn is_expense(&self) -> bool {
+        self.transaction_type == TransactionType::Expense
+    }
+
+    pub fn is_transfer(&self) -> bool {
+        self.transaction_type == TransactionType::Transfer
+    }
+
+    pub fn has_tag(&self, name: &str) -> bool {
+        self.tags.iter().any(|tag| tag == name)
+    }
+
+    pub fn is_split(&self) -> bool {
+        !self.splits.is_empty()
+    }
+
+    /// The amount filed under each category: the split lines, or the whole
+    /// amount under `category_id` when the transaction is not split.
+    pub fn category_amounts(&self) -> Vec<(Option<&str>, Money)> {
+        if self.is_split() {
+            self.splits.iter().map(|s| (s.category_id.as_deref(), s.amount)).collect()
+        } else {
+            vec![(self.category_id.as_deref(), self.amount)]
+        }
+    }
+
+    /// Checks that split lines are positive amounts in the transaction's
+    /// currency that add up to its amount exactly.
+    pub fn validate_splits(&self) -> Result<(), String> {
+        if !self.is_split() {
+            return Ok(());
+        }
+        if self.is_transfer() {
+            return Err("Transfers cannot be split".to_string());
+        }
+
+        let mut total = Money::zero(self.amount.currency());
+        for split in &self.splits {
+            if split.amount.currency() != self.amount.currency() {
+                return Err("Split amounts must be in the transaction's currency".to_string());
+            }
+            if split.amount.is_negative() || split.amount.is_zero() {
+                return Err("Split amounts must be greater than zero".to_string());
+            }
+            total += split.amount;
+        }
+
+        if total != self.amount {
+            return Err(format!("Splits add up to {}, not the transaction's {}", total, self.amount));
+        }
+
+        Ok(())
+    }
 }
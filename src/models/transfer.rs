@@ -0,0 +1,97 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Money, Transaction, TransactionType};
+
+/// Money moved between two accounts. It is stored as two `Transfer`
+/// transactions sharing `transfer_id`: a negative leg on the source account
+/// and a positive leg on the destination. Neither leg is income or spending.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Transfer {
+    pub id: String,
+    pub description: String,
+    pub amount: Money,
+    pub date: NaiveDate,
+    pub from_account_id: String,
+    pub to_account_id: String,
+}
+
+impl Transfer {
+    pub fn new(
+        description: String,
+        amount: Money,
+        date: NaiveDate,
+        from_account_id: String,
+        to_account_id: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            description,
+            amount,
+            date,
+            from_account_id,
+            to_account_id,
+        }
+    }
+
+    /// The outgoing and incoming transactions that make up this transfer.
+    pub fn legs(&self) -> [Transaction; 2] {
+        let leg = |amount: Money, account_id: &str| {
+            let mut transaction = Transaction::new(
+                self.description.clone(),
+                amount,
+                self.date,
+                TransactionType::Transfer,
+                None,
+                Some(account_id.to_string()),
+            );
+            transaction.transfer_id = Some(self.id.clone());
+            transaction
+        };
+
+        [
+            leg(-self.amount, &self.from_account_id),
+            leg(self.amount, &self.to_account_id),
+        ]
+    }
+
+    /// Rebuilds the transfer `transfer_id` from its legs in `transactions`.
+    pub fn find(transactions: &[Transaction], transfer_id: &str) -> Option<Self> {
+        let mut legs = transactions
+            .iter()
+            .filter(|t| t.transfer_id.as_deref() == Some(transfer_id));
+        let (first, second) = (legs.next()?, legs.next()?);
+        let (from, to) = if first.amount.is_negative() { (first, second) } else { (second, first) };
+
+        Some(Self {
+            id: transfer_id.to_string(),
+            description: to.description.clone(),
+            amount: to.amount,
+            date: to.date,
+            from_account_id: from.account_id.clone()?,
+            to_account_id: to.account_id.clone()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, money};
+
+    #[test]
+    fn legs_move_the_amount_and_rebuild_the_transfer() {
+        let transfer = Transfer::new("Saving".to_string(), money(25_000), date(2026, 3, 1), "checking".to_string(), "savings".to_string());
+        let [out, into] = transfer.legs();
+
+        assert_eq!((out.amount, out.account_id.as_deref()), (money(-25_000), Some("checking")));
+        assert_eq!((into.amount, into.account_id.as_deref()), (money(25_000), Some("savings")));
+        assert!(out.is_transfer() && into.is_transfer());
+
+        // Either order of the legs gives the same transfer back
+        assert_eq!(Transfer::find(&[into.clone(), out.clone()], &transfer.id), Some(transfer.clone()));
+        assert_eq!(Transfer::find(&[out, into.clone()], &transfer.id), Some(transfer.clone()));
+        assert_eq!(Transfer::find(&[into], &transfer.id), None);
+    }
+}
@@ -0,0 +1,470 @@
+pub mod pdf;
+pub mod summary;
+
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::db::operations;
+use crate::models::category;
+use crate::models::{Account, Budget, BudgetPeriod, Category, Currency, CurrencyConverter, DateRange, Money, Transaction, TransactionType};
+use crate::ui::components::charts::{BudgetProgressChart, ExpenseByCategoryChart, IncomeVsExpenseChart};
+use crate::utils::{calculate_percentage, format_currency, format_date, month_end, month_start, today};
+use pdf::{Align, Column, PageWriter, CONTENT_WIDTH};
+use summary::{GroupedReport, Grouping, ReportFilter};
+
+/// Chart size in the PDF, in pixels at 96 to the inch.
+const CHART_WIDTH: u32 = 680;
+const CHART_HEIGHT: u32 = 320;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReportError {
+    #[error("Failed to write file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to write PDF: {0}")]
+    Pdf(#[from] printpdf::Error),
+    #[error("Failed to draw chart: {0}")]
+    Chart(String),
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+}
+
+/// A budget running during the report's month.
+#[derive(Debug, Clone)]
+pub struct BudgetLine {
+    pub name: String,
+    /// What the period had available, including anything carried in.
+    pub budgeted: Money,
+    pub spent: Money,
+}
+
+/// One month's figures, with amounts in the base currency unless noted.
+pub struct MonthlyReport {
+    /// The first day of the month.
+    pub month: NaiveDate,
+    pub base: Currency,
+    pub income: Money,
+    pub expenses: Money,
+    /// Transactions left out of the totals for want of an exchange rate.
+    pub unconverted: usize,
+    /// Expenses by category name, largest first.
+    pub categories: Vec<(String, Money)>,
+    /// Expenses by tag, largest first, with untagged ones last. A
+    /// transaction with several tags counts towards each. Empty when
+    /// nothing spent this month is tagged.
+    pub tags: Vec<(String, Money)>,
+    /// In each budget's own currency.
+    pub budgets: Vec<BudgetLine>,
+    /// Balances at the end of the month, in each account's currency.
+    /// `None` when a transaction has no exchange rate to the account's
+    /// currency.
+    pub accounts: Vec<(String, Option<Money>)>,
+    /// The month's transactions oldest first.
+    pub transactions: Vec<Transaction>,
+    account_names: HashMap<String, String>,
+    category_names: HashMap<String, String>,
+    expense_chart: ExpenseByCategoryChart,
+    trend_chart: IncomeVsExpenseChart,
+    budget_chart: BudgetProgressChart,
+}
+
+impl MonthlyReport {
+    pub fn new(
+        month: NaiveDate,
+        transactions: &[Transaction],
+        categories: &[Category],
+        accounts: &[Account],
+        budgets: &[Budget],
+        budget_periods: &[BudgetPeriod],
+        converter: &CurrencyConverter,
+    ) -> Self {
+        let (first_day, last_day) = (month_start(month), month_end(month));
+        let zero = Money::zero(converter.base());
+
+        let mut in_month: Vec<Transaction> = transactions.iter()
+            .filter(|t| t.date >= first_day && t.date <= last_day)
+            .cloned()
+            .collect();
+        in_month.sort_by_key(|t| t.date);
+
+        let mut income = zero;
+        let mut expenses = zero;
+        let mut unconverted = 0;
+        let mut by_category: HashMap<String, Money> = HashMap::new();
+        // Transfers only move money between accounts, so they are left out
+        for transaction in in_month.iter().filter(|t| !t.is_transfer()) {
+            let Some(amount) = converter.to_base(transaction.amount, transaction.date) else {
+                unconverted += 1;
+                continue;
+            };
+
+            match transaction.transaction_type {
+                TransactionType::Income => income += amount,
+                TransactionType::Expense => {
+                    expenses += amount;
+                    // Split transactions count each line under its own category
+                    for (category_id, part) in transaction.category_amounts() {
+                        let Some(part) = converter.to_base(part, transaction.date) else {
+                            continue;
+                        };
+                        let name = match category_id {
+                            Some(id) if categories.iter().any(|c| c.id == id) => category::full_name(categories, id),
+                            Some(_) => "No Category".to_string(),
+                            None => "Uncategorized".to_string(),
+                        };
+                        *by_category.entry(name).or_insert(zero) += part;
+                    }
+                }
+                TransactionType::Transfer => {}
+            }
+        }
+
+        let mut category_totals: Vec<(String, Money)> = by_category.into_iter()
+            .collect();
+        category_totals.sort_by(|a, b| b.1.minor_units().cmp(&a.1.minor_units()).then_with(|| a.0.cmp(&b.0)));
+
+        let by_tag = GroupedReport::new(
+            DateRange::new(first_day, last_day),
+            Grouping::Tag,
+            ReportFilter::default(),
+            transactions,
+            categories,
+            accounts,
+            &[],
+            converter,
+        );
+        let mut tag_totals: Vec<(String, Money)> = by_tag.expenses.lines.into_iter()
+            .filter(|line| !line.current.is_zero())
+            .map(|line| (line.label, line.current))
+            .collect();
+        // Tag labels start with '#'; untagged spending goes last, and is
+        // only worth showing next to tagged spending
+        tag_totals.sort_by_key(|(label, _)| !label.starts_with('#'));
+        if !tag_totals.iter().any(|(label, _)| label.starts_with('#')) {
+            tag_totals.clear();
+        }
+
+        // Renewing budgets count their period in effect at the month's end,
+        // with anything carried into it
+        let budget_lines = budgets.iter()
+            .filter(|b| b.is_active_between(first_day, last_day))
+            .filter_map(|b| {
+                let period = b.history(budget_periods, last_day, transactions, categories, converter).pop()?;
+                Some(BudgetLine {
+                    name: b.name.clone(),
+                    budgeted: period.available(),
+                    spent: period.spent,
+                })
+            })
+            .collect();
+
+        let up_to_month_end: Vec<Transaction> = transactions.iter()
+            .filter(|t| t.date <= last_day)
+            .cloned()
+            .collect();
+        let account_balances = accounts.iter()
+            .map(|a| (a.name.clone(), a.balance(&up_to_month_end, converter)))
+            .collect();
+
+        Self {
+            month: first_day,
+            expense_chart: ExpenseByCategoryChart::new(&in_month, categories, converter),
+            trend_chart: IncomeVsExpenseChart::new(transactions, converter, DateRange::new(first_day, last_day)),
+            budget_chart: BudgetProgressChart::new(budgets, budget_periods, transactions, categories, converter, first_day, last_day),
+            income,
+            expenses,
+            unconverted,
+            categories: category_totals,
+            tags: tag_totals,
+            budgets: budget_lines,
+            accounts: account_balances,
+            transactions: in_month,
+            account_names: accounts.iter().map(|a| (a.id.clone(), a.name.clone())).collect(),
+            category_names: categories.iter().map(|c| (c.id.clone(), category::full_name(categories, &c.id))).collect(),
+            base: converter.base(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!("Monthly Statement - {}", self.month.format("%B %Y"))
+    }
+
+    /// Writes the report as a PDF: the summary and spending breakdown, then
+    /// the trend, budgets and balances, then every transaction of the month.
+    /// Returns the number of pages.
+    pub fn write_pdf(&self, path: &Path) -> Result<usize, ReportError> {
+        let mut writer = PageWriter::new(&self.title())?;
+
+        writer.title(&self.title());
+        writer.note(&format!(
+            "Generated {}. Totals in {}; transfers between accounts are not counted.",
+            format_date(&today()),
+            self.base
+        ));
+        self.summary(&mut writer);
+
+        writer.heading("Expenses by Category");
+        writer.chart(CHART_WIDTH, CHART_HEIGHT, |root| self.expense_chart.render(root))?;
+        self.category_table(&mut writer);
+
+        if !self.tags.is_empty() {
+            writer.heading("Expenses by Tag");
+            self.tag_table(&mut writer);
+        }
+
+        // The bar charts carry their own captions
+        writer.new_page();
+        writer.chart(CHART_WIDTH, CHART_HEIGHT, |root| self.trend_chart.render(root))?;
+
+        writer.space(20.0);
+        if self.budget_chart.is_empty() {
+            writer.heading("Budget Progress");
+            writer.paragraph("No budgets ran during this month.");
+        } else {
+            writer.chart(CHART_WIDTH, CHART_HEIGHT, |root| self.budget_chart.render(root))?;
+            self.budget_table(&mut writer);
+        }
+
+        if !self.accounts.is_empty() {
+            writer.heading("Account Balances");
+            self.account_table(&mut writer);
+        }
+
+        writer.new_page();
+        writer.heading(&format!("Transactions ({})", self.transactions.len()));
+        self.transaction_table(&mut writer);
+
+        writer.save(path)
+    }
+
+    fn summary(&self, writer: &mut PageWriter) {
+        let columns = [
+            Column { x: 0.0, width: 150.0, align: Align::Left },
+            Column { x: 150.0, width: 110.0, align: Align::Right },
+        ];
+
+        writer.heading("Summary");
+        writer.row(&columns, &["Income", &format_currency(self.income)], false);
+        writer.row(&columns, &["Expenses", &format_currency(self.expenses)], false);
+        writer.rule();
+        writer.row(&columns, &["Net", &format_currency(self.income - self.expenses)], true);
+        writer.row(&columns, &["Transactions", &self.transactions.len().to_string()], false);
+        if self.unconverted > 0 {
+            writer.note(&format!(
+                "{} transaction(s) left out: no exchange rate to {}",
+                self.unconverted,
+                self.base
+            ));
+        }
+    }
+
+    fn category_table(&self, writer: &mut PageWriter) {
+        if self.categories.is_empty() {
+            return;
+        }
+
+        let columns = [
+            Column { x: 0.0, width: 250.0, align: Align::Left },
+            Column { x: 250.0, width: 110.0, align: Align::Right },
+            Column { x: 360.0, width: 70.0, align: Align::Right },
+        ];
+
+        writer.row(&columns, &["Category", "Amount", "Share"], true);
+        writer.rule();
+        for (name, amount) in &self.categories {
+            let share = calculate_percentage(
+                amount.minor_units() as f64,
+                self.expenses.minor_units() as f64,
+            );
+            writer.row(&columns, &[name, &format_currency(*amount), &format!("{:.1}%", share)], false);
+        }
+    }
+
+    fn tag_table(&self, writer: &mut PageWriter) {
+        let columns = [
+            Column { x: 0.0, width: 250.0, align: Align::Left },
+            Column { x: 250.0, width: 110.0, align: Align::Right },
+        ];
+
+        writer.row(&columns, &["Tag", "Amount"], true);
+        writer.rule();
+        for (name, amount) in &self.tags {
+            writer.row(&columns, &[name, &format_currency(*amount)], false);
+        }
+        writer.note("A transaction with several tags counts towards each of them.");
+    }
+
+    fn budget_table(&self, writer: &mut PageWriter) {
+        let columns = [
+            Column { x: 0.0, width: 170.0, align: Align::Left },
+            Column { x: 170.0, width: 90.0, align: Align::Right },
+            Column { x: 260.0, width: 90.0, align: Align::Right },
+            Column { x: 350.0, width: 90.0, align: Align::Right },
+            Column { x: 440.0, width: CONTENT_WIDTH - 440.0, align: Align::Right },
+        ];
+
+        writer.row(&columns, &["Budget", "Available", "Spent", "Remaining", "Used"], true);
+        writer.rule();
+        for line in &self.budgets {
+            let used = calculate_percentage(
+                line.spent.minor_units() as f64,
+                line.budgeted.minor_units() as f64,
+            );
+            writer.row(
+                &columns,
+                &[
+                    &line.name,
+                    &format_currency(line.budgeted),
+                    &format_currency(line.spent),
+                    &format_currency(line.budgeted - line.spent),
+                    &format!("{:.1}%", used),
+                ],
+                false,
+            );
+        }
+    }
+
+    fn account_table(&self, writer: &mut PageWriter) {
+        let columns = [
+            Column { x: 0.0, width: 250.0, align: Align::Left },
+            Column { x: 250.0, width: 110.0, align: Align::Right },
+        ];
+
+        writer.row(&columns, &["Account", &format!("Balance on {}", format_date(&month_end(self.month)))], true);
+        writer.rule();
+        for (name, balance) in &self.accounts {
+            let balance = balance.map_or_else(|| "Missing exchange rate".to_string(), format_currency);
+            writer.row(&columns, &[name, &balance], false);
+        }
+    }
+
+    fn transaction_table(&self, writer: &mut PageWriter) {
+        if self.transactions.is_empty() {
+            writer.paragraph("No transactions this month.");
+            return;
+        }
+
+        let columns = [
+            Column { x: 0.0, width: 55.0, align: Align::Left },
+            Column { x: 60.0, width: 190.0, align: Align::Left },
+            Column { x: 255.0, width: 85.0, align: Align::Left },
+            Column { x: 345.0, width: 85.0, align: Align::Left },
+            Column { x: 430.0, width: CONTENT_WIDTH - 430.0, align: Align::Right },
+        ];
+        let header = ["Date", "Description", "Category", "Account", "Amount"];
+
+        writer.row(&columns, &header, true);
+        writer.rule();
+        let lookup = |names: &HashMap<String, String>, id: Option<&str>| {
+            id.and_then(|id| names.get(id)).cloned().unwrap_or_default()
+        };
+        let row = |writer: &mut PageWriter, cells: [&str; 5]| {
+            // Repeat the header at the top of each continuation page
+            let page_before = writer.pages();
+            writer.ensure_space(9.0 * 1.6);
+            if writer.pages() != page_before {
+                writer.row(&columns, &header, true);
+                writer.rule();
+            }
+            writer.row(&columns, &cells, false);
+        };
+
+        for transaction in &self.transactions {
+            let sign = |amount: Money| match transaction.transaction_type {
+                TransactionType::Expense => -amount,
+                TransactionType::Income | TransactionType::Transfer => amount,
+            };
+            let category = if transaction.is_split() {
+                "Split".to_string()
+            } else {
+                lookup(&self.category_names, transaction.category_id.as_deref())
+            };
+
+            row(writer, [
+                &format_date(&transaction.date),
+                &transaction.description,
+                &category,
+                &lookup(&self.account_names, transaction.account_id.as_deref()),
+                &format_currency(sign(transaction.amount)),
+            ]);
+            // Each split line follows on its own indented row
+            for split in &transaction.splits {
+                row(writer, [
+                    "",
+                    &format!("    {}", split.memo),
+                    &lookup(&self.category_names, split.category_id.as_deref()),
+                    "",
+                    &format_currency(sign(split.amount)),
+                ]);
+            }
+        }
+    }
+}
+
+/// Builds the report for `month` from the database and writes it to `path`.
+pub fn export_monthly_report(conn: &Connection, path: &Path, month: NaiveDate) -> Result<usize, ReportError> {
+    let report = MonthlyReport::new(
+        month,
+        &operations::get_all_transactions(conn)?,
+        &operations::get_all_categories(conn)?,
+        &operations::get_all_accounts(conn)?,
+        &operations::get_all_budgets(conn)?,
+        &operations::get_all_budget_periods(conn)?,
+        &operations::get_currency_converter(conn)?,
+    );
+
+    report.write_pdf(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense, income, money};
+    use crate::models::AccountType;
+
+    #[test]
+    fn monthly_report_totals_the_month_and_writes_a_pdf() {
+        let food = Category::new("Food".to_string(), "#000000".to_string(), None);
+        let checking = Account::new("Checking".to_string(), AccountType::Checking, money(100_000), date(2026, 1, 1));
+        let in_checking = |transaction: Transaction| Transaction { account_id: Some(checking.id.clone()), ..transaction };
+        let transactions = vec![
+            in_checking(Transaction {
+                category_id: Some(food.id.clone()),
+                tags: vec!["weekend".to_string()],
+                ..expense(money(4_000), date(2026, 3, 14))
+            }),
+            in_checking(expense(money(1_000), date(2026, 3, 2))),
+            in_checking(income(money(250_000), date(2026, 3, 25))),
+            in_checking(expense(money(9_999), date(2026, 4, 1))),
+            Transaction { transaction_type: TransactionType::Transfer, ..in_checking(expense(money(5_000), date(2026, 3, 3))) },
+        ];
+        let groceries = Budget::new("Food".to_string(), Some(food.id.clone()), money(10_000), date(2026, 3, 1), Some(date(2026, 3, 31)), None, false);
+
+        let report = MonthlyReport::new(
+            date(2026, 3, 17),
+            &transactions,
+            std::slice::from_ref(&food),
+            std::slice::from_ref(&checking),
+            std::slice::from_ref(&groceries),
+            &[],
+            &CurrencyConverter::default(),
+        );
+
+        assert_eq!(report.month, date(2026, 3, 1));
+        assert_eq!((report.income, report.expenses), (money(250_000), money(5_000)));
+        assert_eq!(report.categories, vec![("Food".to_string(), money(4_000)), ("Uncategorized".to_string(), money(1_000))]);
+        assert_eq!(report.tags, vec![("#weekend".to_string(), money(4_000)), ("Untagged".to_string(), money(1_000))]);
+        assert_eq!((report.budgets[0].budgeted, report.budgets[0].spent), (money(10_000), money(4_000)));
+        // The transfer leg moves the balance even though it is not spending
+        assert_eq!(report.accounts, vec![("Checking".to_string(), Some(money(350_000)))]);
+        assert_eq!(report.transactions.len(), 4);
+
+        let path = std::env::temp_dir().join(format!("budget-report-test-{}.pdf", uuid::Uuid::new_v4()));
+        let pages = report.write_pdf(&path);
+        let written = std::fs::read(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(pages.unwrap() >= 1);
+        assert!(written.unwrap().starts_with(b"%PDF"));
+    }
+}
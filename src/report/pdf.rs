@@ -0,0 +1,450 @@
+use plotters::coord::Shift;
+use plotters::prelude::{DrawingArea, IntoDrawingArea};
+use plotters_backend::text_anchor::{HPos, VPos};
+use plotters_backend::{
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingBackend, DrawingErrorKind, FontStyle,
+    FontTransform,
+};
+use printpdf::path::{PaintMode, WindingOrder};
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point,
+    Polygon, Pt, Rgb, TextMatrix,
+};
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use super::ReportError;
+
+// A4 portrait, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 42.0;
+pub const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+
+/// Charts are laid out in pixels as on screen, at 96 pixels to the inch.
+const POINTS_PER_PIXEL: f32 = 0.75;
+
+const TEXT_COLOR: (u8, u8, u8) = (0, 0, 0);
+const MUTED_COLOR: (u8, u8, u8) = (110, 110, 110);
+
+fn pdf_color((r, g, b): (u8, u8, u8)) -> Color {
+    Color::Rgb(Rgb::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, None))
+}
+
+/// Approximate width of `text` set in Helvetica at `size`, in the same
+/// unit as `size`. The built-in PDF fonts carry no metrics we can query,
+/// so this uses average widths for each class of character.
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let ems: f32 = text
+        .chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '\'' | '|' | '!' => 0.25,
+            ' ' | 'f' | 't' | 'r' | 'I' | '(' | ')' | '[' | ']' | '/' | '-' => 0.32,
+            'm' | 'w' | 'M' | 'W' | '%' | '@' => 0.85,
+            c if c.is_ascii_uppercase() => 0.68,
+            _ => 0.556,
+        })
+        .sum();
+    ems * size
+}
+
+/// A plotters backend drawing into a rectangle of a PDF page, so charts
+/// render with the same code as on screen. Coordinates are pixels from the
+/// rectangle's top-left corner.
+pub struct PdfBackend<'a> {
+    layer: &'a PdfLayerReference,
+    font: &'a IndirectFontRef,
+    bold_font: &'a IndirectFontRef,
+    left: f32,
+    top: f32,
+    size: (u32, u32),
+}
+
+impl PdfBackend<'_> {
+    fn point(&self, (x, y): BackendCoord) -> Point {
+        Point {
+            x: Pt(self.left + x as f32 * POINTS_PER_PIXEL),
+            y: Pt(self.top - y as f32 * POINTS_PER_PIXEL),
+        }
+    }
+
+    /// The colour as painted on the white page, since the built-in
+    /// graphics state has no transparency.
+    fn color(color: BackendColor) -> Color {
+        let blend = |channel: u8| (channel as f64 * color.alpha + 255.0 * (1.0 - color.alpha)).round() as u8;
+        pdf_color((blend(color.rgb.0), blend(color.rgb.1), blend(color.rgb.2)))
+    }
+
+    fn stroke<S: BackendStyle>(&self, points: Vec<Point>, closed: bool, style: &S) {
+        if style.color().alpha == 0.0 {
+            return;
+        }
+
+        self.layer.set_outline_color(Self::color(style.color()));
+        self.layer.set_outline_thickness(style.stroke_width().max(1) as f32 * POINTS_PER_PIXEL);
+        self.layer.add_line(Line {
+            points: points.into_iter().map(|p| (p, false)).collect(),
+            is_closed: closed,
+        });
+    }
+
+    fn fill<S: BackendStyle>(&self, points: Vec<Point>, style: &S) {
+        if style.color().alpha == 0.0 {
+            return;
+        }
+
+        self.layer.set_fill_color(Self::color(style.color()));
+        self.layer.add_polygon(Polygon {
+            rings: vec![points.into_iter().map(|p| (p, false)).collect()],
+            mode: PaintMode::Fill,
+            winding_order: WindingOrder::NonZero,
+        });
+    }
+}
+
+impl DrawingBackend for PdfBackend<'_> {
+    type ErrorType = Infallible;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Infallible>> {
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Infallible>> {
+        Ok(())
+    }
+
+    fn draw_pixel(&mut self, point: BackendCoord, color: BackendColor) -> Result<(), DrawingErrorKind<Infallible>> {
+        self.draw_rect(point, (point.0 + 1, point.1 + 1), &color, true)
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        self.stroke(vec![self.point(from), self.point(to)], false, style);
+        Ok(())
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        let corners = vec![
+            self.point(upper_left),
+            self.point((bottom_right.0, upper_left.1)),
+            self.point(bottom_right),
+            self.point((upper_left.0, bottom_right.1)),
+        ];
+
+        if fill {
+            self.fill(corners, style);
+        } else {
+            self.stroke(corners, true, style);
+        }
+        Ok(())
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        let points: Vec<Point> = path.into_iter().map(|p| self.point(p)).collect();
+        if points.len() > 1 {
+            self.stroke(points, false, style);
+        }
+        Ok(())
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        const SEGMENTS: u32 = 36;
+        let points: Vec<Point> = (0..SEGMENTS)
+            .map(|i| {
+                let angle = i as f64 / SEGMENTS as f64 * std::f64::consts::TAU;
+                (
+                    center.0 + (radius as f64 * angle.cos()).round() as i32,
+                    center.1 + (radius as f64 * angle.sin()).round() as i32,
+                )
+            })
+            .map(|p| self.point(p))
+            .collect();
+
+        if fill {
+            self.fill(points, style);
+        } else {
+            self.stroke(points, true, style);
+        }
+        Ok(())
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        let points: Vec<Point> = vert.into_iter().map(|p| self.point(p)).collect();
+        if points.len() > 2 {
+            self.fill(points, style);
+        }
+        Ok(())
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+
+        let size = style.size() as f32;
+        let width = text_width(text, size);
+        let dx = match style.anchor().h_pos {
+            HPos::Left => 0.0,
+            HPos::Right => -width,
+            HPos::Center => -width / 2.0,
+        };
+        // From the anchor down to the baseline
+        let dy = match style.anchor().v_pos {
+            VPos::Top => 0.75 * size,
+            VPos::Center => 0.35 * size,
+            VPos::Bottom => -0.2 * size,
+        };
+
+        let transform = style.transform();
+        let (offset_x, offset_y) = transform.transform(dx.round() as i32, dy.round() as i32);
+        let origin = self.point((pos.0 + offset_x, pos.1 + offset_y));
+        // PDF angles run anticlockwise, plotters' clockwise
+        let rotation = match transform {
+            FontTransform::None => 0.0,
+            FontTransform::Rotate90 => 270.0,
+            FontTransform::Rotate180 => 180.0,
+            FontTransform::Rotate270 => 90.0,
+        };
+        let font = match style.style() {
+            FontStyle::Bold => self.bold_font,
+            _ => self.font,
+        };
+
+        self.layer.begin_text_section();
+        self.layer.set_fill_color(Self::color(style.color()));
+        self.layer.set_font(font, size * POINTS_PER_PIXEL);
+        self.layer.set_text_matrix(TextMatrix::TranslateRotate(origin.x, origin.y, rotation));
+        self.layer.write_text(text, font);
+        self.layer.end_text_section();
+        Ok(())
+    }
+
+    fn estimate_text_size<TStyle: BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &TStyle,
+    ) -> Result<(u32, u32), DrawingErrorKind<Infallible>> {
+        let size = style.size() as f32;
+        Ok((text_width(text, size).ceil() as u32, size.ceil() as u32))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A column of a table: where it starts, how wide it is and how its cells
+/// are aligned.
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    pub x: f32,
+    pub width: f32,
+    pub align: Align,
+}
+
+/// Writes a document top to bottom, starting a new page whenever the next
+/// block does not fit. Positions are in points; `x` is measured from the
+/// left margin.
+pub struct PageWriter {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    bold_font: IndirectFontRef,
+    /// Distance of the next line's top from the bottom of the page.
+    y: f32,
+    pages: usize,
+}
+
+impl PageWriter {
+    pub fn new(title: &str) -> Result<Self, ReportError> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm::from(Pt(PAGE_WIDTH)), Mm::from(Pt(PAGE_HEIGHT)), "Page 1");
+        let font = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+        let bold_font = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        let writer = Self {
+            doc,
+            layer,
+            font,
+            bold_font,
+            y: PAGE_HEIGHT - MARGIN,
+            pages: 1,
+        };
+        writer.footer();
+        Ok(writer)
+    }
+
+    pub fn new_page(&mut self) {
+        self.pages += 1;
+        let name = format!("Page {}", self.pages);
+        let (page, layer) = self.doc.add_page(Mm::from(Pt(PAGE_WIDTH)), Mm::from(Pt(PAGE_HEIGHT)), name);
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.footer();
+    }
+
+    fn footer(&self) {
+        let label = format!("Page {}", self.pages);
+        let x = PAGE_WIDTH - MARGIN - text_width(&label, 8.0);
+        self.text_at(&label, 8.0, false, MUTED_COLOR, x, MARGIN / 2.0);
+    }
+
+    pub fn pages(&self) -> usize {
+        self.pages
+    }
+
+    /// Starts a new page unless `height` more points fit on this one.
+    pub fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    pub fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn text_at(&self, text: &str, size: f32, bold: bool, color: (u8, u8, u8), x: f32, baseline: f32) {
+        let font = if bold { &self.bold_font } else { &self.font };
+        self.layer.set_fill_color(pdf_color(color));
+        self.layer.use_text(text, size, Mm::from(Pt(x)), Mm::from(Pt(baseline)), font);
+    }
+
+    fn line(&mut self, text: &str, size: f32, bold: bool, color: (u8, u8, u8)) {
+        let height = size * 1.4;
+        self.ensure_space(height);
+        self.text_at(text, size, bold, color, MARGIN, self.y - size);
+        self.y -= height;
+    }
+
+    pub fn title(&mut self, text: &str) {
+        self.line(text, 18.0, true, TEXT_COLOR);
+    }
+
+    pub fn heading(&mut self, text: &str) {
+        self.ensure_space(40.0);
+        self.space(8.0);
+        self.line(text, 13.0, true, TEXT_COLOR);
+    }
+
+    pub fn paragraph(&mut self, text: &str) {
+        self.line(text, 10.0, false, TEXT_COLOR);
+    }
+
+    pub fn note(&mut self, text: &str) {
+        self.line(text, 9.0, false, MUTED_COLOR);
+    }
+
+    /// One table row. Cells too wide for their column are cut short.
+    pub fn row(&mut self, columns: &[Column], cells: &[&str], bold: bool) {
+        const SIZE: f32 = 9.0;
+        let height = SIZE * 1.6;
+        self.ensure_space(height);
+
+        for (column, cell) in columns.iter().zip(cells) {
+            let cell = fit(cell, column.width, SIZE);
+            let x = match column.align {
+                Align::Left => MARGIN + column.x,
+                Align::Right => MARGIN + column.x + column.width - text_width(&cell, SIZE),
+            };
+            self.text_at(&cell, SIZE, bold, TEXT_COLOR, x, self.y - SIZE);
+        }
+        self.y -= height;
+    }
+
+    /// A horizontal rule across the content width.
+    pub fn rule(&mut self) {
+        self.layer.set_outline_color(pdf_color(MUTED_COLOR));
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point { x: Pt(MARGIN), y: Pt(self.y) }, false),
+                (Point { x: Pt(PAGE_WIDTH - MARGIN), y: Pt(self.y) }, false),
+            ],
+            is_closed: false,
+        });
+        self.y -= 4.0;
+    }
+
+    /// Draws a chart `width` by `height` pixels at the left margin.
+    pub fn chart<F, E>(&mut self, width: u32, height: u32, draw: F) -> Result<(), ReportError>
+    where
+        F: FnOnce(&DrawingArea<PdfBackend<'_>, Shift>) -> Result<(), E>,
+        E: std::fmt::Display,
+    {
+        let points_high = height as f32 * POINTS_PER_PIXEL;
+        self.ensure_space(points_high);
+
+        let backend = PdfBackend {
+            layer: &self.layer,
+            font: &self.font,
+            bold_font: &self.bold_font,
+            left: MARGIN,
+            top: self.y,
+            size: (width, height),
+        };
+        draw(&backend.into_drawing_area()).map_err(|e| ReportError::Chart(e.to_string()))?;
+
+        self.y -= points_high;
+        Ok(())
+    }
+
+    /// Saves the document, returning how many pages it has.
+    pub fn save(self, path: &Path) -> Result<usize, ReportError> {
+        let pages = self.pages;
+        self.doc.save(&mut BufWriter::new(File::create(path)?))?;
+        Ok(pages)
+    }
+}
+
+/// `text` shortened with an ellipsis to fit `width` at `size`.
+fn fit(text: &str, width: f32, size: f32) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+
+    let mut fitted: String = text.to_string();
+    while !fitted.is_empty() && text_width(&fitted, size) + text_width("...", size) > width {
+        fitted.pop();
+    }
+    fitted.truncate(fitted.trim_end().len());
+    fitted.push_str("...");
+    fitted
+}
@@ -0,0 +1,395 @@
+use chrono::{Months, NaiveDate};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::models::category;
+use crate::models::{Account, Category, Currency, CurrencyConverter, DateRange, Money, Payee, Transaction, TransactionType};
+use crate::utils::{calculate_percentage, month_start};
+
+/// What the lines of a grouped report are totalled by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    Category,
+    Account,
+    Payee,
+    /// A transaction with several tags counts towards each of them.
+    Tag,
+    Month,
+}
+
+impl Grouping {
+    pub const ALL: [Grouping; 5] = [Grouping::Category, Grouping::Account, Grouping::Payee, Grouping::Tag, Grouping::Month];
+}
+
+impl fmt::Display for Grouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Grouping::Category => write!(f, "Category"),
+            Grouping::Account => write!(f, "Account"),
+            Grouping::Payee => write!(f, "Payee"),
+            Grouping::Tag => write!(f, "Tag"),
+            Grouping::Month => write!(f, "Month"),
+        }
+    }
+}
+
+/// Which transactions a grouped report counts. Unset fields do not filter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportFilter {
+    pub tag: Option<String>,
+    pub payee_id: Option<String>,
+}
+
+impl ReportFilter {
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        self.tag.as_ref().is_none_or(|tag| transaction.has_tag(tag))
+            && self.payee_id.as_ref().is_none_or(|id| transaction.payee_id.as_ref() == Some(id))
+    }
+}
+
+/// A total for the report's range next to the one it is compared with.
+#[derive(Debug, Clone)]
+pub struct ReportLine {
+    pub label: String,
+    pub current: Money,
+    pub previous: Money,
+}
+
+impl ReportLine {
+    pub fn change(&self) -> Money {
+        self.current - self.previous
+    }
+
+    /// The change as a percentage of the previous total, when there was one.
+    pub fn change_percent(&self) -> Option<f64> {
+        if self.previous.is_zero() {
+            return None;
+        }
+        Some(calculate_percentage(self.change().to_f64(), self.previous.to_f64().abs()))
+    }
+}
+
+/// The lines for one transaction type and their subtotal.
+#[derive(Debug, Clone)]
+pub struct ReportSection {
+    pub title: String,
+    pub lines: Vec<ReportLine>,
+    pub subtotal: ReportLine,
+}
+
+/// Income and expenses over a date range, grouped and compared with the
+/// range before it. Amounts are in the base currency.
+pub struct GroupedReport {
+    pub range: DateRange,
+    pub previous_range: DateRange,
+    pub grouping: Grouping,
+    pub filter: ReportFilter,
+    pub base: Currency,
+    pub income: ReportSection,
+    pub expenses: ReportSection,
+    pub net: ReportLine,
+    /// Transactions left out of the totals for want of an exchange rate.
+    pub unconverted: usize,
+}
+
+/// Totals of one transaction type by group key.
+type Totals = HashMap<String, Money>;
+
+impl GroupedReport {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        range: DateRange,
+        grouping: Grouping,
+        filter: ReportFilter,
+        transactions: &[Transaction],
+        categories: &[Category],
+        accounts: &[Account],
+        payees: &[Payee],
+        converter: &CurrencyConverter,
+    ) -> Self {
+        let previous_range = range.previous();
+        // Each month is compared with the month before it rather than with
+        // the same month of the previous range
+        let compared_range = match grouping {
+            Grouping::Month => DateRange::new(month_start(range.from) - Months::new(1), range.from.pred_opt().unwrap_or(range.from)),
+            _ => previous_range,
+        };
+
+        // The amounts a transaction adds to each group; split transactions
+        // add each line to its own category
+        let parts = |transaction: &Transaction| -> Vec<(String, Money)> {
+            match grouping {
+                Grouping::Category => transaction.category_amounts().into_iter()
+                    .map(|(category_id, amount)| (category_id.unwrap_or_default().to_string(), amount))
+                    .collect(),
+                Grouping::Account => vec![(transaction.account_id.clone().unwrap_or_default(), transaction.amount)],
+                Grouping::Payee => vec![(transaction.payee_id.clone().unwrap_or_default(), transaction.amount)],
+                Grouping::Tag if transaction.tags.is_empty() => vec![(String::new(), transaction.amount)],
+                Grouping::Tag => transaction.tags.iter()
+                    .map(|tag| (tag.clone(), transaction.amount))
+                    .collect(),
+                Grouping::Month => vec![(month_key(transaction.date), transaction.amount)],
+            }
+        };
+
+        let mut unconverted = 0;
+        let mut current: HashMap<TransactionType, Totals> = HashMap::new();
+        let mut previous: HashMap<TransactionType, Totals> = HashMap::new();
+        let mut current_totals: HashMap<TransactionType, Money> = HashMap::new();
+        let mut previous_totals: HashMap<TransactionType, Money> = HashMap::new();
+        let zero = Money::zero(converter.base());
+
+        // Transfers only move money between accounts, so they are left out
+        let selected = transactions.iter()
+            .filter(|t| !t.is_transfer())
+            .filter(|t| filter.matches(t));
+        for transaction in selected {
+            let in_range = range.contains(transaction.date);
+            if !in_range && !compared_range.contains(transaction.date) && !previous_range.contains(transaction.date) {
+                continue;
+            }
+            if converter.to_base(transaction.amount, transaction.date).is_none() {
+                // Only the report's own range counts towards the warning
+                if in_range {
+                    unconverted += 1;
+                }
+                continue;
+            }
+
+            let transaction_type = transaction.transaction_type;
+            let mut counted = zero;
+            for (key, amount) in parts(transaction) {
+                let Some(amount) = converter.to_base(amount, transaction.date) else {
+                    continue;
+                };
+                counted += amount;
+                if in_range {
+                    *current.entry(transaction_type).or_default().entry(key.clone()).or_insert(zero) += amount;
+                }
+                if compared_range.contains(transaction.date) {
+                    *previous.entry(transaction_type).or_default().entry(key).or_insert(zero) += amount;
+                }
+            }
+
+            // A transaction in several tag groups counts once in the totals
+            if grouping == Grouping::Tag {
+                counted = converter.to_base(transaction.amount, transaction.date).unwrap_or(zero);
+            }
+            if in_range {
+                *current_totals.entry(transaction_type).or_insert(zero) += counted;
+            }
+            if previous_range.contains(transaction.date) {
+                *previous_totals.entry(transaction_type).or_insert(zero) += counted;
+            }
+        }
+
+        let label = |key: &str| -> String {
+            match grouping {
+                Grouping::Category if categories.iter().any(|c| c.id == key) => category::full_name(categories, key),
+                Grouping::Category => "Uncategorized".to_string(),
+                Grouping::Account => accounts.iter()
+                    .find(|a| a.id == key)
+                    .map(|a| a.name.clone())
+                    .unwrap_or_else(|| "No Account".to_string()),
+                Grouping::Payee => payees.iter()
+                    .find(|p| p.id == key)
+                    .map(|p| p.name.clone())
+                    .unwrap_or_else(|| "No Payee".to_string()),
+                Grouping::Tag if key.is_empty() => "Untagged".to_string(),
+                Grouping::Tag => format!("#{}", key),
+                Grouping::Month => NaiveDate::parse_from_str(&format!("{}-01", key), "%Y-%m-%d")
+                    .map(|month| month.format("%B %Y").to_string())
+                    .unwrap_or_else(|_| key.to_string()),
+            }
+        };
+
+        let mut section = |title: &str, transaction_type: TransactionType| -> ReportSection {
+            let current = current.remove(&transaction_type).unwrap_or_default();
+            let previous = previous.remove(&transaction_type).unwrap_or_default();
+            let amount = |totals: &Totals, key: &str| totals.get(key).copied().unwrap_or(zero);
+
+            let lines: Vec<ReportLine> = match grouping {
+                Grouping::Month => range.months().into_iter()
+                    .map(|month| {
+                        let before = month_key(month - Months::new(1));
+                        ReportLine {
+                            label: label(&month_key(month)),
+                            current: amount(&current, &month_key(month)),
+                            previous: current.get(&before).or_else(|| previous.get(&before)).copied().unwrap_or(zero),
+                        }
+                    })
+                    .collect(),
+                _ => {
+                    let mut keys: Vec<&String> = current.keys().chain(previous.keys()).collect();
+                    keys.sort();
+                    keys.dedup();
+
+                    let mut lines: Vec<ReportLine> = keys.into_iter()
+                        .map(|key| ReportLine {
+                            label: label(key),
+                            current: amount(&current, key),
+                            previous: amount(&previous, key),
+                        })
+                        .collect();
+                    lines.sort_by(|a, b| {
+                        b.current.minor_units().cmp(&a.current.minor_units())
+                            .then_with(|| b.previous.minor_units().cmp(&a.previous.minor_units()))
+                            .then_with(|| a.label.cmp(&b.label))
+                    });
+                    lines
+                }
+            };
+
+            let subtotal = ReportLine {
+                label: format!("Total {}", title.to_lowercase()),
+                current: current_totals.get(&transaction_type).copied().unwrap_or(zero),
+                previous: previous_totals.get(&transaction_type).copied().unwrap_or(zero),
+            };
+
+            ReportSection {
+                title: title.to_string(),
+                lines,
+                subtotal,
+            }
+        };
+
+        let income = section("Income", TransactionType::Income);
+        let expenses = section("Expenses", TransactionType::Expense);
+        let net = ReportLine {
+            label: "Net".to_string(),
+            current: income.subtotal.current - expenses.subtotal.current,
+            previous: income.subtotal.previous - expenses.subtotal.previous,
+        };
+
+        Self {
+            range,
+            previous_range,
+            grouping,
+            filter,
+            base: converter.base(),
+            income,
+            expenses,
+            net,
+            unconverted,
+        }
+    }
+}
+
+fn month_key(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{date, expense, income, money};
+    use crate::models::Split;
+
+    fn tagged(amount: i64, tags: &[&str]) -> Transaction {
+        Transaction {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..expense(money(amount), date(2026, 3, 10))
+        }
+    }
+
+    #[test]
+    fn tag_grouping_counts_each_tag_but_totals_once() {
+        let transactions = vec![
+            tagged(3_000, &["trip", "work"]),
+            tagged(1_000, &["trip"]),
+            tagged(500, &[]),
+        ];
+        let report = GroupedReport::new(
+            DateRange::new(date(2026, 3, 1), date(2026, 3, 31)),
+            Grouping::Tag,
+            ReportFilter::default(),
+            &transactions,
+            &[],
+            &[],
+            &[],
+            &CurrencyConverter::default(),
+        );
+
+        let lines: Vec<(&str, i64)> = report.expenses.lines.iter()
+            .map(|line| (line.label.as_str(), line.current.minor_units()))
+            .collect();
+        assert_eq!(lines, vec![("#trip", 4_000), ("#work", 3_000), ("Untagged", 500)]);
+        assert_eq!(report.expenses.subtotal.current.minor_units(), 4_500);
+    }
+
+    fn lines(section: &ReportSection) -> Vec<(&str, i64, i64)> {
+        section.lines.iter()
+            .map(|line| (line.label.as_str(), line.current.minor_units(), line.previous.minor_units()))
+            .collect()
+    }
+
+    #[test]
+    fn category_grouping_splits_lines_and_compares_with_the_range_before() {
+        let food = Category::new("Food".to_string(), "#000000".to_string(), None);
+        let rent = Category::new("Rent".to_string(), "#000000".to_string(), None);
+        let on = |category: &Category, amount, day| Transaction {
+            category_id: Some(category.id.clone()),
+            ..expense(money(amount), day)
+        };
+        let transactions = vec![
+            on(&food, 4_000, date(2026, 3, 5)),
+            on(&food, 5_000, date(2026, 2, 5)),
+            on(&rent, 90_000, date(2026, 2, 1)),
+            Transaction {
+                splits: vec![
+                    Split { category_id: Some(food.id.clone()), amount: money(1_500), memo: String::new() },
+                    Split { category_id: None, amount: money(500), memo: String::new() },
+                ],
+                ..expense(money(2_000), date(2026, 3, 20))
+            },
+            Transaction { transaction_type: TransactionType::Transfer, ..expense(money(7_000), date(2026, 3, 6)) },
+            income(money(250_000), date(2026, 3, 25)),
+        ];
+        let report = GroupedReport::new(
+            DateRange::new(date(2026, 3, 1), date(2026, 3, 31)),
+            Grouping::Category,
+            ReportFilter::default(),
+            &transactions,
+            &[food.clone(), rent.clone()],
+            &[],
+            &[],
+            &CurrencyConverter::default(),
+        );
+
+        assert_eq!(lines(&report.expenses), vec![
+            ("Food", 5_500, 5_000),
+            ("Uncategorized", 500, 0),
+            ("Rent", 0, 90_000),
+        ]);
+        assert_eq!(report.expenses.subtotal.current.minor_units(), 6_000);
+        assert_eq!(report.expenses.subtotal.previous.minor_units(), 95_000);
+        assert_eq!(report.net.current.minor_units(), 244_000);
+        assert_eq!(report.expenses.lines[0].change_percent(), Some(10.0));
+        assert_eq!(report.expenses.lines[2].change_percent(), Some(-100.0));
+    }
+
+    #[test]
+    fn month_grouping_compares_each_month_with_the_one_before() {
+        let transactions = vec![
+            expense(money(1_000), date(2025, 12, 15)),
+            expense(money(3_000), date(2026, 1, 15)),
+            expense(money(2_000), date(2026, 2, 15)),
+        ];
+        let report = GroupedReport::new(
+            DateRange::new(date(2026, 1, 1), date(2026, 3, 31)),
+            Grouping::Month,
+            ReportFilter::default(),
+            &transactions,
+            &[],
+            &[],
+            &[],
+            &CurrencyConverter::default(),
+        );
+
+        assert_eq!(lines(&report.expenses), vec![
+            ("January 2026", 3_000, 1_000),
+            ("February 2026", 2_000, 3_000),
+            ("March 2026", 0, 2_000),
+        ]);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::models::{Account, AccountType, Currency, Money, TransactionType};
+use crate::models::account::net_worth;
+use crate::ui::components::account_form::{account_form_view, AccountFormState};
+use crate::ui::UiState;
+use crate::utils::{format_currency, format_date, today};
+use chrono::NaiveDate;
+use iced::widget::{button, column, container, row, scrollable, text, Text};
+use iced::{Alignment, Color, Element, Length};
+
+#[derive(Debug, Clone)]
+pub enum AccountMessage {
+    Add,
+    NameChanged(String),
+    TypeSelected(AccountType),
+    OpeningBalanceChanged(String),
+    CurrencySelected(Currency),
+    OpenDatePicker(bool),
+    OpeningDateSelected(NaiveDate),
+    Save,
+    Delete(String),
+    Edit(Account),
+    Cancel,
+    Select(String),
+}
+
+const NEGATIVE_COLOR: Color = Color::from_rgb(0.8, 0.0, 0.0);
+
+pub struct AccountView {
+    form: AccountFormState,
+    show_form: bool,
+    selected: Option<String>,
+    default_currency: Currency,
+    error: Option<String>,
+}
+
+impl AccountView {
+    pub fn new() -> Self {
+        Self {
+            form: AccountFormState::default(),
+            show_form: false,
+            selected: None,
+            default_currency: Currency::default(),
+            error: None,
+        }
+    }
+
+    /// Currency preselected for new accounts.
+    pub fn set_default_currency(&mut self, currency: Currency) {
+        self.default_currency = currency;
+    }
+
+    pub fn update(&mut self, message: AccountMessage) {
+        match message {
+            AccountMessage::Add => {
+                self.form = AccountFormState {
+                    currency: self.default_currency,
+                    ..AccountFormState::default()
+                };
+                self.show_form = true;
+            }
+            AccountMessage::NameChanged(name) => {
+                self.form.name = name;
+            }
+            AccountMessage::TypeSelected(account_type) => {
+                self.form.account_type = account_type;
+            }
+            AccountMessage::OpeningBalanceChanged(balance) => {
+                self.form.opening_balance = balance;
+            }
+            AccountMessage::CurrencySelected(currency) => {
+                self.form.currency = currency;
+            }
+            AccountMessage::OpenDatePicker(show) => {
+                self.form.show_date_picker = show;
+            }
+            AccountMessage::OpeningDateSelected(date) => {
+                self.form.opening_date = date;
+                self.form.show_date_picker = false;
+            }
+            AccountMessage::Save => {}
+            AccountMessage::Delete(id) => {
+                self.error = None;
+                if self.form.editing_id.as_ref() == Some(&id) {
+                    self.close_form();
+                }
+                if self.selected.as_ref() == Some(&id) {
+                    self.selected = None;
+                }
+            }
+            AccountMessage::Edit(account) => {
+                self.form = AccountFormState::from_account(&account);
+                self.show_form = true;
+            }
+            AccountMessage::Cancel => {
+                self.close_form();
+            }
+            AccountMessage::Select(id) => {
+                self.selected = if self.selected.as_ref() == Some(&id) { None } else { Some(id) };
+            }
+        }
+    }
+
+    /// Validates the form, returning the account to persist. Validation
+    /// errors are shown in the form instead.
+    pub fn submit(&mut self) -> Option<Account> {
+        match self.form.to_account() {
+            Ok(account) => {
+                self.form.error = None;
+                Some(account)
+            }
+            Err(e) => {
+                self.form.error = Some(e);
+                None
+            }
+        }
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.form.editing_id.is_some()
+    }
+
+    pub fn close_form(&mut self) {
+        self.form = AccountFormState::default();
+        self.show_form = false;
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, AccountMessage> {
+        let header = row![
+            text("Accounts").size(30).width(Length::Fill),
+            button("Add Account")
+                .on_press(AccountMessage::Add)
+                .padding(10),
+        ];
+
+        let net_worth_text = match net_worth(&state.accounts, &state.transactions, &state.converter, today()) {
+            Some(total) => text(format!("Net worth: {}", format_currency(total))),
+            None => text(format!("Net worth: missing exchange rate to {}", state.converter.base())),
+        }
+        .size(20);
+
+        let accounts_list = state.accounts.iter()
+            .fold(column![].spacing(10), |column, account| {
+                let balance = account.balance(&state.transactions, &state.converter);
+                let is_selected = self.selected.as_ref() == Some(&account.id);
+
+                let mut entry = column![
+                    row![
+                        text(&account.name).size(18).width(Length::Fill),
+                        text(account.account_type.to_string()).width(Length::Fixed(120.0)),
+                        balance_text(balance)
+                            .width(Length::Fixed(140.0)),
+                        button(if is_selected { "Hide Register" } else { "Register" })
+                            .on_press(AccountMessage::Select(account.id.clone()))
+                            .style(button::secondary),
+                        button("Edit")
+                            .on_press(AccountMessage::Edit(account.clone())),
+                        button("Delete")
+                            .on_press(AccountMessage::Delete(account.id.clone()))
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                ]
+                .spacing(10);
+
+                if is_selected {
+                    entry = entry.push(self.register_view(state, account));
+                }
+
+                column.push(
+                    container(entry)
+                        .padding(10)
+                        .style(container::rounded_box)
+                )
+            });
+
+        let error = text(self.error.clone().unwrap_or_default())
+            .size(14)
+            .color(NEGATIVE_COLOR);
+
+        let mut content = column![header, net_worth_text, error].spacing(20);
+
+        if self.show_form {
+            content = content.push(account_form_view(&self.form));
+        }
+
+        content = content.push(scrollable(accounts_list).height(Length::Fill));
+
+        container(content)
+            .padding(20)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    /// The account's transactions with the balance after each one.
+    fn register_view<'a>(&self, state: &'a UiState, account: &Account) -> Element<'a, AccountMessage> {
+        let opening = row![
+            text(format_date(&account.opening_date)).width(Length::FillPortion(2)),
+            text("Opening balance").width(Length::FillPortion(3)),
+            text("").width(Length::FillPortion(2)),
+            text(format_currency(account.opening_balance)).width(Length::FillPortion(2)),
+        ]
+        .spacing(10);
+
+        account.running_balances(&state.transactions, &state.converter)
+            .into_iter()
+            .fold(column![opening].spacing(5), |column, (transaction, balance)| {
+                let amount = match transaction.transaction_type {
+                    TransactionType::Income => transaction.amount,
+                    TransactionType::Expense => -transaction.amount,
+                    TransactionType::Transfer => transaction.amount,
+                };
+
+                column.push(
+                    row![
+                        text(format_date(&transaction.date)).width(Length::FillPortion(2)),
+                        text(&transaction.description).width(Length::FillPortion(3)),
+                        text(format_currency(amount)).width(Length::FillPortion(2)),
+                        balance_text(balance)
+                            .width(Length::FillPortion(2)),
+                    ]
+                    .spacing(10)
+                )
+            })
+            .into()
+    }
+}
+
+/// A balance, in red when negative or when a missing exchange rate leaves
+/// it unknown.
+fn balance_text<'a>(balance: Option<Money>) -> Text<'a> {
+    match balance {
+        Some(balance) => text(format_currency(balance))
+            .color_maybe(balance.is_negative().then_some(NEGATIVE_COLOR)),
+        None => text("Missing exchange rate").color(NEGATIVE_COLOR),
+    }
+}
@@ -0,0 +1,216 @@
+use crate::db::backup::BackupInfo;
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{Alignment, Color, Element, Length};
+use std::path::{Path, PathBuf};
+
+const ERROR_COLOR: Color = Color::from_rgb(0.8, 0.0, 0.0);
+
+#[derive(Debug, Clone)]
+pub enum BackupMessage {
+    BackUpNow,
+    KeepChanged(String),
+    SaveKeep,
+    RestorePathChanged(String),
+    /// Asks for confirmation before restoring the backup at this path.
+    Restore(PathBuf),
+    ConfirmRestore,
+    CancelRestore,
+}
+
+/// Taking backups, choosing how many to keep and restoring one.
+pub struct BackupView {
+    backups: Vec<BackupInfo>,
+    keep: String,
+    restore_path: String,
+    pending_restore: Option<PathBuf>,
+    error: Option<String>,
+    status: Option<String>,
+}
+
+impl BackupView {
+    pub fn new() -> Self {
+        Self {
+            backups: Vec::new(),
+            keep: String::new(),
+            restore_path: String::new(),
+            pending_restore: None,
+            error: None,
+            status: None,
+        }
+    }
+
+    pub fn update(&mut self, message: BackupMessage) {
+        match message {
+            BackupMessage::KeepChanged(keep) => {
+                self.keep = keep;
+            }
+            BackupMessage::RestorePathChanged(path) => {
+                self.restore_path = path;
+            }
+            BackupMessage::Restore(path) => {
+                self.pending_restore = Some(path);
+            }
+            BackupMessage::CancelRestore => {
+                self.pending_restore = None;
+            }
+            BackupMessage::BackUpNow
+            | BackupMessage::SaveKeep
+            | BackupMessage::ConfirmRestore => {}
+        }
+    }
+
+    pub fn set_backups(&mut self, backups: Vec<BackupInfo>, keep: usize) {
+        self.backups = backups;
+        self.keep = keep.to_string();
+    }
+
+    /// The backup the user confirmed restoring, clearing the confirmation.
+    pub fn take_restore(&mut self) -> Option<PathBuf> {
+        self.pending_restore.take()
+    }
+
+    /// Validates the number of backups to keep. Validation errors are shown
+    /// on the page instead.
+    pub fn keep(&mut self) -> Option<usize> {
+        match self.keep.trim().parse::<usize>() {
+            Ok(keep) if keep > 0 => {
+                self.error = None;
+                Some(keep)
+            }
+            _ => {
+                self.set_error(format!("Invalid number of backups: {}", self.keep));
+                None
+            }
+        }
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.error = None;
+        self.status = Some(status);
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.status = None;
+        self.error = Some(error);
+    }
+
+    fn confirmation_view(&self, path: &Path) -> Element<'_, BackupMessage> {
+        container(
+            row![
+                text(format!(
+                    "Replace all current data with {}? The current data is backed up first.",
+                    path.display()
+                ))
+                .width(Length::Fill),
+                button("Restore")
+                    .on_press(BackupMessage::ConfirmRestore)
+                    .style(button::danger)
+                    .padding(10),
+                button("Cancel")
+                    .on_press(BackupMessage::CancelRestore)
+                    .style(button::secondary)
+                    .padding(10),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+        )
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+
+    pub fn view(&self) -> Element<'_, BackupMessage> {
+        let settings = row![
+            button("Back Up Now")
+                .on_press(BackupMessage::BackUpNow)
+                .padding(10),
+            text("Keep the last"),
+            text_input("10", &self.keep)
+                .on_input(BackupMessage::KeepChanged)
+                .padding(10)
+                .width(Length::Fixed(70.0)),
+            text("backups"),
+            button("Save")
+                .on_press(BackupMessage::SaveKeep)
+                .style(button::secondary)
+                .padding(10),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let restore_from_file = row![
+            text("Restore from file:"),
+            text_input("Path to a backup", &self.restore_path)
+                .on_input(BackupMessage::RestorePathChanged)
+                .padding(10),
+            button("Restore")
+                .on_press_maybe(
+                    (!self.restore_path.trim().is_empty())
+                        .then(|| BackupMessage::Restore(PathBuf::from(self.restore_path.trim())))
+                )
+                .padding(10),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let backups_list = self.backups.iter()
+            .fold(column![].spacing(5), |column, backup| {
+                let name = backup.path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+
+                column.push(
+                    container(
+                        row![
+                            text(backup.created.format("%Y-%m-%d %H:%M:%S").to_string())
+                                .width(Length::FillPortion(2)),
+                            text(name).width(Length::FillPortion(3)),
+                            text(format!("{} KB", backup.size.div_ceil(1024)))
+                                .width(Length::FillPortion(1)),
+                            button("Restore")
+                                .on_press(BackupMessage::Restore(backup.path.clone()))
+                                .style(button::secondary),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center)
+                    )
+                    .padding(10)
+                    .style(container::rounded_box)
+                )
+            });
+
+        let backups_section: Element<_> = if self.backups.is_empty() {
+            text("No backups yet").into()
+        } else {
+            scrollable(backups_list).height(Length::Fill).into()
+        };
+
+        let message = match (&self.error, &self.status) {
+            (Some(error), _) => text(error.clone()).size(14).color(ERROR_COLOR),
+            (None, Some(status)) => text(status.clone()).size(14),
+            (None, None) => text(""),
+        };
+
+        let mut content = column![
+            text("Backup & Restore").size(30),
+            settings,
+            restore_from_file,
+        ]
+        .spacing(20);
+
+        if let Some(path) = &self.pending_restore {
+            content = content.push(self.confirmation_view(path));
+        }
+
+        container(
+            content
+                .push(message)
+                .push(text("Backups").size(20))
+                .push(backups_section)
+        )
+        .padding(20)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
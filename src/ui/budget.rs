@@ -1,75 +1,403 @@
-use crate::models::{Budget};
-use iced::{Element, widget::{button, column, container, text}};
-
-use uuid::Uuid;
+use crate::models::budget::ALL_SPENDING;
+use crate::models::{category, Allocation, Budget, Currency, Envelopes, Money};
+use crate::ui::components::budget_form::{budget_form_view, BudgetFormState, RenewalChoice};
+use crate::ui::components::charts;
+use crate::ui::UiState;
+use crate::utils::{format_currency, format_date, month_start, today};
+use chrono::{Datelike, Months, NaiveDate};
+use iced::{Alignment, Color, Element, Length, widget::{button, checkbox, column, container, row, scrollable, text, text_input}};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub enum BudgetMessage {
+    Add,
     NameChanged(String),
     AmountChanged(String),
+    CurrencySelected(Currency),
+    CategorySelected(Option<String>),
+    OpenStartDatePicker(bool),
+    StartDateSelected(NaiveDate),
+    OpenEndDatePicker(bool),
+    EndDateSelected(NaiveDate),
+    RenewalSelected(RenewalChoice),
+    RenewalDayChanged(String),
+    RolloverToggled(bool),
+    OpenEndedToggled(bool),
+    AlertThresholdsChanged(String),
     Save,
-    Delete(Uuid),
+    Delete(String),
     Edit(Budget),
     Cancel,
+    EnvelopeModeToggled(bool),
+    PreviousMonth,
+    NextMonth,
+    AssignmentChanged(String, String),
+    Assign(String),
 }
 
+const OVERSPENT_COLOR: Color = Color::from_rgb(0.8, 0.0, 0.0);
+const FUNDED_COLOR: Color = Color::from_rgb(0.0, 0.5, 0.0);
+
+/// Budgets with spending limits, or in envelope mode a month's grid of
+/// what is assigned to each category from the income waiting to be
+/// assigned.
 pub struct BudgetView {
-    budgets: Vec<Budget>,
-    editing_budget: Option<Budget>,
-    name_input: String,
-    amount_input: String,
+    form: BudgetFormState,
+    show_form: bool,
+    default_currency: Currency,
+    envelope_mode: bool,
+    /// The first day of the month shown in envelope mode.
+    month: NaiveDate,
+    /// Amounts being typed into the grid, by category id.
+    assignments: HashMap<String, String>,
+    envelope_error: Option<String>,
 }
 
 impl BudgetView {
     pub fn new() -> Self {
         Self {
-            budgets: Vec::new(),
-            editing_budget: None,
-            name_input: String::new(),
-            amount_input: String::new(),
+            form: BudgetFormState::default(),
+            show_form: false,
+            default_currency: Currency::default(),
+            envelope_mode: false,
+            month: month_start(today()),
+            assignments: HashMap::new(),
+            envelope_error: None,
         }
     }
-    
+
+    pub fn set_envelope_mode(&mut self, enabled: bool) {
+        self.envelope_mode = enabled;
+    }
+
+    /// Currency preselected for new budgets.
+    pub fn set_default_currency(&mut self, currency: Currency) {
+        self.default_currency = currency;
+    }
+
     pub fn update(&mut self, message: BudgetMessage) {
         match message {
+            BudgetMessage::Add => {
+                self.form = BudgetFormState {
+                    currency: self.default_currency,
+                    ..BudgetFormState::default()
+                };
+                self.show_form = true;
+            },
             BudgetMessage::NameChanged(name) => {
-                self.name_input = name;
+                self.form.name = name;
             },
             BudgetMessage::AmountChanged(amount) => {
-                self.amount_input = amount;
+                self.form.amount = amount;
+            },
+            BudgetMessage::CurrencySelected(currency) => {
+                self.form.currency = currency;
+            },
+            BudgetMessage::CategorySelected(category_id) => {
+                self.form.category_id = category_id;
+            },
+            BudgetMessage::OpenStartDatePicker(show) => {
+                self.form.show_start_date_picker = show;
+            },
+            BudgetMessage::StartDateSelected(date) => {
+                self.form.start_date = date;
+                self.form.show_start_date_picker = false;
+            },
+            BudgetMessage::OpenEndDatePicker(show) => {
+                self.form.show_end_date_picker = show;
+            },
+            BudgetMessage::EndDateSelected(date) => {
+                self.form.end_date = date;
+                self.form.show_end_date_picker = false;
+            },
+            BudgetMessage::RenewalSelected(renewal) => {
+                let renewing = renewal != RenewalChoice::Never;
+                if renewal == RenewalChoice::OnDay && self.form.renewal_day.is_empty() {
+                    self.form.renewal_day = self.form.start_date.day().to_string();
+                }
+                self.form.renewal = renewal;
+                self.form.rollover &= renewing;
+                self.form.open_ended &= renewing;
             },
-            BudgetMessage::Save => {
-                // Budget save logic here
+            BudgetMessage::RenewalDayChanged(day) => {
+                self.form.renewal_day = day;
             },
-            BudgetMessage::Delete(_id) => {
-                // Budget delete logic here
+            BudgetMessage::OpenEndedToggled(open_ended) => {
+                self.form.open_ended = open_ended;
+            },
+            BudgetMessage::RolloverToggled(rollover) => {
+                self.form.rollover = rollover;
+            },
+            BudgetMessage::AlertThresholdsChanged(thresholds) => {
+                self.form.alert_thresholds = thresholds;
+            },
+            BudgetMessage::Save => {},
+            BudgetMessage::Delete(id) => {
+                if self.form.editing_id.as_ref() == Some(&id) {
+                    self.close_form();
+                }
             },
             BudgetMessage::Edit(budget) => {
-                self.editing_budget = Some(budget.clone());
-                self.name_input = budget.name;
-                self.amount_input = budget.amount.to_string();
+                self.form = BudgetFormState::from_budget(&budget);
+                self.show_form = true;
             },
             BudgetMessage::Cancel => {
-                self.editing_budget = None;
-                self.name_input = String::new();
-                self.amount_input = String::new();
+                self.close_form();
+            },
+            BudgetMessage::EnvelopeModeToggled(enabled) => {
+                self.envelope_mode = enabled;
+            },
+            BudgetMessage::PreviousMonth => {
+                self.month = self.month - Months::new(1);
+                self.assignments.clear();
+                self.envelope_error = None;
+            },
+            BudgetMessage::NextMonth => {
+                self.month = self.month + Months::new(1);
+                self.assignments.clear();
+                self.envelope_error = None;
+            },
+            BudgetMessage::AssignmentChanged(category_id, amount) => {
+                self.assignments.insert(category_id, amount);
             },
+            BudgetMessage::Assign(_) => {},
         }
     }
-    
-    pub fn view(&self) -> Element<BudgetMessage> {
-        let content = column!()
-            .spacing(20)
-            .push(text("Budgets").size(30))
-            .push(
-                button("Add Budget")
-                    .on_press(BudgetMessage::Cancel) // This resets the form
-            );
-        
+
+    /// Validates the amount typed for `category_id`, returning the
+    /// allocation to persist. It must not assign more than is waiting to
+    /// be assigned in this or any later month.
+    pub fn submit_assignment(&mut self, category_id: &str, state: &UiState) -> Option<Allocation> {
+        let input = self.assignments.get(category_id)?;
+        let currency = state.converter.base();
+        let amount = if input.trim().is_empty() {
+            Ok(Money::zero(currency))
+        } else {
+            Money::parse(input, currency).map_err(|e| e.to_string())
+        };
+
+        let allocation = amount
+            .and_then(|amount| if amount.is_negative() {
+                Err("Enter an amount of zero or more".to_string())
+            } else {
+                Ok(Allocation::new(category_id.to_string(), self.month, amount))
+            })
+            .and_then(|allocation| {
+                Envelopes::new(&state.allocations, &state.transactions, &state.converter)
+                    .check(&allocation)
+                    .map(|()| allocation)
+            });
+
+        match allocation {
+            Ok(allocation) => {
+                self.assignments.remove(category_id);
+                self.envelope_error = None;
+                Some(allocation)
+            }
+            Err(e) => {
+                self.envelope_error = Some(e);
+                None
+            }
+        }
+    }
+
+    pub fn set_envelope_error(&mut self, error: String) {
+        self.envelope_error = Some(error);
+    }
+
+    /// Validates the form, returning the budget to persist. Validation
+    /// errors are shown in the form instead.
+    pub fn submit(&mut self) -> Option<Budget> {
+        match self.form.to_budget() {
+            Ok(budget) => {
+                self.form.error = None;
+                Some(budget)
+            }
+            Err(e) => {
+                self.form.error = Some(e);
+                None
+            }
+        }
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.form.editing_id.is_some()
+    }
+
+    pub fn close_form(&mut self) {
+        self.form = BudgetFormState::default();
+        self.show_form = false;
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, BudgetMessage> {
+        let mut header = row![
+            text("Budgets").size(30).width(Length::Fill),
+            checkbox("Zero-based envelopes", self.envelope_mode)
+                .on_toggle(BudgetMessage::EnvelopeModeToggled),
+        ]
+        .spacing(20)
+        .align_y(Alignment::Center);
+
+        if self.envelope_mode {
+            return container(column![header, self.envelope_view(state)].spacing(20))
+                .padding(20)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into();
+        }
+
+        header = header.push(
+            button("Add Budget")
+                .on_press(BudgetMessage::Add)
+                .padding(10),
+        );
+
+        let budgets_list = state.budgets.iter()
+            .fold(column![].spacing(10), |column, budget| {
+                let category_name = budget.category_id.as_deref()
+                    .map_or(ALL_SPENDING, |id| state.category_name(Some(id)));
+                let now = today();
+                let current = budget.recorded(&state.budget_periods).find(|p| p.range.contains(now));
+                let end = budget.end_date.as_ref().map_or("ongoing".to_string(), format_date);
+                let renewal = match (budget.renewal, budget.rollover) {
+                    (Some(renewal), true) => format!(", renews {} with rollover", renewal.to_string().to_lowercase()),
+                    (Some(renewal), false) => format!(", renews {}", renewal.to_string().to_lowercase()),
+                    (None, _) => String::new(),
+                };
+
+                column.push(
+                    container(
+                        column![
+                            row![
+                                text(format!("{} ({})", budget.name, category_name))
+                                    .size(18)
+                                    .width(Length::Fill),
+                                text(format!(
+                                    "{} - {}{}",
+                                    format_date(&budget.start_date),
+                                    end,
+                                    renewal
+                                )),
+                                button("Edit")
+                                    .on_press(BudgetMessage::Edit(budget.clone())),
+                                button("Delete")
+                                    .on_press(BudgetMessage::Delete(budget.id.clone()))
+                                    .style(button::danger),
+                            ]
+                            .spacing(10),
+                            charts::budget_progress(budget, current, &state.transactions, &state.categories, &state.converter),
+                        ]
+                        .spacing(10)
+                    )
+                    .padding(10)
+                    .style(container::rounded_box)
+                )
+            });
+
+        let mut content = column![header].spacing(20);
+
+        if self.show_form {
+            content = content.push(budget_form_view(&state.categories, &self.form));
+        }
+
+        content = content.push(scrollable(budgets_list).height(Length::Fill));
+
         container(content)
-            .width(iced::Length::Fill)
-            .height(iced::Length::Fill)
-            .center_x()
+            .padding(20)
+            .width(Length::Fill)
+            .height(Length::Fill)
             .into()
     }
+
+    /// The month's assignment grid: each category's envelope with what was
+    /// assigned to it this month, spent from it this month and what is
+    /// left in it, under the amount still to be assigned.
+    fn envelope_view<'a>(&self, state: &'a UiState) -> Element<'a, BudgetMessage> {
+        let envelopes = Envelopes::new(&state.allocations, &state.transactions, &state.converter);
+
+        let month_row = row![
+            button("<")
+                .on_press(BudgetMessage::PreviousMonth)
+                .style(button::secondary),
+            text(self.month.format("%B %Y").to_string()).size(20),
+            button(">")
+                .on_press(BudgetMessage::NextMonth)
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let to_be_assigned = envelopes.to_be_assigned(self.month);
+        let to_be_assigned = text(format!("To be assigned: {}", format_currency(to_be_assigned)))
+            .size(22)
+            .color(if to_be_assigned.is_negative() { OVERSPENT_COLOR } else { FUNDED_COLOR });
+
+        let unconverted = envelopes.unconverted(self.month);
+        let missing_rates = text(if unconverted > 0 {
+            format!(
+                "{} amount(s) left out: no exchange rate to {}",
+                unconverted,
+                state.converter.base()
+            )
+        } else {
+            String::new()
+        })
+        .size(14)
+        .color(OVERSPENT_COLOR);
+
+        let error = text(self.envelope_error.clone().unwrap_or_default())
+            .size(14)
+            .color(OVERSPENT_COLOR);
+
+        let heading = row![
+            text("Category").width(Length::FillPortion(3)),
+            text("Assigned").width(Length::FillPortion(2)),
+            text("Activity").width(Length::FillPortion(2)),
+            text("Available").width(Length::FillPortion(2)),
+        ]
+        .spacing(10);
+
+        let rows = category::tree(&state.categories).into_iter()
+            .fold(column![heading].spacing(5), |column, (category, depth)| {
+                let id = category.id.clone();
+                let assigned = envelopes.assigned(&id, self.month);
+                let input = self.assignments.get(&id).cloned().unwrap_or_else(|| {
+                    if assigned.is_zero() { String::new() } else { assigned.to_input_string() }
+                });
+                let available = envelopes.available(&id, self.month);
+
+                column.push(
+                    row![
+                        text(format!("{}{}", "    ".repeat(depth), category.name)).width(Length::FillPortion(3)),
+                        text_input("0.00", &input)
+                            .on_input({
+                                let id = id.clone();
+                                move |amount| BudgetMessage::AssignmentChanged(id.clone(), amount)
+                            })
+                            .on_submit(BudgetMessage::Assign(id.clone()))
+                            .padding(5)
+                            .width(Length::FillPortion(2)),
+                        text(format_currency(envelopes.activity(&id, self.month))).width(Length::FillPortion(2)),
+                        text(format_currency(available))
+                            .color_maybe(available.is_negative().then_some(OVERSPENT_COLOR))
+                            .width(Length::FillPortion(2)),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                )
+            });
+
+        column![
+            month_row,
+            to_be_assigned,
+            text("Income waits here until it is assigned to an envelope; press Enter to assign an amount. \
+                  What an envelope does not spend stays in it for the following months.")
+                .size(14),
+            missing_rates,
+            error,
+            scrollable(rows).height(Length::Fill),
+        ]
+        .spacing(15)
+        .into()
+    }
 }
@@ -0,0 +1,526 @@
+use crate::models::category::{self, DEFAULT_CATEGORY_COLOR};
+use crate::models::Category;
+use crate::ui::components::charts;
+use crate::ui::UiState;
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input, Row, Space};
+use iced::{Alignment, Border, Color, Element, Length};
+use plotters::style::RGBColor;
+use std::fmt;
+
+/// Indent per level of the category tree, in pixels.
+const INDENT: f32 = 24.0;
+
+/// Colours offered in the category editor, matching the defaults.
+const PALETTE: [&str; 12] = [
+    "#4C8BF5", "#2D9D5A", "#FF9900", "#DB4437", "#673AB7", "#795548",
+    "#E91E63", "#607D8B", "#0F9D58", "#00ACC1", "#FBC02D", "#9E9E9E",
+];
+
+/// A choice of category, or of none with a label saying what that means.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryChoice {
+    pub id: Option<String>,
+    name: String,
+}
+
+impl CategoryChoice {
+    pub fn none(label: &str) -> Self {
+        Self { id: None, name: label.to_string() }
+    }
+
+    /// `none` followed by every category in tree order, by full name.
+    pub fn all(categories: &[Category], none: &str) -> Vec<Self> {
+        std::iter::once(Self::none(none))
+            .chain(category::tree(categories).into_iter().map(|(c, _)| Self {
+                id: Some(c.id.clone()),
+                name: category::full_name(categories, &c.id),
+            }))
+            .collect()
+    }
+}
+
+impl fmt::Display for CategoryChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CategoriesMessage {
+    NameChanged(String),
+    ParentSelected(CategoryChoice),
+    AddCategory,
+    MoveCategory(String, CategoryChoice),
+    Edit(Category),
+    EditNameChanged(String),
+    EditIconChanged(String),
+    EditColorChanged(String),
+    SaveEdit,
+    CancelEdit,
+    Delete(String),
+    Merge(String),
+    ReplacementSelected(CategoryChoice),
+    ConfirmRemoval,
+    CancelRemoval,
+}
+
+/// A category being edited.
+struct Editor {
+    id: String,
+    name: String,
+    icon: String,
+    color: String,
+}
+
+/// A category about to be deleted or merged into another.
+pub struct Removal {
+    pub id: String,
+    /// Where its transactions, budgets and rules go. Required for a merge.
+    pub replacement: Option<String>,
+    pub merge: bool,
+}
+
+/// The category tree, where categories are added, edited, moved between
+/// parents, deleted and merged.
+pub struct CategoriesView {
+    name: String,
+    parent_id: Option<String>,
+    editor: Option<Editor>,
+    removal: Option<Removal>,
+    error: Option<String>,
+}
+
+impl CategoriesView {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            parent_id: None,
+            editor: None,
+            removal: None,
+            error: None,
+        }
+    }
+
+    pub fn update(&mut self, message: CategoriesMessage) {
+        match message {
+            CategoriesMessage::NameChanged(name) => {
+                self.name = name;
+            }
+            CategoriesMessage::ParentSelected(choice) => {
+                self.parent_id = choice.id;
+            }
+            CategoriesMessage::Edit(category) => {
+                self.removal = None;
+                self.error = None;
+                self.editor = Some(Editor {
+                    id: category.id,
+                    name: category.name,
+                    icon: category.icon.unwrap_or_default(),
+                    color: category.color,
+                });
+            }
+            CategoriesMessage::EditNameChanged(name) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.name = name;
+                }
+            }
+            CategoriesMessage::EditIconChanged(icon) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.icon = icon;
+                }
+            }
+            CategoriesMessage::EditColorChanged(color) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.color = color;
+                }
+            }
+            CategoriesMessage::CancelEdit => {
+                self.editor = None;
+                self.error = None;
+            }
+            CategoriesMessage::Delete(id) => {
+                self.start_removal(id, false);
+            }
+            CategoriesMessage::Merge(id) => {
+                self.start_removal(id, true);
+            }
+            CategoriesMessage::ReplacementSelected(choice) => {
+                if let Some(removal) = &mut self.removal {
+                    removal.replacement = choice.id;
+                }
+            }
+            CategoriesMessage::CancelRemoval => {
+                self.removal = None;
+                self.error = None;
+            }
+            CategoriesMessage::AddCategory
+            | CategoriesMessage::MoveCategory(_, _)
+            | CategoriesMessage::SaveEdit
+            | CategoriesMessage::ConfirmRemoval => {}
+        }
+    }
+
+    fn start_removal(&mut self, id: String, merge: bool) {
+        self.editor = None;
+        self.error = None;
+        self.removal = Some(Removal { id, replacement: None, merge });
+    }
+
+    /// Validates the new category form, returning the category to persist.
+    /// Subcategories start with their parent's colour.
+    pub fn submit(&mut self, categories: &[Category]) -> Option<Category> {
+        let name = self.name.trim();
+        if let Err(e) = check_name(categories, name, self.parent_id.as_deref(), None) {
+            self.error = Some(e);
+            return None;
+        }
+
+        self.error = None;
+        let parent = self.parent_id.as_deref()
+            .and_then(|id| categories.iter().find(|c| c.id == id));
+        let color = parent.map(|p| p.color.clone()).unwrap_or_else(|| DEFAULT_CATEGORY_COLOR.to_string());
+        Some(Category::new(name.to_string(), color, parent.map(|p| p.id.clone())))
+    }
+
+    /// Checks a move before it is saved, returning the new parent's id.
+    pub fn validate_move(&mut self, categories: &[Category], id: &str, choice: &CategoryChoice) -> Option<Option<String>> {
+        if let Some(parent_id) = &choice.id {
+            if !category::can_set_parent(categories, id, parent_id) {
+                self.error = Some("A category cannot be moved under itself or one of its subcategories".to_string());
+                return None;
+            }
+        }
+
+        self.error = None;
+        Some(choice.id.clone())
+    }
+
+    /// Validates the editor, returning the updated category to persist.
+    pub fn submit_edit(&mut self, categories: &[Category]) -> Option<Category> {
+        let editor = self.editor.as_ref()?;
+        let original = categories.iter().find(|c| c.id == editor.id)?;
+
+        let name = editor.name.trim();
+        let result = check_name(categories, name, original.parent_id.as_deref(), Some(&original.id))
+            .and_then(|()| check_color(editor.color.trim()));
+        if let Err(e) = result {
+            self.error = Some(e);
+            return None;
+        }
+
+        self.error = None;
+        let icon = editor.icon.trim();
+        Some(Category {
+            name: name.to_string(),
+            color: editor.color.trim().to_uppercase(),
+            icon: (!icon.is_empty()).then(|| icon.to_string()),
+            ..original.clone()
+        })
+    }
+
+    /// Takes the pending deletion or merge once the user confirms it.
+    pub fn take_removal(&mut self) -> Option<Removal> {
+        let removal = self.removal.take()?;
+        if removal.merge && removal.replacement.is_none() {
+            self.error = Some("Choose the category to merge into".to_string());
+            self.removal = Some(removal);
+            return None;
+        }
+
+        self.error = None;
+        Some(removal)
+    }
+
+    pub fn category_saved(&mut self) {
+        self.name.clear();
+        self.editor = None;
+        self.error = None;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, CategoriesMessage> {
+        let categories = &state.categories;
+        let tree = category::tree(categories);
+
+        let all_choices: Vec<CategoryChoice> = tree.iter()
+            .map(|(c, _)| CategoryChoice {
+                id: Some(c.id.clone()),
+                name: category::full_name(categories, &c.id),
+            })
+            .collect();
+        // Categories that `id` can be moved under or merged into
+        let choices_outside = |id: &str| -> Vec<CategoryChoice> {
+            all_choices.iter()
+                .filter(|choice| choice.id.as_deref().is_some_and(|other| category::can_set_parent(categories, id, other)))
+                .cloned()
+                .collect()
+        };
+
+        let parent_choices: Vec<CategoryChoice> = std::iter::once(CategoryChoice::none("(Top level)"))
+            .chain(all_choices.iter().cloned())
+            .collect();
+        let selected_parent = parent_choices.iter()
+            .find(|choice| choice.id == self.parent_id)
+            .cloned();
+
+        let add_form = container(
+            column![
+                text("Add Category").size(20),
+                row![
+                    text_input("Name", &self.name)
+                        .on_input(CategoriesMessage::NameChanged)
+                        .on_submit(CategoriesMessage::AddCategory)
+                        .padding(10)
+                        .width(Length::Fixed(240.0)),
+                    text("under"),
+                    pick_list(parent_choices, selected_parent, CategoriesMessage::ParentSelected),
+                    button("Add")
+                        .on_press(CategoriesMessage::AddCategory)
+                        .padding(10),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(10)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box);
+
+        let panel: Element<'a, CategoriesMessage> = match (&self.editor, &self.removal) {
+            (Some(editor), _) => self.editor_view(editor),
+            (None, Some(removal)) => {
+                let choices = choices_outside(&removal.id);
+                self.removal_view(state, removal, choices)
+            }
+            (None, None) => Space::with_height(Length::Shrink).into(),
+        };
+
+        let error = text(self.error.clone().unwrap_or_default())
+            .size(14)
+            .color(Color::from_rgb(0.8, 0.0, 0.0));
+
+        let rows = tree.iter().fold(column![].spacing(5), |column, (category, depth)| {
+            // A category can only move to places outside its own branch
+            let choices: Vec<CategoryChoice> = std::iter::once(CategoryChoice::none("(Top level)"))
+                .chain(choices_outside(&category.id))
+                .collect();
+            let current = choices.iter()
+                .find(|choice| choice.id == category.parent_id)
+                .cloned();
+            let id = category.id.clone();
+            let label = match &category.icon {
+                Some(icon) => format!("{} {}", icon, category.name),
+                None => category.name.clone(),
+            };
+
+            column.push(
+                container(
+                    row![
+                        Space::with_width(Length::Fixed(INDENT * *depth as f32)),
+                        text("■").color(swatch_color(&category.color)),
+                        text(label).width(Length::Fill),
+                        text("Parent:"),
+                        pick_list(choices, current, move |choice| CategoriesMessage::MoveCategory(id.clone(), choice))
+                            .width(Length::Fixed(260.0)),
+                        button("Edit")
+                            .on_press(CategoriesMessage::Edit((*category).clone()))
+                            .style(button::secondary),
+                        button("Merge")
+                            .on_press(CategoriesMessage::Merge(category.id.clone()))
+                            .style(button::secondary),
+                        button("Delete")
+                            .on_press(CategoriesMessage::Delete(category.id.clone()))
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                )
+                .padding(10)
+                .style(container::rounded_box)
+            )
+        });
+
+        let content = column![
+            text("Categories").size(30),
+            text("Spending in a subcategory also counts towards its parent in charts and budgets.").size(14),
+            add_form,
+            panel,
+            error,
+            rows,
+        ]
+        .spacing(20)
+        .padding(20)
+        .width(Length::Fill);
+
+        scrollable(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn editor_view<'a>(&self, editor: &Editor) -> Element<'a, CategoriesMessage> {
+        let palette = PALETTE.iter().fold(Row::new().spacing(5), |row, hex| {
+            let color = swatch_color(hex);
+            let selected = editor.color.eq_ignore_ascii_case(hex);
+            row.push(
+                button(Space::new(Length::Fixed(20.0), Length::Fixed(20.0)))
+                    .on_press(CategoriesMessage::EditColorChanged(hex.to_string()))
+                    .padding(0)
+                    .style(move |_, _| button::Style {
+                        background: Some(color.into()),
+                        border: Border {
+                            color: if selected { Color::BLACK } else { Color::TRANSPARENT },
+                            width: 2.0,
+                            radius: 4.0.into(),
+                        },
+                        ..button::Style::default()
+                    }),
+            )
+        });
+
+        container(
+            column![
+                text("Edit Category").size(20),
+                row![
+                    text("Name:").width(Length::Fixed(80.0)),
+                    text_input("Name", &editor.name)
+                        .on_input(CategoriesMessage::EditNameChanged)
+                        .padding(10)
+                        .width(Length::Fixed(240.0)),
+                    text("Icon:"),
+                    text_input("Optional, e.g. an emoji", &editor.icon)
+                        .on_input(CategoriesMessage::EditIconChanged)
+                        .padding(10)
+                        .width(Length::Fixed(180.0)),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    text("Colour:").width(Length::Fixed(80.0)),
+                    text("■").size(24).color(swatch_color(&editor.color)),
+                    text_input("#RRGGBB", &editor.color)
+                        .on_input(CategoriesMessage::EditColorChanged)
+                        .padding(10)
+                        .width(Length::Fixed(110.0)),
+                    palette,
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    button("Save")
+                        .on_press(CategoriesMessage::SaveEdit)
+                        .padding(10),
+                    button("Cancel")
+                        .on_press(CategoriesMessage::CancelEdit)
+                        .style(button::secondary)
+                        .padding(10),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box)
+        .into()
+    }
+
+    fn removal_view<'a>(&self, state: &UiState, removal: &Removal, choices: Vec<CategoryChoice>) -> Element<'a, CategoriesMessage> {
+        let name = category::full_name(&state.categories, &removal.id);
+        let uses = |category_id: &Option<String>| category_id.as_deref() == Some(removal.id.as_str());
+        let transactions = state.transactions.iter().filter(|t| uses(&t.category_id)).count();
+        let budgets = state.budgets.iter().filter(|b| uses(&b.category_id)).count();
+        let rules = state.category_rules.iter().filter(|r| r.category_id == removal.id).count()
+            + state.recurring_rules.iter().filter(|r| uses(&r.category_id)).count();
+        let subcategories = category::children(&state.categories, &removal.id).len();
+
+        let (title, choices, action, explanation) = if removal.merge {
+            (
+                format!("Merge {}", name),
+                choices,
+                "Merge",
+                "Its transactions, budgets, rules and subcategories move to the category chosen, and it is deleted.",
+            )
+        } else {
+            (
+                format!("Delete {}", name),
+                std::iter::once(CategoryChoice::none("(Leave uncategorized)")).chain(choices).collect(),
+                "Delete",
+                "Its transactions, budgets and rules move to the category chosen. Left uncategorized, its budgets and category rules are deleted. Subcategories move up a level.",
+            )
+        };
+        let selected = choices.iter()
+            .find(|choice| choice.id == removal.replacement)
+            .cloned();
+
+        container(
+            column![
+                text(title).size(20),
+                text(format!(
+                    "Used by {} transaction(s), {} budget(s) and {} rule(s); {} subcategory(ies).",
+                    transactions, budgets, rules, subcategories
+                )),
+                text(explanation).size(14),
+                row![
+                    text(if removal.merge { "Merge into:" } else { "Move to:" }),
+                    pick_list(choices, selected, CategoriesMessage::ReplacementSelected)
+                        .placeholder("Choose a category"),
+                    button(action)
+                        .on_press(CategoriesMessage::ConfirmRemoval)
+                        .style(button::danger)
+                        .padding(10),
+                    button("Cancel")
+                        .on_press(CategoriesMessage::CancelRemoval)
+                        .style(button::secondary)
+                        .padding(10),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(10)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box)
+        .into()
+    }
+}
+
+/// Category names must be set and unique among their siblings.
+fn check_name(categories: &[Category], name: &str, parent_id: Option<&str>, editing: Option<&str>) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Enter a name for the category".to_string());
+    }
+
+    let duplicate = categories.iter().any(|c| {
+        c.parent_id.as_deref() == parent_id && Some(c.id.as_str()) != editing && c.name.eq_ignore_ascii_case(name)
+    });
+    if duplicate {
+        let parent = parent_id
+            .and_then(|id| categories.iter().find(|c| c.id == id))
+            .map(|p| p.name.as_str())
+            .unwrap_or("The top level");
+        return Err(format!("{} already has a category named {}", parent, name));
+    }
+
+    Ok(())
+}
+
+fn check_color(color: &str) -> Result<(), String> {
+    let hex = color.strip_prefix('#').unwrap_or("");
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(format!("Invalid colour: {} (expected #RRGGBB)", color))
+    }
+}
+
+/// The category's `#RRGGBB` colour for use in the UI.
+fn swatch_color(hex: &str) -> Color {
+    let RGBColor(r, g, b) = charts::parse_color(hex);
+    Color::from_rgb8(r, g, b)
+}
@@ -0,0 +1,154 @@
+use chrono::NaiveDate;
+use crate::models::{Account, AccountType, Currency, Money};
+use crate::ui::account::AccountMessage;
+use crate::ui::components::datepicker::create_date_picker;
+use crate::utils::{format_date, today};
+use iced::widget::{button, column, container, pick_list, row, text, text_input};
+use iced::{Alignment, Color, Element, Length};
+
+#[derive(Debug, Clone)]
+pub struct AccountFormState {
+    pub name: String,
+    pub account_type: AccountType,
+    pub opening_balance: String,
+    pub currency: Currency,
+    pub opening_date: NaiveDate,
+    pub editing_id: Option<String>,
+    pub show_date_picker: bool,
+    pub error: Option<String>,
+}
+
+impl Default for AccountFormState {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            account_type: AccountType::Checking,
+            opening_balance: String::new(),
+            currency: Currency::default(),
+            opening_date: today(),
+            editing_id: None,
+            show_date_picker: false,
+            error: None,
+        }
+    }
+}
+
+impl AccountFormState {
+    pub fn from_account(account: &Account) -> Self {
+        Self {
+            name: account.name.clone(),
+            account_type: account.account_type,
+            opening_balance: account.opening_balance.to_input_string(),
+            currency: account.opening_balance.currency(),
+            opening_date: account.opening_date,
+            editing_id: Some(account.id.clone()),
+            ..Self::default()
+        }
+    }
+
+    /// Validates the form and builds the account it describes. A blank
+    /// opening balance means the account starts empty; it may be negative,
+    /// as for a credit card carrying debt.
+    pub fn to_account(&self) -> Result<Account, String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Account name is required".to_string());
+        }
+
+        let opening_balance = if self.opening_balance.trim().is_empty() {
+            Money::zero(self.currency)
+        } else {
+            Money::parse(&self.opening_balance, self.currency).map_err(|e| e.to_string())?
+        };
+
+        let mut account = Account::new(
+            name.to_string(),
+            self.account_type,
+            opening_balance,
+            self.opening_date,
+        );
+        if let Some(id) = &self.editing_id {
+            account.id = id.clone();
+        }
+
+        Ok(account)
+    }
+}
+
+pub fn account_form_view<'a>(state: &AccountFormState) -> Element<'a, AccountMessage> {
+    let name_input = text_input("Account Name", &state.name)
+        .on_input(AccountMessage::NameChanged)
+        .padding(10);
+
+    let type_picker = pick_list(
+        &AccountType::ALL[..],
+        Some(state.account_type),
+        AccountMessage::TypeSelected,
+    );
+
+    let balance_input = text_input("Opening balance", &state.opening_balance)
+        .on_input(AccountMessage::OpeningBalanceChanged)
+        .padding(10);
+
+    let currency_picker = pick_list(
+        &Currency::ALL[..],
+        Some(state.currency),
+        AccountMessage::CurrencySelected,
+    )
+    .padding(10);
+
+    let date_picker = create_date_picker(
+        state.show_date_picker,
+        state.opening_date,
+        button(text(format_date(&state.opening_date)))
+            .on_press(AccountMessage::OpenDatePicker(true))
+            .padding(10),
+        AccountMessage::OpeningDateSelected,
+        AccountMessage::OpenDatePicker(false),
+    );
+
+    let buttons_row = row![
+        button(text("Save"))
+            .on_press(AccountMessage::Save)
+            .padding(10)
+            .style(button::primary),
+        button(text("Cancel"))
+            .on_press(AccountMessage::Cancel)
+            .padding(10)
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let error_text = text(state.error.clone().unwrap_or_default())
+        .size(14)
+        .color(Color::from_rgb(0.8, 0.0, 0.0));
+
+    let title = if state.editing_id.is_some() { "Edit Account" } else { "New Account" };
+
+    container(
+        column![
+            text(title).size(20),
+            name_input,
+            row![
+                text("Type:").width(Length::Fixed(120.0)),
+                type_picker,
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            row![balance_input, currency_picker].spacing(10),
+            row![
+                text("Opening Date:").width(Length::Fixed(120.0)),
+                date_picker,
+            ]
+            .align_y(Alignment::Center),
+            error_text,
+            buttons_row,
+        ]
+        .spacing(10)
+        .padding(20)
+        .width(Length::Fill)
+    )
+    .style(container::rounded_box)
+    .into()
+}
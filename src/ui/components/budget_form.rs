@@ -1,198 +1,329 @@
 use chrono::NaiveDate;
-use crate::models::{Budget, Category};
-use iced::widget::{button, container};
+use crate::models::budget::ALL_SPENDING;
+use crate::models::{Budget, Category, Currency, Money, Renewal};
 use crate::ui::budget::BudgetMessage;
-use iced::{Element, widget::{column, row, text, text_input, pick_list}, Length, Alignment};
-use iced_aw::date_picker;
-use crate::app::{Message, TransactionType};
+use crate::ui::categories::CategoryChoice;
+use crate::ui::components::datepicker::create_date_picker;
+use crate::utils::{format_date, get_month_end, get_month_start};
+use iced::widget::{button, checkbox, column, container, pick_list, row, text, text_input};
+use iced::{Alignment, Color, Element, Length};
+use std::fmt;
 
-#[derive(Debug, Clone, Default)]
+/// How often a budget renews, if at all. The day a budget renewing
+/// monthly from a set day starts on is entered separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenewalChoice {
+    Never,
+    Every(Renewal),
+    OnDay,
+}
+
+impl RenewalChoice {
+    const ALL: [RenewalChoice; 7] = [
+        RenewalChoice::Never,
+        RenewalChoice::Every(Renewal::Weekly),
+        RenewalChoice::Every(Renewal::BiWeekly),
+        RenewalChoice::Every(Renewal::Monthly),
+        RenewalChoice::Every(Renewal::Quarterly),
+        RenewalChoice::Every(Renewal::Yearly),
+        RenewalChoice::OnDay,
+    ];
+}
+
+impl fmt::Display for RenewalChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenewalChoice::Never => write!(f, "Never"),
+            RenewalChoice::Every(renewal) => write!(f, "{}", renewal),
+            RenewalChoice::OnDay => write!(f, "Monthly from a set day"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct BudgetFormState {
     pub name: String,
     pub amount: String,
-    pub start_date: Option<NaiveDate>,
-    pub end_date: Option<NaiveDate>,
+    pub currency: Currency,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Whether a renewing budget carries on until deleted, ignoring
+    /// `end_date`.
+    pub open_ended: bool,
     pub category_id: Option<String>,
-    pub is_editing: bool,
+    pub renewal: RenewalChoice,
+    /// The day of the month a budget renewing on a set day starts on.
+    pub renewal_day: String,
+    pub rollover: bool,
+    /// Comma-separated percentages of the budget at which to raise alerts.
+    pub alert_thresholds: String,
     pub editing_id: Option<String>,
     pub show_start_date_picker: bool,
     pub show_end_date_picker: bool,
     pub error: Option<String>,
-    pub description: String,
-    pub transaction_type: TransactionType,
+}
+
+impl Default for BudgetFormState {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            amount: String::new(),
+            currency: Currency::default(),
+            start_date: get_month_start(),
+            end_date: get_month_end(),
+            open_ended: false,
+            category_id: None,
+            renewal: RenewalChoice::Never,
+            renewal_day: String::new(),
+            rollover: false,
+            alert_thresholds: "50, 80, 100".to_string(),
+            editing_id: None,
+            show_start_date_picker: false,
+            show_end_date_picker: false,
+            error: None,
+        }
+    }
+}
+
+impl BudgetFormState {
+    pub fn from_budget(budget: &Budget) -> Self {
+        let (renewal, renewal_day) = match budget.renewal {
+            Some(Renewal::MonthlyOnDay(day)) => (RenewalChoice::OnDay, day.to_string()),
+            Some(renewal) => (RenewalChoice::Every(renewal), String::new()),
+            None => (RenewalChoice::Never, String::new()),
+        };
+
+        Self {
+            name: budget.name.clone(),
+            amount: budget.amount.to_input_string(),
+            currency: budget.amount.currency(),
+            start_date: budget.start_date,
+            end_date: budget.end_date.unwrap_or_else(get_month_end),
+            open_ended: budget.end_date.is_none(),
+            category_id: budget.category_id.clone(),
+            renewal,
+            renewal_day,
+            rollover: budget.rollover,
+            alert_thresholds: budget.alert_thresholds.iter()
+                .map(|threshold| threshold.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            editing_id: Some(budget.id.clone()),
+            ..Self::default()
+        }
+    }
+
+    /// Validates the form and builds the budget it describes.
+    pub fn to_budget(&self) -> Result<Budget, String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Budget name is required".to_string());
+        }
+
+        let amount = Money::parse(&self.amount, self.currency)
+            .map_err(|e| e.to_string())?;
+        if amount.is_negative() || amount.is_zero() {
+            return Err("Amount must be greater than zero".to_string());
+        }
+
+        let renewal = match self.renewal {
+            RenewalChoice::Never => None,
+            RenewalChoice::Every(renewal) => Some(renewal),
+            RenewalChoice::OnDay => {
+                let day = self.renewal_day.trim().parse::<u32>().ok()
+                    .filter(|day| (1..=31).contains(day))
+                    .ok_or("Enter the day of the month the budget renews on, from 1 to 31")?;
+                Some(Renewal::MonthlyOnDay(day))
+            }
+        };
+
+        // Only a renewing budget can run without an end date
+        let end_date = if self.open_ended && renewal.is_some() {
+            None
+        } else {
+            if self.end_date < self.start_date {
+                return Err("End date must not be before start date".to_string());
+            }
+            Some(self.end_date)
+        };
+
+        let mut alert_thresholds = Vec::new();
+        for threshold in self.alert_thresholds.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let percent = threshold.trim_end_matches('%').trim().parse::<u32>().ok()
+                .filter(|percent| (1..=1000).contains(percent))
+                .ok_or_else(|| format!("Alert thresholds must be percentages from 1 to 1000, not {}", threshold))?;
+            alert_thresholds.push(percent);
+        }
+        alert_thresholds.sort_unstable();
+        alert_thresholds.dedup();
+
+        let mut budget = Budget::new(
+            name.to_string(),
+            self.category_id.clone(),
+            amount,
+            self.start_date,
+            end_date,
+            renewal,
+            self.rollover,
+        );
+        if let Some(id) = &self.editing_id {
+            budget.id = id.clone();
+        }
+        budget.alert_thresholds = alert_thresholds;
+
+        Ok(budget)
+    }
 }
 
 pub fn budget_form_view<'a>(
-    budget: &'a Budget,
     categories: &'a [Category],
     state: &BudgetFormState,
-    is_editing: bool,
 ) -> Element<'a, BudgetMessage> {
-    let name_input = text_input::TextInput::new(
-        "Budget Name",
-        &state.name_input,
-        BudgetMessage::NameChanged,
-    )
-    .padding(10);
-    
-    let amount_input = text_input::TextInput::new(
-        "Amount",
-        &state.amount_input,
-        BudgetMessage::AmountChanged,
+    let name_input = text_input("Budget Name", &state.name)
+        .on_input(BudgetMessage::NameChanged)
+        .padding(10);
+
+    let amount_input = text_input("Amount", &state.amount)
+        .on_input(BudgetMessage::AmountChanged)
+        .padding(10);
+
+    let currency_picker = pick_list(
+        &Currency::ALL[..],
+        Some(state.currency),
+        BudgetMessage::CurrencySelected,
     )
     .padding(10);
-    
-    // Category picker
-    let category_options: Vec<(String, String)> = categories
-        .iter()
-        .map(|c| (c.id.clone(), c.name.clone()))
-        .collect();
-        
-    let category_picker = pick_list::PickList::new(
-        &category_options[..],
-        budget.category_id.clone(),
-        |id| BudgetMessage::CategorySelected(id),
+
+    // Tracking all spending is a choice of its own, listed first
+    let category_choices = CategoryChoice::all(categories, ALL_SPENDING);
+    let selected_category = category_choices.iter()
+        .find(|choice| choice.id == state.category_id)
+        .cloned();
+
+    let category_picker = pick_list(
+        category_choices,
+        selected_category,
+        |choice: CategoryChoice| BudgetMessage::CategorySelected(choice.id),
     );
-    
-    // Start date picker
-    let start_date_button = button::Button::new(
-        text::Text::new(format!("Start Date: {}", budget.start_date.format("%Y-%m-%d")))
-    )
-    .on_press(BudgetMessage::OpenStartDatePicker(true))
-    .padding(10);
-    
-    let start_datepicker = if state.show_start_date_picker {
-        date_picker::DatePicker::new(budget.start_date)
-            .show(true)
-            .on_cancel(|_| BudgetMessage::OpenStartDatePicker(false))
-            .on_submit(BudgetMessage::StartDateSelected)
-    } else {
-        date_picker::DatePicker::new(budget.start_date)
-            .show(false)
-            .on_cancel(|_| BudgetMessage::OpenStartDatePicker(false))
-            .on_submit(BudgetMessage::StartDateSelected)
-    };
-    
-    // End date picker
-    let end_date_button = button::Button::new(
-        text::Text::new(format!("End Date: {}", budget.end_date.format("%Y-%m-%d")))
-    )
-    .on_press(BudgetMessage::OpenEndDatePicker(true))
-    .padding(10);
-    
-    let end_datepicker = if state.show_end_date_picker {
-        date_picker::DatePicker::new(budget.end_date)
-            .show(true)
-            .on_cancel(|_| BudgetMessage::OpenEndDatePicker(false))
-            .on_submit(BudgetMessage::EndDateSelected)
-    } else {
-        date_picker::DatePicker::new(budget.end_date)
-            .show(false)
-            .on_cancel(|_| BudgetMessage::OpenEndDatePicker(false))
-            .on_submit(BudgetMessage::EndDateSelected)
-    };
-    
-    // Buttons
-    let submit_button = button::Button::new(text::Text::new("Save"))
-        .on_press(BudgetMessage::Submit)
+
+    let start_date_picker = create_date_picker(
+        state.show_start_date_picker,
+        state.start_date,
+        button(text(format_date(&state.start_date)))
+            .on_press(BudgetMessage::OpenStartDatePicker(true))
+            .padding(10),
+        BudgetMessage::StartDateSelected,
+        BudgetMessage::OpenStartDatePicker(false),
+    );
+
+    let end_date_picker = create_date_picker(
+        state.show_end_date_picker,
+        state.end_date,
+        button(text(format_date(&state.end_date)))
+            .on_press(BudgetMessage::OpenEndDatePicker(true))
+            .padding(10),
+        BudgetMessage::EndDateSelected,
+        BudgetMessage::OpenEndDatePicker(false),
+    );
+
+    let renewal_picker = pick_list(
+        &RenewalChoice::ALL[..],
+        Some(state.renewal),
+        BudgetMessage::RenewalSelected,
+    );
+
+    let renewing = state.renewal != RenewalChoice::Never;
+
+    // Only a renewing budget has a next period to carry into
+    let rollover_toggle = checkbox("Carry what is left, or overspent, into the next period", state.rollover)
+        .on_toggle_maybe(renewing.then_some(BudgetMessage::RolloverToggled));
+
+    let open_ended_toggle = checkbox("No end date", state.open_ended)
+        .on_toggle_maybe(renewing.then_some(BudgetMessage::OpenEndedToggled));
+
+    let mut end_date_row = row![text("End Date:").width(Length::Fixed(100.0))]
+        .spacing(10)
+        .align_y(Alignment::Center);
+    if !(renewing && state.open_ended) {
+        end_date_row = end_date_row.push(end_date_picker);
+    }
+    end_date_row = end_date_row.push(open_ended_toggle);
+
+    let mut renewal_row = row![
+        text("Renews:").width(Length::Fixed(100.0)),
+        renewal_picker,
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+    if state.renewal == RenewalChoice::OnDay {
+        renewal_row = renewal_row
+            .push(text("on day"))
+            .push(
+                text_input("1-31", &state.renewal_day)
+                    .on_input(BudgetMessage::RenewalDayChanged)
+                    .padding(10)
+                    .width(Length::Fixed(60.0)),
+            );
+    }
+
+    let alerts_row = row![
+        text("Alert at:").width(Length::Fixed(100.0)),
+        text_input("e.g. 50, 80, 100", &state.alert_thresholds)
+            .on_input(BudgetMessage::AlertThresholdsChanged)
+            .padding(10)
+            .width(Length::Fixed(200.0)),
+        text("% of the budget spent"),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let submit_button = button(text("Save"))
+        .on_press(BudgetMessage::Save)
         .padding(10)
-        .style(Button::Primary);
-        
-    let cancel_button = button::Button::new(text::Text::new("Cancel"))
+        .style(button::primary);
+
+    let cancel_button = button(text("Cancel"))
         .on_press(BudgetMessage::Cancel)
         .padding(10)
-        .style(Button::Secondary);
-        
-    let buttons_row = row::Row::new()
-        .push(submit_button)
-        .push(cancel_button)
-        .spacing(10)
-        .align_items(Alignment::Center);
-        
-    // Error message
-    let error_text = if let Some(error) = &state.error {
-        text::Text::new(error)
-            .size(14)
-            .style(iced::Color::from_rgb(0.8, 0.0, 0.0))
-    } else {
-        text::Text::new("")
-    };
-    
-    let title = text::Text::new("Add New Transaction")
-        .size(24);
-    
-    let transaction_types = vec![
-        TransactionType::Income,
-        TransactionType::Expense,
-    ];
-    
-    let transaction_type_dropdown = pick_list::PickList::new(
-        &transaction_types[..],
-        Some(state.transaction_type),
-        Message::TransactionTypeSelected
-    )
-    .placeholder("Type");
-    
-    let description_input = text_input::TextInput::new(
-        "Description",
-        &state.description,
-        Message::DescriptionChanged
-    )
-    .padding(10);
-    
-    let category_input = text_input::TextInput::new(
-        "Category",
-        &state.category,
-        Message::CategoryChanged
-    )
-    .padding(10);
-    
-    let add_button = button::Button::new(
-        text::Text::new("Add Transaction")
-    )
-    .on_press(Message::AddTransaction)
-    .padding(10);
-    
-    let form_row1 = row::Row::new()
-        .spacing(10)
-        .push(transaction_type_dropdown)
-        .push(description_input);
-    
-    let form_row2 = row::Row::new()
+        .style(button::secondary);
+
+    let buttons_row = row![submit_button, cancel_button]
         .spacing(10)
-        .push(category_input)
-        .push(amount_input);
-    
-    container::Container::new(
-        Column::new()
-            .push(Text::new(if is_editing { "Edit Budget" } else { "New Budget" }).size(20))
-            .push(name_input)
-            .push(amount_input)
-            .push(
-                Row::new()
-                    .push(Text::new("Category:").width(Length::Fixed(100.0)))
-                    .push(category_picker)
-                    .align_items(Alignment::Center)
-            )
-            .push(
-                Row::new()
-                    .push(Text::new("Start Date:").width(Length::Fixed(100.0)))
-                    .push(start_date_button)
-                    .align_items(Alignment::Center)
-            )
-            .push(start_datepicker)
-            .push(
-                Row::new()
-                    .push(Text::new("End Date:").width(Length::Fixed(100.0)))
-                    .push(end_date_button)
-                    .align_items(Alignment::Center)
-            )
-            .push(end_datepicker)
-            .push(error_text)
-            .push(buttons_row)
+        .align_y(Alignment::Center);
+
+    let error_text = text(state.error.clone().unwrap_or_default())
+        .size(14)
+        .color(Color::from_rgb(0.8, 0.0, 0.0));
+
+    let title = if state.editing_id.is_some() { "Edit Budget" } else { "New Budget" };
+
+    container(
+        column![
+            text(title).size(20),
+            name_input,
+            row![amount_input, currency_picker].spacing(10),
+            row![
+                text("Category:").width(Length::Fixed(100.0)),
+                category_picker,
+            ]
             .spacing(10)
-            .padding(20)
-            .width(Length::Fill)
-            .style(Container::Box)
-    ).into()
+            .align_y(Alignment::Center),
+            row![
+                text("Start Date:").width(Length::Fixed(100.0)),
+                start_date_picker,
+            ]
+            .align_y(Alignment::Center),
+            end_date_row,
+            renewal_row,
+            rollover_toggle,
+            alerts_row,
+            error_text,
+            buttons_row,
+        ]
+        .spacing(10)
+        .padding(20)
+        .width(Length::Fill)
+    )
+    .style(container::rounded_box)
+    .into()
 }
@@ -0,0 +1,244 @@
+use crate::models::{Account, Category, CategoryRule, Currency, MatchKind, Money, Payee};
+use crate::ui::payees::PayeeChoice;
+use crate::ui::rules::RulesMessage;
+use iced::widget::{button, column, container, pick_list, row, text, text_input};
+use iced::{Alignment, Color, Element, Length};
+use std::fmt;
+
+/// An entry of the account picker, which also offers "any account".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountChoice {
+    pub id: Option<String>,
+    name: String,
+}
+
+impl fmt::Display for AccountChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CategoryRuleFormState {
+    pub name: String,
+    pub match_kind: MatchKind,
+    pub pattern: String,
+    pub min_amount: String,
+    pub max_amount: String,
+    pub currency: Currency,
+    pub account_id: Option<String>,
+    pub payee_id: Option<String>,
+    pub category_id: Option<String>,
+    pub priority: i64,
+    pub editing_id: Option<String>,
+    pub error: Option<String>,
+}
+
+impl Default for CategoryRuleFormState {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            match_kind: MatchKind::Contains,
+            pattern: String::new(),
+            min_amount: String::new(),
+            max_amount: String::new(),
+            currency: Currency::default(),
+            account_id: None,
+            payee_id: None,
+            category_id: None,
+            priority: 0,
+            editing_id: None,
+            error: None,
+        }
+    }
+}
+
+impl CategoryRuleFormState {
+    pub fn from_rule(rule: &CategoryRule) -> Self {
+        Self {
+            name: rule.name.clone(),
+            match_kind: rule.match_kind,
+            pattern: rule.pattern.clone(),
+            min_amount: rule.min_amount.map(|a| a.to_input_string()).unwrap_or_default(),
+            max_amount: rule.max_amount.map(|a| a.to_input_string()).unwrap_or_default(),
+            currency: rule.min_amount.or(rule.max_amount).map(|a| a.currency()).unwrap_or_default(),
+            account_id: rule.account_id.clone(),
+            payee_id: rule.payee_id.clone(),
+            category_id: Some(rule.category_id.clone()),
+            priority: rule.priority,
+            editing_id: Some(rule.id.clone()),
+            error: None,
+        }
+    }
+
+    /// Validates the form and builds the rule it describes. Blank amount
+    /// bounds are left open.
+    pub fn to_rule(&self) -> Result<CategoryRule, String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Rule name is required".to_string());
+        }
+
+        let pattern = self.pattern.trim();
+        if self.match_kind != MatchKind::Any && pattern.is_empty() {
+            return Err("Enter the text to look for in the description".to_string());
+        }
+
+        let Some(category_id) = self.category_id.clone() else {
+            return Err("Select the category to assign".to_string());
+        };
+
+        let parse_bound = |value: &str| -> Result<Option<Money>, String> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            let amount = Money::parse(value, self.currency).map_err(|e| e.to_string())?;
+            if amount.is_negative() {
+                return Err("Amount bounds cannot be negative".to_string());
+            }
+            Ok(Some(amount))
+        };
+        let min_amount = parse_bound(&self.min_amount)?;
+        let max_amount = parse_bound(&self.max_amount)?;
+        if let (Some(min), Some(max)) = (min_amount, max_amount) {
+            if min.minor_units() > max.minor_units() {
+                return Err("The minimum amount is above the maximum".to_string());
+            }
+        }
+
+        let mut rule = CategoryRule::new(name.to_string(), self.match_kind, pattern.to_string(), category_id);
+        rule.min_amount = min_amount;
+        rule.max_amount = max_amount;
+        rule.account_id = self.account_id.clone();
+        rule.payee_id = self.payee_id.clone();
+        rule.priority = self.priority;
+        if let Some(id) = &self.editing_id {
+            rule.id = id.clone();
+        }
+
+        rule.regex().map_err(|e| format!("Invalid regex: {}", e))?;
+
+        Ok(rule)
+    }
+}
+
+pub fn category_rule_form_view<'a>(
+    state: &CategoryRuleFormState,
+    categories: &'a [Category],
+    accounts: &'a [Account],
+    payees: &'a [Payee],
+) -> Element<'a, RulesMessage> {
+    let label = |label: &'static str| text(label).width(Length::Fixed(120.0));
+
+    let name_input = text_input("Rule Name", &state.name)
+        .on_input(RulesMessage::NameChanged)
+        .padding(10);
+
+    let mut description_row = row![
+        label("Description:"),
+        pick_list(&MatchKind::ALL[..], Some(state.match_kind), RulesMessage::MatchKindSelected),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+    if state.match_kind != MatchKind::Any {
+        let placeholder = if state.match_kind == MatchKind::Regex { "e.g. ^(AMZN|Amazon)" } else { "e.g. coffee" };
+        description_row = description_row.push(
+            text_input(placeholder, &state.pattern)
+                .on_input(RulesMessage::PatternChanged)
+                .padding(10)
+        );
+    }
+
+    let amount_row = row![
+        label("Amount:"),
+        text_input("Minimum", &state.min_amount)
+            .on_input(RulesMessage::MinAmountChanged)
+            .padding(10),
+        text("to"),
+        text_input("Maximum", &state.max_amount)
+            .on_input(RulesMessage::MaxAmountChanged)
+            .padding(10),
+        pick_list(&Currency::ALL[..], Some(state.currency), RulesMessage::CurrencySelected)
+            .padding(10),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let account_choices: Vec<AccountChoice> = std::iter::once(AccountChoice {
+        id: None,
+        name: "Any account".to_string(),
+    })
+    .chain(accounts.iter().map(|a| AccountChoice { id: Some(a.id.clone()), name: a.name.clone() }))
+    .collect();
+    let selected_account = account_choices.iter().find(|c| c.id == state.account_id).cloned();
+
+    let account_row = row![
+        label("Account:"),
+        pick_list(account_choices, selected_account, RulesMessage::AccountSelected),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let payee_row = row![
+        label("Payee:"),
+        pick_list(
+            PayeeChoice::all(payees, "Any payee"),
+            Some(PayeeChoice::selected(payees, state.payee_id.as_deref(), "Any payee")),
+            RulesMessage::PayeeSelected,
+        ),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let selected_category = state.category_id.as_ref()
+        .and_then(|id| categories.iter().find(|c| &c.id == id))
+        .cloned();
+    let category_row = row![
+        label("Set category:"),
+        pick_list(categories, selected_category, |category: Category| {
+            RulesMessage::CategorySelected(category.id)
+        })
+        .placeholder("Select category"),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let buttons_row = row![
+        button(text("Save"))
+            .on_press(RulesMessage::Save)
+            .padding(10)
+            .style(button::primary),
+        button(text("Cancel"))
+            .on_press(RulesMessage::Cancel)
+            .padding(10)
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let error_text = text(state.error.clone().unwrap_or_default())
+        .size(14)
+        .color(Color::from_rgb(0.8, 0.0, 0.0));
+
+    let title = if state.editing_id.is_some() { "Edit Rule" } else { "New Rule" };
+
+    container(
+        column![
+            text(title).size(20),
+            name_input,
+            description_row,
+            amount_row,
+            account_row,
+            payee_row,
+            category_row,
+            error_text,
+            buttons_row,
+        ]
+        .spacing(10)
+        .padding(20)
+        .width(Length::Fill)
+    )
+    .style(container::rounded_box)
+    .into()
+}
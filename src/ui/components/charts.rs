@@ -1,394 +1,507 @@
-use crate::models::{Budget, Category, Transaction, TransactionType};
-use chrono::{Datelike, Duration};
+use crate::models::category;
+use crate::models::{Budget, BudgetPeriod, Category, CurrencyConverter, DateRange, Money, Transaction, TransactionType};
+use crate::utils::{calculate_percentage, format_currency, month_end, today};
+use chrono::{Datelike, NaiveDate};
 use iced::{Element, Length, Alignment};
-use iced::widget::{column, text, container};
+use iced::widget::{column, container, progress_bar, row, text};
+use plotters::coord::Shift;
 use plotters::prelude::*;
-use plotters_iced::ChartWidget;
+use plotters_iced::{Chart, ChartWidget};
 use std::collections::HashMap;
 
 const CHART_HEIGHT: u16 = 300;
 const CHART_WIDTH: u16 = 600;
 
-pub fn create_expense_by_category_chart(
-    transactions: &[Transaction],
-    categories: &[Category],
-) -> Element<'static, crate::ui::Message> {
-    let mut category_totals: HashMap<String, f64> = HashMap::new();
-    let category_map: HashMap<String, &Category> = categories
-        .iter()
-        .map(|c| (c.id.clone(), c))
-        .collect();
-
-    // Sum transactions by category
-    for transaction in transactions.iter() {
-        if transaction.is_expense() {
-            if let Some(cat_id) = &transaction.category_id {
-                *category_totals.entry(cat_id.clone()).or_insert(0.0) += transaction.amount;
-            } else {
-                *category_totals.entry("uncategorized".to_string()).or_insert(0.0) += transaction.amount;
+/// The fewest months shown in the income vs expenses trend.
+const TREND_MONTHS: u32 = 6;
+
+type DrawResult<DB> = Result<(), DrawingAreaErrorKind<<DB as DrawingBackend>::ErrorType>>;
+
+pub struct ExpenseByCategoryChart {
+    // (label, color, amount), largest first
+    slices: Vec<(String, RGBColor, Money)>,
+}
+
+impl ExpenseByCategoryChart {
+    /// Expenses by top-level category in the base currency, the five
+    /// largest. Subcategories are counted in their top-level category.
+    pub fn new(transactions: &[Transaction], categories: &[Category], converter: &CurrencyConverter) -> Self {
+        let zero = Money::zero(converter.base());
+        let mut category_totals: HashMap<String, Money> = HashMap::new();
+        let category_map: HashMap<String, &Category> = categories
+            .iter()
+            .map(|c| (c.id.clone(), c))
+            .collect();
+
+        // Sum transactions by category in the base currency
+        for transaction in transactions.iter() {
+            if !transaction.is_expense() {
+                continue;
+            }
+            // Split transactions count each line under its own category
+            for (cat_id, amount) in transaction.category_amounts() {
+                let Some(amount) = converter.to_base(amount, transaction.date) else {
+                    continue;
+                };
+
+                if let Some(cat_id) = cat_id {
+                    let root = category::root_id(categories, cat_id);
+                    *category_totals.entry(root.to_string()).or_insert(zero) += amount;
+                } else {
+                    *category_totals.entry("uncategorized".to_string()).or_insert(zero) += amount;
+                }
             }
         }
-    }
 
-    // Sort categories by amount (descending)
-    let mut categories_sorted: Vec<(String, f64)> = category_totals.into_iter().collect();
-    categories_sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        // Sort categories by amount (descending)
+        let mut categories_sorted: Vec<(String, Money)> = category_totals.into_iter().collect();
+        categories_sorted.sort_by_key(|(_, amount)| std::cmp::Reverse(amount.minor_units()));
+
+        let slices = categories_sorted
+            .into_iter()
+            .take(5)
+            .map(|(cat_id, amount)| {
+                let category_name = match category_map.get(&cat_id) {
+                    Some(cat) => cat.name.clone(),
+                    None => if cat_id == "uncategorized" { "Uncategorized".to_string() } else { "Unknown".to_string() }
+                };
+
+                let color = match category_map.get(&cat_id) {
+                    Some(cat) => parse_color(&cat.color),
+                    None => parse_color("#CCCCCC"),
+                };
 
-    let chart = ChartWidget::new(move |_, area| {
-        let backend = plotters_iced::DrawingBackend::new(area);
-        let root = backend.into_drawing_area();
+                (category_name, color, amount)
+            })
+            .collect();
+
+        Self { slices }
+    }
+
+    pub fn render<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> DrawResult<DB> {
         root.fill(&WHITE)?;
 
-        let total: f64 = categories_sorted.iter().map(|c| c.1).sum();
-        if total == 0.0 {
+        let total: i64 = self.slices.iter().map(|s| s.2.minor_units()).sum();
+        if total == 0 {
             let style = TextStyle::from(("sans-serif", 20).into_font()).color(&BLACK);
             root.draw_text("No expense data available", &style, (150, 150))?;
             return Ok(());
         }
 
-        let mut chart = ChartBuilder::on(&root)
-            .caption("Expenses by Category", ("sans-serif", 20))
-            .build_cartesian_2d(0.0..1.0, 0.0..1.0)?;
-
-        let mut start_angle = 0.0;
-        for (i, (cat_id, amount)) in categories_sorted.iter().enumerate().take(5) {
-            let percentage = amount / total;
-            let end_angle = start_angle + percentage * 360.0;
-            
-            let category_name = match category_map.get(cat_id) {
-                Some(cat) => cat.name.clone(),
-                None => if cat_id == "uncategorized" { "Uncategorized".to_string() } else { "Unknown".to_string() }
-            };
-            
-            let color_hex = match category_map.get(cat_id) {
-                Some(cat) => cat.color.clone(),
-                None => "#CCCCCC".to_string()
-            };
-            
-            let color = parse_color(&color_hex);
-            
-            // Draw pie slice
-            chart.draw_series(std::iter::once(Pie::new(
-                (0.5, 0.5),
-                0.4,
-                (start_angle, end_angle),
-                color.filled(),
-            )))?;
-            
-            // Draw label
-            let angle = (start_angle + end_angle) / 2.0 * std::f64::consts::PI / 180.0;
-            let x = 0.5 + 0.45 * angle.cos();
-            let y = 0.5 + 0.45 * angle.sin();
-            
-            let label = format!("{}: ${:.2} ({:.1}%)", category_name, amount, percentage * 100.0);
-            
-            root.draw_text(
-                &label,
-                &TextStyle::from(("sans-serif", 12).into_font()).color(&BLACK),
-                (
-                    (x * area.width() as f64) as i32,
-                    (y * area.height() as f64) as i32,
-                ),
-            )?;
-            
-            start_angle = end_angle;
-        }
+        let (width, height) = root.dim_in_pixel();
+        let center = (width as i32 / 2, height as i32 / 2);
+        let radius = f64::from(width.min(height)) * 0.35;
+
+        let sizes: Vec<f64> = self.slices.iter().map(|s| s.2.to_f64()).collect();
+        let colors: Vec<RGBColor> = self.slices.iter().map(|s| s.1).collect();
+        let labels: Vec<String> = self.slices
+            .iter()
+            .map(|(name, _, amount)| {
+                let percentage = calculate_percentage(amount.minor_units() as f64, total as f64);
+                format!("{}: {} ({:.1}%)", name, format_currency(*amount), percentage)
+            })
+            .collect();
+
+        let mut pie = Pie::new(&center, &radius, &sizes, &colors, &labels);
+        pie.label_style(("sans-serif", 12).into_font().color(&BLACK));
+        root.draw(&pie)?;
 
         Ok(())
-    });
+    }
+}
+
+impl<Message> Chart<Message> for ExpenseByCategoryChart {
+    type State = ();
 
-    Column::new()
-        .push(
-            Text::new("Expenses by Category")
-                .size(20)
-                .style(Text::Default)
-        )
-        .push(
-            Container::new(chart.height(Length::Fixed(CHART_HEIGHT as f32)))
-                .width(Length::Fixed(CHART_WIDTH as f32))
-                .style(Container::Box)
-        )
-        .spacing(10)
-        .align_items(Alignment::Center)
-        .into()
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, _builder: ChartBuilder<DB>) {}
+
+    fn draw_chart<DB: DrawingBackend>(&self, _state: &Self::State, root: DrawingArea<DB, Shift>) {
+        if let Err(e) = self.render(&root) {
+            eprintln!("Failed to draw expense chart: {:?}", e);
+        }
+    }
 }
 
-pub fn create_income_vs_expense_chart(
+pub fn create_expense_by_category_chart<'a, Message: 'a>(
     transactions: &[Transaction],
-) -> Element<'static, crate::ui::Message> {
-    // Group transactions by month
-    let mut monthly_data: HashMap<(i32, u32), (f64, f64)> = HashMap::new(); // (year, month) -> (income, expenses)
-    
-    let now = chrono::Local::now().naive_local().date();
-    let six_months_ago = now - Duration::days(180);
-    
-    // Initialize past 6 months
-    for i in 0..6 {
-        let date = six_months_ago + Duration::days(30 * i);
-        monthly_data.insert((date.year(), date.month()), (0.0, 0.0));
-    }
-    
-    // Sum transactions by month
-    for transaction in transactions.iter() {
-        if transaction.date >= six_months_ago {
+    categories: &[Category],
+    converter: &CurrencyConverter,
+) -> Element<'a, Message> {
+    let chart = ChartWidget::new(ExpenseByCategoryChart::new(transactions, categories, converter))
+        .height(Length::Fixed(CHART_HEIGHT as f32));
+
+    column![
+        text("Expenses by Category").size(20),
+        container(chart)
+            .width(Length::Fixed(CHART_WIDTH as f32))
+            .style(container::rounded_box),
+    ]
+    .spacing(10)
+    .align_x(Alignment::Center)
+    .into()
+}
+
+pub struct IncomeVsExpenseChart {
+    // ((year, month), (income, expenses)), oldest first
+    months: Vec<((i32, u32), (f64, f64))>,
+    symbol: &'static str,
+}
+
+impl IncomeVsExpenseChart {
+    /// Income and expenses in the base currency for each month of `range`,
+    /// going back further when needed to show at least `TREND_MONTHS`.
+    pub fn new(transactions: &[Transaction], converter: &CurrencyConverter, range: DateRange) -> Self {
+        // Group transactions by month, in the base currency
+        let zero = Money::zero(converter.base());
+        let mut monthly_data: HashMap<(i32, u32), (Money, Money)> = HashMap::new(); // (year, month) -> (income, expenses)
+
+        let mut months = range.months();
+        if months.len() < TREND_MONTHS as usize {
+            months = DateRange::months_ending(range.to, TREND_MONTHS).months();
+        }
+        let first_month = months[0];
+        let last_day = month_end(range.to);
+
+        // Initialize every month so empty ones still get a slot
+        for date in &months {
+            monthly_data.insert((date.year(), date.month()), (zero, zero));
+        }
+
+        // Sum transactions by month
+        for transaction in transactions.iter() {
+            // Transfers between accounts are neither income nor spending
+            if transaction.date < first_month || transaction.date > last_day || transaction.is_transfer() {
+                continue;
+            }
+            let Some(amount) = converter.to_base(transaction.amount, transaction.date) else {
+                continue;
+            };
+
             let key = (transaction.date.year(), transaction.date.month());
-            let entry = monthly_data.entry(key).or_insert((0.0, 0.0));
-            
+            let entry = monthly_data.entry(key).or_insert((zero, zero));
+
             match transaction.transaction_type {
-                TransactionType::Income => entry.0 += transaction.amount,
-                TransactionType::Expense => entry.1 += transaction.amount,
+                TransactionType::Income => entry.0 += amount,
+                TransactionType::Expense => entry.1 += amount,
+                TransactionType::Transfer => {}
             }
         }
+
+        // Sort months
+        let mut months: Vec<((i32, u32), (f64, f64))> = monthly_data
+            .into_iter()
+            .map(|(month, (income, expenses))| (month, (income.to_f64(), expenses.to_f64())))
+            .collect();
+        months.sort_by_key(|((year, month), _)| *year * 100 + *month as i32);
+
+        Self { months, symbol: converter.base().symbol() }
     }
-    
-    // Sort months
-    let mut monthly_data_sorted: Vec<((i32, u32), (f64, f64))> = monthly_data.into_iter().collect();
-    monthly_data_sorted.sort_by_key(|((year, month), _)| (*year * 100 + *month as i32));
-    
-    let chart = ChartWidget::new(move |_, area| {
-        let backend = plotters_iced::DrawingBackend::new(area);
-        let root = backend.into_drawing_area();
-        root.fill(&WHITE)?;
-        
-        let max_amount = monthly_data_sorted.iter()
-            .flat_map(|(_, (income, expenses))| vec![*income, *expenses])
-            .fold(0.0, |acc, val| if val > acc { val } else { acc });
-            
-        let x_labels: Vec<String> = monthly_data_sorted.iter()
+
+    pub fn render<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> DrawResult<DB> {
+        let max_amount = self.months.iter()
+            .flat_map(|(_, (income, expenses))| [*income, *expenses])
+            .fold(0.0, f64::max)
+            .max(1.0);
+
+        let x_labels: Vec<String> = self.months.iter()
             .map(|((year, month), _)| format!("{}/{}", month, year % 100))
             .collect();
-            
-        let mut chart = ChartBuilder::on(&root)
+
+        let mut chart = ChartBuilder::on(root)
             .caption("Income vs Expenses", ("sans-serif", 20))
             .margin(5)
             .x_label_area_size(40)
-            .y_label_area_size(40)
-            .build_cartesian_2d(0..monthly_data_sorted.len(), 0.0..max_amount * 1.1)?;
-            
+            .y_label_area_size(60)
+            .build_cartesian_2d(-0.5..self.months.len() as f64 - 0.5, 0.0..max_amount * 1.1)?;
+
         chart.configure_mesh()
-            .x_labels(monthly_data_sorted.len())
-            .x_label_formatter(&|idx| {
-                if *idx < x_labels.len() {
-                    x_labels[*idx].clone()
-                } else {
-                    "".to_string()
-                }
-            })
-            .y_label_formatter(&|y| format!("${:.0}", y))
+            .disable_x_mesh()
+            .x_labels(self.months.len())
+            .x_label_formatter(&|x| segment_label(*x, &x_labels))
+            .y_label_formatter(&|y| format!("{}{:.0}", self.symbol, y))
             .draw()?;
-            
-        // Draw income bars
+
+        // Income bars sit left of the month tick, expense bars to the right
         chart.draw_series(
-            monthly_data_sorted.iter().enumerate().map(|(i, (_, (income, _)))| {
-                let bar_width = 0.3;
-                Rectangle::new(
-                    [(i as f32 - bar_width as f32 / 2.0) as i32, 0.0],
-                    [(i as f32 + bar_width as f32 / 2.0) as i32, *income],
-                    GREEN.filled(),
-                )
+            self.months.iter().enumerate().map(|(i, (_, (income, _)))| {
+                let x = i as f64;
+                Rectangle::new([(x - 0.35, 0.0), (x, *income)], GREEN.filled())
             })
-        )?;
-        
-        // Draw expense bars
+        )?
+        .label("Income")
+        .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], GREEN.filled()));
+
         chart.draw_series(
-            monthly_data_sorted.iter().enumerate().map(|(i, (_, (_, expense)))| {
-                let bar_width = 0.3;
-                Rectangle::new(
-                    [(i as f32 + bar_width as f32 / 2.0) as i32, 0.0],
-                    [(i as f32 + 3.0 * bar_width as f32 / 2.0) as i32, *expense],
-                    RED.filled(),
-                )
+            self.months.iter().enumerate().map(|(i, (_, (_, expenses)))| {
+                let x = i as f64;
+                Rectangle::new([(x, 0.0), (x + 0.35, *expenses)], RED.filled())
             })
-        )?;
-        
-        // Add legend
+        )?
+        .label("Expenses")
+        .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], RED.filled()));
+
         chart.configure_series_labels()
-            .background_style(&WHITE)
-            .border_style(&BLACK)
+            .position(SeriesLabelPosition::UpperRight)
+            .background_style(WHITE)
+            .border_style(BLACK)
             .draw()?;
-            
-        root.draw_series(std::iter::once(Rectangle::new(
-            [(area.width() - 120) as i32, 10],
-            [(area.width() - 100) as i32, 20],
-            GREEN.filled(),
-        )))?;
-        root.draw_text("Income", &("sans-serif", 12).into_font().color(&BLACK), ((area.width() - 90) as i32, 18))?;
-        
-        root.draw_series(std::iter::once(Rectangle::new(
-            [(area.width() - 120) as i32, 30],
-            [(area.width() - 100) as i32, 40],
-            RED.filled(),
-        )))?;
-        root.draw_text("Expenses", &("sans-serif", 12).into_font().color(&BLACK), ((area.width() - 90) as i32, 38))?;
-        
+
         Ok(())
-    });
+    }
+}
 
-    Column::new()
-        .push(
-            Text::new("Income vs Expenses (6 Month Trend)")
-                .size(20)
-                .style(Text::Default)
-        )
-        .push(
-            Container::new(chart.height(Length::Fixed(CHART_HEIGHT as f32)))
-                .width(Length::Fixed(CHART_WIDTH as f32))
-                .style(Container::Box)
-        )
-        .spacing(10)
-        .align_items(Alignment::Center)
-        .into()
+impl<Message> Chart<Message> for IncomeVsExpenseChart {
+    type State = ();
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, _builder: ChartBuilder<DB>) {}
+
+    fn draw_chart<DB: DrawingBackend>(&self, _state: &Self::State, root: DrawingArea<DB, Shift>) {
+        if let Err(e) = self.render(&root) {
+            eprintln!("Failed to draw income vs expense chart: {:?}", e);
+        }
+    }
 }
 
-pub fn create_budget_progress_chart(
-    budgets: &[Budget],
+pub fn create_income_vs_expense_chart<'a, Message: 'a>(
     transactions: &[Transaction],
-    categories: &[Category],
-) -> Element<'static, crate::ui::Message> {
-    let chart = ChartWidget::new(move |_, area| {
-        let backend = plotters_iced::DrawingBackend::new(area);
-        let root = backend.into_drawing_area();
-        root.fill(&WHITE)?;
-        
-        // Filter active budgets (current month)
-        let now = chrono::Local::now().naive_local().date();
-        let active_budgets: Vec<&Budget> = budgets.iter()
-            .filter(|b| b.start_date <= now && b.end_date >= now)
-            .collect();
-            
-        if active_budgets.is_empty() {
-            let style = TextStyle::from(("sans-serif", 20).into_font()).color(&BLACK);
-            root.draw_text("No active budgets", &style, (150, 150))?;
-            return Ok(());
-        }
-            
-        let cat_map: HashMap<String, &Category> = categories
-            .iter()
-            .map(|c| (c.id.clone(), c))
-            .collect();
-            
-        // Calculate spent amount for each budget
-        let budget_spent: HashMap<&String, f64> = active_budgets.iter()
-            .map(|budget| {
-                let spent = transactions.iter()
-                    .filter(|t| {
-                        t.is_expense() && 
-                        t.date >= budget.start_date && 
-                        t.date <= budget.end_date &&
-                        match (&t.category_id, &budget.category_id) {
-                            (Some(t_cat), Some(b_cat)) => t_cat == b_cat,
-                            _ => false
-                        }
-                    })
-                    .map(|t| t.amount)
-                    .sum();
-                
-                (&budget.id, spent)
+    converter: &CurrencyConverter,
+    range: DateRange,
+) -> Element<'a, Message> {
+    let trend = IncomeVsExpenseChart::new(transactions, converter, range);
+    let title = format!("Income vs Expenses ({} Month Trend)", trend.months.len());
+    let chart = ChartWidget::new(trend)
+        .height(Length::Fixed(CHART_HEIGHT as f32));
+
+    column![
+        text(title).size(20),
+        container(chart)
+            .width(Length::Fixed(CHART_WIDTH as f32))
+            .style(container::rounded_box),
+    ]
+    .spacing(10)
+    .align_x(Alignment::Center)
+    .into()
+}
+
+pub struct BudgetProgressChart {
+    // (name, available, spent)
+    bars: Vec<(String, f64, f64)>,
+    symbol: &'static str,
+}
+
+impl BudgetProgressChart {
+    /// Budgets running at any point from `from` to `to`, each measured over
+    /// its period in effect on `to` against what that period has available,
+    /// so a renewing budget shows its current allocation plus anything
+    /// carried in. `periods` are the recorded budget periods. Amounts are
+    /// converted to the base currency at the rate on the period's first day
+    /// so the bars share one scale.
+    pub fn new(
+        budgets: &[Budget],
+        periods: &[BudgetPeriod],
+        transactions: &[Transaction],
+        categories: &[Category],
+        converter: &CurrencyConverter,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Self {
+        let bars = budgets.iter()
+            .filter(|b| b.is_active_between(from, to))
+            .filter_map(|b| {
+                let period = b.history(periods, to, transactions, categories, converter).pop()?;
+                let available = converter.to_base(period.available(), period.range.from)?;
+                let spent = converter.to_base(period.spent, period.range.from)?;
+                Some((b.name.clone(), available.to_f64(), spent.to_f64()))
             })
             .collect();
-            
-        let max_budget = active_budgets.iter()
-            .map(|b| b.amount)
-            .fold(0.0, f64::max);
-            
-        let mut chart = ChartBuilder::on(&root)
+
+        Self { bars, symbol: converter.base().symbol() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bars.is_empty()
+    }
+
+    pub fn render<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> DrawResult<DB> {
+        let max_amount = self.bars.iter()
+            .flat_map(|(_, budgeted, spent)| [*budgeted, *spent])
+            .fold(0.0, f64::max)
+            .max(1.0);
+
+        let x_labels: Vec<String> = self.bars.iter().map(|(name, _, _)| name.clone()).collect();
+
+        let mut chart = ChartBuilder::on(root)
             .caption("Budget Progress", ("sans-serif", 20))
             .margin(5)
             .x_label_area_size(40)
             .y_label_area_size(60)
-            .build_cartesian_2d(0..active_budgets.len(), 0.0..max_budget * 1.1)?;
-            
+            .build_cartesian_2d(-0.5..self.bars.len() as f64 - 0.5, 0.0..max_amount * 1.1)?;
+
         chart.configure_mesh()
-            .x_labels(active_budgets.len())
-            .x_label_formatter(&|idx| {
-                if *idx < active_budgets.len() {
-                    let budget = active_budgets[*idx];
-                    budget.name.clone()
-                } else {
-                    "".to_string()
-                }
-            })
-            .y_label_formatter(&|y| format!("${:.0}", y))
+            .disable_x_mesh()
+            .x_labels(self.bars.len())
+            .x_label_formatter(&|x| segment_label(*x, &x_labels))
+            .y_label_formatter(&|y| format!("{}{:.0}", self.symbol, y))
             .draw()?;
-            
-        // Draw budget bars
+
         chart.draw_series(
-            active_budgets.iter().enumerate().map(|(i, budget)| {
-                let bar_width = 0.4;
-                Rectangle::new(
-                    [(i as f32 - bar_width as f32 / 2.0) as i32, 0.0],
-                    [(i as f32 + bar_width as f32 / 2.0) as i32, budget.amount],
-                    BLUE.filled(),
-                )
+            self.bars.iter().enumerate().map(|(i, (_, budgeted, _))| {
+                let x = i as f64;
+                Rectangle::new([(x - 0.35, 0.0), (x, *budgeted)], BLUE.filled())
             })
-        )?;
-        
-        // Draw spent bars
+        )?
+        .label("Available")
+        .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], BLUE.filled()));
+
+        // Overspent budgets are drawn in red
         chart.draw_series(
-            active_budgets.iter().enumerate().map(|(i, budget)| {
-                let bar_width = 0.4;
-                let spent = *budget_spent.get(&budget.id).unwrap_or(&0.0);
-                let color = if spent > budget.amount { RED } else { GREEN };
-                
-                Rectangle::new(
-                    [(i as f32 + bar_width as f32 / 2.0) as i32, 0.0],
-                    [(i as f32 + 3.0 * bar_width as f32 / 2.0) as i32, spent],
-                    color.filled(),
-                )
+            self.bars.iter().enumerate().map(|(i, (_, budgeted, spent))| {
+                let x = i as f64;
+                let color = if spent > budgeted { RED } else { GREEN };
+                Rectangle::new([(x, 0.0), (x + 0.35, *spent)], color.filled())
             })
-        )?;
-        
-        // Add legend
-        root.draw_series(std::iter::once(Rectangle::new(
-            [(area.width() - 120) as i32, 10],
-            [(area.width() - 100) as i32, 20],
-            BLUE.filled(),
-        )))?;
-        root.draw_text("Budget", &("sans-serif", 12).into_font().color(&BLACK), ((area.width() - 90) as i32, 18))?;
-        
-        root.draw_series(std::iter::once(Rectangle::new(
-            [(area.width() - 120) as i32, 30],
-            [(area.width() - 100) as i32, 40],
-            GREEN.filled(),
-        )))?;
-        root.draw_text("Spent", &("sans-serif", 12).into_font().color(&BLACK), ((area.width() - 90) as i32, 38))?;
-        
+        )?
+        .label("Spent")
+        .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], GREEN.filled()));
+
+        chart.configure_series_labels()
+            .position(SeriesLabelPosition::UpperRight)
+            .background_style(WHITE)
+            .border_style(BLACK)
+            .draw()?;
+
         Ok(())
-    });
+    }
+}
+
+impl<Message> Chart<Message> for BudgetProgressChart {
+    type State = ();
 
-    Column::new()
-        .push(
-            Text::new("Budget Progress")
-                .size(20)
-                .style(Text::Default)
-        )
-        .push(
-            Container::new(chart.height(Length::Fixed(CHART_HEIGHT as f32)))
-                .width(Length::Fixed(CHART_WIDTH as f32))
-                .style(Container::Box)
-        )
-        .spacing(10)
-        .align_items(Alignment::Center)
-        .into()
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, _builder: ChartBuilder<DB>) {}
+
+    fn draw_chart<DB: DrawingBackend>(&self, _state: &Self::State, root: DrawingArea<DB, Shift>) {
+        if let Err(e) = self.render(&root) {
+            eprintln!("Failed to draw budget progress chart: {:?}", e);
+        }
+    }
 }
 
-pub fn spending_chart<'a, Message>(_transactions: &[Transaction]) -> Element<'a, Message> {
-    iced::widget::text("Spending Chart").into()
+pub fn create_budget_progress_chart<'a, Message: 'a>(
+    budgets: &[Budget],
+    periods: &[BudgetPeriod],
+    transactions: &[Transaction],
+    categories: &[Category],
+    converter: &CurrencyConverter,
+) -> Element<'a, Message> {
+    // Only budgets covering today are shown, in their current period
+    let now = chrono::Local::now().naive_local().date();
+    let chart = BudgetProgressChart::new(budgets, periods, transactions, categories, converter, now, now);
+
+    let content: Element<'a, Message> = if chart.is_empty() {
+        container(text("No active budgets"))
+            .width(Length::Fill)
+            .height(Length::Fixed(CHART_HEIGHT as f32))
+            .center(Length::Fill)
+            .into()
+    } else {
+        ChartWidget::new(chart)
+            .height(Length::Fixed(CHART_HEIGHT as f32))
+            .into()
+    };
+
+    column![
+        text("Budget Progress").size(20),
+        container(content)
+            .width(Length::Fixed(CHART_WIDTH as f32))
+            .style(container::rounded_box),
+    ]
+    .spacing(10)
+    .align_x(Alignment::Center)
+    .into()
+}
+
+/// Spending against `budget` in its current period, against what `period`
+/// has available when its record is given, with a warning when expenses
+/// were left out for want of an exchange rate.
+pub fn budget_progress<'a, Message: 'a>(
+    budget: &Budget,
+    period: Option<&BudgetPeriod>,
+    transactions: &[Transaction],
+    categories: &[Category],
+    converter: &CurrencyConverter,
+) -> Element<'a, Message> {
+    let (spent, unconverted) = match period.map(|p| p.range).or_else(|| budget.current_period(today())) {
+        Some(range) => budget.spent_in(range, transactions, categories, converter),
+        None => budget.spent(transactions, categories, converter),
+    };
+    let (spent, limit) = match period {
+        Some(period) => (period.spent, period.available()),
+        None => (spent, budget.amount),
+    };
+    // Overspending carried in can leave nothing available at all
+    let percentage = if limit.minor_units() > 0 {
+        calculate_percentage(spent.minor_units() as f64, limit.minor_units() as f64)
+    } else {
+        100.0
+    };
+
+    let carried = period
+        .filter(|p| !p.carried_in.is_zero())
+        .map(|p| format!(", {} carried in", format_currency(p.carried_in)))
+        .unwrap_or_default();
+    let label = text(format!(
+        "{} / {} ({:.1}%){}",
+        format_currency(spent),
+        format_currency(limit),
+        percentage,
+        carried
+    ));
+
+    let bar = progress_bar(0.0..=100.0, percentage.min(100.0) as f32)
+        .height(Length::Fixed(10.0))
+        .style(if spent.minor_units() > limit.minor_units() || limit.minor_units() <= 0 {
+            progress_bar::danger
+        } else {
+            progress_bar::success
+        });
+
+    let missing_rates = (unconverted > 0).then(|| {
+        text(format!(
+            "{} expense(s) left out: no exchange rate to {}",
+            unconverted,
+            budget.amount.currency()
+        ))
+        .size(12)
+        .color(iced::Color::from_rgb(0.8, 0.0, 0.0))
+    });
+
+    column![
+        row![label].width(Length::Fill),
+        bar,
+    ]
+    .push_maybe(missing_rates)
+    .spacing(5)
+    .into()
 }
 
-pub fn budget_progress<'a, Message>(_budget: &Budget, _transactions: &[Transaction]) -> Element<'a, Message> {
-    iced::widget::text("Budget Progress").into()
+fn segment_label(x: f64, labels: &[String]) -> String {
+    let idx = x.round();
+    if (x - idx).abs() > f64::EPSILON || idx < 0.0 {
+        return String::new();
+    }
+
+    labels.get(idx as usize).cloned().unwrap_or_default()
 }
 
-fn parse_color(hex: &str) -> plotters::style::RGBAColor {
+pub fn parse_color(hex: &str) -> RGBColor {
     let hex = hex.trim_start_matches('#');
     if hex.len() != 6 {
-        return plotters::style::RGBAColor(100, 100, 100, 1.0);
+        return RGBColor(100, 100, 100);
     }
-    
+
     let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(100);
     let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(100);
     let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(100);
-    
-    plotters::style::RGBAColor(r, g, b, 1.0)
+
+    RGBColor(r, g, b)
 }
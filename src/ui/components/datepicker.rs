@@ -2,19 +2,23 @@ use chrono::NaiveDate;
 use iced::Element;
 use iced_aw::date_picker;
 
-pub fn create_date_picker<'a, Message, F, G>(
+pub fn create_date_picker<'a, Message, F>(
     is_open: bool,
     selected_date: NaiveDate,
+    underlay: impl Into<Element<'a, Message>>,
     on_date_selected: F,
-    on_close: G,
-) -> Element<'a, Message>  // Added lifetime parameter 'a
+    on_close: Message,
+) -> Element<'a, Message>
 where
+    Message: Clone + 'static,
     F: Fn(NaiveDate) -> Message + 'static,
-    G: Fn() -> Message + 'static,
 {
-    date_picker::DatePicker::new(selected_date)
-        .show(is_open)
-        .on_cancel(on_close)
-        .on_submit(on_date_selected)
-        .into()
+    date_picker::DatePicker::new(
+        is_open,
+        selected_date,
+        underlay,
+        on_close,
+        move |date| on_date_selected(date.into()),
+    )
+    .into()
 }
@@ -3,3 +3,6 @@ pub mod datepicker;
 pub mod nav;
 pub mod transaction_form;
 pub mod budget_form;
+pub mod account_form;
+pub mod recurring_form;
+pub mod category_rule_form;
@@ -3,37 +3,67 @@ use crate::ui::Page;
 
 pub struct Nav {
     active_page: Page,
+    /// Unread notifications, counted on the Notifications button.
+    unread: usize,
 }
 
 impl Nav {
-    pub fn new() -> Self {
-        Self {
-            active_page: Page::Dashboard,
-        }
+    pub fn new(active_page: Page, unread: usize) -> Self {
+        Self { active_page, unread }
     }
 
-    pub fn view(&self) -> Element<Page> {
-        let dashboard_button = button(text("Dashboard"))
-            .on_press(Page::Dashboard)
-            .padding(10);
+    fn page_button(&self, label: impl Into<String>, page: Page) -> Element<'static, Page> {
+        let style = if self.active_page == page {
+            button::primary
+        } else {
+            button::secondary
+        };
 
-        let transactions_button = button(text("Transactions"))
-            .on_press(Page::Transactions)
-            .padding(10);
-
-        let budgets_button = button(text("Budgets"))
-            .on_press(Page::Budgets)
-            .padding(10);
+        button(text(label.into()))
+            .on_press(page)
+            .style(style)
+            .padding(10)
+            .into()
+    }
 
-        let reports_button = button(text("Reports"))
-            .on_press(Page::Reports)
-            .padding(10);
+    pub fn view(&self) -> Element<'static, Page> {
+        let dashboard_button = self.page_button("Dashboard", Page::Dashboard);
+        let transactions_button = self.page_button("Transactions", Page::Transactions);
+        let accounts_button = self.page_button("Accounts", Page::Accounts);
+        let budgets_button = self.page_button("Budgets", Page::Budgets);
+        let categories_button = self.page_button("Categories", Page::Categories);
+        let tags_button = self.page_button("Tags", Page::Tags);
+        let payees_button = self.page_button("Payees", Page::Payees);
+        let recurring_button = self.page_button("Recurring", Page::Recurring);
+        let import_button = self.page_button("Import", Page::Import);
+        let export_button = self.page_button("Export", Page::Export);
+        let backup_button = self.page_button("Backup", Page::Backup);
+        let rules_button = self.page_button("Rules", Page::Rules);
+        let currencies_button = self.page_button("Currencies", Page::Currencies);
+        let reports_button = self.page_button("Reports", Page::Reports);
+        let notifications_label = if self.unread > 0 {
+            format!("Notifications ({})", self.unread)
+        } else {
+            "Notifications".to_string()
+        };
+        let notifications_button = self.page_button(notifications_label, Page::Notifications);
 
         row![
             dashboard_button,
             transactions_button,
+            accounts_button,
             budgets_button,
-            reports_button
+            categories_button,
+            tags_button,
+            payees_button,
+            recurring_button,
+            import_button,
+            export_button,
+            backup_button,
+            rules_button,
+            currencies_button,
+            reports_button,
+            notifications_button
         ]
         .spacing(10)
         .padding(20)
@@ -0,0 +1,348 @@
+use chrono::{NaiveDate, Weekday};
+use crate::models::{
+    Account, Category, Currency, Frequency, Money, NthWeekday, RecurrenceEnd, RecurringRule, TransactionType,
+};
+use crate::ui::components::datepicker::create_date_picker;
+use crate::ui::recurring::RecurringMessage;
+use crate::utils::{format_date, today};
+use iced::widget::{button, checkbox, column, container, pick_list, radio, row, text, text_input};
+use iced::{Alignment, Color, Element, Length};
+use std::fmt;
+
+/// Week of the month for weekday-of-month schedules; -1 is the last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekOfMonth(pub i8);
+
+impl WeekOfMonth {
+    const ALL: [WeekOfMonth; 5] = [
+        WeekOfMonth(1),
+        WeekOfMonth(2),
+        WeekOfMonth(3),
+        WeekOfMonth(4),
+        WeekOfMonth(-1),
+    ];
+}
+
+impl fmt::Display for WeekOfMonth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            1 => write!(f, "First"),
+            2 => write!(f, "Second"),
+            3 => write!(f, "Third"),
+            4 => write!(f, "Fourth"),
+            _ => write!(f, "Last"),
+        }
+    }
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndKind {
+    Never,
+    OnDate,
+    AfterCount,
+}
+
+impl EndKind {
+    const ALL: [EndKind; 3] = [EndKind::Never, EndKind::OnDate, EndKind::AfterCount];
+}
+
+impl fmt::Display for EndKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndKind::Never => write!(f, "Never"),
+            EndKind::OnDate => write!(f, "On date"),
+            EndKind::AfterCount => write!(f, "After occurrences"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecurringFormState {
+    pub description: String,
+    pub amount: String,
+    pub currency: Currency,
+    pub transaction_type: TransactionType,
+    pub category_id: Option<String>,
+    pub account_id: Option<String>,
+    pub frequency: Frequency,
+    pub interval: String,
+    pub by_weekday: bool,
+    pub week: WeekOfMonth,
+    pub weekday: Weekday,
+    pub start_date: NaiveDate,
+    pub end_kind: EndKind,
+    pub end_date: NaiveDate,
+    pub count: String,
+    pub editing_id: Option<String>,
+    pub show_start_date_picker: bool,
+    pub show_end_date_picker: bool,
+    pub error: Option<String>,
+}
+
+impl Default for RecurringFormState {
+    fn default() -> Self {
+        Self {
+            description: String::new(),
+            amount: String::new(),
+            currency: Currency::default(),
+            transaction_type: TransactionType::Expense,
+            category_id: None,
+            account_id: None,
+            frequency: Frequency::Monthly,
+            interval: "1".to_string(),
+            by_weekday: false,
+            week: WeekOfMonth(1),
+            weekday: Weekday::Mon,
+            start_date: today(),
+            end_kind: EndKind::Never,
+            end_date: today(),
+            count: String::new(),
+            editing_id: None,
+            show_start_date_picker: false,
+            show_end_date_picker: false,
+            error: None,
+        }
+    }
+}
+
+impl RecurringFormState {
+    pub fn from_rule(rule: &RecurringRule) -> Self {
+        let defaults = Self::default();
+        let (end_kind, end_date, count) = match rule.end {
+            RecurrenceEnd::Never => (EndKind::Never, defaults.end_date, String::new()),
+            RecurrenceEnd::OnDate(date) => (EndKind::OnDate, date, String::new()),
+            RecurrenceEnd::AfterCount(count) => (EndKind::AfterCount, defaults.end_date, count.to_string()),
+        };
+
+        Self {
+            description: rule.description.clone(),
+            amount: rule.amount.to_input_string(),
+            currency: rule.amount.currency(),
+            transaction_type: rule.transaction_type,
+            category_id: rule.category_id.clone(),
+            account_id: rule.account_id.clone(),
+            frequency: rule.frequency,
+            interval: rule.interval.to_string(),
+            by_weekday: rule.nth_weekday.is_some(),
+            week: rule.nth_weekday.map_or(defaults.week, |nth| WeekOfMonth(nth.week)),
+            weekday: rule.nth_weekday.map_or(defaults.weekday, |nth| nth.weekday),
+            start_date: rule.start_date,
+            end_kind,
+            end_date,
+            count,
+            editing_id: Some(rule.id.clone()),
+            ..defaults
+        }
+    }
+
+    /// Validates the form and builds the rule it describes.
+    pub fn to_rule(&self) -> Result<RecurringRule, String> {
+        let amount = Money::parse(&self.amount, self.currency)
+            .map_err(|e| e.to_string())?;
+        if amount.is_negative() || amount.is_zero() {
+            return Err("Amount must be greater than zero".to_string());
+        }
+
+        let interval: u32 = self.interval.trim().parse()
+            .ok()
+            .filter(|interval| *interval > 0)
+            .ok_or_else(|| "Repeat interval must be a whole number of at least 1".to_string())?;
+
+        let nth_weekday = (self.frequency == Frequency::Monthly && self.by_weekday).then_some(NthWeekday {
+            week: self.week.0,
+            weekday: self.weekday,
+        });
+
+        let end = match self.end_kind {
+            EndKind::Never => RecurrenceEnd::Never,
+            EndKind::OnDate => {
+                if self.end_date < self.start_date {
+                    return Err("End date must not be before start date".to_string());
+                }
+                RecurrenceEnd::OnDate(self.end_date)
+            }
+            EndKind::AfterCount => {
+                let count: u32 = self.count.trim().parse()
+                    .ok()
+                    .filter(|count| *count > 0)
+                    .ok_or_else(|| "Number of occurrences must be at least 1".to_string())?;
+                RecurrenceEnd::AfterCount(count)
+            }
+        };
+
+        let mut rule = RecurringRule::new(
+            self.description.trim().to_string(),
+            amount,
+            self.transaction_type,
+            self.category_id.clone(),
+            self.account_id.clone(),
+            self.frequency,
+            interval,
+            nth_weekday,
+            self.start_date,
+            end,
+        );
+        if let Some(id) = &self.editing_id {
+            rule.id = id.clone();
+        }
+
+        Ok(rule)
+    }
+}
+
+pub fn recurring_form_view<'a>(
+    categories: &'a [Category],
+    accounts: &'a [Account],
+    state: &RecurringFormState,
+) -> Element<'a, RecurringMessage> {
+    let label = |label: &'static str| text(label).width(Length::Fixed(120.0));
+
+    let description_input = text_input("Description", &state.description)
+        .on_input(RecurringMessage::DescriptionChanged)
+        .padding(10);
+
+    let amount_row = row![
+        text_input("Amount", &state.amount)
+            .on_input(RecurringMessage::AmountChanged)
+            .padding(10),
+        pick_list(&Currency::ALL[..], Some(state.currency), RecurringMessage::CurrencySelected)
+            .padding(10),
+        radio("Expense", TransactionType::Expense, Some(state.transaction_type), RecurringMessage::TypeSelected),
+        radio("Income", TransactionType::Income, Some(state.transaction_type), RecurringMessage::TypeSelected),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let selected_category = state.category_id.as_ref()
+        .and_then(|id| categories.iter().find(|c| &c.id == id))
+        .cloned();
+    let selected_account = state.account_id.as_ref()
+        .and_then(|id| accounts.iter().find(|a| &a.id == id))
+        .cloned();
+
+    let category_row = row![
+        label("Category:"),
+        pick_list(categories, selected_category, |category: Category| {
+            RecurringMessage::CategorySelected(category.id)
+        })
+        .placeholder("Select category"),
+        label("Account:"),
+        pick_list(accounts, selected_account, |account: Account| {
+            RecurringMessage::AccountSelected(account.id)
+        })
+        .placeholder("Select account"),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let mut schedule_row = row![
+        label("Repeats:"),
+        pick_list(&Frequency::ALL[..], Some(state.frequency), RecurringMessage::FrequencySelected),
+        text("every"),
+        text_input("1", &state.interval)
+            .on_input(RecurringMessage::IntervalChanged)
+            .padding(10)
+            .width(Length::Fixed(60.0)),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    if state.frequency == Frequency::Monthly {
+        schedule_row = schedule_row.push(
+            checkbox("On a weekday of the month", state.by_weekday)
+                .on_toggle(RecurringMessage::ByWeekdayToggled),
+        );
+        if state.by_weekday {
+            schedule_row = schedule_row
+                .push(pick_list(&WeekOfMonth::ALL[..], Some(state.week), RecurringMessage::WeekSelected))
+                .push(pick_list(&WEEKDAYS[..], Some(state.weekday), RecurringMessage::WeekdaySelected));
+        }
+    }
+
+    let start_date_picker = create_date_picker(
+        state.show_start_date_picker,
+        state.start_date,
+        button(text(format_date(&state.start_date)))
+            .on_press(RecurringMessage::OpenStartDatePicker(true))
+            .padding(10),
+        RecurringMessage::StartDateSelected,
+        RecurringMessage::OpenStartDatePicker(false),
+    );
+
+    let mut end_row = row![
+        label("Ends:"),
+        pick_list(&EndKind::ALL[..], Some(state.end_kind), RecurringMessage::EndKindSelected),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    match state.end_kind {
+        EndKind::Never => {}
+        EndKind::OnDate => {
+            end_row = end_row.push(create_date_picker(
+                state.show_end_date_picker,
+                state.end_date,
+                button(text(format_date(&state.end_date)))
+                    .on_press(RecurringMessage::OpenEndDatePicker(true))
+                    .padding(10),
+                RecurringMessage::EndDateSelected,
+                RecurringMessage::OpenEndDatePicker(false),
+            ));
+        }
+        EndKind::AfterCount => {
+            end_row = end_row.push(
+                text_input("Occurrences", &state.count)
+                    .on_input(RecurringMessage::CountChanged)
+                    .padding(10)
+                    .width(Length::Fixed(120.0)),
+            );
+        }
+    }
+
+    let buttons_row = row![
+        button(text("Save"))
+            .on_press(RecurringMessage::Save)
+            .padding(10)
+            .style(button::primary),
+        button(text("Cancel"))
+            .on_press(RecurringMessage::Cancel)
+            .padding(10)
+            .style(button::secondary),
+    ]
+    .spacing(10);
+
+    let error_text = text(state.error.clone().unwrap_or_default())
+        .size(14)
+        .color(Color::from_rgb(0.8, 0.0, 0.0));
+
+    let title = if state.editing_id.is_some() { "Edit Recurring Transaction" } else { "New Recurring Transaction" };
+
+    container(
+        column![
+            text(title).size(20),
+            description_input,
+            amount_row,
+            category_row,
+            schedule_row,
+            row![label("Starts:"), start_date_picker].align_y(Alignment::Center),
+            end_row,
+            error_text,
+            buttons_row,
+        ]
+        .spacing(10)
+        .padding(20)
+        .width(Length::Fill)
+    )
+    .style(container::rounded_box)
+    .into()
+}
@@ -1,24 +1,61 @@
-use iced::{widget::{button, column, row, text, text_input, radio}, Element, Length, Alignment};
-use crate::models::transaction::{Transaction, TransactionType};
+use iced::{widget::{button, column, pick_list, row, text, text_input, radio, Column, Row, Space}, Alignment, Color, Element, Length};
+use crate::models::tag::normalize_name;
+use crate::models::{
+    Account, Category, Currency, Money, Payee, Split, Suggestion, Tag, Transaction, TransactionType, Transfer,
+};
+use crate::ui::payees::PayeeChoice;
 use crate::ui::transaction::TransactionFormMessage;
-use chrono::{Local};
+use crate::utils::{format_currency, format_date, today};
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+/// What the payee picker shows when no payee is chosen.
+const PAYEE_FROM_DESCRIPTION: &str = "(Recognise from description)";
+
+/// A split line as entered.
+#[derive(Debug, Clone, Default)]
+struct SplitLine {
+    category_id: Option<String>,
+    amount: String,
+    memo: String,
+}
 
 pub struct TransactionForm {
     description: String,
     amount: String,
+    currency: Currency,
     transaction_type: TransactionType,
     date: String,
-    category: String,
+    category_id: Option<String>,
+    // Replaces the category when the amount is split between several
+    splits: Vec<SplitLine>,
+    // Recognised from the description on saving when unset
+    payee_id: Option<String>,
+    tags: Vec<String>,
+    // A tag being typed, added on submit
+    tag_input: String,
+    account_id: Option<String>,
+    // Destination account when the form describes a transfer
+    to_account_id: Option<String>,
+    error: Option<String>,
 }
 
 impl TransactionForm {
-    pub fn new() -> Self {
+    pub fn new(currency: Currency) -> Self {
         Self {
             description: String::new(),
             amount: String::new(),
+            currency,
             transaction_type: TransactionType::Expense,
-            date: Local::now().format("%Y-%m-%d").to_string(),
-            category: String::new(),
+            date: format_date(&today()),
+            category_id: None,
+            splits: Vec::new(),
+            payee_id: None,
+            tags: Vec::new(),
+            tag_input: String::new(),
+            account_id: None,
+            to_account_id: None,
+            error: None,
         }
     }
 
@@ -30,39 +67,207 @@ impl TransactionForm {
             TransactionFormMessage::AmountChanged(amount) => {
                 self.amount = amount;
             }
+            TransactionFormMessage::CurrencyChanged(currency) => {
+                self.currency = currency;
+            }
             TransactionFormMessage::TypeChanged(transaction_type) => {
                 self.transaction_type = transaction_type;
             }
             TransactionFormMessage::DateChanged(date) => {
                 self.date = date;
             }
-            TransactionFormMessage::CategoryChanged(category) => {
-                self.category = category;
+            TransactionFormMessage::CategoryChanged(category_id) => {
+                self.category_id = Some(category_id);
+            }
+            TransactionFormMessage::SplitTransaction => {
+                // Start from the whole amount under the current category
+                self.splits = vec![
+                    SplitLine {
+                        category_id: self.category_id.take(),
+                        amount: self.amount.clone(),
+                        memo: String::new(),
+                    },
+                    SplitLine::default(),
+                ];
+            }
+            TransactionFormMessage::AddSplit => {
+                self.splits.push(SplitLine::default());
+            }
+            TransactionFormMessage::RemoveSplit(index) => {
+                if index < self.splits.len() {
+                    self.splits.remove(index);
+                }
+            }
+            TransactionFormMessage::SplitCategoryChanged(index, category_id) => {
+                if let Some(line) = self.splits.get_mut(index) {
+                    line.category_id = Some(category_id);
+                }
+            }
+            TransactionFormMessage::SplitAmountChanged(index, amount) => {
+                if let Some(line) = self.splits.get_mut(index) {
+                    line.amount = amount;
+                }
+            }
+            TransactionFormMessage::SplitMemoChanged(index, memo) => {
+                if let Some(line) = self.splits.get_mut(index) {
+                    line.memo = memo;
+                }
+            }
+            TransactionFormMessage::PayeeSelected(choice) => {
+                self.payee_id = choice.id;
+            }
+            TransactionFormMessage::TagInputChanged(input) => {
+                self.tag_input = input;
             }
-            TransactionFormMessage::Submit => {
-                // Handle submission - this would be connected to the DB in a real implementation
-                self.description = String::new();
-                self.amount = String::new();
-                self.transaction_type = TransactionType::Expense;
-                self.date = Local::now().format("%Y-%m-%d").to_string();
-                self.category = String::new();
+            TransactionFormMessage::AddTag => {
+                let name = normalize_name(&self.tag_input);
+                self.add_tag(name);
+                self.tag_input.clear();
             }
+            TransactionFormMessage::TagSelected(tag) => {
+                self.add_tag(tag.name);
+            }
+            TransactionFormMessage::RemoveTag(name) => {
+                self.tags.retain(|tag| tag != &name);
+            }
+            TransactionFormMessage::AccountChanged(account_id) => {
+                self.account_id = Some(account_id);
+            }
+            TransactionFormMessage::ToAccountChanged(account_id) => {
+                self.to_account_id = Some(account_id);
+            }
+            TransactionFormMessage::Submit => {}
             TransactionFormMessage::Cancel => {
-                // Reset the form
-                self.description = String::new();
-                self.amount = String::new();
-                self.transaction_type = TransactionType::Expense;
-                self.date = Local::now().format("%Y-%m-%d").to_string();
-                self.category = String::new();
+                *self = Self::new(self.currency);
             }
         }
     }
 
-    pub fn view(&self) -> Element<TransactionFormMessage> {
+    fn add_tag(&mut self, name: String) {
+        if !name.is_empty() && !self.tags.contains(&name) {
+            self.tags.push(name);
+            self.tags.sort();
+        }
+    }
+
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    pub fn is_transfer(&self) -> bool {
+        self.transaction_type == TransactionType::Transfer
+    }
+
+    fn parse_amount(&self) -> Result<Money, String> {
+        let amount = Money::parse(&self.amount, self.currency)
+            .map_err(|e| e.to_string())?;
+        if amount.is_negative() || amount.is_zero() {
+            return Err("Amount must be greater than zero".to_string());
+        }
+        Ok(amount)
+    }
+
+    fn parse_date(&self) -> Result<NaiveDate, String> {
+        NaiveDate::parse_from_str(self.date.trim(), "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date: {} (expected YYYY-MM-DD)", self.date))
+    }
+
+    /// Validates the form and builds the transaction it describes, reusing
+    /// `id` when an existing transaction is being edited.
+    pub fn to_transaction(&self, id: Option<Uuid>) -> Result<Transaction, String> {
+        let amount = self.parse_amount()?;
+        let date = self.parse_date()?;
+
+        let mut transaction = Transaction::new(
+            self.description.trim().to_string(),
+            amount,
+            date,
+            self.transaction_type,
+            self.category_id.clone(),
+            self.account_id.clone(),
+        );
+        if let Some(id) = id {
+            transaction.id = id;
+        }
+        transaction.payee_id = self.payee_id.clone();
+        transaction.tags = self.tags.clone();
+
+        if !self.splits.is_empty() {
+            transaction.category_id = None;
+            transaction.splits = self.parse_splits()?;
+            transaction.validate_splits()?;
+
+            // A single line, which must cover the whole amount, is the same
+            // as choosing its category
+            if let [line] = transaction.splits.as_slice() {
+                transaction.category_id = line.category_id.clone();
+                transaction.splits.clear();
+            }
+        }
+
+        Ok(transaction)
+    }
+
+    fn parse_splits(&self) -> Result<Vec<Split>, String> {
+        self.splits.iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let amount = Money::parse(&line.amount, self.currency)
+                    .map_err(|e| format!("Split line {}: {}", index + 1, e))?;
+                Ok(Split {
+                    category_id: line.category_id.clone(),
+                    amount,
+                    memo: line.memo.trim().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Validates the form and builds the transfer it describes, reusing `id`
+    /// when an existing transfer is being edited.
+    pub fn to_transfer(&self, id: Option<String>) -> Result<Transfer, String> {
+        let amount = self.parse_amount()?;
+        let date = self.parse_date()?;
+
+        let (Some(from), Some(to)) = (&self.account_id, &self.to_account_id) else {
+            return Err("Choose the accounts to transfer from and to".to_string());
+        };
+        if from == to {
+            return Err("Cannot transfer to the same account".to_string());
+        }
+
+        let mut transfer = Transfer::new(
+            self.description.trim().to_string(),
+            amount,
+            date,
+            from.clone(),
+            to.clone(),
+        );
+        if let Some(id) = id {
+            transfer.id = id;
+        }
+
+        Ok(transfer)
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// `suggestions` are learned categories for the current description,
+    /// offered as one-click choices under the category picker.
+    pub fn view<'a>(
+        &self,
+        categories: &'a [Category],
+        accounts: &'a [Account],
+        payees: &'a [Payee],
+        tags: &'a [Tag],
+        suggestions: &[Suggestion],
+    ) -> Element<'a, TransactionFormMessage> {
         let title = text("Transaction Form")
             .size(24)
             .width(Length::Fill)
-            .horizontal_alignment(Alignment::Center);
+            .center();
 
         let description_input = column![
             text("Description:").size(16),
@@ -72,11 +277,27 @@ impl TransactionForm {
         ]
         .spacing(5);
 
+        let payee_input = column![
+            text("Payee:").size(16),
+            pick_list(
+                PayeeChoice::all(payees, PAYEE_FROM_DESCRIPTION),
+                Some(PayeeChoice::selected(payees, self.payee_id.as_deref(), PAYEE_FROM_DESCRIPTION)),
+                TransactionFormMessage::PayeeSelected,
+            )
+            .padding(10)
+        ]
+        .spacing(5);
+
         let amount_input = column![
             text("Amount:").size(16),
-            text_input("Enter amount", &self.amount)
-                .on_input(TransactionFormMessage::AmountChanged)
-                .padding(10)
+            row![
+                text_input("Enter amount", &self.amount)
+                    .on_input(TransactionFormMessage::AmountChanged)
+                    .padding(10),
+                pick_list(&Currency::ALL[..], Some(self.currency), TransactionFormMessage::CurrencyChanged)
+                    .padding(10),
+            ]
+            .spacing(10)
         ]
         .spacing(5);
 
@@ -84,7 +305,8 @@ impl TransactionForm {
             text("Type:").size(16),
             row![
                 radio("Expense", TransactionType::Expense, Some(self.transaction_type), TransactionFormMessage::TypeChanged),
-                radio("Income", TransactionType::Income, Some(self.transaction_type), TransactionFormMessage::TypeChanged)
+                radio("Income", TransactionType::Income, Some(self.transaction_type), TransactionFormMessage::TypeChanged),
+                radio("Transfer", TransactionType::Transfer, Some(self.transaction_type), TransactionFormMessage::TypeChanged)
             ]
             .spacing(10)
         ]
@@ -98,37 +320,139 @@ impl TransactionForm {
         ]
         .spacing(5);
 
-        let category_input = column![
+        let selected_category = self.category_id.as_ref()
+            .and_then(|id| categories.iter().find(|c| &c.id == id))
+            .cloned();
+
+        let mut category_input = column![
             text("Category:").size(16),
-            text_input("Enter category", &self.category)
-                .on_input(TransactionFormMessage::CategoryChanged)
-                .padding(10)
+            pick_list(categories, selected_category, |category: Category| {
+                TransactionFormMessage::CategoryChanged(category.id)
+            })
+            .placeholder("Select category")
+            .padding(10)
+        ]
+        .spacing(5);
+
+        category_input = category_input.push(
+            button(text("Split Between Categories").size(14))
+                .on_press(TransactionFormMessage::SplitTransaction)
+                .style(button::secondary)
+        );
+
+        if !suggestions.is_empty() {
+            let suggestion_buttons = suggestions
+                .iter()
+                .filter_map(|suggestion| {
+                    let category = categories.iter().find(|c| c.id == suggestion.category_id)?;
+                    let is_selected = self.category_id.as_ref() == Some(&category.id);
+                    Some(
+                        button(text(format!("{} {:.0}%", category.name, suggestion.confidence * 100.0)).size(14))
+                            .on_press(TransactionFormMessage::CategoryChanged(category.id.clone()))
+                            .style(if is_selected { button::primary } else { button::secondary })
+                    )
+                })
+                .fold(row![text("Suggested:").size(14)].spacing(10), |row, button| row.push(button));
+
+            category_input = category_input.push(suggestion_buttons.align_y(iced::Alignment::Center));
+        }
+
+        let find_account = |id: &Option<String>| id.as_ref()
+            .and_then(|id| accounts.iter().find(|a| &a.id == id))
+            .cloned();
+
+        let account_input = column![
+            text(if self.is_transfer() { "From Account:" } else { "Account:" }).size(16),
+            pick_list(accounts, find_account(&self.account_id), |account: Account| {
+                TransactionFormMessage::AccountChanged(account.id)
+            })
+            .placeholder("Select account")
+            .padding(10)
         ]
         .spacing(5);
 
+        let to_account_input = column![
+            text("To Account:").size(16),
+            pick_list(accounts, find_account(&self.to_account_id), |account: Account| {
+                TransactionFormMessage::ToAccountChanged(account.id)
+            })
+            .placeholder("Select account")
+            .padding(10)
+        ]
+        .spacing(5);
+
+        // Tags already on the transaction show as chips that remove them
+        let chips = self.tags.iter().fold(Row::new().spacing(5), |row, name| {
+            row.push(
+                button(text(format!("#{} ×", name)).size(14))
+                    .on_press(TransactionFormMessage::RemoveTag(name.clone()))
+                    .style(button::secondary)
+            )
+        });
+        let other_tags: Vec<Tag> = tags.iter()
+            .filter(|tag| !self.tags.contains(&tag.name))
+            .cloned()
+            .collect();
+
+        let tags_input = column![
+            text("Tags:").size(16),
+            chips,
+            row![
+                text_input("Type a tag and press Enter", &self.tag_input)
+                    .on_input(TransactionFormMessage::TagInputChanged)
+                    .on_submit(TransactionFormMessage::AddTag)
+                    .padding(10),
+                pick_list(other_tags, None::<Tag>, TransactionFormMessage::TagSelected)
+                    .placeholder("Existing tags")
+                    .padding(10),
+            ]
+            .spacing(10)
+        ]
+        .spacing(5);
+
+        let error_text = text(self.error.clone().unwrap_or_default())
+            .size(14)
+            .color(Color::from_rgb(0.8, 0.0, 0.0));
+
         let submit_button = button(text("Save"))
             .on_press(TransactionFormMessage::Submit);
 
         let cancel_button = button(text("Cancel"))
-            .on_press(TransactionFormMessage::Cancel);
-
-        let delete_button = button(text("Delete"))
-            .on_press(TransactionFormMessage::Cancel);
+            .on_press(TransactionFormMessage::Cancel)
+            .style(button::secondary);
 
         let buttons = row![
             submit_button,
-            cancel_button,
-            delete_button
+            cancel_button
         ]
         .spacing(10);
 
+        // Transfers have no category; they name both accounts instead
+        let accounts_input: Element<'a, TransactionFormMessage> = if self.is_transfer() {
+            row![account_input, to_account_input].spacing(20).into()
+        } else if !self.splits.is_empty() {
+            column![self.splits_view(categories), account_input].spacing(20).into()
+        } else {
+            column![category_input, account_input].spacing(20).into()
+        };
+        // Payees and tags are kept on transactions only, not transfers
+        let (payee_input, tags_input): (Element<'a, TransactionFormMessage>, Element<'a, TransactionFormMessage>) =
+            if self.is_transfer() {
+                (Space::with_height(Length::Shrink).into(), Space::with_height(Length::Shrink).into())
+            } else {
+                (payee_input.into(), tags_input.into())
+            };
+
         column![
             title,
             description_input,
+            payee_input,
             amount_input,
             type_selection,
             date_input,
-            category_input,
+            accounts_input,
+            tags_input,
+            error_text,
             buttons
         ]
         .spacing(20)
@@ -138,14 +462,152 @@ impl TransactionForm {
     }
 }
 
+impl TransactionForm {
+    fn splits_view<'a>(&self, categories: &'a [Category]) -> Element<'a, TransactionFormMessage> {
+        let lines = self.splits.iter().enumerate().fold(
+            Column::new().spacing(10).push(text("Split:").size(16)),
+            |column, (index, line)| {
+                let selected = line.category_id.as_ref()
+                    .and_then(|id| categories.iter().find(|c| &c.id == id))
+                    .cloned();
+
+                column.push(
+                    row![
+                        pick_list(categories, selected, move |category: Category| {
+                            TransactionFormMessage::SplitCategoryChanged(index, category.id)
+                        })
+                        .placeholder("Select category")
+                        .padding(10),
+                        text_input("Amount", &line.amount)
+                            .on_input(move |amount| TransactionFormMessage::SplitAmountChanged(index, amount))
+                            .padding(10)
+                            .width(Length::Fixed(120.0)),
+                        text_input("Memo", &line.memo)
+                            .on_input(move |memo| TransactionFormMessage::SplitMemoChanged(index, memo))
+                            .padding(10),
+                        button("Remove")
+                            .on_press(TransactionFormMessage::RemoveSplit(index))
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                )
+            },
+        );
+
+        // How far the lines entered so far are from the total
+        let remaining = match Money::parse(&self.amount, self.currency) {
+            Ok(total) => {
+                let assigned = self.splits.iter()
+                    .filter_map(|line| Money::parse(&line.amount, self.currency).ok())
+                    .fold(Money::zero(self.currency), |sum, amount| sum + amount);
+                let remaining = total - assigned;
+                if remaining.is_negative() {
+                    format!("Over by {}", format_currency(-remaining))
+                } else {
+                    format!("Left to assign: {}", format_currency(remaining))
+                }
+            }
+            Err(_) => String::new(),
+        };
+
+        lines
+            .push(
+                row![
+                    button("Add Line")
+                        .on_press(TransactionFormMessage::AddSplit)
+                        .style(button::secondary),
+                    text(remaining).size(14),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center)
+            )
+            .into()
+    }
+}
+
 impl From<Transaction> for TransactionForm {
     fn from(transaction: Transaction) -> Self {
         Self {
             description: transaction.description,
-            amount: transaction.amount.to_string(),
+            amount: transaction.amount.to_input_string(),
+            currency: transaction.amount.currency(),
             transaction_type: transaction.transaction_type,
-            date: transaction.date.format("%Y-%m-%d").to_string(),
-            category: transaction.category.unwrap_or_default(),
+            date: format_date(&transaction.date),
+            category_id: transaction.category_id,
+            splits: transaction.splits.iter()
+                .map(|split| SplitLine {
+                    category_id: split.category_id.clone(),
+                    amount: split.amount.to_input_string(),
+                    memo: split.memo.clone(),
+                })
+                .collect(),
+            payee_id: transaction.payee_id,
+            tags: transaction.tags,
+            tag_input: String::new(),
+            account_id: transaction.account_id,
+            to_account_id: None,
+            error: None,
         }
     }
 }
+
+impl From<Transfer> for TransactionForm {
+    fn from(transfer: Transfer) -> Self {
+        Self {
+            description: transfer.description,
+            amount: transfer.amount.to_input_string(),
+            currency: transfer.amount.currency(),
+            transaction_type: TransactionType::Transfer,
+            date: format_date(&transfer.date),
+            category_id: None,
+            splits: Vec::new(),
+            payee_id: None,
+            tags: Vec::new(),
+            tag_input: String::new(),
+            account_id: Some(transfer.from_account_id),
+            to_account_id: Some(transfer.to_account_id),
+            error: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_form(amount: &str) -> TransactionForm {
+        let mut form = TransactionForm::new(Currency::USD);
+        form.update(TransactionFormMessage::DescriptionChanged("Groceries".to_string()));
+        form.update(TransactionFormMessage::AmountChanged(amount.to_string()));
+        form.update(TransactionFormMessage::CategoryChanged("cat_1".to_string()));
+        form.update(TransactionFormMessage::SplitTransaction);
+        form
+    }
+
+    #[test]
+    fn a_single_split_line_must_cover_the_amount() {
+        let mut form = split_form("40.00");
+        form.update(TransactionFormMessage::RemoveSplit(1));
+        let transaction = form.to_transaction(None).unwrap();
+        assert_eq!(transaction.category_id.as_deref(), Some("cat_1"));
+        assert!(transaction.splits.is_empty());
+
+        form.update(TransactionFormMessage::SplitAmountChanged(0, "25.00".to_string()));
+        assert!(form.to_transaction(None).is_err());
+    }
+
+    #[test]
+    fn split_lines_must_add_up_to_the_amount() {
+        let mut form = split_form("40.00");
+        form.update(TransactionFormMessage::SplitAmountChanged(0, "25.00".to_string()));
+        form.update(TransactionFormMessage::SplitCategoryChanged(1, "cat_2".to_string()));
+        form.update(TransactionFormMessage::SplitAmountChanged(1, "10.00".to_string()));
+        assert!(form.to_transaction(None).is_err());
+
+        form.update(TransactionFormMessage::SplitAmountChanged(1, "15.00".to_string()));
+        let transaction = form.to_transaction(None).unwrap();
+        assert_eq!(transaction.category_id, None);
+        assert_eq!(transaction.splits.len(), 2);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::models::{Currency, ExchangeRate};
+use crate::ui::UiState;
+use crate::utils::{format_date, today};
+use chrono::NaiveDate;
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input};
+use iced::{Alignment, Color, Element, Length};
+
+#[derive(Debug, Clone)]
+pub enum CurrencyMessage {
+    BaseCurrencySelected(Currency),
+    FromSelected(Currency),
+    ToSelected(Currency),
+    RateChanged(String),
+    DateChanged(String),
+    AddRate,
+    DeleteRate(String),
+}
+
+/// Base currency selection and the exchange-rate table.
+pub struct CurrencyView {
+    from: Currency,
+    to: Currency,
+    rate: String,
+    date: String,
+    error: Option<String>,
+}
+
+impl CurrencyView {
+    pub fn new() -> Self {
+        Self {
+            from: Currency::EUR,
+            to: Currency::USD,
+            rate: String::new(),
+            date: format_date(&today()),
+            error: None,
+        }
+    }
+
+    pub fn update(&mut self, message: CurrencyMessage) {
+        match message {
+            CurrencyMessage::FromSelected(currency) => {
+                self.from = currency;
+            }
+            CurrencyMessage::ToSelected(currency) => {
+                self.to = currency;
+            }
+            CurrencyMessage::RateChanged(rate) => {
+                self.rate = rate;
+            }
+            CurrencyMessage::DateChanged(date) => {
+                self.date = date;
+            }
+            CurrencyMessage::BaseCurrencySelected(_)
+            | CurrencyMessage::AddRate
+            | CurrencyMessage::DeleteRate(_) => {}
+        }
+    }
+
+    /// Validates the rate form, returning the rate to persist. Validation
+    /// errors are shown in the form instead.
+    pub fn submit(&mut self) -> Option<ExchangeRate> {
+        match self.to_exchange_rate() {
+            Ok(rate) => {
+                self.error = None;
+                Some(rate)
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    /// Clears the rate after a successful save, keeping the pair and date
+    /// so a run of rates can be entered quickly.
+    pub fn rate_saved(&mut self) {
+        self.rate.clear();
+    }
+
+    fn to_exchange_rate(&self) -> Result<ExchangeRate, String> {
+        if self.from == self.to {
+            return Err("Choose two different currencies".to_string());
+        }
+
+        let rate: f64 = self.rate.trim().parse()
+            .map_err(|_| format!("Invalid rate: {}", self.rate))?;
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err("Rate must be greater than zero".to_string());
+        }
+
+        let date = NaiveDate::parse_from_str(self.date.trim(), "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date: {} (expected YYYY-MM-DD)", self.date))?;
+
+        Ok(ExchangeRate::new(self.from, self.to, rate, date))
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, CurrencyMessage> {
+        let base_currency = row![
+            text("Base currency:").width(Length::Fixed(120.0)),
+            pick_list(&Currency::ALL[..], Some(state.converter.base()), CurrencyMessage::BaseCurrencySelected),
+            text("Dashboard and budget totals are converted into this currency.").size(14),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let rate_form = container(
+            column![
+                text("Add Exchange Rate").size(20),
+                row![
+                    text("1"),
+                    pick_list(&Currency::ALL[..], Some(self.from), CurrencyMessage::FromSelected),
+                    text("="),
+                    text_input("Rate", &self.rate)
+                        .on_input(CurrencyMessage::RateChanged)
+                        .padding(10)
+                        .width(Length::Fixed(120.0)),
+                    pick_list(&Currency::ALL[..], Some(self.to), CurrencyMessage::ToSelected),
+                    text("from"),
+                    text_input("YYYY-MM-DD", &self.date)
+                        .on_input(CurrencyMessage::DateChanged)
+                        .padding(10)
+                        .width(Length::Fixed(140.0)),
+                    button("Add")
+                        .on_press(CurrencyMessage::AddRate)
+                        .padding(10),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                text(self.error.clone().unwrap_or_default())
+                    .size(14)
+                    .color(Color::from_rgb(0.8, 0.0, 0.0)),
+            ]
+            .spacing(10)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box);
+
+        let rates_list = state.converter.rates().iter()
+            .fold(column![].spacing(5), |column, rate| {
+                column.push(
+                    container(
+                        row![
+                            text(format!("1 {} = {} {}", rate.from, rate.rate, rate.to))
+                                .width(Length::FillPortion(3)),
+                            text(format!("from {}", format_date(&rate.effective_date)))
+                                .width(Length::FillPortion(2)),
+                            button("Delete")
+                                .on_press(CurrencyMessage::DeleteRate(rate.id.clone()))
+                                .style(button::danger),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center)
+                    )
+                    .padding(10)
+                    .style(container::rounded_box)
+                )
+            });
+
+        container(
+            column![
+                text("Currencies").size(30),
+                base_currency,
+                rate_form,
+                text("Exchange Rates").size(20),
+                scrollable(rates_list).height(Length::Fill),
+            ]
+            .spacing(20)
+        )
+        .padding(20)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
@@ -1,432 +1,318 @@
-use crate::models::{Budget, Category, Transaction, TransactionType};
-use crate::ui::UiState;
-use chrono::{Datelike, NaiveDate};
-
-use iced::{Alignment, Element, Length};
-use iced::widget::{column, container, row, scrollable, text, rule};
+use crate::models::{Account, Budget, BudgetPeriod, Category, CurrencyConverter, DateRange, Money, Period, Transaction, TransactionType};
+use crate::models::account::net_worth;
+use crate::models::budget::ALL_SPENDING;
+use crate::ui::category_name;
 use crate::ui::components::charts;
-use iced::{Element, Length, Padding};
-use iced::widget::{button};
+use crate::utils::{format_currency, format_date, today};
 
-use crate::app::{BudgetTracker, Message, TransactionType};
+use iced::{Alignment, Color, Element, Length};
+use iced::widget::{button, column, container, horizontal_rule, pick_list, row, scrollable, text};
 
 #[derive(Debug, Clone)]
 pub enum DashboardMessage {
     Refresh,
-    Error(String),
     ViewTransactions,
     ViewBudgets,
+    PeriodSelected(Period),
+    PreviousPeriod,
+    NextPeriod,
 }
 
+const INCOME_COLOR: Color = Color::from_rgb(0.0, 0.5, 0.0);
+const EXPENSE_COLOR: Color = Color::from_rgb(0.8, 0.0, 0.0);
+const TRANSFER_COLOR: Color = Color::from_rgb(0.4, 0.4, 0.4);
+
+/// How many past periods of a renewing budget the overview lists.
+const BUDGET_HISTORY_SHOWN: usize = 3;
+
 pub struct Dashboard<'a> {
     transactions: &'a [Transaction],
     categories: &'a [Category],
+    accounts: &'a [Account],
     budgets: &'a [Budget],
+    budget_periods: &'a [BudgetPeriod],
+    converter: &'a CurrencyConverter,
+    range: DateRange,
 }
 
 impl<'a> Dashboard<'a> {
     pub fn new(
         transactions: &'a [Transaction],
         categories: &'a [Category],
+        accounts: &'a [Account],
         budgets: &'a [Budget],
+        budget_periods: &'a [BudgetPeriod],
+        converter: &'a CurrencyConverter,
+        range: DateRange,
     ) -> Self {
-        Self { 
+        Self {
             transactions,
             categories,
+            accounts,
             budgets,
+            budget_periods,
+            converter,
+            range,
         }
     }
-    
-    pub fn view(&self) -> Element<DashboardMessage> {
-        let title = Text::new("Dashboard")
-            .size(28)
-            .style(Text::Default);
-            
+
+    pub fn view(&self) -> Element<'a, DashboardMessage> {
+        let period = Period::ALL.into_iter().find(|p| p.containing(self.range.from) == self.range);
+
+        let title = row![
+            text("Dashboard").size(28).width(Length::Fill),
+            button("<")
+                .on_press(DashboardMessage::PreviousPeriod)
+                .style(button::secondary),
+            pick_list(&Period::ALL[..], period, DashboardMessage::PeriodSelected)
+                .placeholder("Period"),
+            button(">")
+                .on_press(DashboardMessage::NextPeriod)
+                .style(button::secondary),
+            button("Refresh")
+                .on_press(DashboardMessage::Refresh)
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
         let summary = self.summary_view();
-        
-        let charts_column = Column::new()
-            .push(charts::create_expense_by_category_chart(
-                self.transactions, 
-                self.categories
-            ).map(|_| DashboardMessage::ViewTransactions))
-            .push(charts::create_income_vs_expense_chart(
-                self.transactions
-            ).map(|_| DashboardMessage::ViewTransactions))
-            .push(charts::create_budget_progress_chart(
+
+        let overview = row![
+            self.recent_transactions_section(),
+            self.budget_overview_section(),
+        ]
+        .spacing(20);
+
+        let charts_column = column![
+            charts::create_expense_by_category_chart(self.transactions, self.categories, self.converter),
+            charts::create_income_vs_expense_chart(self.transactions, self.converter, self.range),
+            charts::create_budget_progress_chart(
                 self.budgets,
+                self.budget_periods,
                 self.transactions,
-                self.categories
-            ).map(|_| DashboardMessage::ViewBudgets))
-            .spacing(20)
-            .width(Length::Fill)
-            .align_items(Alignment::Center);
-            
-        let content = Column::new()
-            .push(title)
-            .push(summary)
-            .push(charts_column)
-            .spacing(20)
-            .padding(20)
-            .width(Length::Fill);
-            
-        Scrollable::new(content)
+                self.categories,
+                self.converter,
+            ),
+        ]
+        .spacing(20)
+        .width(Length::Fill)
+        .align_x(Alignment::Center);
+
+        let content = column![
+            title,
+            summary,
+            horizontal_rule(10),
+            overview,
+            horizontal_rule(10),
+            charts_column,
+        ]
+        .spacing(20)
+        .padding(20)
+        .width(Length::Fill);
+
+        scrollable(content)
             .width(Length::Fill)
             .height(Length::Fill)
             .into()
     }
-    
-    fn summary_view(&self) -> Element<DashboardMessage> {
-        // Calculate total income, expenses and balance for the current month
-        let now = chrono::Local::now().naive_local().date();
-        let start_of_month = NaiveDate::from_ymd_opt(
-            now.year(),
-            now.month(),
-            1
-        ).unwrap();
-        
+
+    fn summary_view(&self) -> Element<'a, DashboardMessage> {
+        // Calculate total income, expenses and balance for the selected
+        // period in the base currency
+        let now = today();
+        let zero = Money::zero(self.converter.base());
+
+        let mut unconverted = 0;
+        // Transfers only move money between accounts, so they are left out
         let (total_income, total_expenses) = self.transactions.iter()
-            .filter(|t| t.date >= start_of_month && t.date <= now)
-            .fold((0.0, 0.0), |(inc, exp), t| {
-                match t.transaction_type {
-                    TransactionType::Income => (inc + t.amount, exp),
-                    TransactionType::Expense => (inc, exp + t.amount),
+            .filter(|t| self.range.contains(t.date) && !t.is_transfer())
+            .filter_map(|t| match self.converter.to_base(t.amount, t.date) {
+                Some(amount) => Some((t.transaction_type, amount)),
+                None => {
+                    unconverted += 1;
+                    None
+                }
+            })
+            .fold((zero, zero), |(inc, exp), (transaction_type, amount)| {
+                match transaction_type {
+                    TransactionType::Income => (inc + amount, exp),
+                    TransactionType::Expense => (inc, exp + amount),
+                    TransactionType::Transfer => (inc, exp),
                 }
             });
-            
+
         let balance = total_income - total_expenses;
-        
-        let income_text = Text::new(format!("Income: ${:.2}", total_income))
+
+        let income_text = text(format!("Income: {}", format_currency(total_income)))
             .size(20)
-            .style(Text::Color(iced::Color::from_rgb(0.0, 0.5, 0.0)));
-            
-        let expenses_text = Text::new(format!("Expenses: ${:.2}", total_expenses))
+            .color(INCOME_COLOR);
+
+        let expenses_text = text(format!("Expenses: {}", format_currency(total_expenses)))
             .size(20)
-            .style(Text::Color(iced::Color::from_rgb(0.8, 0.0, 0.0)));
-            
-        let balance_text = Text::new(format!("Balance: ${:.2}", balance))
+            .color(EXPENSE_COLOR);
+
+        let balance_text = text(format!("Balance: {}", format_currency(balance)))
             .size(20)
-            .style(if balance >= 0.0 {
-                Text::Color(iced::Color::from_rgb(0.0, 0.5, 0.0))
-            } else {
-                Text::Color(iced::Color::from_rgb(0.8, 0.0, 0.0))
-            });
-            
-        let month_name = chrono::Month::from_u32(now.month())
-            .unwrap()
-            .name();
-            
-        let month_text = Text::new(format!("Summary for {}", month_name))
-            .size(22);
-            
-        let summary_row = Row::new()
-            .push(Container::new(income_text).width(Length::Fill))
-            .push(Container::new(expenses_text).width(Length::Fill))
-            .push(Container::new(balance_text).width(Length::Fill))
-            .spacing(20);
-            
-        Column::new()
-            .push(month_text)
-            .push(summary_row)
-            .spacing(10)
-            .padding(20)
-            .width(Length::Fill)
-            .style(Container::Box)
-            .into()
+            .color(if balance.is_negative() { EXPENSE_COLOR } else { INCOME_COLOR });
+
+        let month_text = text(format!(
+            "Summary for {} ({})",
+            self.range.label(),
+            self.converter.base()
+        ))
+        .size(22);
+
+        let missing_rates_text = text(if unconverted > 0 {
+            format!(
+                "{} transaction(s) left out: no exchange rate to {}",
+                unconverted,
+                self.converter.base()
+            )
+        } else {
+            String::new()
+        })
+        .size(14)
+        .color(EXPENSE_COLOR);
+
+        let mut summary_row = row![
+            container(income_text).width(Length::Fill),
+            container(expenses_text).width(Length::Fill),
+            container(balance_text).width(Length::Fill),
+        ]
+        .spacing(20);
+
+        // Net worth is only meaningful once accounts are being tracked
+        if !self.accounts.is_empty() {
+            let net_worth_text = match net_worth(self.accounts, self.transactions, self.converter, now) {
+                Some(total) => text(format!("Net Worth: {}", format_currency(total)))
+                    .color(if total.is_negative() { EXPENSE_COLOR } else { INCOME_COLOR }),
+                None => text("Net Worth: missing exchange rate"),
+            };
+            summary_row = summary_row.push(container(net_worth_text.size(20)).width(Length::Fill));
+        }
+
+        container(
+            column![month_text, summary_row, missing_rates_text]
+                .spacing(10)
+                .padding(20)
+                .width(Length::Fill)
+        )
+        .style(container::rounded_box)
+        .into()
     }
-}
 
-pub fn view(state: &UiState) -> Element<DashboardMessage> {
-    let title = Text::new("Dashboard")
-        .size(30)
-        .width(Length::Fill);
+    fn recent_transactions_section(&self) -> Element<'a, DashboardMessage> {
+        let title = text("Recent Transactions").size(22);
+
+        let transactions_list = self.transactions.iter()
+            .take(5) // Show only the 5 most recent transactions
+            .fold(
+                column![].spacing(10),
+                |column, transaction| {
+                    let amount_color = match transaction.transaction_type {
+                        TransactionType::Income => INCOME_COLOR,
+                        TransactionType::Expense => EXPENSE_COLOR,
+                        TransactionType::Transfer => TRANSFER_COLOR,
+                    };
 
-    let summary = summary_section(state);
-    
-    let recent_transactions = recent_transactions_section(state);
-    
-    let budgets_overview = budget_overview_section(state);
-
-    Column::new()
-        .push(title)
-        .push(Rule::horizontal(10))
-        .push(summary)
-        .push(Rule::horizontal(10))
-        .push(
-            Row::new()
-                .push(recent_transactions)
-                .push(budgets_overview)
-                .spacing(20)
+                    column.push(
+                        container(
+                            row![
+                                text(format_date(&transaction.date)).width(Length::Fill),
+                                text(&transaction.description).width(Length::Fill),
+                                text(format_currency(transaction.amount))
+                                    .color(amount_color)
+                                    .width(Length::Fill),
+                                text(category_name(self.categories, transaction.category_id.as_deref())).width(Length::Fill),
+                            ]
+                        )
+                        .padding(10)
+                        .style(container::rounded_box)
+                    )
+                }
+            );
+
+        let view_all_button = button("View All Transactions")
+            .on_press(DashboardMessage::ViewTransactions)
+            .padding(10);
+
+        container(
+            column![title, transactions_list, view_all_button]
+                .spacing(15)
+                .padding(15)
+                .width(Length::Fill)
         )
-        .spacing(20)
-        .padding(20)
         .width(Length::Fill)
+        .style(container::rounded_box)
         .into()
-}
+    }
 
-fn summary_section(state: &UiState) -> Element<DashboardMessage> {
-    // Calculate total income
-    let total_income: f64 = state.transactions.iter()
-        .filter(|t| t.transaction_type == TransactionType::Income)
-        .map(|t| t.amount)
-        .sum();
-    
-    // Calculate total expenses
-    let total_expenses: f64 = state.transactions.iter()
-        .filter(|t| t.transaction_type == TransactionType::Expense)
-        .map(|t| t.amount)
-        .sum();
-    
-    // Calculate balance
-    let balance = total_income - total_expenses;
-    
-    let income_text = Text::new(format!("Total Income: ${:.2}", total_income))
-        .size(18);
-    
-    let expenses_text = Text::new(format!("Total Expenses: ${:.2}", total_expenses))
-        .size(18);
-    
-    let balance_text = Text::new(format!("Balance: ${:.2}", balance))
-        .size(20);
-    
-    Container::new(
-        Column::new()
-            .push(Text::new("Financial Summary").size(22))
-            .push(
-                Row::new()
-                    .push(income_text)
-                    .push(expenses_text)
-                    .spacing(20)
-                    .width(Length::Fill)
-            )
-            .push(balance_text)
-            .spacing(15)
-            .padding(15)
-            .width(Length::Fill)
-    )
-    .width(Length::Fill)
-    .style(Container::Box)
-    .into()
-}
+    fn budget_overview_section(&self) -> Element<'a, DashboardMessage> {
+        let title = text("Budget Overview").size(22);
 
-fn recent_transactions_section(state: &UiState) -> Element<DashboardMessage> {
-    let title = Text::new("Recent Transactions").size(22);
-    
-    let transactions_list = state.transactions.iter()
-        .take(5) // Show only the 5 most recent transactions
-        .fold(
-            Column::new().spacing(10),
-            |column, transaction| {
-                let date = transaction.date.format("%Y-%m-%d").to_string();
-                let transaction_type = match transaction.transaction_type {
-                    TransactionType::Income => "Income",
-                    TransactionType::Expense => "Expense",
-                };
-                
-                let category_name = state.categories.iter()
-                    .find(|c| c.id == transaction.category_id)
-                    .map(|c| c.name.as_str())
-                    .unwrap_or("No Category");
-                
-                column.push(
-                    Container::new(
-                        Row::new()
-                            .push(Text::new(date).width(Length::Fill))
-                            .push(Text::new(format!("${:.2}", transaction.amount)).width(Length::Fill))
-                            .push(Text::new(transaction_type).width(Length::Fill))
-                            .push(Text::new(category_name).width(Length::Fill))
-                    )
-                    .padding(10)
-                    .style(Container::Box)
-                )
-            }
-        );
-    
-    Container::new(
-        Column::new()
-            .push(title)
-            .push(transactions_list)
-            .spacing(15)
-            .padding(15)
-            .width(Length::Fill)
-    )
-    .width(Length::Fill)
-    .style(Container::Box)
-    .into()
-}
+        let budgets_list = self.budgets.iter()
+            .take(5) // Show only 5 budgets
+            .fold(
+                column![].spacing(10),
+                |column, budget| {
+                    let category_name = budget.category_id.as_deref()
+                        .map_or(ALL_SPENDING, |id| category_name(self.categories, Some(id)));
+                    let now = today();
+                    let periods: Vec<&BudgetPeriod> = budget.recorded(self.budget_periods).collect();
+                    let current = periods.iter().copied().find(|p| p.range.contains(now));
 
-fn budget_overview_section(state: &UiState) -> Element<DashboardMessage> {
-    let title = Text::new("Budget Overview").size(22);
-    
-    let budgets_list = state.budgets.iter()
-        .take(5) // Show only 5 budgets
-        .fold(
-            Column::new().spacing(10),
-            |column, budget| {
-                let category_name = state.categories.iter()
-                    .find(|c| c.id == budget.category_id)
-                    .map(|c| c.name.as_str())
-                    .unwrap_or("No Category");
-                
-                // Calculate spending for this budget
-                let spending: f64 = state.transactions.iter()
-                    .filter(|t| t.transaction_type == TransactionType::Expense)
-                    .filter(|t| t.category_id == budget.category_id)
-                    .filter(|t| t.date >= budget.start_date && t.date <= budget.end_date)
-                    .map(|t| t.amount)
-                    .sum();
-                
-                // Calculate percentage of budget used
-                let percentage = if budget.amount > 0.0 {
-                    (spending / budget.amount) * 100.0
-                } else {
-                    0.0
-                };
-                
-                column.push(
-                    Container::new(
-                        Column::new()
-                            .push(
-                                Row::new()
-                                    .push(Text::new(format!("{} ({})", budget.name, category_name)))
-                                    .push(Text::new(format!("${:.2} / ${:.2} ({:.1}%)", 
-                                        spending, budget.amount, percentage)))
-                                    .spacing(10)
+                    // Earlier periods of a renewing budget, most recent first
+                    let history = periods.iter()
+                        .rev()
+                        .filter(|p| p.range.to < now)
+                        .take(if budget.renewal.is_some() { BUDGET_HISTORY_SHOWN } else { 0 })
+                        .fold(column![].spacing(2), |column, period| {
+                            let carry = if budget.rollover {
+                                format!(", {} carried forward", format_currency(period.left()))
+                            } else {
+                                String::new()
+                            };
+                            column.push(
+                                text(format!(
+                                    "{} to {}: {} allocated, {} spent{}",
+                                    format_date(&period.range.from),
+                                    format_date(&period.range.to),
+                                    format_currency(period.allocated),
+                                    format_currency(period.spent),
+                                    carry
+                                ))
+                                .size(12)
                             )
-                            // Here you might add a progress bar
+                        });
+
+                    column.push(
+                        container(
+                            column![
+                                text(format!("{} ({})", budget.name, category_name)),
+                                charts::budget_progress(budget, current, self.transactions, self.categories, self.converter),
+                                history,
+                            ]
                             .spacing(5)
+                        )
+                        .padding(10)
+                        .style(container::rounded_box)
                     )
-                    .padding(10)
-                    .style(Container::Box)
-                )
-            }
-        );
-    
-    Container::new(
-        Column::new()
-            .push(title)
-            .push(budgets_list)
-            .spacing(15)
-            .padding(15)
-            .width(Length::Fill)
-    )
-    .width(Length::Fill)
-    .style(Container::Box)
-    .into()
-}
+                }
+            );
 
-pub fn view<'a>(budget_tracker: &BudgetTracker) -> Element<'a, Message> {
-    let title = Text::new("Budget Dashboard")
-        .size(30);
-
-    let summary_title = Text::new("Financial Summary")
-        .size(24);
-        
-    let total_income = budget_tracker.calculate_total_income();
-    let total_expenses = budget_tracker.calculate_total_expenses();
-    let balance = total_income - total_expenses;
-    
-    let summary_row = Row::new()
-        .spacing(20)
-        .push(
-            Container::new(
-                Column::new()
-                    .spacing(5)
-                    .push(Text::new("Income").size(18))
-                    .push(Text::new(format!("${:.2}", total_income)).size(24))
-            )
-            .padding(Padding::new(10))
-            .width(Length::FillPortion(1))
-        )
-        .push(
-            Container::new(
-                Column::new()
-                    .spacing(5)
-                    .push(Text::new("Expenses").size(18))
-                    .push(Text::new(format!("${:.2}", total_expenses)).size(24))
-            )
-            .padding(Padding::new(10))
-            .width(Length::FillPortion(1))
+        let view_all_button = button("Manage Budgets")
+            .on_press(DashboardMessage::ViewBudgets)
+            .padding(10);
+
+        container(
+            column![title, budgets_list, view_all_button]
+                .spacing(15)
+                .padding(15)
+                .width(Length::Fill)
         )
-        .push(
-            Container::new(
-                Column::new()
-                    .spacing(5)
-                    .push(Text::new("Balance").size(18))
-                    .push(Text::new(format!("${:.2}", balance))
-                        .size(24)
-                        .style(if balance >= 0.0 { 
-                            iced::Color::from_rgb(0.0, 0.6, 0.0) 
-                        } else { 
-                            iced::Color::from_rgb(0.8, 0.0, 0.0) 
-                        }))
-            )
-            .padding(Padding::new(10))
-            .width(Length::FillPortion(1))
-        );
-
-    // Chart placeholder - in a real app, this would use plotters-iced
-    let chart_placeholder = Container::new(
-        Text::new("Expense Breakdown Chart (Placeholder)")
-    )
-    .width(Length::Fill)
-    .height(Length::Units(200))
-    .center_x()
-    .center_y();
-
-    let recent_transactions_title = Text::new("Recent Transactions")
-        .size(24);
-    
-    // Build recent transactions list
-    let mut recent_transactions = Column::new()
-        .spacing(10);
-    
-    // Add headers
-    let header_row = Row::new()
-        .spacing(20)
-        .push(Text::new("Date").width(Length::FillPortion(2)))
-        .push(Text::new("Description").width(Length::FillPortion(3)))
-        .push(Text::new("Category").width(Length::FillPortion(2)))
-        .push(Text::new("Amount").width(Length::FillPortion(1)));
-    
-    recent_transactions = recent_transactions.push(header_row);
-    
-    // Add up to 5 recent transactions
-    for transaction in budget_tracker.transactions.iter().take(5) {
-        let amount_text = format!("${:.2}", transaction.amount);
-        
-        let row = Row::new()
-            .spacing(20)
-            .push(Text::new(&transaction.date.to_string()).width(Length::FillPortion(2)))
-            .push(Text::new(&transaction.description).width(Length::FillPortion(3)))
-            .push(Text::new(&transaction.category).width(Length::FillPortion(2)))
-            .push(
-                Text::new(&amount_text)
-                    .width(Length::FillPortion(1))
-                    .style(match transaction.transaction_type {
-                        TransactionType::Income => iced::Color::from_rgb(0.0, 0.6, 0.0),
-                        TransactionType::Expense => iced::Color::from_rgb(0.8, 0.0, 0.0),
-                    })
-            );
-            
-        recent_transactions = recent_transactions.push(row);
-    }
-    
-    // View all transactions button
-    let view_all_button = Button::new(
-        Text::new("View All Transactions")
-    )
-    .on_press(Message::ViewAllTransactions)
-    .padding(10);
-    
-    Column::new()
-        .spacing(20)
-        .push(title)
-        .push(summary_title)
-        .push(summary_row)
-        .push(chart_placeholder)
-        .push(recent_transactions_title)
-        .push(recent_transactions)
-        .push(view_all_button)
-        .padding(20)
+        .width(Length::Fill)
+        .style(container::rounded_box)
         .into()
+    }
 }
@@ -0,0 +1,267 @@
+use crate::export::ExportFilter;
+use crate::ui::tags::TagChoice;
+use crate::ui::UiState;
+use crate::utils::{documents_path, format_date, today};
+use chrono::NaiveDate;
+use iced::widget::{button, column, container, pick_list, row, text, text_input};
+use iced::{Alignment, Color, Element, Length};
+use std::fmt;
+use std::path::PathBuf;
+
+/// A category or account to filter on, or any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterChoice {
+    id: Option<String>,
+    name: String,
+}
+
+impl FilterChoice {
+    fn any() -> Self {
+        Self { id: None, name: "Any".to_string() }
+    }
+}
+
+impl fmt::Display for FilterChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ExportMessage {
+    FromChanged(String),
+    ToChanged(String),
+    CategorySelected(FilterChoice),
+    AccountSelected(FilterChoice),
+    TagSelected(TagChoice),
+    CsvPathChanged(String),
+    JsonPathChanged(String),
+    ExportCsv,
+    ExportJson,
+}
+
+/// Writes transactions to CSV and all data to JSON.
+pub struct ExportView {
+    from: String,
+    to: String,
+    category_id: Option<String>,
+    account_id: Option<String>,
+    tag: Option<String>,
+    csv_path: String,
+    json_path: String,
+    error: Option<String>,
+    status: Option<String>,
+}
+
+impl ExportView {
+    pub fn new() -> Self {
+        let date = format_date(&today());
+
+        Self {
+            from: String::new(),
+            to: String::new(),
+            category_id: None,
+            account_id: None,
+            tag: None,
+            csv_path: documents_path(&format!("transactions-{}.csv", date)),
+            json_path: documents_path(&format!("budget-tracker-{}.json", date)),
+            error: None,
+            status: None,
+        }
+    }
+
+    pub fn update(&mut self, message: ExportMessage) {
+        match message {
+            ExportMessage::FromChanged(from) => {
+                self.from = from;
+            }
+            ExportMessage::ToChanged(to) => {
+                self.to = to;
+            }
+            ExportMessage::CategorySelected(choice) => {
+                self.category_id = choice.id;
+            }
+            ExportMessage::AccountSelected(choice) => {
+                self.account_id = choice.id;
+            }
+            ExportMessage::TagSelected(choice) => {
+                self.tag = choice.name;
+            }
+            ExportMessage::CsvPathChanged(path) => {
+                self.csv_path = path;
+            }
+            ExportMessage::JsonPathChanged(path) => {
+                self.json_path = path;
+            }
+            ExportMessage::ExportCsv | ExportMessage::ExportJson => {}
+        }
+    }
+
+    pub fn csv_path(&self) -> PathBuf {
+        PathBuf::from(self.csv_path.trim())
+    }
+
+    pub fn json_path(&self) -> PathBuf {
+        PathBuf::from(self.json_path.trim())
+    }
+
+    /// Validates the filter fields, returning the filter to export with.
+    /// Validation errors are shown on the page instead.
+    pub fn filter(&mut self) -> Option<ExportFilter> {
+        match self.to_filter() {
+            Ok(filter) => {
+                self.error = None;
+                Some(filter)
+            }
+            Err(e) => {
+                self.set_error(e);
+                None
+            }
+        }
+    }
+
+    fn to_filter(&self) -> Result<ExportFilter, String> {
+        let parse_date = |value: &str| -> Result<Option<NaiveDate>, String> {
+            if value.trim().is_empty() {
+                return Ok(None);
+            }
+            NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| format!("Invalid date: {} (expected YYYY-MM-DD)", value))
+        };
+
+        let from = parse_date(&self.from)?;
+        let to = parse_date(&self.to)?;
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err("The start date is after the end date".to_string());
+            }
+        }
+
+        Ok(ExportFilter {
+            from,
+            to,
+            category_id: self.category_id.clone(),
+            account_id: self.account_id.clone(),
+            tag: self.tag.clone(),
+        })
+    }
+
+    pub fn exported(&mut self, status: String) {
+        self.error = None;
+        self.status = Some(status);
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.status = None;
+        self.error = Some(error);
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, ExportMessage> {
+        let category_choices: Vec<FilterChoice> = std::iter::once(FilterChoice::any())
+            .chain(state.categories.iter().map(|c| FilterChoice { id: Some(c.id.clone()), name: c.name.clone() }))
+            .collect();
+        let selected_category = category_choices.iter()
+            .find(|choice| choice.id == self.category_id)
+            .cloned();
+
+        let account_choices: Vec<FilterChoice> = std::iter::once(FilterChoice::any())
+            .chain(state.accounts.iter().map(|a| FilterChoice { id: Some(a.id.clone()), name: a.name.clone() }))
+            .collect();
+        let selected_account = account_choices.iter()
+            .find(|choice| choice.id == self.account_id)
+            .cloned();
+
+        let transactions_section = container(
+            column![
+                text("Transactions (CSV)").size(20),
+                text("One row per transaction with signed amounts, ready to open in a spreadsheet.").size(14),
+                row![
+                    text("From:").width(Length::Fixed(80.0)),
+                    text_input("YYYY-MM-DD", &self.from)
+                        .on_input(ExportMessage::FromChanged)
+                        .padding(10)
+                        .width(Length::Fixed(140.0)),
+                    text("To:"),
+                    text_input("YYYY-MM-DD", &self.to)
+                        .on_input(ExportMessage::ToChanged)
+                        .padding(10)
+                        .width(Length::Fixed(140.0)),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    text("Category:").width(Length::Fixed(80.0)),
+                    pick_list(category_choices, selected_category, ExportMessage::CategorySelected),
+                    text("Account:"),
+                    pick_list(account_choices, selected_account, ExportMessage::AccountSelected),
+                    text("Tag:"),
+                    pick_list(
+                        TagChoice::all(&state.tags),
+                        Some(TagChoice::selected(self.tag.as_deref())),
+                        ExportMessage::TagSelected,
+                    ),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    text("File:").width(Length::Fixed(80.0)),
+                    text_input("Path to CSV file", &self.csv_path)
+                        .on_input(ExportMessage::CsvPathChanged)
+                        .padding(10),
+                    button("Export CSV")
+                        .on_press(ExportMessage::ExportCsv)
+                        .padding(10),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(10)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box);
+
+        let everything_section = container(
+            column![
+                text("All Data (JSON)").size(20),
+                text("Accounts, categories, tags, payees, transactions, budgets, rules, exchange rates and settings.").size(14),
+                row![
+                    text("File:").width(Length::Fixed(80.0)),
+                    text_input("Path to JSON file", &self.json_path)
+                        .on_input(ExportMessage::JsonPathChanged)
+                        .padding(10),
+                    button("Export JSON")
+                        .on_press(ExportMessage::ExportJson)
+                        .padding(10),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(10)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box);
+
+        let message = match (&self.error, &self.status) {
+            (Some(error), _) => text(error.clone()).size(14).color(Color::from_rgb(0.8, 0.0, 0.0)),
+            (None, Some(status)) => text(status.clone()).size(14),
+            (None, None) => text(""),
+        };
+
+        container(
+            column![
+                text("Export").size(30),
+                transactions_section,
+                everything_section,
+                message,
+            ]
+            .spacing(20)
+        )
+        .padding(20)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
@@ -0,0 +1,724 @@
+use crate::import::csv::{parse_records, read_records, AmountColumns, CsvMapping, ImportProfile};
+use crate::import::ofx::parse_ofx;
+use crate::import::qif::parse_qif;
+use crate::import::{already_imported, DecimalSeparator, ImportBatch, ImportRow, StatementFormat};
+use crate::models::duplicate::find_candidates;
+use crate::models::{Account, Categorizer, Currency, DuplicatePair, Suggestion, Transaction, TransactionType};
+use crate::ui::UiState;
+use crate::utils::{format_currency, format_date};
+use iced::widget::{button, checkbox, column, container, pick_list, radio, row, scrollable, text, text_input};
+use iced::{Alignment, Color, Element, Length};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Rows shown in the preview; all valid rows are imported regardless.
+const PREVIEW_ROWS: usize = 100;
+
+const DATE_FORMATS: [&str; 5] = ["%Y-%m-%d", "%d/%m/%Y", "%m/%d/%Y", "%d.%m.%Y", "%d-%m-%Y"];
+
+const ERROR_COLOR: Color = Color::from_rgb(0.8, 0.0, 0.0);
+const SKIPPED_COLOR: Color = Color::from_rgb(0.5, 0.5, 0.5);
+const DUPLICATE_COLOR: Color = Color::from_rgb(0.8, 0.5, 0.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delimiter(char);
+
+impl Delimiter {
+    const ALL: [Delimiter; 3] = [Delimiter(','), Delimiter(';'), Delimiter('\t')];
+}
+
+impl fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ',' => write!(f, "Comma"),
+            ';' => write!(f, "Semicolon"),
+            '\t' => write!(f, "Tab"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// A column of the loaded file, labelled by its header when there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnChoice {
+    index: Option<usize>,
+    label: String,
+}
+
+impl fmt::Display for ColumnChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountMode {
+    Single,
+    DebitCredit,
+}
+
+/// How a parsed row relates to the transactions already recorded.
+enum RowStatus<'a> {
+    New,
+    AlreadyImported,
+    Duplicate(Vec<&'a Transaction>),
+}
+
+fn row_status<'a>(transaction: &Transaction, state: &'a UiState) -> RowStatus<'a> {
+    if already_imported(transaction, &state.transactions) {
+        return RowStatus::AlreadyImported;
+    }
+
+    let candidates = find_candidates(transaction, &state.transactions, &state.dismissed_duplicates);
+    if candidates.is_empty() {
+        RowStatus::New
+    } else {
+        RowStatus::Duplicate(candidates)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportMessage {
+    PathChanged(String),
+    LoadFile,
+    FileLoaded(Result<String, String>),
+    FormatSelected(StatementFormat),
+    /// Whether to import the row on this line alongside the transaction it
+    /// seems to duplicate, rather than merging it in.
+    KeepBothToggled(usize, bool),
+    UseSuggestionsToggled(bool),
+    DelimiterSelected(Delimiter),
+    HasHeaderToggled(bool),
+    DateColumnSelected(ColumnChoice),
+    DateFormatChanged(String),
+    DateFormatPreset(&'static str),
+    DescriptionColumnSelected(ColumnChoice),
+    AmountModeSelected(AmountMode),
+    AmountColumnSelected(ColumnChoice),
+    DebitColumnSelected(ColumnChoice),
+    CreditColumnSelected(ColumnChoice),
+    CategoryColumnSelected(ColumnChoice),
+    DecimalSeparatorSelected(DecimalSeparator),
+    CurrencySelected(Currency),
+    AccountSelected(String),
+    ProfileSelected(ImportProfile),
+    ProfileNameChanged(String),
+    SaveProfile,
+    DeleteProfile(String),
+    Import,
+}
+
+pub struct ImportView {
+    path: String,
+    contents: String,
+    format: StatementFormat,
+    records: Vec<Vec<String>>,
+    mapping: CsvMapping,
+    account_id: Option<String>,
+    profile_name: String,
+    keep_both: HashSet<usize>,
+    /// Fill uncategorised rows with the top learned suggestion on import.
+    use_suggestions: bool,
+    error: Option<String>,
+    status: Option<String>,
+}
+
+impl ImportView {
+    pub fn new() -> Self {
+        Self {
+            path: String::new(),
+            contents: String::new(),
+            format: StatementFormat::default(),
+            records: Vec::new(),
+            mapping: CsvMapping::default(),
+            account_id: None,
+            profile_name: String::new(),
+            keep_both: HashSet::new(),
+            use_suggestions: false,
+            error: None,
+            status: None,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.trim()
+    }
+
+    pub fn update(&mut self, message: ImportMessage) {
+        match message {
+            ImportMessage::PathChanged(path) => {
+                self.path = path;
+            }
+            ImportMessage::FileLoaded(result) => {
+                match result {
+                    Ok(contents) => {
+                        self.contents = contents;
+                        self.format = StatementFormat::from_path(Path::new(self.path()));
+                        self.keep_both.clear();
+                        self.status = None;
+                        self.error = None;
+                        self.reload_records();
+                    }
+                    Err(e) => {
+                        self.contents.clear();
+                        self.records.clear();
+                        self.error = Some(e);
+                    }
+                }
+            }
+            ImportMessage::FormatSelected(format) => {
+                self.format = format;
+                self.error = None;
+                self.reload_records();
+            }
+            ImportMessage::KeepBothToggled(line, keep_both) => {
+                if keep_both {
+                    self.keep_both.insert(line);
+                } else {
+                    self.keep_both.remove(&line);
+                }
+            }
+            ImportMessage::UseSuggestionsToggled(use_suggestions) => {
+                self.use_suggestions = use_suggestions;
+            }
+            ImportMessage::DelimiterSelected(delimiter) => {
+                self.mapping.delimiter = delimiter.0;
+                self.reload_records();
+            }
+            ImportMessage::HasHeaderToggled(has_header) => {
+                self.mapping.has_header = has_header;
+            }
+            ImportMessage::DateColumnSelected(choice) => {
+                if let Some(index) = choice.index {
+                    self.mapping.date_column = index;
+                }
+            }
+            ImportMessage::DateFormatChanged(format) => {
+                self.mapping.date_format = format;
+            }
+            ImportMessage::DateFormatPreset(format) => {
+                self.mapping.date_format = format.to_string();
+            }
+            ImportMessage::DescriptionColumnSelected(choice) => {
+                if let Some(index) = choice.index {
+                    self.mapping.description_column = index;
+                }
+            }
+            ImportMessage::AmountModeSelected(mode) => {
+                self.mapping.amount = match (mode, self.mapping.amount) {
+                    (AmountMode::Single, AmountColumns::DebitCredit { debit, .. }) => AmountColumns::Single(debit),
+                    (AmountMode::DebitCredit, AmountColumns::Single(column)) => {
+                        AmountColumns::DebitCredit { debit: column, credit: column + 1 }
+                    }
+                    (_, amount) => amount,
+                };
+            }
+            ImportMessage::AmountColumnSelected(choice) => {
+                if let Some(index) = choice.index {
+                    self.mapping.amount = AmountColumns::Single(index);
+                }
+            }
+            ImportMessage::DebitColumnSelected(choice) => {
+                if let (Some(index), AmountColumns::DebitCredit { debit, .. }) = (choice.index, &mut self.mapping.amount) {
+                    *debit = index;
+                }
+            }
+            ImportMessage::CreditColumnSelected(choice) => {
+                if let (Some(index), AmountColumns::DebitCredit { credit, .. }) = (choice.index, &mut self.mapping.amount) {
+                    *credit = index;
+                }
+            }
+            ImportMessage::CategoryColumnSelected(choice) => {
+                self.mapping.category_column = choice.index;
+            }
+            ImportMessage::DecimalSeparatorSelected(separator) => {
+                self.mapping.decimal_separator = separator;
+            }
+            ImportMessage::CurrencySelected(currency) => {
+                self.mapping.currency = currency;
+            }
+            ImportMessage::AccountSelected(account_id) => {
+                self.account_id = Some(account_id);
+            }
+            ImportMessage::ProfileSelected(profile) => {
+                self.profile_name = profile.name;
+                self.mapping = profile.mapping;
+                self.account_id = profile.account_id;
+                self.reload_records();
+            }
+            ImportMessage::ProfileNameChanged(name) => {
+                self.profile_name = name;
+            }
+            ImportMessage::LoadFile
+            | ImportMessage::SaveProfile
+            | ImportMessage::DeleteProfile(_)
+            | ImportMessage::Import => {}
+        }
+    }
+
+    /// Splits CSV contents into records; other formats are parsed whole.
+    fn reload_records(&mut self) {
+        self.records.clear();
+        if self.contents.is_empty() || self.format != StatementFormat::Csv {
+            return;
+        }
+
+        match read_records(&self.contents, self.mapping.delimiter) {
+            Ok(records) => {
+                self.records = records;
+                self.error = None;
+            }
+            Err(e) => {
+                self.records.clear();
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Parses the loaded file and fills in categories from the rules, as
+    /// saving will.
+    fn rows(&self, state: &UiState) -> Result<Vec<ImportRow>, String> {
+        let categories = &state.categories;
+        let account_id = self.account_id.as_deref();
+        let mut rows = match self.format {
+            StatementFormat::Csv => Ok(parse_records(&self.records, &self.mapping, categories, account_id)),
+            StatementFormat::Ofx => parse_ofx(&self.contents, self.mapping.currency, account_id)
+                .map_err(|e| e.to_string()),
+            StatementFormat::Qif => Ok(parse_qif(
+                &self.contents,
+                &self.mapping.date_format,
+                self.mapping.decimal_separator,
+                self.mapping.currency,
+                categories,
+                account_id,
+            )),
+        }?;
+
+        let categorizer = Categorizer::new(&state.category_rules);
+        for row in &mut rows {
+            if let Ok(transaction) = &mut row.result {
+                categorizer.categorize(std::slice::from_mut(transaction));
+            }
+        }
+
+        Ok(rows)
+    }
+
+    /// What importing the rows that parsed cleanly will write. Rows already
+    /// imported are left out, and likely duplicates are merged into the
+    /// existing transaction unless the user chose to keep both.
+    pub fn batch(&self, state: &UiState) -> ImportBatch {
+        let mut batch = ImportBatch::default();
+
+        for row in self.rows(state).unwrap_or_default() {
+            let Ok(mut transaction) = row.result else {
+                continue;
+            };
+            if self.use_suggestions && transaction.category_id.is_none() {
+                transaction.category_id = top_suggestion(state, &transaction)
+                    .map(|suggestion| suggestion.category_id);
+            }
+
+            match row_status(&transaction, state) {
+                RowStatus::AlreadyImported => {}
+                RowStatus::New => batch.transactions.push(transaction),
+                RowStatus::Duplicate(candidates) if self.keep_both.contains(&row.line) => {
+                    batch.dismissed.extend(
+                        candidates.iter().map(|existing| DuplicatePair::new(transaction.id, existing.id))
+                    );
+                    batch.transactions.push(transaction);
+                }
+                RowStatus::Duplicate(candidates) => {
+                    batch.merges.push((candidates[0].id, transaction));
+                }
+            }
+        }
+
+        batch
+    }
+
+    /// The current mapping saved under the profile name, or `None` with an
+    /// error shown when no name has been given.
+    pub fn profile(&mut self) -> Option<ImportProfile> {
+        let name = self.profile_name.trim();
+        if name.is_empty() {
+            self.error = Some("Enter a profile name to save the mapping".to_string());
+            return None;
+        }
+
+        Some(ImportProfile {
+            name: name.to_string(),
+            mapping: self.mapping.clone(),
+            account_id: self.account_id.clone(),
+        })
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    /// Clears the loaded file after a successful import.
+    pub fn imported(&mut self, count: usize) {
+        self.contents.clear();
+        self.records.clear();
+        self.keep_both.clear();
+        self.error = None;
+        self.status = Some(format!("Imported {} transactions", count));
+    }
+
+    fn column_choices(&self, optional: bool) -> Vec<ColumnChoice> {
+        let width = self.records.iter().map(Vec::len).max().unwrap_or(0);
+        let header = self.records.first().filter(|_| self.mapping.has_header);
+
+        let none = optional.then(|| ColumnChoice { index: None, label: "(none)".to_string() });
+        none.into_iter()
+            .chain((0..width).map(|index| {
+                let label = match header.and_then(|h| h.get(index)).filter(|h| !h.is_empty()) {
+                    Some(name) => format!("{}: {}", index + 1, name),
+                    None => format!("Column {}", index + 1),
+                };
+                ColumnChoice { index: Some(index), label }
+            }))
+            .collect()
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, ImportMessage> {
+        let file_row = row![
+            text_input("Path to statement file (CSV, OFX, QFX or QIF)", &self.path)
+                .on_input(ImportMessage::PathChanged)
+                .on_submit(ImportMessage::LoadFile)
+                .padding(10),
+            button("Load")
+                .on_press(ImportMessage::LoadFile)
+                .padding(10),
+        ]
+        .spacing(10);
+
+        let profile_row = row![
+            text("Profile:").width(Length::Fixed(120.0)),
+            pick_list(
+                state.import_profiles.as_slice(),
+                state.import_profiles.iter().find(|p| p.name == self.profile_name).cloned(),
+                ImportMessage::ProfileSelected,
+            )
+            .placeholder("Saved profiles"),
+            text_input("Profile name", &self.profile_name)
+                .on_input(ImportMessage::ProfileNameChanged)
+                .padding(10)
+                .width(Length::Fixed(200.0)),
+            button("Save Profile")
+                .on_press(ImportMessage::SaveProfile)
+                .style(button::secondary),
+            button("Delete Profile")
+                .on_press_maybe(
+                    state.import_profiles.iter()
+                        .any(|p| p.name == self.profile_name)
+                        .then(|| ImportMessage::DeleteProfile(self.profile_name.clone()))
+                )
+                .style(button::danger),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let mut content = column![text("Import Transactions").size(30), file_row, profile_row].spacing(20);
+
+        let loaded = !self.contents.is_empty();
+        if loaded {
+            content = content.push(self.mapping_view(state));
+        }
+
+        if let Some(error) = &self.error {
+            content = content.push(text(error.clone()).size(14).color(ERROR_COLOR));
+        }
+        if let Some(status) = &self.status {
+            content = content.push(text(status.clone()).size(16));
+        }
+
+        if loaded {
+            content = content.push(self.preview_view(state));
+        }
+
+        container(scrollable(content))
+            .padding(20)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn mapping_view<'a>(&self, state: &'a UiState) -> Element<'a, ImportMessage> {
+        let label = |label: &'static str| text(label).width(Length::Fixed(120.0));
+
+        let date_format = row![
+            text_input("Date format", &self.mapping.date_format)
+                .on_input(ImportMessage::DateFormatChanged)
+                .padding(10)
+                .width(Length::Fixed(140.0)),
+            pick_list(&DATE_FORMATS[..], None::<&'static str>, ImportMessage::DateFormatPreset)
+                .placeholder("Common formats"),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let number_format = row![
+            label("Number format:"),
+            radio(DecimalSeparator::Dot.to_string(), DecimalSeparator::Dot, Some(self.mapping.decimal_separator), ImportMessage::DecimalSeparatorSelected),
+            radio(DecimalSeparator::Comma.to_string(), DecimalSeparator::Comma, Some(self.mapping.decimal_separator), ImportMessage::DecimalSeparatorSelected),
+            pick_list(&Currency::ALL[..], Some(self.mapping.currency), ImportMessage::CurrencySelected),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let selected_account = self.account_id.as_ref()
+            .and_then(|id| state.accounts.iter().find(|a| &a.id == id))
+            .cloned();
+        let account = row![
+            label("Account:"),
+            pick_list(state.accounts.as_slice(), selected_account, |account: Account| {
+                ImportMessage::AccountSelected(account.id)
+            })
+            .placeholder("Select account"),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let mut fields = column![
+            text("Mapping").size(20),
+            row![
+                label("Format:"),
+                pick_list(&StatementFormat::ALL[..], Some(self.format), ImportMessage::FormatSelected),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10);
+
+        fields = match self.format {
+            StatementFormat::Csv => fields.push(self.columns_view(date_format, number_format)),
+            StatementFormat::Qif => fields
+                .push(row![label("Date:"), date_format].spacing(10).align_y(Alignment::Center))
+                .push(number_format),
+            // OFX fixes its date and number formats; the currency is only
+            // used when the statement does not name one
+            StatementFormat::Ofx => fields.push(
+                row![
+                    label("Currency:"),
+                    pick_list(&Currency::ALL[..], Some(self.mapping.currency), ImportMessage::CurrencySelected),
+                    text("used when the statement has none").size(14),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ),
+        };
+
+        container(
+            fields
+                .push(account)
+                .padding(20)
+                .width(Length::Fill)
+        )
+        .style(container::rounded_box)
+        .into()
+    }
+
+    /// The CSV column mapping, with the shared date and number format rows.
+    fn columns_view<'a>(
+        &self,
+        date_format: iced::widget::Row<'a, ImportMessage>,
+        number_format: iced::widget::Row<'a, ImportMessage>,
+    ) -> Element<'a, ImportMessage> {
+        let columns = self.column_choices(false);
+        let optional_columns = self.column_choices(true);
+        let selected = |choices: &[ColumnChoice], index: Option<usize>| {
+            choices.iter().find(|c| c.index == index).cloned()
+        };
+        let label = |label: &'static str| text(label).width(Length::Fixed(120.0));
+
+        let amount_mode = match self.mapping.amount {
+            AmountColumns::Single(_) => AmountMode::Single,
+            AmountColumns::DebitCredit { .. } => AmountMode::DebitCredit,
+        };
+
+        let amount_columns: Element<'a, ImportMessage> = match self.mapping.amount {
+            AmountColumns::Single(column) => pick_list(
+                columns.clone(),
+                selected(&columns, Some(column)),
+                ImportMessage::AmountColumnSelected,
+            )
+            .into(),
+            AmountColumns::DebitCredit { debit, credit } => row![
+                text("Debit:"),
+                pick_list(columns.clone(), selected(&columns, Some(debit)), ImportMessage::DebitColumnSelected),
+                text("Credit:"),
+                pick_list(columns.clone(), selected(&columns, Some(credit)), ImportMessage::CreditColumnSelected),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .into(),
+        };
+
+        column![
+            row![
+                label("Delimiter:"),
+                pick_list(&Delimiter::ALL[..], Some(Delimiter(self.mapping.delimiter)), ImportMessage::DelimiterSelected),
+                checkbox("First row is a header", self.mapping.has_header)
+                    .on_toggle(ImportMessage::HasHeaderToggled),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            row![
+                label("Date:"),
+                pick_list(columns.clone(), selected(&columns, Some(self.mapping.date_column)), ImportMessage::DateColumnSelected),
+                date_format,
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            row![
+                label("Description:"),
+                pick_list(columns.clone(), selected(&columns, Some(self.mapping.description_column)), ImportMessage::DescriptionColumnSelected),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            row![
+                label("Amount:"),
+                radio("Signed amount", AmountMode::Single, Some(amount_mode), ImportMessage::AmountModeSelected),
+                radio("Debit and credit", AmountMode::DebitCredit, Some(amount_mode), ImportMessage::AmountModeSelected),
+                amount_columns,
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            number_format,
+            row![
+                label("Category:"),
+                pick_list(optional_columns.clone(), selected(&optional_columns, self.mapping.category_column), ImportMessage::CategoryColumnSelected),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn preview_view<'a>(&self, state: &'a UiState) -> Element<'a, ImportMessage> {
+        let rows = match self.rows(state) {
+            Ok(rows) => rows,
+            Err(e) => return text(e).color(ERROR_COLOR).into(),
+        };
+
+        let rows: Vec<(ImportRow, Option<RowStatus>)> = rows.into_iter()
+            .map(|row| {
+                let status = row.result.as_ref().ok().map(|t| row_status(t, state));
+                (row, status)
+            })
+            .collect();
+
+        let count = |matches: fn(&RowStatus) -> bool| {
+            rows.iter().filter(|(_, status)| status.as_ref().is_some_and(matches)).count()
+        };
+        let new = count(|status| matches!(status, RowStatus::New));
+        let duplicates = count(|status| matches!(status, RowStatus::Duplicate(_)));
+        let skipped = count(|status| matches!(status, RowStatus::AlreadyImported));
+        let invalid = rows.iter().filter(|(_, status)| status.is_none()).count();
+
+        let summary = row![
+            text(format!(
+                "{} new, {} possible duplicates, {} already imported, {} with errors",
+                new, duplicates, skipped, invalid
+            ))
+            .width(Length::Fill),
+            checkbox("Use suggested categories", self.use_suggestions)
+                .on_toggle(ImportMessage::UseSuggestionsToggled),
+            button("Import")
+                .on_press_maybe((new + duplicates > 0).then_some(ImportMessage::Import))
+                .padding(10)
+                .style(button::primary),
+        ]
+        .spacing(20)
+        .align_y(Alignment::Center);
+
+        let preview = rows.into_iter()
+            .take(PREVIEW_ROWS)
+            .fold(column![].spacing(5), |column, (row, status)| {
+                let line = text(format!("{}", row.line)).width(Length::Fixed(50.0));
+                let entry: Element<'a, ImportMessage> = match (row.result, status) {
+                    (Ok(transaction), Some(RowStatus::Duplicate(candidates))) => {
+                        let keep_both = self.keep_both.contains(&row.line);
+                        container(
+                            row![
+                                line,
+                                transaction_cells(state, &transaction, Some(DUPLICATE_COLOR), None),
+                                text("matches").size(14).color(DUPLICATE_COLOR),
+                                transaction_cells(state, candidates[0], None, None),
+                                button("Merge")
+                                    .on_press(ImportMessage::KeepBothToggled(row.line, false))
+                                    .style(if keep_both { button::secondary } else { button::primary }),
+                                button("Keep Both")
+                                    .on_press(ImportMessage::KeepBothToggled(row.line, true))
+                                    .style(if keep_both { button::primary } else { button::secondary }),
+                            ]
+                            .spacing(10)
+                            .align_y(Alignment::Center)
+                        )
+                        .padding(5)
+                        .style(container::rounded_box)
+                        .into()
+                    }
+                    (Ok(transaction), Some(RowStatus::AlreadyImported)) => row![
+                        line,
+                        transaction_cells(state, &transaction, Some(SKIPPED_COLOR), Some("Already imported")),
+                    ]
+                    .spacing(10)
+                    .into(),
+                    (Ok(transaction), _) => row![line, transaction_cells(state, &transaction, None, None)]
+                        .spacing(10)
+                        .into(),
+                    (Err(e), _) => row![line, text(e).color(ERROR_COLOR)].spacing(10).into(),
+                };
+
+                column.push(entry)
+            });
+
+        column![summary, preview].spacing(10).into()
+    }
+}
+
+/// The learned category suggestion for an uncategorised row.
+fn top_suggestion(state: &UiState, transaction: &Transaction) -> Option<Suggestion> {
+    state.suggester.suggest(&transaction.description, 1).into_iter().next()
+}
+
+/// Date, description, signed amount and category of a row. `note` replaces
+/// the category when given; uncategorised rows show the top suggestion.
+fn transaction_cells<'a>(
+    state: &'a UiState,
+    transaction: &Transaction,
+    color: Option<Color>,
+    note: Option<&'static str>,
+) -> Element<'a, ImportMessage> {
+    let amount = match transaction.transaction_type {
+        TransactionType::Expense => -transaction.amount,
+        _ => transaction.amount,
+    };
+    let suggestion = transaction.category_id.is_none()
+        .then(|| top_suggestion(state, transaction))
+        .flatten();
+    let category = match (note, suggestion) {
+        (Some(note), _) => note.to_string(),
+        (None, Some(suggestion)) => format!(
+            "{}? {:.0}%",
+            state.category_name(Some(&suggestion.category_id)),
+            suggestion.confidence * 100.0
+        ),
+        (None, None) => state.category_name(transaction.category_id.as_deref()).to_string(),
+    };
+
+    row![
+        text(format_date(&transaction.date)).width(Length::FillPortion(2)).color_maybe(color),
+        text(transaction.description.clone()).width(Length::FillPortion(4)).color_maybe(color),
+        text(format_currency(amount)).width(Length::FillPortion(2)).color_maybe(color),
+        text(category).width(Length::FillPortion(2)).color_maybe(color),
+    ]
+    .spacing(10)
+    .width(Length::Fill)
+    .into()
+}
@@ -1,22 +1,116 @@
+pub mod account;
+pub mod backup;
 pub mod budget;
+pub mod categories;
+pub mod currency;
 pub mod transaction;
 pub mod dashboard;
+pub mod export;
+pub mod import;
+pub mod notifications;
+pub mod payees;
+pub mod recurring;
+pub mod reports;
+pub mod rules;
+pub mod tags;
 pub mod components;
 
-use crate::models::{Budget, Category, Transaction};
+use crate::db::backup::BackupInfo;
+use crate::import::csv::ImportProfile;
+use crate::models::{Account, Allocation, Budget, BudgetPeriod, Category, CategoryRule, CategorySuggester, CurrencyConverter, DateRange, DuplicatePair, Notification, Payee, Period, RecurringRule, Tag, Transaction};
+use crate::utils::today;
+use std::collections::HashSet;
+use std::path::PathBuf;
 use crate::ui::dashboard::DashboardMessage;
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    Account(account::AccountMessage),
     Budget(budget::BudgetMessage),
+    Categories(categories::CategoriesMessage),
     Transaction(transaction::TransactionMessage),
+    Currency(currency::CurrencyMessage),
+    Recurring(recurring::RecurringMessage),
+    Import(import::ImportMessage),
+    Export(export::ExportMessage),
+    Backup(backup::BackupMessage),
+    Rules(rules::RulesMessage),
+    Reports(reports::ReportsMessage),
+    Tags(tags::TagsMessage),
+    Payees(payees::PayeesMessage),
+    Notifications(notifications::NotificationsMessage),
     ChangePage(Page),
     LoadTransactions,
-    TransactionsLoaded(Vec<Transaction>),
+    TransactionsLoaded(Result<Vec<Transaction>, String>),
+    TransactionSaved(Result<(), String>),
+    TransactionDeleted(Result<(), String>),
+    LoadDismissedDuplicates,
+    DismissedDuplicatesLoaded(Result<HashSet<DuplicatePair>, String>),
+    DuplicatesMerged(Result<(), String>),
+    DuplicateDismissed(Result<(), String>),
     LoadCategories,
-    CategoriesLoaded(Vec<Category>),
+    CategoriesLoaded(Result<Vec<Category>, String>),
+    CategorySaved(Result<(), String>),
+    CategoryRemoved(Result<(), String>),
+    LoadTags,
+    TagsLoaded(Result<Vec<Tag>, String>),
+    TagSaved(Result<(), String>),
+    TagDeleted(Result<(), String>),
+    LoadPayees,
+    PayeesLoaded(Result<Vec<Payee>, String>),
+    PayeeSaved(Result<(), String>),
+    PayeeDeleted(Result<(), String>),
+    PayeesApplied(Result<usize, String>),
     LoadBudgets,
-    BudgetsLoaded(Vec<Budget>),
+    BudgetsLoaded(Result<Vec<Budget>, String>),
+    BudgetSaved(Result<(), String>),
+    BudgetDeleted(Result<(), String>),
+    SyncBudgetPeriods,
+    BudgetPeriodsSynced(Result<Vec<BudgetPeriod>, String>),
+    LoadAllocations,
+    AllocationsLoaded(Result<Vec<Allocation>, String>),
+    AllocationSaved(Result<(), String>),
+    LoadEnvelopeBudgeting,
+    EnvelopeBudgetingLoaded(Result<bool, String>),
+    EnvelopeBudgetingSaved(Result<(), String>),
+    CheckBudgetAlerts,
+    BudgetAlertsChecked(Result<Vec<Notification>, String>),
+    LoadNotifications,
+    NotificationsLoaded(Result<Vec<Notification>, String>),
+    NotificationsUpdated(Result<(), String>),
+    LoadDesktopNotifications,
+    DesktopNotificationsLoaded(Result<bool, String>),
+    DesktopNotificationsSaved(Result<(), String>),
+    LoadAccounts,
+    AccountsLoaded(Result<Vec<Account>, String>),
+    AccountSaved(Result<(), String>),
+    AccountDeleted(Result<(), String>),
+    LoadRecurringRules,
+    RecurringRulesLoaded(Result<Vec<RecurringRule>, String>),
+    RecurringRuleSaved(Result<(), String>),
+    RecurringRuleDeleted(Result<(), String>),
+    LoadCategoryRules,
+    CategoryRulesLoaded(Result<Vec<CategoryRule>, String>),
+    CategoryRuleSaved(Result<(), String>),
+    CategoryRuleDeleted(Result<(), String>),
+    CategoryRulesReordered(Result<(), String>),
+    CategoryRulesApplied(Result<usize, String>),
+    LoadImportProfiles,
+    ImportProfilesLoaded(Result<Vec<ImportProfile>, String>),
+    ImportProfileSaved(Result<(), String>),
+    TransactionsImported(Result<usize, String>),
+    DataExported(Result<String, String>),
+    ReportGenerated(Result<String, String>),
+    LoadBackups,
+    BackupsLoaded(Result<(Vec<BackupInfo>, usize), String>),
+    BackupCreated(Result<PathBuf, String>),
+    BackupsToKeepSaved(Result<(), String>),
+    BackupRestored(Result<PathBuf, String>),
+    LoadExchangeRates,
+    ExchangeRatesLoaded(Result<CurrencyConverter, String>),
+    ExchangeRateSaved(Result<(), String>),
+    ExchangeRateDeleted(Result<(), String>),
+    BaseCurrencySaved(Result<(), String>),
     Dashboard(DashboardMessage),
 }
 
@@ -24,15 +118,51 @@ pub enum Message {
 pub enum Page {
     Dashboard,
     Transactions,
+    Accounts,
     Budgets,
+    Categories,
+    Tags,
+    Payees,
+    Recurring,
+    Import,
+    Export,
+    Backup,
+    Rules,
+    Currencies,
     Reports,
+    Notifications,
 }
 
+/// The data the views show, loaded from the database. Views never write to
+/// the database themselves; the application persists the messages that
+/// change stored data and then reloads this state.
 pub struct UiState {
     pub page: Page,
     pub transactions: Vec<Transaction>,
     pub categories: Vec<Category>,
+    /// Alphabetical.
+    pub tags: Vec<Tag>,
+    /// Alphabetical.
+    pub payees: Vec<Payee>,
+    pub accounts: Vec<Account>,
     pub budgets: Vec<Budget>,
+    /// Each budget's periods up to the current one, oldest first.
+    pub budget_periods: Vec<BudgetPeriod>,
+    /// Money assigned to category envelopes, by month.
+    pub allocations: Vec<Allocation>,
+    /// Newest first.
+    pub notifications: Vec<Notification>,
+    pub recurring_rules: Vec<RecurringRule>,
+    pub import_profiles: Vec<ImportProfile>,
+    /// In priority order.
+    pub category_rules: Vec<CategoryRule>,
+    /// Transaction pairs the user confirmed are not duplicates.
+    pub dismissed_duplicates: HashSet<DuplicatePair>,
+    pub converter: CurrencyConverter,
+    /// Trained on `transactions` whenever they are loaded.
+    pub suggester: CategorySuggester,
+    /// The period summarised on the dashboard.
+    pub dashboard_range: DateRange,
 }
 
 impl UiState {
@@ -41,7 +171,43 @@ impl UiState {
             page: Page::Dashboard,
             transactions: Vec::new(),
             categories: Vec::new(),
+            tags: Vec::new(),
+            payees: Vec::new(),
+            accounts: Vec::new(),
             budgets: Vec::new(),
+            budget_periods: Vec::new(),
+            allocations: Vec::new(),
+            notifications: Vec::new(),
+            recurring_rules: Vec::new(),
+            import_profiles: Vec::new(),
+            category_rules: Vec::new(),
+            dismissed_duplicates: HashSet::new(),
+            converter: CurrencyConverter::default(),
+            suggester: CategorySuggester::default(),
+            dashboard_range: Period::Month.containing(today()),
         }
     }
+
+    pub fn category_name(&self, category_id: Option<&str>) -> &str {
+        category_name(&self.categories, category_id)
+    }
+
+    /// The transaction's category, or the categories of its split lines.
+    pub fn transaction_category(&self, transaction: &Transaction) -> String {
+        if transaction.is_split() {
+            let names: Vec<&str> = transaction.splits.iter()
+                .map(|split| self.category_name(split.category_id.as_deref()))
+                .collect();
+            format!("Split: {}", names.join(", "))
+        } else {
+            self.category_name(transaction.category_id.as_deref()).to_string()
+        }
+    }
+}
+
+pub fn category_name<'a>(categories: &'a [Category], category_id: Option<&str>) -> &'a str {
+    category_id
+        .and_then(|id| categories.iter().find(|c| c.id == id))
+        .map(|c| c.name.as_str())
+        .unwrap_or("No Category")
 }
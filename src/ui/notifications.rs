@@ -0,0 +1,120 @@
+use crate::ui::UiState;
+use iced::widget::{button, checkbox, column, container, row, scrollable, text};
+use iced::{Alignment, Color, Element, Length};
+
+#[derive(Debug, Clone)]
+pub enum NotificationsMessage {
+    MarkAllRead,
+    Dismiss(String),
+    ClearAll,
+    DesktopToggled(bool),
+}
+
+/// The notification centre: budget alerts and other messages, newest
+/// first, with unread ones marked.
+pub struct NotificationsView {
+    desktop: bool,
+    error: Option<String>,
+}
+
+impl NotificationsView {
+    pub fn new() -> Self {
+        Self {
+            desktop: false,
+            error: None,
+        }
+    }
+
+    pub fn update(&mut self, message: NotificationsMessage) {
+        match message {
+            NotificationsMessage::DesktopToggled(enabled) => {
+                self.desktop = enabled;
+                self.error = None;
+            }
+            NotificationsMessage::MarkAllRead
+            | NotificationsMessage::Dismiss(_)
+            | NotificationsMessage::ClearAll => {}
+        }
+    }
+
+    pub fn set_desktop(&mut self, enabled: bool) {
+        self.desktop = enabled;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, NotificationsMessage> {
+        let unread = state.notifications.iter().filter(|n| !n.read).count();
+
+        let actions = row![
+            button("Mark all read")
+                .on_press_maybe((unread > 0).then_some(NotificationsMessage::MarkAllRead))
+                .style(button::secondary),
+            button("Clear all")
+                .on_press_maybe((!state.notifications.is_empty()).then_some(NotificationsMessage::ClearAll))
+                .style(button::danger),
+            checkbox("Also show alerts as desktop notifications", self.desktop)
+                .on_toggle(NotificationsMessage::DesktopToggled),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let error = text(self.error.clone().unwrap_or_default())
+            .size(14)
+            .color(Color::from_rgb(0.8, 0.0, 0.0));
+
+        let rows = state.notifications.iter().fold(column![].spacing(5), |column, notification| {
+            let title = if notification.read {
+                text(notification.title.clone())
+            } else {
+                text(format!("● {}", notification.title.clone()))
+            };
+
+            column.push(
+                container(
+                    row![
+                        column![
+                            title.size(16),
+                            text(notification.body.clone()).size(14),
+                            text(notification.created_at.format("%Y-%m-%d %H:%M").to_string()).size(12),
+                        ]
+                        .spacing(5)
+                        .width(Length::Fill),
+                        button("Dismiss")
+                            .on_press(NotificationsMessage::Dismiss(notification.id.clone()))
+                            .style(button::secondary),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                )
+                .padding(10)
+                .style(container::rounded_box)
+            )
+        });
+
+        let rows: Element<'a, NotificationsMessage> = if state.notifications.is_empty() {
+            text("No notifications. Budgets raise alerts here when spending reaches their alert thresholds.")
+                .size(14)
+                .into()
+        } else {
+            rows.into()
+        };
+
+        let content = column![
+            text("Notifications").size(30),
+            actions,
+            error,
+            rows,
+        ]
+        .spacing(20)
+        .padding(20)
+        .width(Length::Fill);
+
+        scrollable(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
@@ -0,0 +1,302 @@
+use crate::models::{payee, Payee};
+use crate::ui::categories::CategoryChoice;
+use crate::ui::UiState;
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input, Row};
+use iced::{Alignment, Color, Element, Length};
+use std::collections::HashSet;
+use std::fmt;
+
+/// A payee to filter or file transactions under, or none with a label
+/// saying what that means.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayeeChoice {
+    pub id: Option<String>,
+    name: String,
+}
+
+impl PayeeChoice {
+    /// `none` followed by each of `payees`.
+    pub fn all(payees: &[Payee], none: &str) -> Vec<Self> {
+        std::iter::once(Self { id: None, name: none.to_string() })
+            .chain(payees.iter().map(|p| Self { id: Some(p.id.clone()), name: p.name.clone() }))
+            .collect()
+    }
+
+    pub fn selected(payees: &[Payee], id: Option<&str>, none: &str) -> Self {
+        id.and_then(|id| payees.iter().find(|p| p.id == id))
+            .map(|p| Self { id: Some(p.id.clone()), name: p.name.clone() })
+            .unwrap_or_else(|| Self { id: None, name: none.to_string() })
+    }
+}
+
+impl fmt::Display for PayeeChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PayeesMessage {
+    NameChanged(String),
+    AliasInputChanged(String),
+    AddAlias,
+    RemoveAlias(String),
+    CategorySelected(CategoryChoice),
+    Save,
+    Edit(Payee),
+    Cancel,
+    Delete(String),
+    Apply,
+}
+
+/// Payees with the description patterns that identify them, where payees
+/// are added, edited and deleted and applied to existing transactions.
+pub struct PayeesView {
+    name: String,
+    aliases: Vec<String>,
+    // An alias being typed, added on submit
+    alias_input: String,
+    category_id: Option<String>,
+    editing: Option<String>,
+    error: Option<String>,
+    status: Option<String>,
+}
+
+impl PayeesView {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            aliases: Vec::new(),
+            alias_input: String::new(),
+            category_id: None,
+            editing: None,
+            error: None,
+            status: None,
+        }
+    }
+
+    pub fn update(&mut self, message: PayeesMessage) {
+        match message {
+            PayeesMessage::NameChanged(name) => {
+                self.name = name;
+            }
+            PayeesMessage::AliasInputChanged(input) => {
+                self.alias_input = input;
+            }
+            PayeesMessage::AddAlias => {
+                let alias = self.alias_input.trim().to_string();
+                if !alias.is_empty() && !self.aliases.iter().any(|a| a.eq_ignore_ascii_case(&alias)) {
+                    self.aliases.push(alias);
+                }
+                self.alias_input.clear();
+            }
+            PayeesMessage::RemoveAlias(alias) => {
+                self.aliases.retain(|a| a != &alias);
+            }
+            PayeesMessage::CategorySelected(choice) => {
+                self.category_id = choice.id;
+            }
+            PayeesMessage::Edit(payee) => {
+                *self = Self::new();
+                self.name = payee.name;
+                self.aliases = payee.aliases;
+                self.category_id = payee.category_id;
+                self.editing = Some(payee.id);
+            }
+            PayeesMessage::Cancel => {
+                *self = Self::new();
+            }
+            PayeesMessage::Save | PayeesMessage::Delete(_) | PayeesMessage::Apply => {}
+        }
+    }
+
+    /// Validates the form, returning the payee to persist and whether it is
+    /// new. An alias still being typed is included.
+    pub fn submit(&mut self, payees: &[Payee]) -> Option<(Payee, bool)> {
+        self.update(PayeesMessage::AddAlias);
+
+        let name = self.name.trim();
+        let duplicate = payees.iter()
+            .any(|p| p.name.eq_ignore_ascii_case(name) && Some(&p.id) != self.editing.as_ref());
+        let error = if name.is_empty() {
+            Some("Enter a name for the payee".to_string())
+        } else if duplicate {
+            Some(format!("There is already a payee named {}", name))
+        } else {
+            None
+        };
+        if error.is_some() {
+            self.error = error;
+            return None;
+        }
+
+        let mut payee = Payee::new(name.to_string(), self.aliases.clone(), self.category_id.clone());
+        if let Some(id) = &self.editing {
+            payee.id = id.clone();
+        }
+
+        self.error = None;
+        Some((payee, self.editing.is_none()))
+    }
+
+    pub fn payee_saved(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn applied(&mut self, linked: usize) {
+        self.error = None;
+        self.status = Some(format!("Linked {} transaction(s) to their payee", linked));
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.status = None;
+        self.error = Some(error);
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, PayeesMessage> {
+        let category_choices = CategoryChoice::all(&state.categories, "(No default)");
+        let selected_category = category_choices.iter()
+            .find(|choice| choice.id == self.category_id)
+            .cloned();
+
+        let alias_chips = self.aliases.iter().fold(Row::new().spacing(5), |row, alias| {
+            row.push(
+                button(text(format!("{} ×", alias)).size(14))
+                    .on_press(PayeesMessage::RemoveAlias(alias.clone()))
+                    .style(button::secondary)
+            )
+        });
+
+        let label = |label: &'static str| text(label).width(Length::Fixed(140.0));
+        let form = container(
+            column![
+                text(if self.editing.is_some() { "Edit Payee" } else { "Add Payee" }).size(20),
+                row![
+                    label("Name:"),
+                    text_input("e.g. Amazon", &self.name)
+                        .on_input(PayeesMessage::NameChanged)
+                        .padding(10)
+                        .width(Length::Fixed(240.0)),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    label("Descriptions with:"),
+                    text_input("e.g. AMZN MKTP, then Enter", &self.alias_input)
+                        .on_input(PayeesMessage::AliasInputChanged)
+                        .on_submit(PayeesMessage::AddAlias)
+                        .padding(10)
+                        .width(Length::Fixed(240.0)),
+                    alias_chips,
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    label("Default category:"),
+                    pick_list(category_choices, selected_category, PayeesMessage::CategorySelected),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    button("Save")
+                        .on_press(PayeesMessage::Save)
+                        .padding(10),
+                    button("Cancel")
+                        .on_press(PayeesMessage::Cancel)
+                        .style(button::secondary)
+                        .padding(10),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box);
+
+        let message = match (&self.error, &self.status) {
+            (Some(error), _) => text(error.clone()).size(14).color(Color::from_rgb(0.8, 0.0, 0.0)),
+            (None, Some(status)) => text(status.clone()).size(14),
+            (None, None) => text(""),
+        };
+
+        let apply_row = row![
+            text("A description containing a payee's name or one of its patterns, ignoring case, belongs to the payee. \
+                  Deleting a payee also deletes the category rules that depend on it.")
+                .size(14)
+                .width(Length::Fill),
+            button("Apply to Existing Transactions")
+                .on_press_maybe((!state.payees.is_empty()).then_some(PayeesMessage::Apply))
+                .style(button::secondary)
+                .padding(10),
+        ]
+        .spacing(20)
+        .align_y(Alignment::Center);
+
+        let rows = state.payees.iter().fold(column![].spacing(5), |column, payee| {
+            let uses = state.transactions.iter()
+                .filter(|t| t.payee_id.as_deref() == Some(payee.id.as_str()))
+                .count();
+            let category = payee.category_id.as_deref()
+                .map_or("No default category", |id| state.category_name(Some(id)));
+
+            column.push(
+                container(
+                    row![
+                        text(&payee.name).size(18).width(Length::FillPortion(2)),
+                        text(payee.aliases.join(", ")).size(14).width(Length::FillPortion(3)),
+                        text(format!("→ {}", category)).width(Length::FillPortion(2)),
+                        text(format!("{} transaction(s)", uses)).size(14),
+                        button("Edit")
+                            .on_press(PayeesMessage::Edit(payee.clone()))
+                            .style(button::secondary),
+                        button("Delete")
+                            .on_press(PayeesMessage::Delete(payee.id.clone()))
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                )
+                .padding(10)
+                .style(container::rounded_box)
+            )
+        });
+
+        // Recent descriptions no payee recognises, to help with new patterns
+        let mut seen = HashSet::new();
+        let unmatched: Vec<&str> = state.transactions.iter()
+            .filter(|t| !t.is_transfer() && payee::find(&state.payees, &t.description).is_none())
+            .map(|t| t.description.as_str())
+            .filter(|description| !description.is_empty() && seen.insert(*description))
+            .collect();
+        let unmatched = text(if unmatched.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "Not yet recognised: {}",
+                unmatched.into_iter().take(UNMATCHED_SHOWN).collect::<Vec<_>>().join(" · ")
+            )
+        })
+        .size(14);
+
+        let content = column![
+            text("Payees").size(30),
+            form,
+            message,
+            apply_row,
+            rows,
+            unmatched,
+        ]
+        .spacing(20)
+        .padding(20)
+        .width(Length::Fill);
+
+        scrollable(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+/// How many unrecognised descriptions the page lists.
+const UNMATCHED_SHOWN: usize = 10;
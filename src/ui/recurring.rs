@@ -0,0 +1,245 @@
+use crate::models::{Currency, Frequency, RecurrenceEnd, RecurringRule, TransactionType};
+use crate::ui::components::recurring_form::{recurring_form_view, EndKind, RecurringFormState, WeekOfMonth};
+use crate::ui::UiState;
+use crate::utils::{format_currency, format_date, today};
+use chrono::{Duration, NaiveDate, Weekday};
+use iced::widget::{button, column, container, row, scrollable, text};
+use iced::{Alignment, Element, Length};
+
+/// How far ahead the upcoming list looks.
+const UPCOMING_DAYS: i64 = 60;
+
+#[derive(Debug, Clone)]
+pub enum RecurringMessage {
+    Add,
+    Edit(RecurringRule),
+    Delete(String),
+    DescriptionChanged(String),
+    AmountChanged(String),
+    CurrencySelected(Currency),
+    TypeSelected(TransactionType),
+    CategorySelected(String),
+    AccountSelected(String),
+    FrequencySelected(Frequency),
+    IntervalChanged(String),
+    ByWeekdayToggled(bool),
+    WeekSelected(WeekOfMonth),
+    WeekdaySelected(Weekday),
+    OpenStartDatePicker(bool),
+    StartDateSelected(NaiveDate),
+    EndKindSelected(EndKind),
+    OpenEndDatePicker(bool),
+    EndDateSelected(NaiveDate),
+    CountChanged(String),
+    Save,
+    Cancel,
+}
+
+pub struct RecurringView {
+    form: RecurringFormState,
+    show_form: bool,
+    default_currency: Currency,
+}
+
+impl RecurringView {
+    pub fn new() -> Self {
+        Self {
+            form: RecurringFormState::default(),
+            show_form: false,
+            default_currency: Currency::default(),
+        }
+    }
+
+    /// Currency preselected for new rules.
+    pub fn set_default_currency(&mut self, currency: Currency) {
+        self.default_currency = currency;
+    }
+
+    pub fn update(&mut self, message: RecurringMessage) {
+        match message {
+            RecurringMessage::Add => {
+                self.form = RecurringFormState {
+                    currency: self.default_currency,
+                    ..RecurringFormState::default()
+                };
+                self.show_form = true;
+            }
+            RecurringMessage::Edit(rule) => {
+                self.form = RecurringFormState::from_rule(&rule);
+                self.show_form = true;
+            }
+            RecurringMessage::Delete(id) => {
+                if self.form.editing_id.as_ref() == Some(&id) {
+                    self.close_form();
+                }
+            }
+            RecurringMessage::DescriptionChanged(description) => {
+                self.form.description = description;
+            }
+            RecurringMessage::AmountChanged(amount) => {
+                self.form.amount = amount;
+            }
+            RecurringMessage::CurrencySelected(currency) => {
+                self.form.currency = currency;
+            }
+            RecurringMessage::TypeSelected(transaction_type) => {
+                self.form.transaction_type = transaction_type;
+            }
+            RecurringMessage::CategorySelected(category_id) => {
+                self.form.category_id = Some(category_id);
+            }
+            RecurringMessage::AccountSelected(account_id) => {
+                self.form.account_id = Some(account_id);
+            }
+            RecurringMessage::FrequencySelected(frequency) => {
+                self.form.frequency = frequency;
+            }
+            RecurringMessage::IntervalChanged(interval) => {
+                self.form.interval = interval;
+            }
+            RecurringMessage::ByWeekdayToggled(by_weekday) => {
+                self.form.by_weekday = by_weekday;
+            }
+            RecurringMessage::WeekSelected(week) => {
+                self.form.week = week;
+            }
+            RecurringMessage::WeekdaySelected(weekday) => {
+                self.form.weekday = weekday;
+            }
+            RecurringMessage::OpenStartDatePicker(show) => {
+                self.form.show_start_date_picker = show;
+            }
+            RecurringMessage::StartDateSelected(date) => {
+                self.form.start_date = date;
+                self.form.show_start_date_picker = false;
+            }
+            RecurringMessage::EndKindSelected(end_kind) => {
+                self.form.end_kind = end_kind;
+            }
+            RecurringMessage::OpenEndDatePicker(show) => {
+                self.form.show_end_date_picker = show;
+            }
+            RecurringMessage::EndDateSelected(date) => {
+                self.form.end_date = date;
+                self.form.show_end_date_picker = false;
+            }
+            RecurringMessage::CountChanged(count) => {
+                self.form.count = count;
+            }
+            RecurringMessage::Save => {}
+            RecurringMessage::Cancel => {
+                self.close_form();
+            }
+        }
+    }
+
+    /// Validates the form, returning the rule to persist. Validation errors
+    /// are shown in the form instead.
+    pub fn submit(&mut self) -> Option<RecurringRule> {
+        match self.form.to_rule() {
+            Ok(rule) => {
+                self.form.error = None;
+                Some(rule)
+            }
+            Err(e) => {
+                self.form.error = Some(e);
+                None
+            }
+        }
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.form.editing_id.is_some()
+    }
+
+    pub fn close_form(&mut self) {
+        self.form = RecurringFormState::default();
+        self.show_form = false;
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, RecurringMessage> {
+        let header = row![
+            text("Recurring Transactions").size(30).width(Length::Fill),
+            button("Add Recurring")
+                .on_press(RecurringMessage::Add)
+                .padding(10),
+        ];
+
+        let now = today();
+        let until = now + Duration::days(UPCOMING_DAYS);
+
+        let rules_list = state.recurring_rules.iter()
+            .fold(column![].spacing(10), |column, rule| {
+                let ends = match rule.end {
+                    RecurrenceEnd::Never => String::new(),
+                    RecurrenceEnd::OnDate(date) => format!(", until {}", format_date(&date)),
+                    RecurrenceEnd::AfterCount(count) => format!(", {} times", count),
+                };
+                let next = rule.next_after(now)
+                    .map(|date| format!("Next: {}", format_date(&date)))
+                    .unwrap_or_else(|| "Finished".to_string());
+
+                column.push(
+                    container(
+                        row![
+                            text(&rule.description).size(18).width(Length::FillPortion(3)),
+                            text(format_currency(rule.amount)).width(Length::FillPortion(2)),
+                            text(format!("{}{}", rule.schedule_label(), ends)).width(Length::FillPortion(4)),
+                            text(next).width(Length::FillPortion(2)),
+                            button("Edit")
+                                .on_press(RecurringMessage::Edit(rule.clone())),
+                            button("Delete")
+                                .on_press(RecurringMessage::Delete(rule.id.clone()))
+                                .style(button::danger),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center)
+                    )
+                    .padding(10)
+                    .style(container::rounded_box)
+                )
+            });
+
+        let mut upcoming: Vec<(NaiveDate, &RecurringRule)> = state.recurring_rules.iter()
+            .flat_map(|rule| rule.upcoming(now, until).into_iter().map(move |date| (date, rule)))
+            .collect();
+        upcoming.sort_by_key(|(date, _)| *date);
+
+        let upcoming_list = upcoming.into_iter()
+            .fold(column![].spacing(5), |column, (date, rule)| {
+                column.push(
+                    row![
+                        text(format_date(&date)).width(Length::FillPortion(2)),
+                        text(&rule.description).width(Length::FillPortion(3)),
+                        text(format_currency(rule.amount)).width(Length::FillPortion(2)),
+                        text(state.category_name(rule.category_id.as_deref())).width(Length::FillPortion(2)),
+                    ]
+                    .spacing(10)
+                )
+            });
+
+        let mut content = column![header].spacing(20);
+
+        if self.show_form {
+            content = content.push(recurring_form_view(&state.categories, &state.accounts, &self.form));
+        }
+
+        content = content.push(
+            scrollable(
+                column![
+                    rules_list,
+                    text(format!("Upcoming (next {} days)", UPCOMING_DAYS)).size(20),
+                    upcoming_list,
+                ]
+                .spacing(20)
+            )
+            .height(Length::Fill)
+        );
+
+        container(content)
+            .padding(20)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
@@ -0,0 +1,437 @@
+use crate::models::{DateRange, Money, Period};
+use crate::report::summary::{GroupedReport, Grouping, ReportFilter, ReportLine, ReportSection};
+use crate::report::MonthlyReport;
+use crate::ui::payees::PayeeChoice;
+use crate::ui::tags::TagChoice;
+use crate::ui::UiState;
+use crate::utils::{documents_path, format_currency, format_date, month_start, today};
+use chrono::{Months, NaiveDate};
+use iced::widget::{button, column, container, horizontal_rule, pick_list, row, scrollable, text, text_input, Column};
+use iced::{Alignment, Color, Element, Length};
+use std::fmt;
+use std::path::PathBuf;
+
+const INCOME_COLOR: Color = Color::from_rgb(0.0, 0.5, 0.0);
+const EXPENSE_COLOR: Color = Color::from_rgb(0.8, 0.0, 0.0);
+
+/// How the report's date range was chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeChoice {
+    Period(Period),
+    Custom,
+}
+
+impl RangeChoice {
+    const ALL: [RangeChoice; 4] = [
+        RangeChoice::Period(Period::Month),
+        RangeChoice::Period(Period::Quarter),
+        RangeChoice::Period(Period::Year),
+        RangeChoice::Custom,
+    ];
+}
+
+impl fmt::Display for RangeChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeChoice::Period(period) => write!(f, "{}", period),
+            RangeChoice::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ReportsMessage {
+    RangeSelected(RangeChoice),
+    PreviousRange,
+    NextRange,
+    FromChanged(String),
+    ToChanged(String),
+    GroupingSelected(Grouping),
+    TagSelected(TagChoice),
+    PayeeSelected(PayeeChoice),
+    PreviousMonth,
+    NextMonth,
+    PathChanged(String),
+    GeneratePdf,
+}
+
+/// Totals over any date range grouped and compared with the range before,
+/// and monthly statements saved as PDF.
+pub struct ReportsView {
+    range: DateRange,
+    choice: RangeChoice,
+    from: String,
+    to: String,
+    grouping: Grouping,
+    filter: ReportFilter,
+    /// The first day of the month of the PDF statement.
+    month: NaiveDate,
+    path: String,
+    error: Option<String>,
+    status: Option<String>,
+}
+
+impl ReportsView {
+    pub fn new() -> Self {
+        let month = month_start(today());
+        let range = Period::Month.containing(month);
+
+        Self {
+            range,
+            choice: RangeChoice::Period(Period::Month),
+            from: format_date(&range.from),
+            to: format_date(&range.to),
+            grouping: Grouping::Category,
+            filter: ReportFilter::default(),
+            month,
+            path: default_pdf_path(month),
+            error: None,
+            status: None,
+        }
+    }
+
+    pub fn update(&mut self, message: ReportsMessage) {
+        match message {
+            ReportsMessage::RangeSelected(choice) => {
+                self.choice = choice;
+                if let RangeChoice::Period(period) = choice {
+                    // Keep to the dates being looked at rather than jumping to today
+                    self.select_range(period.containing(self.range.from));
+                }
+            }
+            ReportsMessage::PreviousRange => {
+                self.select_range(self.range.previous());
+            }
+            ReportsMessage::NextRange => {
+                self.select_range(self.range.next());
+            }
+            ReportsMessage::FromChanged(value) => {
+                self.from = value;
+                self.custom_range_edited();
+            }
+            ReportsMessage::ToChanged(value) => {
+                self.to = value;
+                self.custom_range_edited();
+            }
+            ReportsMessage::GroupingSelected(grouping) => {
+                self.grouping = grouping;
+            }
+            ReportsMessage::TagSelected(choice) => {
+                self.filter.tag = choice.name;
+            }
+            ReportsMessage::PayeeSelected(choice) => {
+                self.filter.payee_id = choice.id;
+            }
+            ReportsMessage::PreviousMonth => {
+                self.select_month(self.month - Months::new(1));
+            }
+            ReportsMessage::NextMonth => {
+                self.select_month(self.month + Months::new(1));
+            }
+            ReportsMessage::PathChanged(path) => {
+                self.path = path;
+            }
+            ReportsMessage::GeneratePdf => {}
+        }
+    }
+
+    fn select_range(&mut self, range: DateRange) {
+        self.range = range;
+        self.from = format_date(&range.from);
+        self.to = format_date(&range.to);
+    }
+
+    /// Switches to a custom range once both dates parse and are in order,
+    /// leaving the last valid range shown until then.
+    fn custom_range_edited(&mut self) {
+        self.choice = RangeChoice::Custom;
+        let parse = |value: &str| NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok();
+        if let (Some(from), Some(to)) = (parse(&self.from), parse(&self.to)) {
+            if from <= to {
+                self.range = DateRange::new(from, to);
+            }
+        }
+    }
+
+    /// Moves to `month`, following it with the file name unless the user
+    /// chose their own.
+    fn select_month(&mut self, month: NaiveDate) {
+        if self.path == default_pdf_path(self.month) {
+            self.path = default_pdf_path(month);
+        }
+        self.month = month;
+        self.status = None;
+        self.error = None;
+    }
+
+    pub fn month(&self) -> NaiveDate {
+        self.month
+    }
+
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(self.path.trim())
+    }
+
+    pub fn generated(&mut self, status: String) {
+        self.error = None;
+        self.status = Some(status);
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.status = None;
+        self.error = Some(error);
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, ReportsMessage> {
+        let grouped = GroupedReport::new(
+            self.range,
+            self.grouping,
+            self.filter.clone(),
+            &state.transactions,
+            &state.categories,
+            &state.accounts,
+            &state.payees,
+            &state.converter,
+        );
+
+        let range_picker = row![
+            pick_list(&RangeChoice::ALL[..], Some(self.choice), ReportsMessage::RangeSelected),
+            button("<")
+                .on_press(ReportsMessage::PreviousRange)
+                .style(button::secondary),
+            text_input("From (YYYY-MM-DD)", &self.from)
+                .on_input(ReportsMessage::FromChanged)
+                .width(Length::Fixed(140.0))
+                .padding(8),
+            text("to"),
+            text_input("To (YYYY-MM-DD)", &self.to)
+                .on_input(ReportsMessage::ToChanged)
+                .width(Length::Fixed(140.0))
+                .padding(8),
+            button(">")
+                .on_press(ReportsMessage::NextRange)
+                .style(button::secondary),
+            text("Group by"),
+            pick_list(&Grouping::ALL[..], Some(self.grouping), ReportsMessage::GroupingSelected),
+            text("Tagged"),
+            pick_list(
+                TagChoice::all(&state.tags),
+                Some(TagChoice::selected(self.filter.tag.as_deref())),
+                ReportsMessage::TagSelected,
+            ),
+            pick_list(
+                PayeeChoice::all(&state.payees, "Any payee"),
+                Some(PayeeChoice::selected(&state.payees, self.filter.payee_id.as_deref(), "Any payee")),
+                ReportsMessage::PayeeSelected,
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let mut compared_with = if grouping_compares_months(grouped.grouping) {
+            "Each month is compared with the month before it".to_string()
+        } else {
+            format!("Compared with {}", grouped.previous_range.label())
+        };
+        if grouped.grouping == Grouping::Tag {
+            compared_with.push_str(". A transaction with several tags counts towards each, and once in the totals");
+        }
+
+        let missing_rates = text(if grouped.unconverted > 0 {
+            format!(
+                "{} transaction(s) left out: no exchange rate to {}",
+                grouped.unconverted,
+                grouped.base
+            )
+        } else {
+            String::new()
+        })
+        .size(14)
+        .color(EXPENSE_COLOR);
+
+        let table = column![
+            table_header(grouped.grouping),
+            section_view(&grouped.income),
+            section_view(&grouped.expenses),
+            horizontal_rule(1),
+            line_row(&grouped.net, true),
+        ]
+        .spacing(10);
+
+        let grouped_section = container(
+            column![
+                text(report_title(&grouped, state)).size(20),
+                text(compared_with).size(14),
+                table,
+                missing_rates,
+            ]
+            .spacing(15)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box);
+
+        let report = MonthlyReport::new(
+            self.month,
+            &state.transactions,
+            &state.categories,
+            &state.accounts,
+            &state.budgets,
+            &state.budget_periods,
+            &state.converter,
+        );
+
+        let month_picker = row![
+            button("<")
+                .on_press(ReportsMessage::PreviousMonth)
+                .style(button::secondary),
+            text(self.month.format("%B %Y").to_string())
+                .size(20)
+                .width(Length::Fixed(180.0))
+                .center(),
+            button(">")
+                .on_press(ReportsMessage::NextMonth)
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let net = report.income - report.expenses;
+        let summary = row![
+            text(format!("Income: {}", format_currency(report.income))).color(INCOME_COLOR),
+            text(format!("Expenses: {}", format_currency(report.expenses))).color(EXPENSE_COLOR),
+            text(format!("Net: {}", format_currency(net)))
+                .color(if net.is_negative() { EXPENSE_COLOR } else { INCOME_COLOR }),
+            text(format!("{} transactions", report.transactions.len())),
+        ]
+        .spacing(30);
+
+        let generate = row![
+            text_input("Path to PDF file", &self.path)
+                .on_input(ReportsMessage::PathChanged)
+                .padding(10),
+            button("Save PDF")
+                .on_press(ReportsMessage::GeneratePdf)
+                .padding(10),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let message = match (&self.error, &self.status) {
+            (Some(error), _) => text(error.clone()).size(14).color(EXPENSE_COLOR),
+            (None, Some(status)) => text(status.clone()).size(14),
+            (None, None) => text(""),
+        };
+
+        let statement_section = container(
+            column![
+                text(format!("Monthly Statement ({})", report.base)).size(20),
+                month_picker,
+                summary,
+                text("The PDF adds the charts, spending by tag, budgets, account balances and every transaction of the month.").size(14),
+                generate,
+                message,
+            ]
+            .spacing(15)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box);
+
+        let content = column![
+            text("Reports").size(30),
+            range_picker,
+            grouped_section,
+            statement_section,
+        ]
+        .spacing(20)
+        .padding(20)
+        .width(Length::Fill);
+
+        scrollable(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+/// E.g. "Q1 2026 by Category (USD), tagged #vacation-2026, payee Amazon".
+fn report_title(grouped: &GroupedReport, state: &UiState) -> String {
+    let mut title = format!("{} by {} ({})", grouped.range.label(), grouped.grouping, grouped.base);
+    if let Some(tag) = &grouped.filter.tag {
+        title.push_str(&format!(", tagged #{}", tag));
+    }
+    if let Some(payee) = grouped.filter.payee_id.as_ref()
+        .and_then(|id| state.payees.iter().find(|p| &p.id == id))
+    {
+        title.push_str(&format!(", payee {}", payee.name));
+    }
+    title
+}
+
+fn grouping_compares_months(grouping: Grouping) -> bool {
+    grouping == Grouping::Month
+}
+
+fn table_header<'a>(grouping: Grouping) -> Element<'a, ReportsMessage> {
+    let previous = if grouping_compares_months(grouping) { "Month before" } else { "Previous" };
+
+    row![
+        text(grouping.to_string()).width(Length::FillPortion(3)),
+        text("Total").width(Length::FillPortion(2)),
+        text(previous).width(Length::FillPortion(2)),
+        text("Change").width(Length::FillPortion(2)),
+        text("%").width(Length::FillPortion(1)),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn section_view<'a>(section: &ReportSection) -> Element<'a, ReportsMessage> {
+    let mut lines = Column::new()
+        .spacing(5)
+        .push(text(section.title.clone()).size(18));
+
+    if section.lines.is_empty() {
+        lines = lines.push(text("Nothing in this range").size(14));
+    }
+    for line in &section.lines {
+        lines = lines.push(line_row(line, false));
+    }
+
+    lines.push(line_row(&section.subtotal, true)).into()
+}
+
+fn line_row<'a>(line: &ReportLine, bold: bool) -> Element<'a, ReportsMessage> {
+    let percent = line.change_percent()
+        .map(|percent| format!("{:+.0}%", percent))
+        .unwrap_or_else(|| "-".to_string());
+    let font = if bold {
+        iced::Font { weight: iced::font::Weight::Bold, ..iced::Font::default() }
+    } else {
+        iced::Font::default()
+    };
+
+    row![
+        text(line.label.clone()).font(font).width(Length::FillPortion(3)),
+        text(format_currency(line.current)).font(font).width(Length::FillPortion(2)),
+        text(format_currency(line.previous)).font(font).width(Length::FillPortion(2)),
+        text(signed(line.change())).font(font).width(Length::FillPortion(2)),
+        text(percent).font(font).width(Length::FillPortion(1)),
+    ]
+    .spacing(10)
+    .into()
+}
+
+/// `amount` with a plus sign when it is an increase.
+fn signed(amount: Money) -> String {
+    if amount.is_negative() || amount.is_zero() {
+        format_currency(amount)
+    } else {
+        format!("+{}", format_currency(amount))
+    }
+}
+
+fn default_pdf_path(month: NaiveDate) -> String {
+    documents_path(&format!("statement-{}.pdf", month.format("%Y-%m")))
+}
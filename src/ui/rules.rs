@@ -0,0 +1,270 @@
+use crate::models::{CategoryRule, Currency, MatchKind};
+use crate::ui::components::category_rule_form::{category_rule_form_view, AccountChoice, CategoryRuleFormState};
+use crate::ui::payees::PayeeChoice;
+use crate::ui::UiState;
+use crate::utils::format_currency;
+use iced::widget::{button, checkbox, column, container, row, scrollable, text};
+use iced::{Alignment, Element, Length};
+
+#[derive(Debug, Clone)]
+pub enum RulesMessage {
+    Add,
+    Edit(CategoryRule),
+    Delete(String),
+    NameChanged(String),
+    MatchKindSelected(MatchKind),
+    PatternChanged(String),
+    MinAmountChanged(String),
+    MaxAmountChanged(String),
+    CurrencySelected(Currency),
+    AccountSelected(AccountChoice),
+    PayeeSelected(PayeeChoice),
+    CategorySelected(String),
+    Save,
+    Cancel,
+    MoveUp(String),
+    MoveDown(String),
+    OverwriteToggled(bool),
+    Apply,
+}
+
+pub struct RulesView {
+    form: CategoryRuleFormState,
+    show_form: bool,
+    overwrite: bool,
+    status: Option<String>,
+    default_currency: Currency,
+}
+
+impl RulesView {
+    pub fn new() -> Self {
+        Self {
+            form: CategoryRuleFormState::default(),
+            show_form: false,
+            overwrite: false,
+            status: None,
+            default_currency: Currency::default(),
+        }
+    }
+
+    /// Currency preselected for amount bounds of new rules.
+    pub fn set_default_currency(&mut self, currency: Currency) {
+        self.default_currency = currency;
+    }
+
+    /// Whether re-applying should also replace categories already set.
+    pub fn overwrite(&self) -> bool {
+        self.overwrite
+    }
+
+    pub fn update(&mut self, message: RulesMessage) {
+        match message {
+            RulesMessage::Add => {
+                self.form = CategoryRuleFormState {
+                    currency: self.default_currency,
+                    ..CategoryRuleFormState::default()
+                };
+                self.show_form = true;
+            }
+            RulesMessage::Edit(rule) => {
+                self.form = CategoryRuleFormState::from_rule(&rule);
+                self.show_form = true;
+            }
+            RulesMessage::Delete(id) => {
+                if self.form.editing_id.as_ref() == Some(&id) {
+                    self.close_form();
+                }
+            }
+            RulesMessage::NameChanged(name) => {
+                self.form.name = name;
+            }
+            RulesMessage::MatchKindSelected(match_kind) => {
+                self.form.match_kind = match_kind;
+            }
+            RulesMessage::PatternChanged(pattern) => {
+                self.form.pattern = pattern;
+            }
+            RulesMessage::MinAmountChanged(amount) => {
+                self.form.min_amount = amount;
+            }
+            RulesMessage::MaxAmountChanged(amount) => {
+                self.form.max_amount = amount;
+            }
+            RulesMessage::CurrencySelected(currency) => {
+                self.form.currency = currency;
+            }
+            RulesMessage::AccountSelected(choice) => {
+                self.form.account_id = choice.id;
+            }
+            RulesMessage::PayeeSelected(choice) => {
+                self.form.payee_id = choice.id;
+            }
+            RulesMessage::CategorySelected(category_id) => {
+                self.form.category_id = Some(category_id);
+            }
+            RulesMessage::Cancel => {
+                self.close_form();
+            }
+            RulesMessage::OverwriteToggled(overwrite) => {
+                self.overwrite = overwrite;
+            }
+            RulesMessage::Save
+            | RulesMessage::MoveUp(_)
+            | RulesMessage::MoveDown(_)
+            | RulesMessage::Apply => {}
+        }
+    }
+
+    /// Validates the form, returning the rule to persist. New rules go to
+    /// the end of `rules`. Validation errors are shown in the form instead.
+    pub fn submit(&mut self, rules: &[CategoryRule]) -> Option<CategoryRule> {
+        match self.form.to_rule() {
+            Ok(mut rule) => {
+                if !self.is_editing() {
+                    rule.priority = rules.iter().map(|r| r.priority + 1).max().unwrap_or(0);
+                }
+                self.form.error = None;
+                Some(rule)
+            }
+            Err(e) => {
+                self.form.error = Some(e);
+                None
+            }
+        }
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.form.editing_id.is_some()
+    }
+
+    pub fn close_form(&mut self) {
+        self.form = CategoryRuleFormState::default();
+        self.show_form = false;
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, RulesMessage> {
+        let header = row![
+            text("Category Rules").size(30).width(Length::Fill),
+            button("Add Rule")
+                .on_press(RulesMessage::Add)
+                .padding(10),
+        ];
+
+        let apply_row = row![
+            text("Rules are tried from the top; the first match sets the category.").width(Length::Fill),
+            checkbox("Replace existing categories", self.overwrite)
+                .on_toggle(RulesMessage::OverwriteToggled),
+            button("Apply to Existing Transactions")
+                .on_press_maybe((!state.category_rules.is_empty()).then_some(RulesMessage::Apply))
+                .style(button::secondary)
+                .padding(10),
+        ]
+        .spacing(20)
+        .align_y(Alignment::Center);
+
+        let last = state.category_rules.len().saturating_sub(1);
+        let rules_list = state.category_rules.iter()
+            .enumerate()
+            .fold(column![].spacing(10), |column, (index, rule)| {
+                column.push(
+                    container(
+                        row![
+                            text(format!("{}.", index + 1)).width(Length::Fixed(30.0)),
+                            text(&rule.name).size(18).width(Length::FillPortion(2)),
+                            text(conditions_label(state, rule)).width(Length::FillPortion(4)),
+                            text(format!("→ {}", state.category_name(Some(&rule.category_id))))
+                                .width(Length::FillPortion(2)),
+                            button("↑")
+                                .on_press_maybe((index > 0).then(|| RulesMessage::MoveUp(rule.id.clone())))
+                                .style(button::secondary),
+                            button("↓")
+                                .on_press_maybe((index < last).then(|| RulesMessage::MoveDown(rule.id.clone())))
+                                .style(button::secondary),
+                            button("Edit")
+                                .on_press(RulesMessage::Edit(rule.clone())),
+                            button("Delete")
+                                .on_press(RulesMessage::Delete(rule.id.clone()))
+                                .style(button::danger),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center)
+                    )
+                    .padding(10)
+                    .style(container::rounded_box)
+                )
+            });
+
+        let mut content = column![header, apply_row].spacing(20);
+
+        if let Some(status) = &self.status {
+            content = content.push(text(status.clone()).size(16));
+        }
+
+        if self.show_form {
+            content = content.push(category_rule_form_view(&self.form, &state.categories, &state.accounts, &state.payees));
+        }
+
+        content = content.push(scrollable(rules_list).height(Length::Fill));
+
+        container(content)
+            .padding(20)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+/// The rule ids in priority order with `rule_id` moved one place up or
+/// down, or `None` when it cannot move that way.
+pub fn reordered(rules: &[CategoryRule], rule_id: &str, up: bool) -> Option<Vec<String>> {
+    let mut ids: Vec<String> = rules.iter().map(|r| r.id.clone()).collect();
+    let index = ids.iter().position(|id| id == rule_id)?;
+    let other = if up { index.checked_sub(1)? } else { index + 1 };
+    if other >= ids.len() {
+        return None;
+    }
+
+    ids.swap(index, other);
+    Some(ids)
+}
+
+/// A readable summary of a rule's conditions, e.g.
+/// "Contains \"coffee\", up to $10.00".
+fn conditions_label(state: &UiState, rule: &CategoryRule) -> String {
+    let mut conditions = Vec::new();
+
+    match rule.match_kind {
+        MatchKind::Any => {}
+        MatchKind::Contains => conditions.push(format!("Contains \"{}\"", rule.pattern)),
+        MatchKind::Regex => conditions.push(format!("Matches /{}/", rule.pattern)),
+    }
+
+    match (rule.min_amount, rule.max_amount) {
+        (Some(min), Some(max)) => conditions.push(format!("{} to {}", format_currency(min), format_currency(max))),
+        (Some(min), None) => conditions.push(format!("at least {}", format_currency(min))),
+        (None, Some(max)) => conditions.push(format!("up to {}", format_currency(max))),
+        (None, None) => {}
+    }
+
+    if let Some(account) = rule.account_id.as_ref()
+        .and_then(|id| state.accounts.iter().find(|a| &a.id == id))
+    {
+        conditions.push(format!("in {}", account.name));
+    }
+
+    if let Some(payee) = rule.payee_id.as_ref()
+        .and_then(|id| state.payees.iter().find(|p| &p.id == id))
+    {
+        conditions.push(format!("payee {}", payee.name));
+    }
+
+    if conditions.is_empty() {
+        "Every transaction".to_string()
+    } else {
+        conditions.join(", ")
+    }
+}
@@ -0,0 +1,221 @@
+use crate::models::tag::{self, Tag};
+use crate::ui::UiState;
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{Alignment, Color, Element, Length};
+use std::fmt;
+
+/// A tag to filter transactions on, or any tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagChoice {
+    pub name: Option<String>,
+}
+
+impl TagChoice {
+    /// Any tag followed by each of `tags`.
+    pub fn all(tags: &[Tag]) -> Vec<Self> {
+        std::iter::once(Self { name: None })
+            .chain(tags.iter().map(|tag| Self { name: Some(tag.name.clone()) }))
+            .collect()
+    }
+
+    pub fn selected(name: Option<&str>) -> Self {
+        Self { name: name.map(str::to_string) }
+    }
+}
+
+impl fmt::Display for TagChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "#{}", name),
+            None => write!(f, "Any tag"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TagsMessage {
+    NameChanged(String),
+    AddTag,
+    Rename(Tag),
+    RenameChanged(String),
+    SaveRename,
+    CancelRename,
+    Delete(String),
+}
+
+/// A tag being renamed.
+struct Renaming {
+    id: String,
+    name: String,
+}
+
+/// The tags in use, where tags are added, renamed and deleted. Tags are
+/// also created by typing a new one on a transaction.
+pub struct TagsView {
+    name: String,
+    renaming: Option<Renaming>,
+    error: Option<String>,
+}
+
+impl TagsView {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            renaming: None,
+            error: None,
+        }
+    }
+
+    pub fn update(&mut self, message: TagsMessage) {
+        match message {
+            TagsMessage::NameChanged(name) => {
+                self.name = name;
+            }
+            TagsMessage::Rename(tag) => {
+                self.error = None;
+                self.renaming = Some(Renaming { id: tag.id, name: tag.name });
+            }
+            TagsMessage::RenameChanged(name) => {
+                if let Some(renaming) = &mut self.renaming {
+                    renaming.name = name;
+                }
+            }
+            TagsMessage::CancelRename => {
+                self.renaming = None;
+                self.error = None;
+            }
+            TagsMessage::AddTag | TagsMessage::SaveRename | TagsMessage::Delete(_) => {}
+        }
+    }
+
+    /// Validates the new tag form, returning the tag to persist.
+    pub fn submit(&mut self, tags: &[Tag]) -> Option<Tag> {
+        match check_name(tags, &self.name, None) {
+            Ok(name) => {
+                self.error = None;
+                Some(Tag::new(name))
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    /// Validates the tag being renamed, returning its id and new name.
+    pub fn submit_rename(&mut self, tags: &[Tag]) -> Option<(String, String)> {
+        let renaming = self.renaming.as_ref()?;
+        match check_name(tags, &renaming.name, Some(&renaming.id)) {
+            Ok(name) => {
+                self.error = None;
+                Some((renaming.id.clone(), name))
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    pub fn tag_saved(&mut self) {
+        self.name.clear();
+        self.renaming = None;
+        self.error = None;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, TagsMessage> {
+        let add_form = container(
+            column![
+                text("Add Tag").size(20),
+                row![
+                    text_input("e.g. vacation-2026", &self.name)
+                        .on_input(TagsMessage::NameChanged)
+                        .on_submit(TagsMessage::AddTag)
+                        .padding(10)
+                        .width(Length::Fixed(240.0)),
+                    button("Add")
+                        .on_press(TagsMessage::AddTag)
+                        .padding(10),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(10)
+            .padding(20)
+            .width(Length::Fill)
+        )
+        .style(container::rounded_box);
+
+        let error = text(self.error.clone().unwrap_or_default())
+            .size(14)
+            .color(Color::from_rgb(0.8, 0.0, 0.0));
+
+        let rows = state.tags.iter().fold(column![].spacing(5), |column, tag| {
+            let uses = state.transactions.iter().filter(|t| t.has_tag(&tag.name)).count();
+
+            let content = match &self.renaming {
+                Some(renaming) if renaming.id == tag.id => row![
+                    text_input("Name", &renaming.name)
+                        .on_input(TagsMessage::RenameChanged)
+                        .on_submit(TagsMessage::SaveRename)
+                        .padding(10)
+                        .width(Length::Fill),
+                    button("Save").on_press(TagsMessage::SaveRename),
+                    button("Cancel")
+                        .on_press(TagsMessage::CancelRename)
+                        .style(button::secondary),
+                ],
+                _ => row![
+                    text(format!("#{}", tag.name)).width(Length::Fill),
+                    text(format!("{} transaction(s)", uses)).size(14),
+                    button("Rename")
+                        .on_press(TagsMessage::Rename(tag.clone()))
+                        .style(button::secondary),
+                    button("Delete")
+                        .on_press(TagsMessage::Delete(tag.id.clone()))
+                        .style(button::danger),
+                ],
+            };
+
+            column.push(
+                container(content.spacing(10).align_y(Alignment::Center))
+                    .padding(10)
+                    .style(container::rounded_box)
+            )
+        });
+
+        let content = column![
+            text("Tags").size(30),
+            text("Tags label transactions across categories. Deleting a tag removes it from its transactions.").size(14),
+            add_form,
+            error,
+            rows,
+        ]
+        .spacing(20)
+        .padding(20)
+        .width(Length::Fill);
+
+        scrollable(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+/// Normalises a tag name, which must be set and not already taken by
+/// another tag.
+fn check_name(tags: &[Tag], name: &str, editing: Option<&str>) -> Result<String, String> {
+    let name = tag::normalize_name(name);
+    if name.is_empty() {
+        return Err("Enter a name for the tag".to_string());
+    }
+    if tags.iter().any(|t| t.name == name && Some(t.id.as_str()) != editing) {
+        return Err(format!("There is already a tag named {}", name));
+    }
+
+    Ok(name)
+}
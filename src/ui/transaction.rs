@@ -1,30 +1,67 @@
 use crate::models::transaction::{Transaction, TransactionType};
-use iced::{widget::{button, column, container, row, text, text_input}, Element, Length};
+use crate::models::duplicate::find_duplicates;
+use crate::models::payee;
+use crate::models::{Currency, DuplicatePair, Tag, Transfer};
+use iced::{widget::{button, column, container, pick_list, row, scrollable, text, text_input}, Element, Length};
 use crate::ui::components::transaction_form::TransactionForm;
-use crate::ui::components::nav::Nav;
+use crate::ui::payees::PayeeChoice;
+use crate::ui::tags::TagChoice;
+use crate::ui::UiState;
+use crate::utils::{format_currency, format_date};
 use uuid::Uuid;
 
+/// How many learned category suggestions the form offers.
+const SUGGESTIONS: usize = 3;
+
 #[derive(Debug, Clone)]
 pub enum TransactionMessage {
     AddTransaction,
-    EditTransaction(Uuid),
+    EditTransaction(Transaction),
+    EditTransfer(Transfer),
     DeleteTransaction(Uuid),
     FormUpdated(TransactionFormMessage),
     FilterChanged(String),
+    TagFilterSelected(TagChoice),
     SortByChanged(SortBy),
+    ToggleDuplicates,
+    /// Keep `keep` and fold `duplicate` into it.
+    MergeDuplicates { keep: Uuid, duplicate: Transaction },
+    DismissDuplicates(DuplicatePair),
 }
 
 #[derive(Debug, Clone)]
 pub enum TransactionFormMessage {
     DescriptionChanged(String),
     AmountChanged(String),
+    CurrencyChanged(Currency),
     TypeChanged(TransactionType),
     DateChanged(String),
     CategoryChanged(String),
+    SplitTransaction,
+    AddSplit,
+    RemoveSplit(usize),
+    SplitCategoryChanged(usize, String),
+    SplitAmountChanged(usize, String),
+    SplitMemoChanged(usize, String),
+    PayeeSelected(PayeeChoice),
+    TagInputChanged(String),
+    AddTag,
+    TagSelected(Tag),
+    RemoveTag(String),
+    AccountChanged(String),
+    ToAccountChanged(String),
     Submit,
     Cancel,
 }
 
+/// What a valid form saves: a single transaction or both legs of a
+/// transfer.
+#[derive(Debug, Clone)]
+pub enum Submission {
+    Transaction(Transaction),
+    Transfer(Transfer),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SortBy {
     Date,
@@ -33,38 +70,64 @@ pub enum SortBy {
 }
 
 pub struct TransactionView {
-    transactions: Vec<Transaction>,
     transaction_form: TransactionForm,
     filter: String,
+    tag: Option<String>,
     sort_by: SortBy,
     editing: Option<Uuid>,
+    editing_transfer: Option<String>,
+    show_form: bool,
+    show_duplicates: bool,
+    default_currency: Currency,
 }
 
 impl TransactionView {
     pub fn new() -> Self {
         Self {
-            transactions: Vec::new(),
-            transaction_form: TransactionForm::new(),
+            transaction_form: TransactionForm::new(Currency::default()),
             filter: String::new(),
+            tag: None,
             sort_by: SortBy::Date,
             editing: None,
+            editing_transfer: None,
+            show_form: false,
+            show_duplicates: false,
+            default_currency: Currency::default(),
         }
     }
 
+    /// Currency preselected for new transactions.
+    pub fn set_default_currency(&mut self, currency: Currency) {
+        self.default_currency = currency;
+    }
+
     pub fn update(&mut self, message: TransactionMessage) {
         match message {
             TransactionMessage::AddTransaction => {
-                self.transaction_form = TransactionForm::new();
-                self.editing = None;
+                self.close_form();
+                self.show_form = true;
+            }
+            TransactionMessage::EditTransaction(transaction) => {
+                self.close_form();
+                self.editing = Some(transaction.id);
+                self.transaction_form = TransactionForm::from(transaction);
+                self.show_form = true;
             }
-            TransactionMessage::EditTransaction(id) => {
-                if let Some(transaction) = self.transactions.iter().find(|t| t.id == id) {
-                    self.transaction_form = TransactionForm::from(transaction.clone());
-                    self.editing = Some(id);
+            TransactionMessage::EditTransfer(transfer) => {
+                self.close_form();
+                self.editing_transfer = Some(transfer.id.clone());
+                self.transaction_form = TransactionForm::from(transfer);
+                self.show_form = true;
+            }
+            TransactionMessage::DeleteTransaction(_) => {
+                // Deleting may remove a transfer's other leg too, so any
+                // open edit is abandoned
+                if self.is_editing() {
+                    self.close_form();
                 }
             }
-            TransactionMessage::DeleteTransaction(id) => {
-                self.transactions.retain(|t| t.id != id);
+            TransactionMessage::FormUpdated(TransactionFormMessage::Cancel) => {
+                self.close_form();
             }
             TransactionMessage::FormUpdated(msg) => {
                 self.transaction_form.update(msg);
@@ -72,75 +135,260 @@ impl TransactionView {
             TransactionMessage::FilterChanged(filter) => {
                 self.filter = filter;
             }
+            TransactionMessage::TagFilterSelected(choice) => {
+                self.tag = choice.name;
+            }
             TransactionMessage::SortByChanged(sort_by) => {
                 self.sort_by = sort_by;
             }
+            TransactionMessage::ToggleDuplicates => {
+                self.show_duplicates = !self.show_duplicates;
+            }
+            TransactionMessage::MergeDuplicates { duplicate, .. } => {
+                // The duplicate is deleted, so stop editing it
+                if self.editing == Some(duplicate.id) {
+                    self.close_form();
+                }
+            }
+            TransactionMessage::DismissDuplicates(_) => {}
+        }
+    }
+
+    /// Validates the form, returning what to persist. Validation errors are
+    /// shown in the form instead.
+    pub fn submit(&mut self) -> Option<Submission> {
+        let result = if self.editing.is_some() && self.transaction_form.is_transfer() {
+            Err("A transaction cannot become a transfer; delete it and add a transfer instead".to_string())
+        } else if self.editing_transfer.is_some() && !self.transaction_form.is_transfer() {
+            Err("A transfer cannot become a transaction; delete it and add a transaction instead".to_string())
+        } else if self.transaction_form.is_transfer() {
+            self.transaction_form.to_transfer(self.editing_transfer.clone()).map(Submission::Transfer)
+        } else {
+            self.transaction_form.to_transaction(self.editing).map(Submission::Transaction)
+        };
+
+        match result {
+            Ok(submission) => {
+                self.transaction_form.set_error(None);
+                Some(submission)
+            }
+            Err(e) => {
+                self.transaction_form.set_error(Some(e));
+                None
+            }
         }
     }
 
-    pub fn view(&self) -> Element<TransactionMessage> {
-        let nav = Nav::new().view();
-        
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some() || self.editing_transfer.is_some()
+    }
+
+    pub fn close_form(&mut self) {
+        self.transaction_form = TransactionForm::new(self.default_currency);
+        self.editing = None;
+        self.editing_transfer = None;
+        self.show_form = false;
+    }
+
+    pub fn view<'a>(&self, state: &'a UiState) -> Element<'a, TransactionMessage> {
         let add_button = button("Add Transaction")
             .on_press(TransactionMessage::AddTransaction)
             .padding(10);
-        
+
         let filter_input = text_input("Filter transactions...", &self.filter)
             .on_input(TransactionMessage::FilterChanged)
             .padding(10);
-        
+
+        let tag_filter = pick_list(
+            TagChoice::all(&state.tags),
+            Some(TagChoice::selected(self.tag.as_deref())),
+            TransactionMessage::TagFilterSelected,
+        )
+        .padding(10);
+
+        let sort_button = |label, sort_by: SortBy| {
+            let style = if self.sort_by == sort_by { button::primary } else { button::secondary };
+            button(label)
+                .on_press(TransactionMessage::SortByChanged(sort_by))
+                .style(style)
+        };
+
         let sort_controls = row![
             text("Sort by:").size(14),
-            button("Date").on_press(TransactionMessage::SortByChanged(SortBy::Date)),
-            button("Amount").on_press(TransactionMessage::SortByChanged(SortBy::Amount)),
-            button("Category").on_press(TransactionMessage::SortByChanged(SortBy::Category)),
+            sort_button("Date", SortBy::Date),
+            sort_button("Amount", SortBy::Amount),
+            sort_button("Category", SortBy::Category),
         ]
         .spacing(10);
-        
-        let transactions_list = self.transactions
+
+        let filter = self.filter.to_lowercase();
+        let mut transactions: Vec<&Transaction> = state.transactions
             .iter()
-            .filter(|t| t.description.to_lowercase().contains(&self.filter.to_lowercase()))
+            .filter(|t| {
+                t.description.to_lowercase().contains(&filter)
+                    || payee::display_name(&state.payees, t).to_lowercase().contains(&filter)
+            })
+            .filter(|t| self.tag.as_ref().is_none_or(|tag| t.has_tag(tag)))
+            .collect();
+
+        match self.sort_by {
+            SortBy::Date => transactions.sort_by_key(|t| std::cmp::Reverse(t.date)),
+            SortBy::Amount => transactions.sort_by_key(|t| {
+                // Compare in the base currency; amounts without a rate sort last
+                std::cmp::Reverse(state.converter.to_base(t.amount, t.date).map(|m| m.minor_units()))
+            }),
+            SortBy::Category => transactions.sort_by_key(|t| state.transaction_category(t)),
+        }
+
+        let transactions_list = transactions
+            .into_iter()
             .fold(column![].spacing(5), |column, transaction| {
+                let amount = match transaction.transaction_type {
+                    TransactionType::Income => format_currency(transaction.amount),
+                    TransactionType::Expense => format_currency(-transaction.amount),
+                    TransactionType::Transfer => format_currency(transaction.amount),
+                };
+
+                // Either leg of a transfer edits the transfer as a whole
+                let transfer = transaction.transfer_id
+                    .as_deref()
+                    .and_then(|id| Transfer::find(&state.transactions, id));
+                let (category, edit) = match transfer {
+                    Some(transfer) => ("Transfer".to_string(), TransactionMessage::EditTransfer(transfer)),
+                    None => (
+                        state.transaction_category(transaction),
+                        TransactionMessage::EditTransaction(transaction.clone()),
+                    ),
+                };
+
                 column.push(
                     container(
                         row![
-                            text(&transaction.description).width(Length::FillPortion(3)),
-                            text(format!("${:.2}", transaction.amount)).width(Length::FillPortion(1)),
-                            text(transaction.category.as_ref().unwrap_or(&"None".to_string())).width(Length::FillPortion(2)),
-                            text(transaction.date.format("%Y-%m-%d").to_string()).width(Length::FillPortion(2)),
+                            column![
+                                text(payee::display_name(&state.payees, transaction)),
+                                text(details(transaction)).size(12),
+                            ]
+                            .width(Length::FillPortion(3)),
+                            text(amount).width(Length::FillPortion(1)),
+                            text(category).width(Length::FillPortion(2)),
+                            text(format_date(&transaction.date)).width(Length::FillPortion(2)),
                             button("Edit")
-                                .on_press(TransactionMessage::EditTransaction(transaction.id))
+                                .on_press(edit)
                                 .width(Length::Shrink),
                             button("Delete")
                                 .on_press(TransactionMessage::DeleteTransaction(transaction.id))
+                                .style(button::danger)
                                 .width(Length::Shrink)
                         ]
                         .spacing(10)
                         .padding(10)
                     )
-                    .style(container::Style::default())
+                    .style(container::rounded_box)
                     .padding(5)
                 )
             });
-        
-        let form_view = if self.editing.is_some() {
-            self.transaction_form.view().map(TransactionMessage::FormUpdated)
+
+        let form_view = if self.show_form {
+            let suggestions = state.suggester.suggest(self.transaction_form.description(), SUGGESTIONS);
+            self.transaction_form
+                .view(&state.categories, &state.accounts, &state.payees, &state.tags, &suggestions)
+                .map(TransactionMessage::FormUpdated)
         } else {
             container(text("")).into()
         };
-        
-        column![
-            nav,
+
+        let duplicates = find_duplicates(&state.transactions, &state.dismissed_duplicates);
+        let duplicates_button = button(text(format!("Possible Duplicates ({})", duplicates.len())))
+            .on_press_maybe((!duplicates.is_empty()).then_some(TransactionMessage::ToggleDuplicates))
+            .style(if self.show_duplicates { button::primary } else { button::secondary })
+            .padding(10);
+
+        let mut content = column![
             row![
                 add_button,
                 filter_input,
-                sort_controls
+                tag_filter,
+                sort_controls,
+                duplicates_button,
             ].padding(10).spacing(20),
-            transactions_list,
-            form_view
+            form_view,
         ]
         .padding(20)
-        .spacing(20)
-        .into()
+        .spacing(20);
+
+        if self.show_duplicates && !duplicates.is_empty() {
+            content = content.push(duplicates_view(state, &duplicates));
+        }
+
+        content
+            .push(scrollable(transactions_list).height(Length::Fill))
+            .into()
     }
 }
+
+/// The raw description of a transaction shown under its payee, followed by
+/// its tags as `#name`s.
+fn details(transaction: &Transaction) -> String {
+    let description = transaction.payee_id.as_ref().map(|_| transaction.description.as_str());
+    description.into_iter()
+        .map(str::to_string)
+        .chain(transaction.tags.iter().map(|tag| format!("#{}", tag)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Each likely duplicate pair side by side, with a choice of which to keep
+/// or a way to mark them as distinct.
+fn duplicates_view<'a>(
+    state: &'a UiState,
+    duplicates: &[(&'a Transaction, &'a Transaction)],
+) -> Element<'a, TransactionMessage> {
+    let pairs = duplicates
+        .iter()
+        .fold(column![].spacing(10), |column, (a, b)| {
+            column.push(
+                container(
+                    row![
+                        duplicate_card(state, a, b),
+                        duplicate_card(state, b, a),
+                        button("Not Duplicates")
+                            .on_press(TransactionMessage::DismissDuplicates(DuplicatePair::new(a.id, b.id)))
+                            .style(button::secondary),
+                    ]
+                    .spacing(10)
+                )
+                .padding(10)
+                .style(container::rounded_box)
+            )
+        });
+
+    scrollable(
+        column![text("Possible Duplicates").size(20), pairs].spacing(10)
+    )
+    .height(Length::Shrink)
+    .into()
+}
+
+fn duplicate_card<'a>(
+    state: &'a UiState,
+    transaction: &'a Transaction,
+    other: &'a Transaction,
+) -> Element<'a, TransactionMessage> {
+    let account = transaction.account_id.as_ref()
+        .and_then(|id| state.accounts.iter().find(|a| &a.id == id))
+        .map_or("No account", |a| a.name.as_str());
+
+    column![
+        text(&transaction.description).size(16),
+        text(format!("{} · {}", format_date(&transaction.date), format_currency(transaction.amount))),
+        text(format!("{} · {}", state.transaction_category(transaction), account)).size(14),
+        button("Keep This")
+            .on_press(TransactionMessage::MergeDuplicates {
+                keep: transaction.id,
+                duplicate: other.clone(),
+            }),
+    ]
+    .spacing(5)
+    .width(Length::Fill)
+    .into()
+}
@@ -0,0 +1,44 @@
+use crate::models::Notification;
+
+const APP_NAME: &str = "Budget Tracker";
+
+/// Shows each notification on the desktop through the freedesktop
+/// notification service on the session bus.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn notify(notifications: &[Notification]) -> Result<(), String> {
+    use std::collections::HashMap;
+    use zbus::blocking::Connection;
+    use zbus::zvariant::Value;
+
+    let connection = Connection::session()
+        .map_err(|e| format!("Could not connect to the session bus: {}", e))?;
+
+    for notification in notifications {
+        connection
+            .call_method(
+                Some("org.freedesktop.Notifications"),
+                "/org/freedesktop/Notifications",
+                Some("org.freedesktop.Notifications"),
+                "Notify",
+                &(
+                    APP_NAME,
+                    0u32,
+                    "",
+                    notification.title.as_str(),
+                    notification.body.as_str(),
+                    Vec::<&str>::new(),
+                    HashMap::<&str, Value>::new(),
+                    -1i32,
+                ),
+            )
+            .map_err(|e| format!("Could not show desktop notification: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Shows each notification on the desktop.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub fn notify(_notifications: &[Notification]) -> Result<(), String> {
+    Err(format!("{} cannot show desktop notifications on this platform", APP_NAME))
+}
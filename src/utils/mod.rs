@@ -1,7 +1,11 @@
 use chrono::{NaiveDate, Local, Datelike};
+use crate::models::Money;
+use std::path::PathBuf;
 
-pub fn format_currency(amount: f64) -> String {
-    format!("${:.2}", amount)
+pub mod desktop;
+
+pub fn format_currency(amount: Money) -> String {
+    amount.to_string()
 }
 
 pub fn format_date(date: &NaiveDate) -> String {
@@ -15,27 +19,30 @@ pub fn today() -> NaiveDate {
         .expect("Failed to create NaiveDate")
 }
 
-pub fn format_month_year(date: &NaiveDate) -> String {
-    date.format("%b %Y").to_string()
-}
-
 pub fn get_month_start() -> NaiveDate {
-    let now = Local::now().naive_local().date();
-    NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap_or(now)
+    month_start(Local::now().naive_local().date())
 }
 
 pub fn get_month_end() -> NaiveDate {
-    let now = Local::now().naive_local().date();
-    
+    month_end(Local::now().naive_local().date())
+}
+
+/// The first day of the month containing `date`.
+pub fn month_start(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap_or(date)
+}
+
+/// The last day of the month containing `date`.
+pub fn month_end(date: NaiveDate) -> NaiveDate {
     // Get the first day of the next month and subtract 1 day
-    let (year, month) = if now.month() == 12 {
-        (now.year() + 1, 1)
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
     } else {
-        (now.year(), now.month() + 1)
+        (date.year(), date.month() + 1)
     };
-    
-    let first_of_next_month = NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(now);
-    first_of_next_month.pred_opt().unwrap_or(now)
+
+    let first_of_next_month = NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(date);
+    first_of_next_month.pred_opt().unwrap_or(date)
 }
 
 pub fn calculate_percentage(value: f64, total: f64) -> f64 {
@@ -45,3 +52,13 @@ pub fn calculate_percentage(value: f64, total: f64) -> f64 {
         (value / total) * 100.0
     }
 }
+
+/// `file_name` in the user's documents folder, or home folder without one,
+/// as a default place to save exported files.
+pub fn documents_path(file_name: &str) -> String {
+    directories::UserDirs::new()
+        .map(|dirs| dirs.document_dir().unwrap_or(dirs.home_dir()).join(file_name))
+        .unwrap_or_else(|| PathBuf::from(file_name))
+        .display()
+        .to_string()
+}